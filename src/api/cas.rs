@@ -1,5 +1,5 @@
 use crate::api::client::ApiClient;
-use crate::api::types::{ApiErrorResponse, CasUploadRequest, CasUploadResponse};
+use crate::api::types::{ApiErrorResponse, CasMessagesObject, CasUploadRequest, CasUploadResponse};
 use crate::error::GitAiError;
 
 /// CAS API endpoints
@@ -54,4 +54,28 @@ impl ApiClient {
             ))),
         }
     }
+
+    /// Download the prompt messages stored in CAS under `hash`
+    ///
+    /// This is the object a prompt's `messages_url` (`{api_base_url}/cas/{hash}`) points at.
+    pub fn read_cas_messages(&self, hash: &str) -> Result<CasMessagesObject, GitAiError> {
+        let response = self.context().get(&format!("/cas/{}", hash))?;
+        let status_code = response.status_code;
+
+        let body = response
+            .as_str()
+            .map_err(|e| GitAiError::Generic(format!("Failed to read response body: {}", e)))?;
+
+        match status_code {
+            200 => serde_json::from_str(body).map_err(GitAiError::JsonError),
+            404 => Err(GitAiError::Generic(format!(
+                "CAS object {} not found",
+                hash
+            ))),
+            _ => Err(GitAiError::Generic(format!(
+                "Unexpected status code {}: {}",
+                status_code, body
+            ))),
+        }
+    }
 }
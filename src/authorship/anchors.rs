@@ -0,0 +1,459 @@
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log_serialization::{AuthorshipLog, FileAttestation};
+use crate::git::refs::get_reference_as_authorship_log_v3;
+use crate::git::repository::Repository;
+use crate::utils::debug_log;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+
+/// Git config key that turns on content-hash anchors for newly written notes
+pub const NOTE_ANCHORS_CONFIG: &str = "gitai.noteAnchors";
+
+/// Length of an anchor in hex characters
+const ANCHOR_LEN: usize = 8;
+
+/// Outcome of checking a file's anchored ranges against its content
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnchorCheck {
+    /// Anchored ranges whose lines still match
+    pub matched: usize,
+    /// Ranges whose lines were found elsewhere in the file, as (hash, attested, found)
+    pub moved: Vec<(String, LineRange, LineRange)>,
+    /// Ranges whose lines no longer appear anywhere in the file, as (hash, attested)
+    pub lost: Vec<(String, LineRange)>,
+}
+
+impl AnchorCheck {
+    pub fn is_clean(&self) -> bool {
+        self.moved.is_empty() && self.lost.is_empty()
+    }
+}
+
+/// Short content hash of a run of lines, ignoring line endings
+pub fn content_anchor(lines: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for line in lines {
+        hasher.update(line.as_bytes());
+        hasher.update(b"\n");
+    }
+    let digest = format!("{:x}", hasher.finalize());
+    digest[..ANCHOR_LEN].to_string()
+}
+
+/// Anchor of the lines a range covers, or None if the range is outside the file
+fn range_anchor(lines: &[&str], range: &LineRange) -> Option<String> {
+    let (start, end) = (range.start() as usize, range.end() as usize);
+    if start == 0 || start > end || end > lines.len() {
+        return None;
+    }
+    Some(content_anchor(&lines[start - 1..end]))
+}
+
+/// Anchors of every run of a file's lines, indexed once per run length
+///
+/// Looking a drifted range up means hashing every window of its length; the index keeps
+/// that to one pass over the file per distinct length instead of one per range.
+struct AnchorIndex<'a> {
+    lines: Vec<&'a str>,
+    /// Window length -> anchor -> first lines of the windows with that anchor
+    windows: HashMap<usize, HashMap<String, Vec<u32>>>,
+}
+
+impl<'a> AnchorIndex<'a> {
+    fn new(content: &'a str) -> Self {
+        Self {
+            lines: content.lines().collect(),
+            windows: HashMap::new(),
+        }
+    }
+
+    /// The range of the same length nearest to `range` whose lines hash to `anchor`
+    fn find(&mut self, range: &LineRange, anchor: &str) -> Option<LineRange> {
+        let len = range.end().checked_sub(range.start())? as usize + 1;
+        if len > self.lines.len() {
+            return None;
+        }
+        let lines = &self.lines;
+        let starts = self
+            .windows
+            .entry(len)
+            .or_insert_with(|| {
+                let mut index: HashMap<String, Vec<u32>> = HashMap::new();
+                for start in 1..=lines.len() - len + 1 {
+                    index
+                        .entry(content_anchor(&lines[start - 1..start - 1 + len]))
+                        .or_default()
+                        .push(start as u32);
+                }
+                index
+            })
+            .get(anchor)?;
+        starts
+            .iter()
+            .min_by_key(|start| (**start as i64 - range.start() as i64).abs())
+            .map(|&start| {
+                if len == 1 {
+                    LineRange::Single(start)
+                } else {
+                    LineRange::Range(start, start + len as u32 - 1)
+                }
+            })
+    }
+}
+
+/// Record the anchor of every range of a file's entries, given the file's content
+pub fn anchor_file_attestation(file: &mut FileAttestation, content: &str) {
+    let lines: Vec<&str> = content.lines().collect();
+    for entry in &mut file.entries {
+        entry.anchors = entry
+            .line_ranges
+            .iter()
+            .filter_map(|range| range_anchor(&lines, range).map(|anchor| (range.clone(), anchor)))
+            .collect();
+    }
+}
+
+/// Check a file's anchored ranges against its content and re-anchor the ones that drifted
+///
+/// Ranges whose lines moved are rewritten to where the lines are now; ranges whose lines
+/// can't be found are dropped rather than attributed to whatever replaced them. Ranges
+/// without an anchor are kept as they are.
+pub fn reanchor_file_attestation(file: &mut FileAttestation, content: &str) -> AnchorCheck {
+    let mut index = AnchorIndex::new(content);
+    let mut check = AnchorCheck::default();
+
+    for entry in &mut file.entries {
+        if entry.anchors.is_empty() {
+            continue;
+        }
+
+        let mut ranges = Vec::new();
+        let mut anchors = Vec::new();
+        for range in &entry.line_ranges {
+            let Some(anchor) = entry.anchor_for(range) else {
+                ranges.push(range.clone());
+                continue;
+            };
+            if range_anchor(&index.lines, range).as_deref() == Some(anchor) {
+                check.matched += 1;
+                ranges.push(range.clone());
+                anchors.push((range.clone(), anchor.to_string()));
+            } else if let Some(found) = index.find(range, anchor) {
+                check
+                    .moved
+                    .push((entry.hash.clone(), range.clone(), found.clone()));
+                ranges.push(found.clone());
+                anchors.push((found, anchor.to_string()));
+            } else {
+                check.lost.push((entry.hash.clone(), range.clone()));
+            }
+        }
+
+        ranges.sort_by_key(|range| range.start());
+        entry.line_ranges = ranges;
+        entry.anchors = anchors;
+    }
+
+    file.entries.retain(|entry| !entry.line_ranges.is_empty());
+    check
+}
+
+/// Re-anchor a file attestation against the file as of `commit_sha`
+///
+/// Returns None when the attestation has no anchors or the file can't be read.
+pub fn reanchor_file_at_commit(
+    repo: &Repository,
+    commit_sha: &str,
+    file: &mut FileAttestation,
+) -> Option<AnchorCheck> {
+    if file.entries.iter().all(|entry| entry.anchors.is_empty()) {
+        return None;
+    }
+    let content = repo.get_file_content(&file.file_path, commit_sha).ok()?;
+    let check = reanchor_file_attestation(file, &String::from_utf8_lossy(&content));
+    if !check.is_clean() {
+        debug_log(&format!(
+            "Re-anchored {} in {}: {} range(s) moved, {} range(s) lost",
+            file.file_path,
+            commit_sha,
+            check.moved.len(),
+            check.lost.len()
+        ));
+    }
+    Some(check)
+}
+
+/// Lines each prompt held in the source notes of a rewrite, as confirmed by their anchors
+///
+/// Rebase and cherry-pick rewrites carry attributions onto new commits by transforming line
+/// numbers. The new notes get fresh anchors on write, so anchors only catch a bad transform
+/// if the rewritten lines are checked against the source notes' anchors first.
+#[derive(Debug, Default)]
+pub struct AnchoredSourceLines {
+    /// File path -> prompt hash -> content of the lines its anchored ranges cover
+    files: HashMap<String, HashMap<String, HashSet<String>>>,
+}
+
+impl AnchoredSourceLines {
+    /// Collect the anchored lines of the notes on `commits`
+    ///
+    /// Each note is re-anchored against the commit it annotates first, so ranges that had
+    /// already drifted contribute the lines they point at now and lost ranges contribute none.
+    pub fn from_commits(repo: &Repository, commits: &[String]) -> Self {
+        let mut source = Self::default();
+        for commit in commits {
+            let Ok(log) = get_reference_as_authorship_log_v3(repo, commit) else {
+                continue;
+            };
+            for file in log.attestations {
+                if file.entries.iter().all(|entry| entry.anchors.is_empty()) {
+                    continue;
+                }
+                let Ok(content) = repo.get_file_content(&file.file_path, commit) else {
+                    continue;
+                };
+                source.add_file(file, &String::from_utf8_lossy(&content));
+            }
+        }
+        source
+    }
+
+    /// Add a source file attestation, given the content of the file it annotates
+    fn add_file(&mut self, mut file: FileAttestation, content: &str) {
+        reanchor_file_attestation(&mut file, content);
+
+        let lines: Vec<&str> = content.lines().collect();
+        let prompts = self.files.entry(file.file_path.clone()).or_default();
+        for entry in &file.entries {
+            let expected = prompts.entry(entry.hash.clone()).or_default();
+            for (range, _) in &entry.anchors {
+                for line in range.expand() {
+                    if let Some(text) = line.checked_sub(1).and_then(|idx| lines.get(idx as usize))
+                    {
+                        expected.insert(text.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Drop lines of a rewritten log that don't match what their prompt held in the source
+    ///
+    /// `contents` maps each file to its content in the rewritten commit. Only prompts with
+    /// anchored lines in a file are checked; everything else is kept as it is. Returns the
+    /// number of lines dropped.
+    pub fn verify_rewritten_log(
+        &self,
+        log: &mut AuthorshipLog,
+        contents: &HashMap<String, String>,
+    ) -> usize {
+        let mut dropped = 0;
+        for file in &mut log.attestations {
+            let (Some(prompts), Some(content)) = (
+                self.files.get(&file.file_path),
+                contents.get(&file.file_path),
+            ) else {
+                continue;
+            };
+            let lines: Vec<&str> = content.lines().collect();
+
+            for entry in &mut file.entries {
+                let Some(expected) = prompts.get(&entry.hash) else {
+                    continue;
+                };
+                let (kept, mismatched): (Vec<u32>, Vec<u32>) = entry
+                    .line_ranges
+                    .iter()
+                    .flat_map(|range| range.expand())
+                    .partition(|line| {
+                        line.checked_sub(1)
+                            .and_then(|idx| lines.get(idx as usize))
+                            .is_some_and(|text| expected.contains(*text))
+                    });
+                if mismatched.is_empty() {
+                    continue;
+                }
+                debug_log(&format!(
+                    "Dropping {} line(s) of {} in {} that don't match the source anchors: {:?}",
+                    mismatched.len(),
+                    entry.hash,
+                    file.file_path,
+                    LineRange::compress_lines(&mismatched)
+                ));
+                dropped += mismatched.len();
+                entry.line_ranges = LineRange::compress_lines(&kept);
+                entry.anchors.clear();
+            }
+            file.entries.retain(|entry| !entry.line_ranges.is_empty());
+        }
+        log.attestations.retain(|file| !file.entries.is_empty());
+        dropped
+    }
+}
+
+/// Add content-hash anchors to a serialized note when `gitai.noteAnchors` is enabled
+///
+/// Anchors are computed from the files as of `commit_sha`, the commit the note annotates.
+/// Files missing from the commit are left unanchored.
+pub fn anchor_note_if_configured(
+    repo: &Repository,
+    commit_sha: &str,
+    note_content: &str,
+) -> String {
+    let enabled = repo
+        .config_get_bool(NOTE_ANCHORS_CONFIG)
+        .ok()
+        .flatten()
+        .unwrap_or(false);
+    if !enabled {
+        return note_content.to_string();
+    }
+
+    let mut log = match AuthorshipLog::deserialize_from_string(note_content) {
+        Ok(log) => log,
+        Err(e) => {
+            debug_log(&format!("Not anchoring unparseable authorship note: {}", e));
+            return note_content.to_string();
+        }
+    };
+    for file in &mut log.attestations {
+        if let Ok(content) = repo.get_file_content(&file.file_path, commit_sha) {
+            anchor_file_attestation(file, &String::from_utf8_lossy(&content));
+        }
+    }
+    log.serialize_to_string()
+        .unwrap_or_else(|_| note_content.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::authorship_log_serialization::AttestationEntry;
+
+    fn anchored_file(content: &str, ranges: Vec<LineRange>) -> FileAttestation {
+        let mut file = FileAttestation::new("a.rs".to_string());
+        file.add_entry(AttestationEntry::new("abcd".to_string(), ranges));
+        anchor_file_attestation(&mut file, content);
+        file
+    }
+
+    #[test]
+    fn test_anchors_survive_serialization() {
+        let mut log = AuthorshipLog::new();
+        log.attestations.push(anchored_file(
+            "one\ntwo\nthree\nfour\n",
+            vec![LineRange::Range(1, 2), LineRange::Single(4)],
+        ));
+        let serialized = log.serialize_to_string().unwrap();
+        assert!(serialized.starts_with("a.rs\n  abcd 1-2,4\n~ abcd 1-2="));
+
+        let parsed = AuthorshipLog::deserialize_from_string(&serialized).unwrap();
+        assert_eq!(parsed, log);
+        assert_eq!(parsed.attestations[0].entries[0].anchors.len(), 2);
+    }
+
+    #[test]
+    fn test_matching_content_is_left_alone() {
+        let content = "one\ntwo\nthree\n";
+        let mut file = anchored_file(content, vec![LineRange::Range(2, 3)]);
+        let before = file.clone();
+
+        let check = reanchor_file_attestation(&mut file, content);
+        assert!(check.is_clean());
+        assert_eq!(check.matched, 1);
+        assert_eq!(file, before);
+    }
+
+    #[test]
+    fn test_moved_lines_are_reanchored_and_missing_lines_dropped() {
+        let mut file = anchored_file(
+            "ai one\nai two\nhuman\nai three\n",
+            vec![LineRange::Range(1, 2), LineRange::Single(4)],
+        );
+
+        // Two lines were inserted above the first range and the last line was rewritten
+        let check =
+            reanchor_file_attestation(&mut file, "new\nnew\nai one\nai two\nhuman\nchanged\n");
+        assert_eq!(
+            check.moved,
+            vec![(
+                "abcd".to_string(),
+                LineRange::Range(1, 2),
+                LineRange::Range(3, 4)
+            )]
+        );
+        assert_eq!(check.lost, vec![("abcd".to_string(), LineRange::Single(4))]);
+        assert_eq!(file.entries[0].line_ranges, vec![LineRange::Range(3, 4)]);
+        assert!(
+            file.entries[0]
+                .anchor_for(&LineRange::Range(3, 4))
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_read_attestations_attaches_anchors() {
+        let note = "a.rs\n  abcd 1-2\n~ abcd 1-2=0123abcd\nb.rs\n  abcd 3\n---\n{}";
+        let attestations =
+            crate::authorship::authorship_log_serialization::read_attestations(note.as_bytes())
+                .unwrap();
+        assert_eq!(attestations.len(), 2);
+        assert_eq!(
+            attestations[0].entries[0].anchor_for(&LineRange::Range(1, 2)),
+            Some("0123abcd")
+        );
+        assert!(attestations[1].entries[0].anchors.is_empty());
+    }
+
+    #[test]
+    fn test_ranges_of_the_same_length_find_their_nearest_copy() {
+        let mut file = anchored_file(
+            "dup\ndup\nx\nai\ny\n",
+            vec![LineRange::Single(1), LineRange::Single(4)],
+        );
+
+        let check = reanchor_file_attestation(&mut file, "new\ndup\ndup\nx\nnew\nai\ny\n");
+        assert_eq!(check.lost, vec![]);
+        assert_eq!(
+            file.entries[0].line_ranges,
+            vec![LineRange::Single(2), LineRange::Single(6)]
+        );
+    }
+
+    #[test]
+    fn test_rewritten_lines_are_checked_against_source_anchors() {
+        let mut source = AnchoredSourceLines::default();
+        source.add_file(
+            anchored_file("human\nai one\nai two\n", vec![LineRange::Range(2, 3)]),
+            "human\nai one\nai two\n",
+        );
+
+        // A rewrite that shifted the range by one line onto a human line
+        let mut log = AuthorshipLog::new();
+        log.get_or_create_file("a.rs")
+            .add_entry(AttestationEntry::new(
+                "abcd".to_string(),
+                vec![LineRange::Range(1, 2)],
+            ));
+        log.get_or_create_file("b.rs")
+            .add_entry(AttestationEntry::new(
+                "abcd".to_string(),
+                vec![LineRange::Single(1)],
+            ));
+        let contents = HashMap::from([
+            ("a.rs".to_string(), "ai one\nhuman\nai two\n".to_string()),
+            ("b.rs".to_string(), "anything\n".to_string()),
+        ]);
+
+        assert_eq!(source.verify_rewritten_log(&mut log, &contents), 1);
+        assert_eq!(
+            log.attestations[0].entries[0].line_ranges,
+            vec![LineRange::Single(1)]
+        );
+        // Files without anchored source lines aren't checked
+        assert_eq!(
+            log.attestations[1].entries[0].line_ranges,
+            vec![LineRange::Single(1)]
+        );
+    }
+}
@@ -1,14 +1,9 @@
 use crate::authorship::transcript::Message;
 use crate::authorship::working_log::AgentId;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct Author {
-    pub username: String,
-    pub email: String,
-}
-
 /// Represents either a single line or a range of lines
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum LineRange {
@@ -17,6 +12,22 @@ pub enum LineRange {
 }
 
 impl LineRange {
+    /// First line of the range
+    pub fn start(&self) -> u32 {
+        match self {
+            LineRange::Single(l) => *l,
+            LineRange::Range(start, _) => *start,
+        }
+    }
+
+    /// Last line of the range
+    pub fn end(&self) -> u32 {
+        match self {
+            LineRange::Single(l) => *l,
+            LineRange::Range(_, end) => *end,
+        }
+    }
+
     pub fn contains(&self, line: u32) -> bool {
         match self {
             LineRange::Single(l) => *l == line,
@@ -125,6 +136,37 @@ impl LineRange {
         ranges
     }
 
+    /// Sort ranges and merge any that overlap or touch, without expanding them
+    pub fn normalize(ranges: &[LineRange]) -> Vec<LineRange> {
+        let mut bounds: Vec<(u32, u32)> = ranges
+            .iter()
+            .map(|range| match range {
+                LineRange::Single(l) => (*l, *l),
+                LineRange::Range(start, end) => (*start.min(end), *start.max(end)),
+            })
+            .collect();
+        bounds.sort_unstable();
+
+        let mut merged: Vec<(u32, u32)> = Vec::new();
+        for (start, end) in bounds {
+            match merged.last_mut() {
+                Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+
+        merged
+            .into_iter()
+            .map(|(start, end)| {
+                if start == end {
+                    LineRange::Single(start)
+                } else {
+                    LineRange::Range(start, end)
+                }
+            })
+            .collect()
+    }
+
     #[allow(dead_code)]
     pub fn expand(&self) -> Vec<u32> {
         match self {
@@ -185,7 +227,7 @@ impl fmt::Display for LineRange {
 }
 
 /// Prompt session details stored in the top-level prompts map keyed by short hash (agent_id + tool)
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct PromptRecord {
     pub agent_id: AgentId,
     pub human_author: Option<String>,
@@ -281,4 +323,26 @@ mod tests {
                 || records[1].total_deletions > 0
         );
     }
+
+    #[test]
+    fn test_normalize_merges_overlapping_and_adjacent_ranges() {
+        let ranges = vec![
+            LineRange::Range(10, 12),
+            LineRange::Single(3),
+            LineRange::Single(13),
+            LineRange::Range(1, 2),
+            LineRange::Range(11, 11),
+            LineRange::Single(20),
+        ];
+
+        assert_eq!(
+            LineRange::normalize(&ranges),
+            vec![
+                LineRange::Range(1, 3),
+                LineRange::Range(10, 13),
+                LineRange::Single(20),
+            ]
+        );
+        assert!(LineRange::normalize(&[]).is_empty());
+    }
 }
@@ -1,11 +1,18 @@
-use crate::authorship::authorship_log::{Author, LineRange, PromptRecord};
+use crate::authorship::authorship_log::{LineRange, PromptRecord};
 use crate::authorship::working_log::CheckpointKind;
+use crate::error::GitAiError;
 use crate::git::repository::Repository;
+use crate::utils::debug_log;
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use schemars::JsonSchema;
+use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, Visitor};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::{BTreeMap, HashMap};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
-use std::io::{BufRead, Write};
+use std::io::{BufRead, Read, Write};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Authorship log format version identifier
@@ -21,12 +28,19 @@ pub const GIT_AI_VERSION: &str = concat!("development:", env!("CARGO_PKG_VERSION
 pub const GIT_AI_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// Metadata section that goes below the divider as JSON
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct AuthorshipMetadata {
     pub schema_version: String,
     pub git_ai_version: Option<String>,
     pub base_commit_sha: String,
     pub prompts: BTreeMap<String, PromptRecord>,
+    /// Lines each prompt deleted, keyed by file then prompt hash. Ranges use the
+    /// attestation format ("1,5-7") and are line numbers in the parent commit.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub deletions: BTreeMap<String, BTreeMap<String, String>>,
+    /// Armored SSH signature over the note serialized without this field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
 }
 
 impl AuthorshipMetadata {
@@ -36,6 +50,8 @@ impl AuthorshipMetadata {
             git_ai_version: Some(GIT_AI_VERSION.to_string()),
             base_commit_sha: String::new(),
             prompts: BTreeMap::new(),
+            deletions: BTreeMap::new(),
+            signature: None,
         }
     }
 }
@@ -50,17 +66,47 @@ impl Default for AuthorshipMetadata {
 ///
 /// IMPORTANT: The hash ALWAYS corresponds to a prompt in the prompts section.
 /// This system only tracks AI-generated content, not human-authored content.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq)]
 pub struct AttestationEntry {
     /// Short hash (7 chars) that maps to an entry in the prompts section of the metadata
     pub hash: String,
     /// Line ranges that this prompt is responsible for
     pub line_ranges: Vec<LineRange>,
+    /// Optional content hash of the lines in each range, see `authorship::anchors`
+    pub anchors: Vec<(LineRange, String)>,
+}
+
+impl fmt::Debug for AttestationEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("AttestationEntry");
+        debug
+            .field("hash", &self.hash)
+            .field("line_ranges", &self.line_ranges);
+        if !self.anchors.is_empty() {
+            debug.field("anchors", &self.anchors);
+        }
+        debug.finish()
+    }
 }
 
 impl AttestationEntry {
     pub fn new(hash: String, line_ranges: Vec<LineRange>) -> Self {
-        Self { hash, line_ranges }
+        Self {
+            hash,
+            line_ranges,
+            anchors: Vec::new(),
+        }
+    }
+
+    /// The anchor recorded for `range`, if it is still one of this entry's ranges
+    pub fn anchor_for(&self, range: &LineRange) -> Option<&str> {
+        if !self.line_ranges.contains(range) {
+            return None;
+        }
+        self.anchors
+            .iter()
+            .find(|(anchored, _)| anchored == range)
+            .map(|(_, anchor)| anchor.as_str())
     }
 
     #[allow(dead_code)]
@@ -109,6 +155,16 @@ impl FileAttestation {
     pub fn add_entry(&mut self, entry: AttestationEntry) {
         self.entries.push(entry);
     }
+
+    /// Hashes of the entries covering `line`, latest entry first
+    pub fn hashes_for_line(&self, line: u32) -> Vec<String> {
+        self.entries
+            .iter()
+            .rev()
+            .filter(|entry| entry.line_ranges.iter().any(|range| range.contains(line)))
+            .map(|entry| entry.hash.clone())
+            .collect()
+    }
 }
 
 /// The complete authorship log format
@@ -151,6 +207,42 @@ impl AuthorshipLog {
             .unwrap()
     }
 
+    /// Record that the prompt `hash` deleted `ranges` (parent commit line numbers) from a file
+    pub fn record_deletions(&mut self, file_path: &str, hash: &str, ranges: &[LineRange]) {
+        let file_deletions = self
+            .metadata
+            .deletions
+            .entry(file_path.to_string())
+            .or_default();
+        let mut all_ranges = file_deletions
+            .get(hash)
+            .and_then(|existing| parse_line_ranges(existing).ok())
+            .unwrap_or_default();
+        all_ranges.extend_from_slice(ranges);
+        file_deletions.insert(
+            hash.to_string(),
+            format_line_ranges(&LineRange::normalize(&all_ranges)),
+        );
+    }
+
+    /// Deleted line ranges for a file, keyed by prompt hash; unparseable entries are skipped
+    pub fn deletions_for_file(&self, file_path: &str) -> BTreeMap<String, Vec<LineRange>> {
+        self.metadata
+            .deletions
+            .get(file_path)
+            .map(|by_hash| {
+                by_hash
+                    .iter()
+                    .filter_map(|(hash, ranges)| {
+                        parse_line_ranges(ranges)
+                            .ok()
+                            .map(|ranges| (hash.clone(), ranges))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Serialize to the new text format
     pub fn serialize_to_string(&self) -> Result<String, fmt::Error> {
         let mut output = String::new();
@@ -173,6 +265,18 @@ impl AuthorshipLog {
                 output.push_str(&format_line_ranges(&entry.line_ranges));
                 output.push('\n');
             }
+
+            // Anchors follow the entries as unindented lines, which older readers take for
+            // a path with no entries and skip
+            for entry in &file_attestation.entries {
+                if let Some(anchors) = format_anchors(entry) {
+                    output.push_str(ANCHOR_LINE_PREFIX);
+                    output.push_str(&entry.hash);
+                    output.push(' ');
+                    output.push_str(&anchors);
+                    output.push('\n');
+                }
+            }
         }
 
         // Write divider
@@ -208,10 +312,11 @@ impl AuthorshipLog {
         let attestation_lines = &lines[..divider_pos];
         let attestations = parse_attestation_section(attestation_lines)?;
 
-        // Parse JSON metadata section (after divider)
+        // Parse JSON metadata section (after divider), which may be compressed
         let json_lines = &lines[divider_pos + 1..];
         let json_content = json_lines.join("\n");
-        let metadata: AuthorshipMetadata = serde_json::from_str(&json_content)?;
+        let metadata: AuthorshipMetadata =
+            serde_json::from_str(&decode_metadata_section(&json_content)?)?;
 
         Ok(Self {
             attestations,
@@ -228,76 +333,6 @@ impl AuthorshipLog {
         Self::deserialize_from_string(&content)
     }
 
-    /// Lookup the author and optional prompt for a given file and line
-    pub fn get_line_attribution(
-        &self,
-        repo: &Repository,
-        file: &str,
-        line: u32,
-        foreign_prompts_cache: &mut HashMap<String, Option<PromptRecord>>,
-    ) -> Option<(Author, Option<String>, Option<PromptRecord>)> {
-        // Find the file attestation
-        let file_attestation = self.attestations.iter().find(|f| f.file_path == file)?;
-
-        // Check entries in reverse order (latest wins)
-        for entry in file_attestation.entries.iter().rev() {
-            // Check if this line is covered by any of the line ranges
-            let contains = entry.line_ranges.iter().any(|range| range.contains(line));
-            if contains {
-                // The hash corresponds to a prompt session short hash
-                if let Some(prompt_record) = self.metadata.prompts.get(&entry.hash) {
-                    // Create author info from the prompt record
-                    let author = Author {
-                        username: prompt_record.agent_id.tool.clone(),
-                        email: String::new(), // AI agents don't have email
-                    };
-
-                    // Return author and prompt info
-                    return Some((
-                        author,
-                        Some(entry.hash.clone()),
-                        Some(prompt_record.clone()),
-                    ));
-                } else {
-                    // Check cache first before grepping
-                    let prompt_record = if let Some(cached_result) =
-                        foreign_prompts_cache.get(&entry.hash)
-                    {
-                        cached_result.clone()
-                    } else {
-                        // Try to find prompt record using git grep
-                        let shas =
-                            crate::git::refs::grep_ai_notes(repo, &format!("\"{}\"", &entry.hash))
-                                .unwrap_or_default();
-                        let result = if let Some(latest_sha) = shas.first() {
-                            if let Some(authorship_log) =
-                                crate::git::refs::get_authorship(repo, latest_sha)
-                            {
-                                authorship_log.metadata.prompts.get(&entry.hash).cloned()
-                            } else {
-                                None
-                            }
-                        } else {
-                            None
-                        };
-                        // Cache the result (even if None) to avoid repeated grepping
-                        foreign_prompts_cache.insert(entry.hash.clone(), result.clone());
-                        result
-                    };
-
-                    if let Some(prompt_record) = prompt_record {
-                        let author = Author {
-                            username: prompt_record.agent_id.tool.clone(),
-                            email: String::new(), // AI agents don't have email
-                        };
-                        return Some((author, Some(entry.hash.clone()), Some(prompt_record)));
-                    }
-                }
-            }
-        }
-        None
-    }
-
     /// Convert authorship log to working log checkpoints for merge --squash
     ///
     /// Creates one checkpoint per file per session that touched that file. This ensures that:
@@ -555,6 +590,68 @@ fn parse_line_ranges(input: &str) -> Result<Vec<LineRange>, Box<dyn std::error::
     Ok(ranges)
 }
 
+/// Start of an anchor line in the attestation section: `~ <hash> <range>=<anchor>,...`
+///
+/// Paths containing spaces are always quoted, so no file path line can start with this.
+const ANCHOR_LINE_PREFIX: &str = "~ ";
+
+/// Format an entry's anchors as "1-3=ab12cd34,7=9f8e7d6c", ordered like its ranges
+///
+/// Anchors for ranges the entry no longer has are dropped. Returns None if none are left.
+fn format_anchors(entry: &AttestationEntry) -> Option<String> {
+    let mut anchored: Vec<(&LineRange, &str)> = entry
+        .line_ranges
+        .iter()
+        .filter_map(|range| entry.anchor_for(range).map(|anchor| (range, anchor)))
+        .collect();
+    if anchored.is_empty() {
+        return None;
+    }
+    anchored.sort_by_key(|(range, _)| range.start());
+    Some(
+        anchored
+            .into_iter()
+            .map(|(range, anchor)| {
+                format!(
+                    "{}={}",
+                    format_line_ranges(std::slice::from_ref(range)),
+                    anchor
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(","),
+    )
+}
+
+/// Parse an anchor line (without its prefix) onto the first unanchored entry with its hash
+fn attach_anchor_line(
+    file_attestation: &mut FileAttestation,
+    line: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (hash, anchors_str) = line
+        .split_once(' ')
+        .ok_or_else(|| format!("Invalid anchor line format: {}", line))?;
+    let mut anchors = Vec::new();
+    for part in anchors_str.split(',') {
+        let (range_str, anchor) = part
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid anchor format: {}", part))?;
+        let range = parse_line_ranges(range_str)?
+            .pop()
+            .ok_or_else(|| format!("Invalid anchor range: {}", part))?;
+        anchors.push((range, anchor.to_string()));
+    }
+
+    if let Some(entry) = file_attestation
+        .entries
+        .iter_mut()
+        .find(|entry| entry.hash == hash && entry.anchors.is_empty())
+    {
+        entry.anchors = anchors;
+    }
+    Ok(())
+}
+
 /// Parse the attestation section (before the divider)
 fn parse_attestation_section(
     lines: &[&str],
@@ -589,6 +686,11 @@ fn parse_attestation_section(
             } else {
                 return Err(format!("Invalid attestation entry format: {}", entry_line).into());
             }
+        } else if let Some(anchor_line) = line.strip_prefix(ANCHOR_LINE_PREFIX) {
+            let Some(ref mut file_attestation) = current_file else {
+                return Err("Anchor line found without a file path".into());
+            };
+            attach_anchor_line(file_attestation, anchor_line)?;
         } else {
             // File path line (not indented)
             if let Some(file_attestation) = current_file.take()
@@ -620,6 +722,464 @@ fn parse_attestation_section(
     Ok(attestations)
 }
 
+/// Read only the attestation section of a note, stopping at the `---` divider
+///
+/// The metadata JSON below the divider is never read, so callers that only need
+/// line ranges don't pay for notes that embed full transcripts.
+pub fn read_attestations<R: BufRead>(
+    reader: R,
+) -> Result<Vec<FileAttestation>, Box<dyn std::error::Error>> {
+    let mut section: Vec<String> = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line == "---" {
+            let lines: Vec<&str> = section.iter().map(String::as_str).collect();
+            return parse_attestation_section(&lines);
+        }
+        section.push(line);
+    }
+    Err("Missing divider '---' in authorship log".into())
+}
+
+/// Read the schema version from the metadata section that follows the `---` divider
+///
+/// `schema_version` is the first field the metadata is serialized with, so only the start
+/// of the section is read (and decoded, for compressed notes).
+pub fn read_schema_version<R: BufRead>(reader: R) -> Result<String, Box<dyn std::error::Error>> {
+    const PREFIX_LEN: u64 = 4096;
+
+    let mut prefix = Vec::new();
+    metadata_reader(reader)?
+        .take(PREFIX_LEN)
+        .read_to_end(&mut prefix)?;
+    let prefix = String::from_utf8_lossy(&prefix);
+    let value = prefix
+        .split_once("\"schema_version\"")
+        .and_then(|(_, rest)| rest.trim_start().strip_prefix(':'))
+        .ok_or("Missing schema_version in authorship log metadata")?;
+    let version = serde_json::Deserializer::from_str(value)
+        .into_iter::<String>()
+        .next()
+        .ok_or("Missing schema_version in authorship log metadata")??;
+    Ok(version)
+}
+
+/// Parse only the requested prompt records out of a serialized note
+///
+/// The note is streamed: the attestation section is skipped line by line and the metadata is
+/// decoded as it is parsed. Prompts whose hash isn't in `hashes` are skipped by the JSON
+/// parser without being materialized, as is everything else in the metadata section. Notes
+/// of another schema version are an error.
+pub fn read_prompts_for_hashes<R: BufRead>(
+    mut reader: R,
+    hashes: &HashSet<String>,
+) -> Result<BTreeMap<String, PromptRecord>, Box<dyn std::error::Error>> {
+    skip_to_metadata(&mut reader)?;
+
+    let mut deserializer = serde_json::Deserializer::from_reader(metadata_reader(reader)?);
+    let prompts = SelectedMetadataPrompts(hashes).deserialize(&mut deserializer)?;
+    Ok(prompts)
+}
+
+/// Read only `metadata.deletions` out of a serialized note, streaming it like
+/// `read_prompts_for_hashes`
+pub fn read_deletions<R: BufRead>(
+    mut reader: R,
+) -> Result<BTreeMap<String, BTreeMap<String, String>>, Box<dyn std::error::Error>> {
+    skip_to_metadata(&mut reader)?;
+
+    let mut deserializer = serde_json::Deserializer::from_reader(metadata_reader(reader)?);
+    let deletions = MetadataDeletions.deserialize(&mut deserializer)?;
+    Ok(deletions)
+}
+
+/// Advance `reader` past the `---` divider
+fn skip_to_metadata<R: BufRead>(reader: &mut R) -> Result<(), Box<dyn std::error::Error>> {
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Err("Missing divider '---' in authorship log".into());
+        }
+        if line.trim_ascii_end() == b"---" {
+            return Ok(());
+        }
+    }
+}
+
+/// The metadata JSON of a note, read from just past the divider and decoded as it's read
+/// if it was written compressed
+fn metadata_reader<'a, R: BufRead + 'a>(
+    mut reader: R,
+) -> Result<Box<dyn Read + 'a>, Box<dyn std::error::Error>> {
+    let mut first_line = String::new();
+    reader.read_line(&mut first_line)?;
+    let Some(header) = first_line.strip_prefix(ENCODING_HEADER_PREFIX) else {
+        return Ok(Box::new(std::io::Cursor::new(first_line).chain(reader)));
+    };
+    let encoding = header
+        .trim()
+        .strip_suffix("+base64")
+        .and_then(MetadataEncoding::from_name)
+        .ok_or_else(|| format!("Unsupported metadata encoding '{}'", header.trim()))?;
+    Ok(encoding.decoder(Base64LinesReader::new(reader))?)
+}
+
+/// Decodes line-wrapped base64 as it is read
+struct Base64LinesReader<R> {
+    inner: R,
+    /// Base64 characters not decoded yet, fewer than a 4-character group after each refill
+    pending: Vec<u8>,
+    decoded: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: BufRead> Base64LinesReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            pending: Vec::new(),
+            decoded: Vec::new(),
+            pos: 0,
+        }
+    }
+}
+
+impl<R: BufRead> Read for Base64LinesReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.pos == self.decoded.len() {
+            let mut line = Vec::new();
+            let at_end = self.inner.read_until(b'\n', &mut line)? == 0;
+            self.pending
+                .extend(line.iter().filter(|byte| !byte.is_ascii_whitespace()));
+            let ready = if at_end {
+                self.pending.len()
+            } else {
+                self.pending.len() / 4 * 4
+            };
+            if ready == 0 {
+                if at_end {
+                    return Ok(0);
+                }
+                continue;
+            }
+            self.decoded = BASE64_STANDARD
+                .decode(&self.pending[..ready])
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+            self.pending.drain(..ready);
+            self.pos = 0;
+        }
+
+        let len = buf.len().min(self.decoded.len() - self.pos);
+        buf[..len].copy_from_slice(&self.decoded[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+/// Visits the metadata object, descending only into `prompts`
+#[derive(Clone, Copy)]
+struct SelectedMetadataPrompts<'a>(&'a HashSet<String>);
+
+/// Visits the `prompts` map, deserializing only the selected hashes
+#[derive(Clone, Copy)]
+struct SelectedPrompts<'a>(&'a HashSet<String>);
+
+impl<'de> DeserializeSeed<'de> for SelectedMetadataPrompts<'_> {
+    type Value = BTreeMap<String, PromptRecord>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for SelectedMetadataPrompts<'_> {
+    type Value = BTreeMap<String, PromptRecord>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("authorship metadata object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut prompts = BTreeMap::new();
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "schema_version" {
+                check_schema_version::<A::Error>(&map.next_value::<String>()?)?;
+            } else if key == "prompts" {
+                prompts = map.next_value_seed(SelectedPrompts(self.0))?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(prompts)
+    }
+}
+
+/// Visits the metadata object, reading only `deletions`
+#[derive(Clone, Copy)]
+struct MetadataDeletions;
+
+impl<'de> DeserializeSeed<'de> for MetadataDeletions {
+    type Value = BTreeMap<String, BTreeMap<String, String>>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for MetadataDeletions {
+    type Value = BTreeMap<String, BTreeMap<String, String>>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("authorship metadata object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut deletions = BTreeMap::new();
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "schema_version" {
+                check_schema_version::<A::Error>(&map.next_value::<String>()?)?;
+            } else if key == "deletions" {
+                deletions = map.next_value()?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(deletions)
+    }
+}
+
+/// Reject metadata of another schema version while it is being visited
+fn check_schema_version<E: serde::de::Error>(version: &str) -> Result<(), E> {
+    if version != AUTHORSHIP_LOG_VERSION {
+        return Err(E::custom(format!(
+            "Unsupported authorship log version: {} (expected: {})",
+            version, AUTHORSHIP_LOG_VERSION
+        )));
+    }
+    Ok(())
+}
+
+impl<'de> DeserializeSeed<'de> for SelectedPrompts<'_> {
+    type Value = BTreeMap<String, PromptRecord>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for SelectedPrompts<'_> {
+    type Value = BTreeMap<String, PromptRecord>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("map of prompt records")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut prompts = BTreeMap::new();
+        while let Some(hash) = map.next_key::<String>()? {
+            if self.0.contains(&hash) {
+                let record = map.next_value::<PromptRecord>()?;
+                prompts.insert(hash, record);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(prompts)
+    }
+}
+
+/// Git config key choosing how note metadata is written: "zstd", "deflate" or "none"
+pub const NOTE_ENCODING_CONFIG: &str = "gitai.noteEncoding";
+
+/// First line of an encoded metadata section, followed by the codec name and "+base64"
+const ENCODING_HEADER_PREFIX: &str = "encoding: ";
+
+/// Wrap base64 like MIME so notes stay diffable and friendly to line-based tools
+const BASE64_LINE_WIDTH: usize = 76;
+
+/// Compression applied to the metadata section of a note
+///
+/// The attestation section is always plain text so blame can read it without decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataEncoding {
+    Zstd,
+    Deflate,
+}
+
+impl MetadataEncoding {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "zstd" => Some(MetadataEncoding::Zstd),
+            "deflate" => Some(MetadataEncoding::Deflate),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MetadataEncoding::Zstd => "zstd",
+            MetadataEncoding::Deflate => "deflate",
+        }
+    }
+
+    fn compress(&self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            MetadataEncoding::Zstd => zstd::encode_all(data, 0),
+            MetadataEncoding::Deflate => {
+                let mut encoder =
+                    flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+        }
+    }
+
+    /// Decompress `reader` as it is read
+    fn decoder<'a, R: Read + 'a>(&self, reader: R) -> std::io::Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            MetadataEncoding::Zstd => Box::new(zstd::stream::read::Decoder::new(reader)?),
+            MetadataEncoding::Deflate => Box::new(flate2::read::DeflateDecoder::new(reader)),
+        })
+    }
+
+    fn decompress(&self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            MetadataEncoding::Zstd => zstd::decode_all(data),
+            MetadataEncoding::Deflate => {
+                let mut decoded = Vec::new();
+                flate2::read::DeflateDecoder::new(data).read_to_end(&mut decoded)?;
+                Ok(decoded)
+            }
+        }
+    }
+}
+
+/// Split a serialized note into its attestation section and metadata section
+fn split_note(content: &str) -> Option<(&str, &str)> {
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let end = offset + line.len();
+        if line.trim_end_matches(['\r', '\n']) == "---" {
+            return Some((&content[..offset], &content[end..]));
+        }
+        offset = end;
+    }
+    None
+}
+
+/// The metadata JSON of a note, decoding it first if it was written compressed
+fn decode_metadata_section(section: &str) -> Result<Cow<'_, str>, Box<dyn std::error::Error>> {
+    let Some(rest) = section.strip_prefix(ENCODING_HEADER_PREFIX) else {
+        return Ok(Cow::Borrowed(section));
+    };
+    let (header, body) = rest.split_once('\n').unwrap_or((rest, ""));
+    let encoding = header
+        .trim()
+        .strip_suffix("+base64")
+        .and_then(MetadataEncoding::from_name)
+        .ok_or_else(|| format!("Unsupported metadata encoding '{}'", header.trim()))?;
+
+    let compressed = BASE64_STANDARD.decode(body.split_whitespace().collect::<String>())?;
+    let json = String::from_utf8(encoding.decompress(&compressed)?)?;
+    Ok(Cow::Owned(json))
+}
+
+/// Compress the metadata section of a serialized note, leaving attestations as plain text
+///
+/// Notes that are already encoded, or that have no divider, are returned unchanged.
+pub fn encode_note(content: &str, encoding: MetadataEncoding) -> Result<String, GitAiError> {
+    let Some((attestations, metadata)) = split_note(content) else {
+        return Ok(content.to_string());
+    };
+    if metadata.starts_with(ENCODING_HEADER_PREFIX) {
+        return Ok(content.to_string());
+    }
+
+    let encoded = BASE64_STANDARD.encode(encoding.compress(metadata.as_bytes())?);
+    let mut output = String::with_capacity(attestations.len() + encoded.len() + 64);
+    output.push_str(attestations);
+    output.push_str("---\n");
+    output.push_str(ENCODING_HEADER_PREFIX);
+    output.push_str(encoding.name());
+    output.push_str("+base64");
+    for chunk in encoded.as_bytes().chunks(BASE64_LINE_WIDTH) {
+        output.push('\n');
+        // Base64 output is ASCII, so every chunk boundary is a char boundary
+        output.push_str(std::str::from_utf8(chunk).unwrap_or_default());
+    }
+    Ok(output)
+}
+
+/// A serialized note with its metadata section decoded back to plain JSON
+pub fn decode_note(content: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let Some((attestations, metadata)) = split_note(content) else {
+        return Ok(content.to_string());
+    };
+    Ok(format!(
+        "{}---\n{}",
+        attestations,
+        decode_metadata_section(metadata)?
+    ))
+}
+
+/// Encode a serialized note as configured by `gitai.noteEncoding`, otherwise return it untouched
+///
+/// Encoding failures are logged and the note is written as plain text.
+pub fn encode_note_if_configured(repo: &Repository, note_content: &str) -> String {
+    let Some(name) = repo
+        .config_get_str(NOTE_ENCODING_CONFIG)
+        .ok()
+        .flatten()
+        .map(|name| name.trim().to_lowercase())
+    else {
+        return note_content.to_string();
+    };
+    if name.is_empty() || name == "none" {
+        return note_content.to_string();
+    }
+
+    let Some(encoding) = MetadataEncoding::from_name(&name) else {
+        eprintln!(
+            "Warning: unknown {} '{}', writing authorship note uncompressed",
+            NOTE_ENCODING_CONFIG, name
+        );
+        return note_content.to_string();
+    };
+
+    encode_note(note_content, encoding).unwrap_or_else(|e| {
+        debug_log(&format!("Failed to encode authorship note: {}", e));
+        note_content.to_string()
+    })
+}
+
+/// Find a prompt record that lives in another commit's note (e.g. after a rebase or squash)
+///
+/// Results, including misses, are cached by hash to avoid grepping the notes ref repeatedly.
+pub fn lookup_foreign_prompt(
+    repo: &Repository,
+    hash: &str,
+    foreign_prompts_cache: &mut HashMap<String, Option<PromptRecord>>,
+) -> Option<PromptRecord> {
+    if let Some(cached_result) = foreign_prompts_cache.get(hash) {
+        return cached_result.clone();
+    }
+
+    // grep_ai_notes returns commits sorted by date (newest first). The bare hash also
+    // matches attestation lines, which stay greppable when the metadata is compressed,
+    // so take the newest of those notes that actually carries the prompt.
+    let shas = crate::git::refs::grep_ai_notes(repo, hash).unwrap_or_default();
+    let wanted = HashSet::from([hash.to_string()]);
+    let result = shas.iter().find_map(|sha| {
+        crate::git::refs::get_authorship_prompts(repo, sha, &wanted)
+            .and_then(|mut prompts| prompts.remove(hash))
+    });
+
+    // Cache the result (even if None) to avoid repeated grepping
+    foreign_prompts_cache.insert(hash.to_string(), result.clone());
+    result
+}
+
 /// Check if a file path needs quoting (contains spaces or whitespace)
 fn needs_quoting(path: &str) -> bool {
     path.contains(' ') || path.contains('\t') || path.contains('\n')
@@ -1344,4 +1904,218 @@ mod tests {
             .sum();
         assert_eq!(lines_session2, 20);
     }
+
+    fn log_with_two_prompts() -> (AuthorshipLog, String, String) {
+        let mut log = AuthorshipLog::new();
+        let mut hashes = Vec::new();
+        for session in ["session_a", "session_b"] {
+            let agent_id = crate::authorship::working_log::AgentId {
+                tool: "cursor".to_string(),
+                id: session.to_string(),
+                model: "claude-3-sonnet".to_string(),
+            };
+            let prompt_hash = generate_short_hash(&agent_id.id, &agent_id.tool);
+            log.metadata.prompts.insert(
+                prompt_hash.clone(),
+                crate::authorship::authorship_log::PromptRecord {
+                    agent_id,
+                    human_author: None,
+                    messages: vec![],
+                    total_additions: 0,
+                    total_deletions: 0,
+                    accepted_lines: 0,
+                    overriden_lines: 0,
+                    messages_url: None,
+                },
+            );
+            hashes.push(prompt_hash);
+        }
+
+        let mut file1 = FileAttestation::new("src/my file.rs".to_string());
+        file1.add_entry(AttestationEntry::new(
+            hashes[0].clone(),
+            vec![LineRange::Range(1, 10)],
+        ));
+        file1.add_entry(AttestationEntry::new(
+            hashes[1].clone(),
+            vec![LineRange::Range(5, 6)],
+        ));
+        log.attestations.push(file1);
+
+        let (a, b) = (hashes[0].clone(), hashes[1].clone());
+        (log, a, b)
+    }
+
+    #[test]
+    fn test_read_attestations_stops_at_divider() {
+        let (log, _, _) = log_with_two_prompts();
+        let serialized = log.serialize_to_string().unwrap();
+
+        let attestations = read_attestations(serialized.as_bytes()).unwrap();
+        assert_eq!(attestations, log.attestations);
+
+        // Anything after the divider is never read, even if it isn't valid JSON
+        let truncated = format!(
+            "{}\n---\n{{ not json",
+            serialized.split("\n---\n").next().unwrap()
+        );
+        assert_eq!(
+            read_attestations(truncated.as_bytes()).unwrap(),
+            log.attestations
+        );
+
+        assert!(read_attestations("src/main.rs\n  abc 1\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_read_prompts_for_hashes_only_returns_requested() {
+        let (log, hash_a, hash_b) = log_with_two_prompts();
+        let serialized = log.serialize_to_string().unwrap();
+
+        let wanted = HashSet::from([hash_b.clone()]);
+        let prompts = read_prompts_for_hashes(serialized.as_bytes(), &wanted).unwrap();
+        assert_eq!(prompts.len(), 1);
+        assert_eq!(prompts.get(&hash_b), log.metadata.prompts.get(&hash_b));
+        assert!(!prompts.contains_key(&hash_a));
+
+        let none = read_prompts_for_hashes(serialized.as_bytes(), &HashSet::new()).unwrap();
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn test_read_deletions_only() {
+        let (mut log, _, _) = log_with_two_prompts();
+        assert!(
+            read_deletions(log.serialize_to_string().unwrap().as_bytes())
+                .unwrap()
+                .is_empty()
+        );
+
+        log.record_deletions("src/lib.rs", "abcd", &[LineRange::Range(5, 7)]);
+        let plain = log.serialize_to_string().unwrap();
+        for note in [
+            plain.clone(),
+            encode_note(&plain, MetadataEncoding::Zstd).unwrap(),
+        ] {
+            assert_eq!(
+                read_deletions(note.as_bytes()).unwrap(),
+                log.metadata.deletions
+            );
+        }
+
+        let other_version = plain.replace(AUTHORSHIP_LOG_VERSION, "authorship/2.0.0");
+        assert!(read_deletions(other_version.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_streaming_readers_check_schema_version() {
+        let (log, _, hash_b) = log_with_two_prompts();
+        let plain = log.serialize_to_string().unwrap();
+        let other_version = plain.replace(AUTHORSHIP_LOG_VERSION, "authorship/2.0.0");
+        let wanted = HashSet::from([hash_b]);
+
+        for encoding in [
+            None,
+            Some(MetadataEncoding::Zstd),
+            Some(MetadataEncoding::Deflate),
+        ] {
+            let encode = |note: &str| match encoding {
+                Some(encoding) => encode_note(note, encoding).unwrap(),
+                None => note.to_string(),
+            };
+
+            let note = encode(&plain);
+            let mut reader = note.as_bytes();
+            read_attestations(&mut reader).unwrap();
+            assert_eq!(read_schema_version(reader).unwrap(), AUTHORSHIP_LOG_VERSION);
+            assert_eq!(
+                read_prompts_for_hashes(note.as_bytes(), &wanted)
+                    .unwrap()
+                    .len(),
+                1
+            );
+
+            let note = encode(&other_version);
+            let mut reader = note.as_bytes();
+            read_attestations(&mut reader).unwrap();
+            assert_eq!(read_schema_version(reader).unwrap(), "authorship/2.0.0");
+            assert!(read_prompts_for_hashes(note.as_bytes(), &wanted).is_err());
+        }
+    }
+
+    #[test]
+    fn test_record_deletions_roundtrip() {
+        let mut log = AuthorshipLog::new();
+        log.record_deletions("src/lib.rs", "abcd", &[LineRange::Range(5, 7)]);
+        log.record_deletions(
+            "src/lib.rs",
+            "abcd",
+            &[LineRange::Single(8), LineRange::Single(2)],
+        );
+        assert_eq!(log.metadata.deletions["src/lib.rs"]["abcd"], "2,5-8");
+
+        let parsed =
+            AuthorshipLog::deserialize_from_string(&log.serialize_to_string().unwrap()).unwrap();
+        assert_eq!(
+            parsed.deletions_for_file("src/lib.rs"),
+            BTreeMap::from([(
+                "abcd".to_string(),
+                vec![LineRange::Single(2), LineRange::Range(5, 8)]
+            )])
+        );
+        assert!(parsed.deletions_for_file("missing.rs").is_empty());
+    }
+
+    #[test]
+    fn test_hashes_for_line_latest_first() {
+        let (log, hash_a, hash_b) = log_with_two_prompts();
+        let file = &log.attestations[0];
+        assert_eq!(file.hashes_for_line(5), vec![hash_b, hash_a.clone()]);
+        assert_eq!(file.hashes_for_line(1), vec![hash_a]);
+        assert!(file.hashes_for_line(11).is_empty());
+    }
+
+    #[test]
+    fn test_encoded_notes_roundtrip() {
+        let (log, hash_a, hash_b) = log_with_two_prompts();
+        let plain = log.serialize_to_string().unwrap();
+
+        for encoding in [MetadataEncoding::Zstd, MetadataEncoding::Deflate] {
+            let encoded = encode_note(&plain, encoding).unwrap();
+            let (attestations, metadata) = split_note(&encoded).unwrap();
+            assert_eq!(attestations, split_note(&plain).unwrap().0);
+            assert!(metadata.starts_with(&format!("encoding: {}+base64\n", encoding.name())));
+            assert!(metadata.lines().all(|line| line.len() <= BASE64_LINE_WIDTH));
+
+            // Encoding is idempotent and decoding restores the plain note
+            assert_eq!(encode_note(&encoded, encoding).unwrap(), encoded);
+            assert_eq!(decode_note(&encoded).unwrap(), plain);
+            assert_eq!(
+                AuthorshipLog::deserialize_from_string(&encoded).unwrap(),
+                log
+            );
+
+            // Fast blame reads attestations without touching the encoded metadata
+            assert_eq!(
+                read_attestations(encoded.as_bytes()).unwrap(),
+                log.attestations
+            );
+            let prompts =
+                read_prompts_for_hashes(encoded.as_bytes(), &HashSet::from([hash_b.clone()]))
+                    .unwrap();
+            assert_eq!(prompts.get(&hash_b), log.metadata.prompts.get(&hash_b));
+            assert!(!prompts.contains_key(&hash_a));
+        }
+
+        // Plain notes decode to themselves
+        assert_eq!(decode_note(&plain).unwrap(), plain);
+    }
+
+    #[test]
+    fn test_unknown_metadata_encoding_is_an_error() {
+        let note = "src/main.rs\n  abcd 1\n---\nencoding: brotli+base64\nAAAA\n";
+        assert!(AuthorshipLog::deserialize_from_string(note).is_err());
+        assert!(decode_note(note).is_err());
+        assert_eq!(MetadataEncoding::from_name("brotli"), None);
+    }
 }
@@ -20,7 +20,23 @@ pub fn diff_ai_accepted_stats(
     ignore_patterns: &[String],
 ) -> Result<DiffAiAcceptedStats, GitAiError> {
     let added_lines_by_file = repo.diff_added_lines(from_ref, to_ref, None)?;
+    ai_accepted_stats_for_lines(
+        repo,
+        added_lines_by_file,
+        to_ref,
+        oldest_commit,
+        ignore_patterns,
+    )
+}
 
+/// Count the given lines of `to_ref` whose blame lands on an AI prompt
+pub fn ai_accepted_stats_for_lines(
+    repo: &Repository,
+    added_lines_by_file: HashMap<String, Vec<u32>>,
+    to_ref: &str,
+    oldest_commit: Option<&str>,
+    ignore_patterns: &[String],
+) -> Result<DiffAiAcceptedStats, GitAiError> {
     let mut stats = DiffAiAcceptedStats::default();
 
     for (file_path, mut lines) in added_lines_by_file {
@@ -0,0 +1,365 @@
+use crate::authorship::stats::{
+    CommitStats, add_commit_stats, stats_for_commit_files, stats_for_commit_stats,
+};
+use crate::error::GitAiError;
+use crate::git::repository::{CommitRange, Repository, exec_git_stdin};
+use chrono::{DateTime, Datelike};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// What `stats <range> --group-by` splits the range's stats by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Author,
+    Tool,
+    Model,
+    Week,
+    Month,
+    Directory,
+}
+
+impl GroupBy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "author" => Some(GroupBy::Author),
+            "tool" => Some(GroupBy::Tool),
+            "model" => Some(GroupBy::Model),
+            "week" => Some(GroupBy::Week),
+            "month" => Some(GroupBy::Month),
+            "directory" | "dir" => Some(GroupBy::Directory),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GroupBy::Author => "author",
+            GroupBy::Tool => "tool",
+            GroupBy::Model => "model",
+            GroupBy::Week => "week",
+            GroupBy::Month => "month",
+            GroupBy::Directory => "directory",
+        }
+    }
+}
+
+/// Output format for grouped stats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupedStatsFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsGroup {
+    pub key: String,
+    /// Commits that contributed to the group
+    pub commits: usize,
+    pub stats: CommitStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupedRangeStats {
+    pub group_by: String,
+    pub groups: Vec<StatsGroup>,
+}
+
+/// Group key for commits without any AI when grouping by tool or model
+const NO_AI_GROUP: &str = "none";
+
+/// Group key for files at the repository root when grouping by directory
+const ROOT_DIRECTORY_GROUP: &str = ".";
+
+/// Compute the stats of every commit in the range and sum them per group
+///
+/// Tool and model groups only get the AI fields of the tool/model breakdown; commits without
+/// AI go to the "none" group with all their lines. Directory groups are keyed by top-level
+/// directory, each counting only the commit's files below it.
+pub fn grouped_range_stats(
+    commit_range: CommitRange,
+    group_by: GroupBy,
+    ignore_patterns: &[String],
+) -> Result<GroupedRangeStats, GitAiError> {
+    commit_range.is_valid()?;
+    let repo = commit_range.repo();
+    let commits = commit_metadata(repo, &commit_range.all_commits())?;
+
+    let mut groups: BTreeMap<String, (usize, CommitStats)> = BTreeMap::new();
+    let mut add = |key: String, stats: &CommitStats| {
+        let group = groups.entry(key).or_default();
+        group.0 += 1;
+        add_commit_stats(&mut group.1, stats);
+    };
+
+    for commit in &commits {
+        if group_by == GroupBy::Directory {
+            for (directory, stats) in directory_stats(repo, &commit.sha, ignore_patterns)? {
+                add(directory, &stats);
+            }
+            continue;
+        }
+
+        let stats = stats_for_commit_stats(repo, &commit.sha, ignore_patterns)?;
+        match group_by {
+            GroupBy::Author => add(commit.author.clone(), &stats),
+            GroupBy::Week => add(week_key(&commit.date), &stats),
+            GroupBy::Month => add(month_key(&commit.date), &stats),
+            GroupBy::Tool | GroupBy::Model => {
+                if stats.tool_model_breakdown.is_empty() {
+                    add(NO_AI_GROUP.to_string(), &stats);
+                    continue;
+                }
+                // A commit counts once per tool even when several of its models match
+                let mut per_key: BTreeMap<String, CommitStats> = BTreeMap::new();
+                for (tool_model, tool_stats) in &stats.tool_model_breakdown {
+                    let (tool, model) = tool_model.split_once("::").unwrap_or((tool_model, ""));
+                    let key = if group_by == GroupBy::Tool {
+                        tool
+                    } else {
+                        model
+                    };
+                    let group = per_key.entry(key.to_string()).or_default();
+                    group.ai_additions += tool_stats.ai_additions;
+                    group.mixed_additions += tool_stats.mixed_additions;
+                    group.ai_accepted += tool_stats.ai_accepted;
+                    group.total_ai_additions += tool_stats.total_ai_additions;
+                    group.total_ai_deletions += tool_stats.total_ai_deletions;
+                    group.time_waiting_for_ai += tool_stats.time_waiting_for_ai;
+                    group
+                        .tool_model_breakdown
+                        .insert(tool_model.clone(), tool_stats.clone());
+                }
+                for (key, group_stats) in per_key {
+                    add(key, &group_stats);
+                }
+            }
+            GroupBy::Directory => unreachable!("directories are handled above"),
+        }
+    }
+
+    Ok(GroupedRangeStats {
+        group_by: group_by.as_str().to_string(),
+        groups: groups
+            .into_iter()
+            .map(|(key, (commits, stats))| StatsGroup {
+                key,
+                commits,
+                stats,
+            })
+            .collect(),
+    })
+}
+
+struct CommitMetadata {
+    sha: String,
+    author: String,
+    /// Author date, ISO 8601
+    date: String,
+}
+
+fn commit_metadata(
+    repo: &Repository,
+    commits: &[String],
+) -> Result<Vec<CommitMetadata>, GitAiError> {
+    if commits.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut args = repo.global_args_for_exec();
+    args.push("log".to_string());
+    args.push("--no-walk=unsorted".to_string());
+    args.push("--stdin".to_string());
+    args.push("-z".to_string());
+    args.push("--format=%H%x1f%an <%ae>%x1f%aI".to_string());
+    let output = exec_git_stdin(&args, format!("{}\n", commits.join("\n")).as_bytes())?;
+
+    Ok(String::from_utf8(output.stdout)?
+        .split('\0')
+        .filter_map(|record| {
+            let mut fields = record.trim().splitn(3, '\x1f');
+            let sha = fields.next().filter(|sha| !sha.is_empty())?;
+            Some(CommitMetadata {
+                sha: sha.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// ISO week of an ISO 8601 date, e.g. "2025-W07"
+pub fn week_key(date: &str) -> String {
+    match DateTime::parse_from_rfc3339(date) {
+        Ok(date) => {
+            let week = date.iso_week();
+            format!("{}-W{:02}", week.year(), week.week())
+        }
+        Err(_) => date.to_string(),
+    }
+}
+
+/// Month of an ISO 8601 date, e.g. "2025-02"
+pub fn month_key(date: &str) -> String {
+    date.get(..7).unwrap_or(date).to_string()
+}
+
+/// Top-level directory of a path, "." for files at the root
+pub fn top_level_directory(path: &str) -> String {
+    match path.split_once('/') {
+        Some((directory, _)) => directory.to_string(),
+        None => ROOT_DIRECTORY_GROUP.to_string(),
+    }
+}
+
+/// The commit's stats split by the top-level directory of the files it changed
+fn directory_stats(
+    repo: &Repository,
+    commit_sha: &str,
+    ignore_patterns: &[String],
+) -> Result<Vec<(String, CommitStats)>, GitAiError> {
+    let files = repo.list_commit_files(commit_sha, None)?;
+    let directories: BTreeSet<String> = files.iter().map(|f| top_level_directory(f)).collect();
+
+    let mut stats = Vec::new();
+    for directory in directories {
+        // Anchored exact paths, so a file elsewhere with the same name isn't ignored too
+        let mut patterns = ignore_patterns.to_vec();
+        patterns.extend(
+            files
+                .iter()
+                .filter(|file| top_level_directory(file) != directory)
+                .map(|file| format!("/{}", glob::Pattern::escape(file))),
+        );
+        stats.push((
+            directory,
+            stats_for_commit_files(repo, commit_sha, &patterns)?,
+        ));
+    }
+    Ok(stats)
+}
+
+/// AI lines as a percentage of the added lines, "-" for groups without line totals
+fn ai_percent(stats: &CommitStats) -> String {
+    if stats.git_diff_added_lines == 0 {
+        return "-".to_string();
+    }
+    let percent = stats.ai_additions as f64 / stats.git_diff_added_lines as f64 * 100.0;
+    format!("{:.0}%", percent.min(100.0))
+}
+
+const CSV_HEADER: &str = "group,commits,git_diff_added_lines,git_diff_deleted_lines,human_additions,ai_additions,mixed_additions,ai_accepted,total_ai_additions,total_ai_deletions,time_waiting_for_ai";
+
+/// Render grouped stats as a table, CSV or JSON
+pub fn format_grouped_stats(grouped: &GroupedRangeStats, format: GroupedStatsFormat) -> String {
+    match format {
+        GroupedStatsFormat::Json => serde_json::to_string(grouped).unwrap_or_default(),
+        GroupedStatsFormat::Csv => {
+            let mut output = String::from(CSV_HEADER);
+            output.push('\n');
+            for group in &grouped.groups {
+                let stats = &group.stats;
+                output.push_str(&format!(
+                    "{},{},{},{},{},{},{},{},{},{},{}\n",
+                    csv_field(&group.key),
+                    group.commits,
+                    stats.git_diff_added_lines,
+                    stats.git_diff_deleted_lines,
+                    stats.human_additions,
+                    stats.ai_additions,
+                    stats.mixed_additions,
+                    stats.ai_accepted,
+                    stats.total_ai_additions,
+                    stats.total_ai_deletions,
+                    stats.time_waiting_for_ai
+                ));
+            }
+            output
+        }
+        GroupedStatsFormat::Table => {
+            let key_width = grouped
+                .groups
+                .iter()
+                .map(|group| group.key.chars().count())
+                .chain(std::iter::once(grouped.group_by.len()))
+                .max()
+                .unwrap_or(0);
+            let mut output = format!(
+                "{:<key_width$}  {:>7}  {:>7}  {:>7}  {:>7}  {:>7}  {:>7}  {:>5}  {:>8}\n",
+                grouped.group_by,
+                "commits",
+                "added",
+                "human",
+                "ai",
+                "mixed",
+                "accepted",
+                "ai %",
+                "waiting",
+            );
+            for group in &grouped.groups {
+                let stats = &group.stats;
+                output.push_str(&format!(
+                    "{:<key_width$}  {:>7}  {:>7}  {:>7}  {:>7}  {:>7}  {:>7}  {:>5}  {:>7}s\n",
+                    group.key,
+                    group.commits,
+                    stats.git_diff_added_lines,
+                    stats.human_additions,
+                    stats.ai_additions,
+                    stats.mixed_additions,
+                    stats.ai_accepted,
+                    ai_percent(stats),
+                    stats.time_waiting_for_ai,
+                ));
+            }
+            output
+        }
+    }
+}
+
+/// Quote a CSV field when it contains a separator, quote or newline
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_keys() {
+        assert_eq!(week_key("2025-01-01T10:00:00+01:00"), "2025-W01");
+        assert_eq!(week_key("2024-12-30T10:00:00Z"), "2025-W01");
+        assert_eq!(month_key("2025-02-14T10:00:00Z"), "2025-02");
+        assert_eq!(top_level_directory("src/commands/log.rs"), "src");
+        assert_eq!(top_level_directory("README.md"), ".");
+    }
+
+    #[test]
+    fn test_format_grouped_stats_csv() {
+        let grouped = GroupedRangeStats {
+            group_by: "author".to_string(),
+            groups: vec![StatsGroup {
+                key: "Doe, Jane <jane@example.com>".to_string(),
+                commits: 2,
+                stats: CommitStats {
+                    git_diff_added_lines: 10,
+                    human_additions: 4,
+                    ai_additions: 6,
+                    ai_accepted: 6,
+                    ..Default::default()
+                },
+            }],
+        };
+        let csv = format_grouped_stats(&grouped, GroupedStatsFormat::Csv);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(
+            lines[1],
+            "\"Doe, Jane <jane@example.com>\",2,10,0,4,6,0,6,0,0,0"
+        );
+    }
+}
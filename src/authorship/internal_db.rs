@@ -909,6 +909,21 @@ impl InternalDatabase {
         Ok(hash)
     }
 
+    /// Get the canonical JSON of a CAS object that is still waiting in the sync queue
+    pub fn get_cas_object(&self, hash: &str) -> Result<Option<String>, GitAiError> {
+        let result = self.conn.query_row(
+            "SELECT data FROM cas_sync_queue WHERE hash = ?1",
+            params![hash],
+            |row| row.get(0),
+        );
+
+        match result {
+            Ok(data) => Ok(Some(data)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
     /// Dequeue a batch of CAS objects for syncing (with lock acquisition)
     pub fn dequeue_cas_batch(
         &mut self,
@@ -1368,6 +1383,21 @@ mod tests {
         assert_eq!(metadata, "{}");
     }
 
+    #[test]
+    fn test_get_cas_object() {
+        let (mut db, _temp_dir) = create_test_db();
+
+        let json_data = serde_json::json!({"messages": [{"type": "user", "text": "hi"}]});
+        let hash = db.enqueue_cas_object(&json_data, None).unwrap();
+
+        let stored = db.get_cas_object(&hash).unwrap().unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&stored).unwrap(),
+            json_data
+        );
+        assert!(db.get_cas_object("missing").unwrap().is_none());
+    }
+
     #[test]
     fn test_enqueue_duplicate_hash() {
         let (mut db, _temp_dir) = create_test_db();
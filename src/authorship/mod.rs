@@ -1,16 +1,21 @@
+pub mod anchors;
 pub mod attribution_tracker;
 pub mod authorship_log;
 pub mod authorship_log_serialization;
 pub mod diff_ai_accepted;
+pub mod grouped_stats;
 pub mod imara_diff_utils;
 pub mod internal_db;
 pub mod move_detection;
+pub mod notes_merge;
+pub mod patch_authorship;
 pub mod post_commit;
 pub mod pre_commit;
 pub mod prompt_utils;
 pub mod range_authorship;
 pub mod rebase_authorship;
 pub mod secrets;
+pub mod signing;
 pub mod stats;
 pub mod transcript;
 pub mod virtual_attribution;
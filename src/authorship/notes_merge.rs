@@ -0,0 +1,270 @@
+use crate::authorship::authorship_log::{LineRange, PromptRecord};
+use crate::authorship::authorship_log_serialization::{
+    AUTHORSHIP_LOG_VERSION, AttestationEntry, AuthorshipLog, FileAttestation, GIT_AI_VERSION,
+};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Union two notes attached to the same commit
+///
+/// Used when two clones wrote different notes for one commit. Every attributed line from
+/// either side is kept. The result doesn't depend on which side is "ours", so clones that
+/// merge each other's notes converge on the same note:
+/// - prompts present on both sides keep the newest record (see `newness_key`)
+/// - a line attributed to different prompts goes to the newer prompt, then the larger hash
+/// - deleted lines are kept per prompt from both sides
+pub fn union_authorship_logs(ours: &AuthorshipLog, theirs: &AuthorshipLog) -> AuthorshipLog {
+    let prompts = union_prompts(&ours.metadata.prompts, &theirs.metadata.prompts);
+
+    let file_paths: BTreeSet<&str> = ours
+        .attestations
+        .iter()
+        .chain(theirs.attestations.iter())
+        .map(|file| file.file_path.as_str())
+        .collect();
+
+    let mut attestations = Vec::new();
+    for file_path in file_paths {
+        let mut line_owners = line_owners_of(ours, file_path);
+        for (line, their_hash) in line_owners_of(theirs, file_path) {
+            match line_owners.get(&line) {
+                Some(our_hash) if compare_hashes(our_hash, &their_hash, &prompts).is_ge() => {}
+                _ => {
+                    line_owners.insert(line, their_hash);
+                }
+            }
+        }
+
+        let mut lines_by_hash: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+        for (line, hash) in line_owners {
+            lines_by_hash.entry(hash).or_default().push(line);
+        }
+
+        let mut file = FileAttestation::new(file_path.to_string());
+        for (hash, lines) in lines_by_hash {
+            file.add_entry(AttestationEntry::new(
+                hash,
+                LineRange::compress_lines(&lines),
+            ));
+        }
+        if !file.entries.is_empty() {
+            attestations.push(file);
+        }
+    }
+
+    let mut merged = AuthorshipLog::new();
+    merged.attestations = attestations;
+    merged.metadata.schema_version = AUTHORSHIP_LOG_VERSION.to_string();
+    merged.metadata.git_ai_version = Some(GIT_AI_VERSION.to_string());
+    merged.metadata.base_commit_sha = ours.metadata.base_commit_sha.clone();
+    merged.metadata.prompts = prompts;
+    for log in [ours, theirs] {
+        for file_path in log.metadata.deletions.keys() {
+            for (hash, ranges) in log.deletions_for_file(file_path) {
+                merged.record_deletions(file_path, &hash, &ranges);
+            }
+        }
+    }
+    merged
+}
+
+/// Keep the newest record for each prompt
+fn union_prompts(
+    ours: &BTreeMap<String, PromptRecord>,
+    theirs: &BTreeMap<String, PromptRecord>,
+) -> BTreeMap<String, PromptRecord> {
+    let mut merged = ours.clone();
+    for (hash, their_record) in theirs {
+        let keep_ours = merged
+            .get(hash)
+            .is_some_and(|our_record| newness_key(our_record) >= newness_key(their_record));
+        if !keep_ours {
+            merged.insert(hash.clone(), their_record.clone());
+        }
+    }
+    merged
+}
+
+/// Total order on prompt records, oldest first
+///
+/// `PromptRecord`'s own `Ord` calls each record newer than the other when one has more messages
+/// and the other more additions, which made the merge depend on the side. The serialized record
+/// breaks the remaining ties.
+fn newness_key(record: &PromptRecord) -> (usize, u32, u32, String) {
+    (
+        record.messages.len(),
+        record.total_additions,
+        record.total_deletions,
+        serde_json::to_string(record).unwrap_or_default(),
+    )
+}
+
+/// Which of two hashes should own a contested line; prompts missing from the note lose
+fn compare_hashes(a: &str, b: &str, prompts: &BTreeMap<String, PromptRecord>) -> Ordering {
+    match (prompts.get(a), prompts.get(b)) {
+        (Some(a_record), Some(b_record)) => newness_key(a_record).cmp(&newness_key(b_record)),
+        (Some(_), None) => Ordering::Greater,
+        (None, Some(_)) => Ordering::Less,
+        (None, None) => Ordering::Equal,
+    }
+    .then_with(|| a.cmp(b))
+}
+
+/// The hash owning each attributed line of a file; later entries win, as in blame
+fn line_owners_of(log: &AuthorshipLog, file_path: &str) -> BTreeMap<u32, String> {
+    let mut owners = BTreeMap::new();
+    if let Some(file) = log.attestations.iter().find(|f| f.file_path == file_path) {
+        for entry in &file.entries {
+            for range in &entry.line_ranges {
+                for line in range.expand() {
+                    owners.insert(line, entry.hash.clone());
+                }
+            }
+        }
+    }
+    owners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::transcript::Message;
+    use crate::authorship::working_log::AgentId;
+
+    fn prompt(id: &str, messages: usize) -> PromptRecord {
+        prompt_with_additions(id, messages, 0)
+    }
+
+    fn prompt_with_additions(id: &str, messages: usize, additions: u32) -> PromptRecord {
+        PromptRecord {
+            agent_id: AgentId {
+                tool: "cursor".to_string(),
+                id: id.to_string(),
+                model: "test-model".to_string(),
+            },
+            human_author: None,
+            messages: (0..messages)
+                .map(|_| Message::user("hi".to_string(), None))
+                .collect(),
+            total_additions: additions,
+            total_deletions: 0,
+            accepted_lines: 0,
+            overriden_lines: 0,
+            messages_url: None,
+        }
+    }
+
+    fn log(
+        prompts: &[(&str, PromptRecord)],
+        entries: &[(&str, &str, Vec<LineRange>)],
+    ) -> AuthorshipLog {
+        let mut log = AuthorshipLog::new();
+        log.metadata.base_commit_sha = "abc".to_string();
+        for (hash, record) in prompts {
+            log.metadata
+                .prompts
+                .insert(hash.to_string(), record.clone());
+        }
+        for (file, hash, ranges) in entries {
+            log.get_or_create_file(file)
+                .add_entry(AttestationEntry::new(hash.to_string(), ranges.clone()));
+        }
+        log
+    }
+
+    #[test]
+    fn test_union_keeps_lines_from_both_sides() {
+        let ours = log(
+            &[("aaaa", prompt("a", 1))],
+            &[("a.rs", "aaaa", vec![LineRange::Range(1, 3)])],
+        );
+        let theirs = log(
+            &[("bbbb", prompt("b", 1))],
+            &[
+                ("a.rs", "bbbb", vec![LineRange::Range(10, 12)]),
+                ("b.rs", "bbbb", vec![LineRange::Single(1)]),
+            ],
+        );
+
+        let merged = union_authorship_logs(&ours, &theirs);
+        assert_eq!(merged.metadata.prompts.len(), 2);
+        assert_eq!(merged.metadata.base_commit_sha, "abc");
+        assert_eq!(
+            merged.attestations,
+            vec![
+                FileAttestation {
+                    file_path: "a.rs".to_string(),
+                    entries: vec![
+                        AttestationEntry::new("aaaa".to_string(), vec![LineRange::Range(1, 3)]),
+                        AttestationEntry::new("bbbb".to_string(), vec![LineRange::Range(10, 12)]),
+                    ],
+                },
+                FileAttestation {
+                    file_path: "b.rs".to_string(),
+                    entries: vec![AttestationEntry::new(
+                        "bbbb".to_string(),
+                        vec![LineRange::Single(1)]
+                    )],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overlaps_resolve_the_same_way_from_either_side() {
+        let ours = log(
+            &[("aaaa", prompt("a", 1))],
+            &[("a.rs", "aaaa", vec![LineRange::Range(1, 5)])],
+        );
+        let theirs = log(
+            &[("bbbb", prompt("b", 3)), ("aaaa", prompt("a", 2))],
+            &[("a.rs", "bbbb", vec![LineRange::Range(4, 8)])],
+        );
+
+        let merged = union_authorship_logs(&ours, &theirs);
+        assert_eq!(merged, union_authorship_logs(&theirs, &ours));
+
+        // bbbb has the newer record, so it owns the contested lines 4-5
+        assert_eq!(
+            merged.attestations[0].entries,
+            vec![
+                AttestationEntry::new("aaaa".to_string(), vec![LineRange::Range(1, 3)]),
+                AttestationEntry::new("bbbb".to_string(), vec![LineRange::Range(4, 8)]),
+            ]
+        );
+        // The newer copy of a shared prompt wins
+        assert_eq!(merged.metadata.prompts["aaaa"].messages.len(), 2);
+    }
+
+    #[test]
+    fn test_conflicting_records_merge_the_same_way_from_either_side() {
+        // One copy has more messages, the other more additions
+        let ours = log(
+            &[
+                ("aaaa", prompt_with_additions("a", 3, 1)),
+                ("bbbb", prompt_with_additions("b", 1, 2)),
+            ],
+            &[("a.rs", "aaaa", vec![LineRange::Range(1, 4)])],
+        );
+        let theirs = log(
+            &[
+                ("aaaa", prompt_with_additions("a", 2, 9)),
+                ("bbbb", prompt_with_additions("b", 2, 1)),
+            ],
+            &[("a.rs", "bbbb", vec![LineRange::Range(3, 6)])],
+        );
+
+        let merged = union_authorship_logs(&ours, &theirs);
+        assert_eq!(merged, union_authorship_logs(&theirs, &ours));
+        assert_eq!(merged.metadata.prompts["aaaa"].messages.len(), 3);
+        assert_eq!(merged.metadata.prompts["bbbb"].messages.len(), 2);
+        // aaaa has more messages than bbbb, so it owns the contested lines 3-4
+        assert_eq!(
+            merged.attestations[0].entries,
+            vec![
+                AttestationEntry::new("aaaa".to_string(), vec![LineRange::Range(1, 4)]),
+                AttestationEntry::new("bbbb".to_string(), vec![LineRange::Range(5, 6)]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,380 @@
+use crate::authorship::authorship_log_serialization::{AuthorshipLog, FileAttestation};
+use crate::authorship::rebase_authorship::remap_attestations_to_content;
+use crate::error::GitAiError;
+use crate::git::repository::Repository;
+use crate::utils::debug_log;
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use std::collections::{BTreeMap, HashMap};
+
+/// Git config key that makes `git format-patch` embed each commit's authorship log
+pub const PATCH_AUTHORSHIP_CONFIG: &str = "gitai.formatPatchAuthorship";
+
+/// Lines delimiting the embedded authorship log, placed after a patch's `---` divider
+///
+/// `git am` ignores everything between the divider and the diff, so the block never ends up
+/// in the commit message or the applied change.
+const BLOCK_START: &str = "git-ai-authorship:";
+const BLOCK_END: &str = "git-ai-authorship-end";
+
+/// Wrap base64 like MIME so mail transports leave it alone
+const BASE64_LINE_WIDTH: usize = 76;
+
+/// Split an mbox written by `git format-patch` into its messages
+pub fn split_mbox(content: &str) -> Vec<&str> {
+    let mut starts: Vec<usize> = Vec::new();
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        if is_message_start(line) {
+            starts.push(offset);
+        }
+        offset += line.len();
+    }
+    if starts.is_empty() {
+        return if content.trim().is_empty() {
+            Vec::new()
+        } else {
+            vec![content]
+        };
+    }
+    starts.push(content.len());
+    starts
+        .windows(2)
+        .map(|bounds| &content[bounds[0]..bounds[1]])
+        .collect()
+}
+
+/// `From <sha> Mon Sep 17 00:00:00 2001`, the first line of every format-patch message
+fn is_message_start(line: &str) -> bool {
+    line.strip_prefix("From ")
+        .and_then(|rest| rest.split_whitespace().next())
+        .is_some_and(|sha| sha.len() == 40 && sha.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// The commit a format-patch message was generated from
+pub fn patch_source_commit(message: &str) -> Option<String> {
+    let first = message.lines().next()?;
+    is_message_start(first).then(|| first[5..45].to_string())
+}
+
+/// The message's subject with `[PATCH ...]` prefixes removed, as `git am` uses it
+pub fn patch_subject(message: &str) -> Option<String> {
+    let mut lines = message.lines().take_while(|line| !line.is_empty());
+    let mut subject = lines
+        .by_ref()
+        .find_map(|line| line.strip_prefix("Subject:"))?
+        .to_string();
+    // Long subjects are folded onto continuation lines that start with whitespace
+    for line in lines {
+        if !line.starts_with([' ', '\t']) {
+            break;
+        }
+        subject.push_str(line);
+    }
+
+    let mut subject = subject.trim();
+    while subject.starts_with('[')
+        && let Some(end) = subject.find(']')
+    {
+        subject = subject[end + 1..].trim_start();
+    }
+    Some(subject.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+fn has_diff(message: &str) -> bool {
+    message.lines().any(|line| line.starts_with("diff --git "))
+}
+
+/// Embed an authorship log after the `---` divider of a format-patch message
+///
+/// Returns None when the message has no diff, no divider, or already carries a log.
+pub fn embed_authorship_in_patch(message: &str, note: &str) -> Option<String> {
+    if !has_diff(message) || message.lines().any(|line| line == BLOCK_START) {
+        return None;
+    }
+
+    let mut offset = 0;
+    for line in message.split_inclusive('\n') {
+        offset += line.len();
+        if line.trim_end_matches(['\r', '\n']) == "---" {
+            let encoded = BASE64_STANDARD.encode(note);
+            let mut block = String::with_capacity(encoded.len() + 64);
+            block.push_str(BLOCK_START);
+            block.push('\n');
+            for chunk in encoded.as_bytes().chunks(BASE64_LINE_WIDTH) {
+                // Base64 output is ASCII, so every chunk boundary is a char boundary
+                block.push_str(std::str::from_utf8(chunk).unwrap_or_default());
+                block.push('\n');
+            }
+            block.push_str(BLOCK_END);
+            block.push_str("\n\n");
+            return Some(format!(
+                "{}{}{}",
+                &message[..offset],
+                block,
+                &message[offset..]
+            ));
+        }
+        if line.starts_with("diff --git ") {
+            break;
+        }
+    }
+    None
+}
+
+/// The authorship log embedded in a format-patch message, if it carries one
+pub fn extract_authorship_from_patch(message: &str) -> Option<Result<AuthorshipLog, GitAiError>> {
+    let mut lines = message.lines();
+    lines.by_ref().find(|line| line.trim_end() == BLOCK_START)?;
+    let encoded: String = lines
+        .take_while(|line| line.trim_end() != BLOCK_END)
+        .map(str::trim)
+        .collect();
+
+    Some(
+        BASE64_STANDARD
+            .decode(encoded)
+            .map_err(|e| GitAiError::Generic(format!("Invalid embedded authorship log: {}", e)))
+            .and_then(|bytes| {
+                String::from_utf8(bytes).map_err(|e| {
+                    GitAiError::Generic(format!("Invalid embedded authorship log: {}", e))
+                })
+            })
+            .and_then(|note| {
+                AuthorshipLog::deserialize_from_string(&note).map_err(|e| {
+                    GitAiError::Generic(format!("Invalid embedded authorship log: {}", e))
+                })
+            }),
+    )
+}
+
+/// Lines of each file's post-image that a patch shows, keyed by their line number
+///
+/// These are the context and added lines of every hunk, numbered as in the commit the
+/// patch was generated from.
+fn patch_post_images(message: &str) -> HashMap<String, BTreeMap<u32, String>> {
+    let mut files: HashMap<String, BTreeMap<u32, String>> = HashMap::new();
+    let mut current: Option<String> = None;
+    let mut next_line = 0u32;
+    // Lines left in the current hunk's pre-image and post-image
+    let (mut old_left, mut new_left) = (0u32, 0u32);
+
+    for line in message.lines() {
+        if old_left > 0 || new_left > 0 {
+            // Mailers may strip the space from blank context lines
+            let (marker, text) = match line.chars().next() {
+                Some(marker) => (marker, &line[1..]),
+                None => (' ', ""),
+            };
+            match marker {
+                '+' | ' ' => {
+                    if let Some(file) = &current {
+                        files
+                            .entry(file.clone())
+                            .or_default()
+                            .insert(next_line, text.to_string());
+                    }
+                    next_line += 1;
+                    new_left = new_left.saturating_sub(1);
+                    if marker == ' ' {
+                        old_left = old_left.saturating_sub(1);
+                    }
+                }
+                '-' => old_left = old_left.saturating_sub(1),
+                _ => {}
+            }
+        } else if line.starts_with("diff --git ") {
+            current = None;
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            current = path.strip_prefix("b/").map(str::to_string);
+        } else if let Some(header) = line.strip_prefix("@@ ") {
+            // @@ -a,b +c,d @@ where a missing count means 1
+            let range = |prefix: char| {
+                header
+                    .split_whitespace()
+                    .find_map(|part| part.strip_prefix(prefix))
+                    .map(|range| {
+                        let mut parts = range.split(',');
+                        let start = parts.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+                        let count = parts.next().and_then(|n| n.parse().ok()).unwrap_or(1);
+                        (start, count)
+                    })
+                    .unwrap_or((0, 0))
+            };
+            (_, old_left) = range('-');
+            (next_line, new_left) = range('+');
+        }
+    }
+
+    files
+}
+
+/// Re-attach the authorship log embedded in a patch to the commit `git am` created from it
+///
+/// When every line the patch shows is at the same place in the applied commit, the log's
+/// ranges are kept as they are. Otherwise the patch applied with an offset or fuzz, and the
+/// ranges are carried over with the same attribution transform cherry-pick uses. Lines the
+/// patch doesn't show can't be located and are dropped.
+///
+/// Returns false when the patch carries no authorship log.
+pub fn attach_patch_authorship(
+    repo: &Repository,
+    message: &str,
+    applied_commit: &str,
+) -> Result<bool, GitAiError> {
+    let Some(log) = extract_authorship_from_patch(message) else {
+        return Ok(false);
+    };
+    let mut log = log?;
+    let post_images = patch_post_images(message);
+
+    let mut remapped = Vec::new();
+    for file in &log.attestations {
+        let content = repo
+            .get_file_content(&file.file_path, applied_commit)
+            .map(|bytes| String::from_utf8_lossy(&bytes).to_string())
+            .unwrap_or_default();
+        let known = post_images.get(&file.file_path);
+        let applied_lines: Vec<&str> = content.lines().collect();
+        let in_place = known.is_some_and(|known| {
+            known.iter().all(|(line_no, text)| {
+                (*line_no as usize)
+                    .checked_sub(1)
+                    .and_then(|index| applied_lines.get(index))
+                    .copied()
+                    == Some(text.as_str())
+            })
+        });
+        if !in_place {
+            remapped.push((file.file_path.clone(), content));
+        }
+    }
+
+    if !remapped.is_empty() {
+        debug_log(&format!(
+            "Patch for {} applied with fuzz, remapping {} file(s)",
+            applied_commit,
+            remapped.len()
+        ));
+        let remapped_paths: Vec<String> = remapped.iter().map(|(path, _)| path.clone()).collect();
+        let remapped_files =
+            remap_attestations(repo, &log, &post_images, remapped, applied_commit)?;
+        log.attestations
+            .retain(|file| !remapped_paths.contains(&file.file_path));
+        log.attestations.extend(remapped_files);
+    }
+
+    log.metadata.base_commit_sha = applied_commit.to_string();
+    // Any signature covered the original commit, not this one
+    log.metadata.signature = None;
+
+    let note = log
+        .serialize_to_string()
+        .map_err(|_| GitAiError::Generic("Failed to serialize authorship log".to_string()))?;
+    crate::git::refs::notes_add(repo, applied_commit, &note)?;
+    Ok(true)
+}
+
+/// Carry a log's attestations for `files` onto their content in the applied commit
+///
+/// The original file is rebuilt from the lines the patch shows, with unique placeholders
+/// for the lines it doesn't, and diffed against the applied content.
+fn remap_attestations(
+    repo: &Repository,
+    log: &AuthorshipLog,
+    post_images: &HashMap<String, BTreeMap<u32, String>>,
+    files: Vec<(String, String)>,
+    applied_commit: &str,
+) -> Result<Vec<FileAttestation>, GitAiError> {
+    let mut remaps = Vec::new();
+    for (file_path, applied_content) in files {
+        let Some(file) = log
+            .attestations
+            .iter()
+            .find(|file| file.file_path == file_path)
+        else {
+            continue;
+        };
+        let known = post_images.get(&file_path).cloned().unwrap_or_default();
+
+        let last_line = file
+            .entries
+            .iter()
+            .flat_map(|entry| entry.line_ranges.iter().map(|range| range.end()))
+            .chain(known.keys().copied())
+            .max()
+            .unwrap_or(0);
+        let mut original = String::new();
+        for line_no in 1..=last_line {
+            match known.get(&line_no) {
+                Some(text) => original.push_str(text),
+                None => original.push_str(&format!("\u{1}git-ai unknown line {}", line_no)),
+            }
+            original.push('\n');
+        }
+
+        remaps.push((file.clone(), original, applied_content));
+    }
+
+    remap_attestations_to_content(repo, applied_commit, remaps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATCH: &str = "From 0123456789abcdef0123456789abcdef01234567 Mon Sep 17 00:00:00 2001
+From: Test User <test@example.com>
+Date: Mon, 1 Jan 2024 00:00:00 +0000
+Subject: [PATCH 1/2] Add a rather long subject that
+ git folds onto a second line
+
+---
+ file.txt | 2 ++
+ 1 file changed, 2 insertions(+)
+
+diff --git a/file.txt b/file.txt
+index 1111111..2222222 100644
+--- a/file.txt
++++ b/file.txt
+@@ -1,2 +1,4 @@
+ Base
++AI one
++AI two
+ End
+--
+2.39.5
+
+";
+
+    #[test]
+    fn test_patch_headers() {
+        assert_eq!(
+            patch_source_commit(PATCH).as_deref(),
+            Some("0123456789abcdef0123456789abcdef01234567")
+        );
+        assert_eq!(
+            patch_subject(PATCH).as_deref(),
+            Some("Add a rather long subject that git folds onto a second line")
+        );
+        assert_eq!(split_mbox(&format!("{}{}", PATCH, PATCH)).len(), 2);
+    }
+
+    #[test]
+    fn test_embedded_log_roundtrip() {
+        let note = "file.txt\n  abcd 2-3\n---\n{\n  \"schema_version\": \"authorship/3.0.0\",\n  \"base_commit_sha\": \"0123456789abcdef0123456789abcdef01234567\",\n  \"prompts\": {}\n}";
+        let embedded = embed_authorship_in_patch(PATCH, note).unwrap();
+        assert!(embedded.contains("\n---\ngit-ai-authorship:\n"));
+        assert!(embed_authorship_in_patch(&embedded, note).is_none());
+
+        let log = extract_authorship_from_patch(&embedded).unwrap().unwrap();
+        assert_eq!(log.attestations[0].file_path, "file.txt");
+        assert!(extract_authorship_from_patch(PATCH).is_none());
+
+        let post_images = patch_post_images(&embedded);
+        assert_eq!(
+            post_images["file.txt"].values().collect::<Vec<_>>(),
+            vec!["Base", "AI one", "AI two", "End"]
+        );
+    }
+}
@@ -1,16 +1,19 @@
 use crate::api::{ApiClient, ApiContext};
-use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log_serialization::{AuthorshipLog, generate_short_hash};
+use crate::authorship::imara_diff_utils::{LineChangeTag, compute_line_changes};
 use crate::authorship::prompt_utils::{PromptUpdateResult, update_prompt_from_tool};
 use crate::authorship::secrets::{redact_secrets_from_prompts, strip_prompt_messages};
 use crate::authorship::stats::{stats_for_commit_stats, write_stats_to_terminal};
 use crate::authorship::virtual_attribution::VirtualAttributions;
-use crate::authorship::working_log::{Checkpoint, CheckpointKind};
+use crate::authorship::working_log::{Checkpoint, CheckpointKind, deleted_line_hash};
 use crate::config::{Config, PromptStorageMode};
 use crate::error::GitAiError;
 use crate::git::refs::notes_add;
+use crate::git::repo_storage::PersistedWorkingLog;
 use crate::git::repository::Repository;
 use crate::utils::debug_log;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::io::IsTerminal;
 
 pub fn post_commit(
@@ -94,6 +97,27 @@ pub fn post_commit(
 
     authorship_log.metadata.base_commit_sha = commit_sha.clone();
 
+    // Lines a merge brought in unchanged are already attributed on their own commits,
+    // so a merge's log only covers what was written while resolving it
+    let parents: Vec<String> = repo
+        .find_commit(commit_sha.clone())?
+        .parents()
+        .map(|parent| parent.id())
+        .collect();
+    if parents.len() > 1 {
+        let resolution_lines = repo.merge_resolution_lines(&commit_sha, &parents)?;
+        restrict_to_lines(&mut authorship_log, &resolution_lines);
+    }
+
+    attribute_deleted_lines(
+        repo,
+        &working_log,
+        &parent_sha,
+        &commit_sha,
+        &parent_working_log,
+        &mut authorship_log,
+    );
+
     // Handle prompts based on effective prompt storage mode for this repository
     // The effective mode considers include/exclude lists and fallback settings
     let effective_storage = Config::get().effective_prompt_storage(&Some(repo.clone()));
@@ -109,6 +133,20 @@ pub fn post_commit(
             if count > 0 {
                 debug_log(&format!("Redacted {} secrets from prompts", count));
             }
+
+            // Transcripts over the repo's budget go to CAS; the note keeps the hash reference
+            if let Some(budget) = note_messages_budget(repo)
+                && let Err(e) = enqueue_prompt_messages_to_cas(
+                    repo,
+                    &mut authorship_log.metadata.prompts,
+                    Some(budget),
+                )
+            {
+                debug_log(&format!(
+                    "[Warning] Failed to offload oversized prompt messages to CAS: {}",
+                    e
+                ));
+            }
         }
         PromptStorageMode::Default => {
             // "default" - attempt CAS upload, NEVER keep messages in notes
@@ -132,7 +170,7 @@ pub fn post_commit(
                 }
 
                 if let Err(e) =
-                    enqueue_prompt_messages_to_cas(repo, &mut authorship_log.metadata.prompts)
+                    enqueue_prompt_messages_to_cas(repo, &mut authorship_log.metadata.prompts, None)
                 {
                     debug_log(&format!(
                         "[Warning] Failed to enqueue prompt messages to CAS: {}",
@@ -188,6 +226,256 @@ pub fn post_commit(
     Ok((commit_sha.to_string(), authorship_log))
 }
 
+/// Drop attested ranges outside `lines`, and files left without any
+fn restrict_to_lines(authorship_log: &mut AuthorshipLog, lines: &HashMap<String, Vec<u32>>) {
+    authorship_log.attestations.retain_mut(|file_attestation| {
+        let Some(kept_lines) = lines.get(&file_attestation.file_path) else {
+            return false;
+        };
+        for entry in &mut file_attestation.entries {
+            let mut entry_lines: Vec<u32> = entry
+                .line_ranges
+                .iter()
+                .flat_map(|range| range.expand())
+                .filter(|line| kept_lines.contains(line))
+                .collect();
+            entry_lines.sort_unstable();
+            entry.line_ranges = LineRange::compress_lines(&entry_lines);
+            entry.anchors.clear();
+        }
+        file_attestation
+            .entries
+            .retain(|entry| !entry.line_ranges.is_empty());
+        !file_attestation.entries.is_empty()
+    });
+}
+
+/// Record which AI prompts deleted the lines this commit removed from its parent
+///
+/// Checkpoint line numbers are relative to the file's previous checkpoint, so each file's
+/// checkpoints are replayed from the parent's content to find the parent line every AI
+/// deletion removed. Lines the AI added and removed again have no parent line and are
+/// skipped, and a parent line only counts as deleted by the AI if the commit removes it too.
+fn attribute_deleted_lines(
+    repo: &Repository,
+    working_log: &PersistedWorkingLog,
+    parent_sha: &str,
+    commit_sha: &str,
+    checkpoints: &[Checkpoint],
+    authorship_log: &mut AuthorshipLog,
+) {
+    if parent_sha == "initial" {
+        return;
+    }
+
+    let files: BTreeSet<&str> = checkpoints
+        .iter()
+        .flat_map(|checkpoint| &checkpoint.entries)
+        .filter(|entry| !entry.deleted_lines.is_empty())
+        .map(|entry| entry.file.as_str())
+        .collect();
+    let mut pending = PendingDeletions::new(parent_sha);
+    for file_path in files {
+        replay_file_deletions(
+            repo,
+            working_log,
+            parent_sha,
+            file_path,
+            checkpoints,
+            authorship_log,
+            &mut pending,
+        );
+    }
+
+    pending.claim_for_commit(repo, parent_sha, commit_sha, authorship_log);
+}
+
+/// Add the parent lines AI checkpoints deleted from `file_path` to `pending`
+///
+/// Stops at the first checkpoint whose file content can't be read, since later line numbers
+/// can't be followed past it.
+fn replay_file_deletions(
+    repo: &Repository,
+    working_log: &PersistedWorkingLog,
+    parent_sha: &str,
+    file_path: &str,
+    checkpoints: &[Checkpoint],
+    authorship_log: &AuthorshipLog,
+    pending: &mut PendingDeletions,
+) {
+    let mut content = repo
+        .get_file_content(file_path, parent_sha)
+        .map(|bytes| String::from_utf8_lossy(&bytes).to_string())
+        .unwrap_or_default();
+    // The parent line each line of `content` is, if it is one
+    let mut origins: Vec<Option<u32>> = (1..=content.lines().count() as u32).map(Some).collect();
+
+    for checkpoint in checkpoints {
+        let prompt_hash = checkpoint
+            .agent_id
+            .as_ref()
+            .map(|agent_id| generate_short_hash(&agent_id.id, &agent_id.tool))
+            .filter(|hash| authorship_log.metadata.prompts.contains_key(hash));
+
+        for entry in checkpoint.entries.iter().filter(|e| e.file == file_path) {
+            let Ok(new_content) = working_log.get_file_version(&entry.blob_sha) else {
+                return;
+            };
+            let deleted: HashMap<u32, &str> = entry
+                .deleted_lines
+                .iter()
+                .map(|deleted| (deleted.line, deleted.hash.as_str()))
+                .collect();
+
+            let mut new_origins = Vec::new();
+            let mut old_line = 0u32;
+            for change in compute_line_changes(&content, &new_content) {
+                match change.tag() {
+                    LineChangeTag::Equal => {
+                        new_origins.push(origins.get(old_line as usize).copied().flatten());
+                        old_line += 1;
+                    }
+                    LineChangeTag::Delete => {
+                        let origin = origins.get(old_line as usize).copied().flatten();
+                        old_line += 1;
+                        let line_hash = deleted_line_hash(change.value());
+                        if let (Some(prompt_hash), Some(parent_line)) = (&prompt_hash, origin)
+                            && deleted.get(&old_line) == Some(&line_hash.as_str())
+                        {
+                            pending.add(file_path, parent_line, line_hash, prompt_hash.clone());
+                        }
+                    }
+                    LineChangeTag::Insert => new_origins.push(None),
+                }
+            }
+            content = new_content;
+            origins = new_origins;
+        }
+    }
+}
+
+/// AI deletions of lines in a base commit that haven't been matched to a commit's diff yet
+#[derive(Debug)]
+pub(crate) struct PendingDeletions {
+    /// Commit the pending line numbers refer to
+    base: String,
+    /// File -> line in `base` -> (`deleted_line_hash` of the line, prompt that deleted it)
+    files: HashMap<String, BTreeMap<u32, (String, String)>>,
+}
+
+impl PendingDeletions {
+    pub(crate) fn new(base: &str) -> Self {
+        Self {
+            base: base.to_string(),
+            files: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn add(
+        &mut self,
+        file_path: &str,
+        line: u32,
+        line_hash: String,
+        prompt_hash: String,
+    ) {
+        self.files
+            .entry(file_path.to_string())
+            .or_default()
+            .insert(line, (line_hash, prompt_hash));
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.files.values().all(BTreeMap::is_empty)
+    }
+
+    pub(crate) fn file_paths(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+
+    /// Record in `authorship_log` the pending lines `commit_sha` removes from `parent_sha`,
+    /// using each pending deletion at most once
+    ///
+    /// A deleted line matches by position, with its hash as a check. When `parent_sha` isn't
+    /// the base, positions are carried over through the diff between the two, and lines that
+    /// changed in between can't match.
+    pub(crate) fn claim_for_commit(
+        &mut self,
+        repo: &Repository,
+        parent_sha: &str,
+        commit_sha: &str,
+        authorship_log: &mut AuthorshipLog,
+    ) {
+        let base = &self.base;
+        for (file_path, by_line) in &mut self.files {
+            if by_line.is_empty() {
+                continue;
+            }
+            // A file missing on either side reads as empty: added or deleted by the commit
+            let read = |commit: &str| {
+                repo.get_file_content(file_path, commit)
+                    .map(|bytes| String::from_utf8_lossy(&bytes).to_string())
+                    .unwrap_or_default()
+            };
+            let parent_content = read(parent_sha);
+            let commit_content = read(commit_sha);
+            // Parent line -> base line, unless the parent is the base
+            let base_lines =
+                (parent_sha != base).then(|| unchanged_lines(&read(base), &parent_content));
+
+            let mut old_line = 0u32;
+            let mut lines_by_hash: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+            for change in compute_line_changes(&parent_content, &commit_content) {
+                match change.tag() {
+                    LineChangeTag::Equal => old_line += 1,
+                    LineChangeTag::Delete => {
+                        old_line += 1;
+                        let base_line = match &base_lines {
+                            Some(base_lines) => base_lines.get(&old_line).copied(),
+                            None => Some(old_line),
+                        };
+                        let Some(base_line) = base_line else {
+                            continue;
+                        };
+                        let matches = by_line.get(&base_line).is_some_and(|(line_hash, _)| {
+                            *line_hash == deleted_line_hash(change.value())
+                        });
+                        if matches && let Some((_, prompt_hash)) = by_line.remove(&base_line) {
+                            lines_by_hash.entry(prompt_hash).or_default().push(old_line);
+                        }
+                    }
+                    LineChangeTag::Insert => {}
+                }
+            }
+
+            for (hash, lines) in lines_by_hash {
+                authorship_log.record_deletions(
+                    file_path,
+                    &hash,
+                    &LineRange::compress_lines(&lines),
+                );
+            }
+        }
+    }
+}
+
+/// Lines of `new` left unchanged from `old`, mapped to their line number in `old`
+fn unchanged_lines(old: &str, new: &str) -> HashMap<u32, u32> {
+    let mut mapping = HashMap::new();
+    let (mut old_line, mut new_line) = (0u32, 0u32);
+    for change in compute_line_changes(old, new) {
+        match change.tag() {
+            LineChangeTag::Equal => {
+                old_line += 1;
+                new_line += 1;
+                mapping.insert(new_line, old_line);
+            }
+            LineChangeTag::Delete => old_line += 1,
+            LineChangeTag::Insert => new_line += 1,
+        }
+    }
+    mapping
+}
+
 /// Update prompts/transcripts in working log checkpoints to their latest versions.
 /// This helps prevent race conditions where we miss the last message in a conversation.
 ///
@@ -302,8 +590,24 @@ fn batch_upsert_prompts_to_db(
     Ok(())
 }
 
+/// Git config key for the largest serialized transcript, in bytes, kept inline in a note
+///
+/// Only applies in "notes" prompt storage mode. Accepts git's k/m/g suffixes.
+pub const NOTE_MESSAGES_BUDGET_CONFIG: &str = "gitai.noteMessagesBudget";
+
+/// The configured per-prompt transcript budget for notes, if any
+pub fn note_messages_budget(repo: &Repository) -> Option<usize> {
+    match repo.config_get_int(NOTE_MESSAGES_BUDGET_CONFIG) {
+        Ok(budget) => budget.and_then(|budget| usize::try_from(budget).ok()),
+        Err(e) => {
+            eprintln!("Warning: ignoring {}: {}", NOTE_MESSAGES_BUDGET_CONFIG, e);
+            None
+        }
+    }
+}
+
 /// Enqueue prompt messages to CAS for external storage.
-/// For each prompt with non-empty messages:
+/// For each prompt with non-empty messages (over `inline_budget` bytes, when given):
 /// - Serialize messages to JSON
 /// - Enqueue to CAS (returns hash)
 /// - Set messages_url (format: {api_base_url}/cas/{hash}) and clear messages
@@ -313,9 +617,33 @@ fn enqueue_prompt_messages_to_cas(
         String,
         crate::authorship::authorship_log::PromptRecord,
     >,
+    inline_budget: Option<usize>,
 ) -> Result<(), GitAiError> {
     use crate::authorship::internal_db::InternalDatabase;
 
+    // Wrap messages in CasMessagesObject and serialize to JSON, skipping transcripts
+    // small enough to stay in the note
+    let mut to_enqueue = Vec::new();
+    for (key, prompt) in prompts.iter() {
+        if prompt.messages.is_empty() {
+            continue;
+        }
+        let messages_obj = crate::api::types::CasMessagesObject {
+            messages: prompt.messages.clone(),
+        };
+        let messages_json = serde_json::to_value(&messages_obj)
+            .map_err(|e| GitAiError::Generic(format!("Failed to serialize messages: {}", e)))?;
+        if let Some(budget) = inline_budget
+            && messages_json.to_string().len() <= budget
+        {
+            continue;
+        }
+        to_enqueue.push((key.clone(), messages_json));
+    }
+    if to_enqueue.is_empty() {
+        return Ok(());
+    }
+
     let db = InternalDatabase::global()?;
     let mut db_lock = db
         .lock()
@@ -349,19 +677,12 @@ fn enqueue_prompt_messages_to_cas(
     // Get API base URL for constructing messages_url
     let api_base_url = Config::get().api_base_url();
 
-    for (_key, prompt) in prompts.iter_mut() {
-        if !prompt.messages.is_empty() {
-            // Wrap messages in CasMessagesObject and serialize to JSON
-            let messages_obj = crate::api::types::CasMessagesObject {
-                messages: prompt.messages.clone(),
-            };
-            let messages_json = serde_json::to_value(&messages_obj)
-                .map_err(|e| GitAiError::Generic(format!("Failed to serialize messages: {}", e)))?;
-
-            // Enqueue to CAS (returns hash)
-            let hash = db_lock.enqueue_cas_object(&messages_json, Some(&metadata))?;
+    for (key, messages_json) in to_enqueue {
+        // Enqueue to CAS (returns hash)
+        let hash = db_lock.enqueue_cas_object(&messages_json, Some(&metadata))?;
 
-            // Set full URL and clear messages
+        // Set full URL and clear messages
+        if let Some(prompt) = prompts.get_mut(&key) {
             prompt.messages_url = Some(format!("{}/cas/{}", api_base_url, hash));
             prompt.messages.clear();
         }
@@ -1,3 +1,5 @@
+use crate::api::client::{ApiClient, ApiContext};
+use crate::api::types::CasMessagesObject;
 use crate::authorship::authorship_log::PromptRecord;
 use crate::authorship::internal_db::InternalDatabase;
 use crate::authorship::transcript::AiTranscript;
@@ -71,8 +73,9 @@ pub fn find_prompt_in_history(
     offset: usize,
 ) -> Result<(String, PromptRecord), GitAiError> {
     // Use git grep to search for the prompt ID in authorship notes
-    // grep_ai_notes returns commits sorted by date (newest first)
-    let shas = grep_ai_notes(repo, &format!("\"{}\"", prompt_id)).unwrap_or_default();
+    // grep_ai_notes returns commits sorted by date (newest first). The bare ID also matches
+    // attestation lines, which stay plain text when the metadata is compressed.
+    let shas = grep_ai_notes(repo, prompt_id).unwrap_or_default();
 
     if shas.is_empty() {
         return Err(GitAiError::Generic(format!(
@@ -135,7 +138,15 @@ pub fn find_prompt_with_db_fallback(
     if let Some(repo) = repo {
         // Try to find in history (most recent occurrence)
         match find_prompt_in_history(repo, prompt_id, 0) {
-            Ok((commit_sha, prompt)) => Ok((Some(commit_sha), prompt)),
+            Ok((commit_sha, mut prompt)) => {
+                if let Err(e) = resolve_cas_messages(&mut prompt) {
+                    debug_log(&format!(
+                        "Failed to load offloaded messages for prompt {}: {}",
+                        prompt_id, e
+                    ));
+                }
+                Ok((Some(commit_sha), prompt))
+            }
             Err(_) => Err(GitAiError::Generic(format!(
                 "Prompt '{}' not found in database or repository",
                 prompt_id
@@ -149,6 +160,46 @@ pub fn find_prompt_with_db_fallback(
     }
 }
 
+/// Fill in messages that were offloaded to CAS, leaving only a `messages_url` in the note
+///
+/// The local CAS sync queue is checked first, so prompts committed on this machine resolve
+/// before they are uploaded; otherwise the object is downloaded from the CAS API.
+/// Prompts that already carry messages, or have no CAS reference, are left alone.
+pub fn resolve_cas_messages(prompt: &mut PromptRecord) -> Result<(), GitAiError> {
+    if !prompt.messages.is_empty() {
+        return Ok(());
+    }
+    let Some(hash) = prompt.messages_url.as_deref().and_then(cas_hash_from_url) else {
+        return Ok(());
+    };
+
+    let queued = {
+        let db = InternalDatabase::global()?;
+        let db_guard = db
+            .lock()
+            .map_err(|e| GitAiError::Generic(format!("Failed to lock database: {}", e)))?;
+        db_guard.get_cas_object(hash)?
+    };
+
+    let messages = match queued {
+        Some(data) => serde_json::from_str::<CasMessagesObject>(&data)?.messages,
+        None => {
+            ApiClient::new(ApiContext::new(None))
+                .read_cas_messages(hash)?
+                .messages
+        }
+    };
+    prompt.messages = messages;
+    Ok(())
+}
+
+/// The content hash at the end of a `{api_base_url}/cas/{hash}` messages URL
+fn cas_hash_from_url(url: &str) -> Option<&str> {
+    url.rsplit_once("/cas/")
+        .map(|(_, hash)| hash)
+        .filter(|hash| !hash.is_empty() && hash.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
 /// Result of attempting to update a prompt from a tool
 pub enum PromptUpdateResult {
     Updated(AiTranscript, String), // (new_transcript, new_model)
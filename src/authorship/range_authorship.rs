@@ -18,7 +18,8 @@ use std::io::IsTerminal;
 const EMPTY_TREE_HASH: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
 
 /// Check if a file path should be ignored based on the provided patterns
-/// Supports both exact matches and glob patterns (e.g., "*.lock", "**/*.generated.js")
+/// Supports both exact matches and glob patterns (e.g., "*.lock", "**/*.generated.js").
+/// A leading "/" anchors the pattern to the repository root, so it only matches full paths.
 pub fn should_ignore_file(path: &str, ignore_patterns: &[String]) -> bool {
     use glob::Pattern;
 
@@ -28,6 +29,9 @@ pub fn should_ignore_file(path: &str, ignore_patterns: &[String]) -> bool {
         .unwrap_or("");
 
     ignore_patterns.iter().any(|pattern| {
+        if let Some(anchored) = pattern.strip_prefix('/') {
+            return Pattern::new(anchored).is_ok_and(|glob_pattern| glob_pattern.matches(path));
+        }
         // Try to parse as glob pattern
         if let Ok(glob_pattern) = Pattern::new(pattern) {
             // Match against both the full path and just the filename
@@ -219,6 +223,8 @@ fn create_authorship_log_for_range(
                     ),
                     base_commit_sha: end_sha.to_string(),
                     prompts: std::collections::BTreeMap::new(),
+                    deletions: std::collections::BTreeMap::new(),
+                    signature: None,
                 },
             },
         );
@@ -1107,6 +1113,17 @@ mod tests {
         assert!(!should_ignore_file("manual.ts", &prefix_patterns));
     }
 
+    #[test]
+    fn test_should_ignore_file_with_anchored_patterns() {
+        // A leading slash only matches from the repository root
+        let anchored_patterns = vec!["/README.md".to_string(), "/docs/*".to_string()];
+
+        assert!(should_ignore_file("README.md", &anchored_patterns));
+        assert!(!should_ignore_file("src/README.md", &anchored_patterns));
+        assert!(should_ignore_file("docs/guide.md", &anchored_patterns));
+        assert!(!should_ignore_file("src/docs/guide.md", &anchored_patterns));
+    }
+
     #[test]
     fn test_should_ignore_file_with_complex_glob_patterns() {
         // Test complex patterns (note: brace expansion like {js,ts} is not supported by glob crate)
@@ -1,5 +1,9 @@
-use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::anchors::AnchoredSourceLines;
+use crate::authorship::authorship_log::PromptRecord;
+use crate::authorship::authorship_log_serialization::{AuthorshipLog, FileAttestation};
 use crate::authorship::post_commit;
+use crate::authorship::post_commit::PendingDeletions;
+use crate::authorship::working_log::deleted_line_hash;
 use crate::error::GitAiError;
 use crate::git::authorship_traversal::{
     commits_have_authorship_notes, load_ai_touched_files_for_commits,
@@ -8,7 +12,7 @@ use crate::git::refs::get_reference_as_authorship_log_v3;
 use crate::git::repository::{CommitRange, Repository};
 use crate::git::rewrite_log::RewriteLogEvent;
 use crate::utils::debug_log;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 // Process events in the rewrite log and call the correct rewrite functions in this file
 pub fn rewrite_authorship_if_needed(
@@ -364,7 +368,10 @@ pub fn rewrite_authorship_after_rebase_v2(
 
     // Step 1: Extract pathspecs from all original commits
     let pathspecs = get_pathspecs_from_commits(repo, original_commits)?;
-    let pathspecs = filter_pathspecs_to_ai_touched_files(repo, original_commits, &pathspecs)?;
+    let mut pathspecs = filter_pathspecs_to_ai_touched_files(repo, original_commits, &pathspecs)?;
+    // Files whose notes only record deletions don't count as AI-touched
+    let mut pending_deletions = RewrittenDeletions::from_notes(repo, original_commits);
+    pending_deletions.add_file_paths_to(&mut pathspecs);
 
     if pathspecs.is_empty() {
         // No files were modified, nothing to do
@@ -406,6 +413,9 @@ pub fn rewrite_authorship_after_rebase_v2(
         new_commits.len() - commits_to_process.len()
     ));
 
+    // Anchored lines of the original notes, to check the rewritten line numbers against
+    let anchored_source = AnchoredSourceLines::from_commits(repo, original_commits);
+
     // Step 2: Create VirtualAttributions from original_head (before rebase)
     // Compute merge base to bound blame depth — without this, blame walks entire file history
     let new_head = new_commits.last().unwrap();
@@ -535,6 +545,8 @@ pub fn rewrite_authorship_after_rebase_v2(
             }
         });
 
+        anchored_source.verify_rewritten_log(&mut authorship_log, &new_content_state);
+        pending_deletions.claim_for_commit(repo, &parent_obj.id(), new_commit, &mut authorship_log);
         authorship_log.metadata.base_commit_sha = new_commit.clone();
 
         // Save authorship log
@@ -590,7 +602,10 @@ pub fn rewrite_authorship_after_cherry_pick(
 
     // Step 1: Extract pathspecs from all source commits
     let pathspecs = get_pathspecs_from_commits(repo, source_commits)?;
-    let pathspecs = filter_pathspecs_to_ai_touched_files(repo, source_commits, &pathspecs)?;
+    let mut pathspecs = filter_pathspecs_to_ai_touched_files(repo, source_commits, &pathspecs)?;
+    // Files whose notes only record deletions don't count as AI-touched
+    let mut pending_deletions = RewrittenDeletions::from_notes(repo, source_commits);
+    pending_deletions.add_file_paths_to(&mut pathspecs);
 
     if pathspecs.is_empty() {
         // No files were modified, nothing to do
@@ -604,6 +619,9 @@ pub fn rewrite_authorship_after_cherry_pick(
         source_commits.len()
     ));
 
+    // Anchored lines of the source notes, to check the rewritten line numbers against
+    let anchored_source = AnchoredSourceLines::from_commits(repo, source_commits);
+
     // Step 2: Create VirtualAttributions from the LAST source commit
     // This is the key difference from rebase: cherry-pick applies patches sequentially,
     // so the last source commit contains all the accumulated changes being cherry-picked
@@ -723,6 +741,8 @@ pub fn rewrite_authorship_after_cherry_pick(
             }
         });
 
+        anchored_source.verify_rewritten_log(&mut authorship_log, &new_content_state);
+        pending_deletions.claim_for_commit(repo, &parent_obj.id(), new_commit, &mut authorship_log);
         authorship_log.metadata.base_commit_sha = new_commit.clone();
 
         // Save authorship log
@@ -742,6 +762,279 @@ pub fn rewrite_authorship_after_cherry_pick(
     Ok(())
 }
 
+/// Deletions recorded in the notes of rewritten commits, waiting for their new commits
+///
+/// A deletion is recorded against line numbers in its commit's parent, which change when the
+/// commit is replayed elsewhere. Each note's deletions stay pending against its old parent
+/// and are carried over to each new commit's parent when matched against its diff.
+struct RewrittenDeletions {
+    /// One per rewritten commit whose note recorded deletions
+    pending: Vec<PendingDeletions>,
+    /// Records of the prompts that made the deletions, for new notes that lack them
+    prompts: BTreeMap<String, PromptRecord>,
+}
+
+impl RewrittenDeletions {
+    fn from_notes(repo: &Repository, commits: &[String]) -> Self {
+        let mut pending = Vec::new();
+        let mut prompts = BTreeMap::new();
+        for commit in commits {
+            let Ok(log) = get_reference_as_authorship_log_v3(repo, commit) else {
+                continue;
+            };
+            if log.metadata.deletions.is_empty() {
+                continue;
+            }
+            let Ok(parent_sha) = repo
+                .find_commit(commit.clone())
+                .and_then(|commit| commit.parent(0))
+                .map(|parent| parent.id())
+            else {
+                continue;
+            };
+
+            let mut deletions = PendingDeletions::new(&parent_sha);
+            for file_path in log.metadata.deletions.keys() {
+                let Ok(content) = repo.get_file_content(file_path, &parent_sha) else {
+                    continue;
+                };
+                let content = String::from_utf8_lossy(&content);
+                let lines: Vec<&str> = content.lines().collect();
+                for (hash, ranges) in log.deletions_for_file(file_path) {
+                    for line in ranges.iter().flat_map(|range| range.expand()) {
+                        if let Some(text) =
+                            line.checked_sub(1).and_then(|idx| lines.get(idx as usize))
+                        {
+                            deletions.add(file_path, line, deleted_line_hash(text), hash.clone());
+                        }
+                    }
+                    if let Some(record) = log.metadata.prompts.get(&hash) {
+                        prompts.insert(hash, record.clone());
+                    }
+                }
+            }
+            pending.push(deletions);
+        }
+        Self { pending, prompts }
+    }
+
+    fn add_file_paths_to(&self, pathspecs: &mut Vec<String>) {
+        for file_path in self.pending.iter().flat_map(PendingDeletions::file_paths) {
+            if !pathspecs.iter().any(|pathspec| pathspec == file_path) {
+                pathspecs.push(file_path.to_string());
+            }
+        }
+    }
+
+    /// Record the pending deletions `commit_sha` makes in its new authorship log
+    fn claim_for_commit(
+        &mut self,
+        repo: &Repository,
+        parent_sha: &str,
+        commit_sha: &str,
+        authorship_log: &mut AuthorshipLog,
+    ) {
+        if self.pending.iter().all(PendingDeletions::is_empty) {
+            return;
+        }
+        for deletions in &mut self.pending {
+            deletions.claim_for_commit(repo, parent_sha, commit_sha, authorship_log);
+        }
+
+        let deleting_prompts: HashSet<String> = authorship_log
+            .metadata
+            .deletions
+            .values()
+            .flat_map(|by_hash| by_hash.keys().cloned())
+            .collect();
+        for hash in deleting_prompts {
+            if let Some(record) = self.prompts.get(&hash) {
+                authorship_log
+                    .metadata
+                    .prompts
+                    .entry(hash)
+                    .or_insert_with(|| record.clone());
+            }
+        }
+    }
+}
+
+/// Rewrite authorship for a commit created by `git revert`
+///
+/// A revert brings back the lines the reverted commit deleted, so those lines take their
+/// attribution from the reverted commit's parent. Every other line keeps the attribution it
+/// had before the revert.
+///
+/// # Arguments
+/// * `repo` - Git repository
+/// * `reverted_commit` - SHA of the commit being undone
+/// * `revert_commit` - SHA of the commit `git revert` created
+/// * `mainline` - Parent number given with `-m/--mainline`, for reverted merges
+///
+/// Returns the files the revert changed.
+pub fn rewrite_authorship_after_revert(
+    repo: &Repository,
+    reverted_commit: &str,
+    revert_commit: &str,
+    mainline: Option<usize>,
+) -> Result<Vec<String>, GitAiError> {
+    let parent_sha = repo.find_commit(revert_commit.to_string())?.parent(0)?.id();
+    let changed_files = repo.diff_changed_files(&parent_sha, revert_commit)?;
+
+    debug_log(&format!(
+        "Rewriting authorship for revert of {} in {} ({} files)",
+        reverted_commit,
+        revert_commit,
+        changed_files.len()
+    ));
+
+    let committed_files = get_committed_files_content(repo, revert_commit, &changed_files)?;
+    let reverted_va = attributions_after_revert(
+        repo,
+        &parent_sha,
+        &[reverted_commit.to_string()],
+        mainline,
+        &changed_files,
+        committed_files,
+    )?;
+
+    let mut authorship_log = reverted_va.to_authorship_log()?;
+    authorship_log.metadata.base_commit_sha = revert_commit.to_string();
+
+    let authorship_json = authorship_log
+        .serialize_to_string()
+        .map_err(|_| GitAiError::Generic("Failed to serialize authorship log".to_string()))?;
+    crate::git::refs::notes_add(repo, revert_commit, &authorship_json)?;
+
+    Ok(changed_files)
+}
+
+/// Prepare working log after `git revert --no-commit`
+///
+/// The reverted changes are staged on top of HEAD, so the restored lines are written to
+/// INITIAL and picked up by the next commit, the same way `prepare_working_log_after_squash`
+/// handles a squash merge.
+///
+/// Returns the files changed by each reverted commit, in the order given.
+pub fn prepare_working_log_after_revert(
+    repo: &Repository,
+    head_sha: &str,
+    reverted_commits: &[String],
+    mainline: Option<usize>,
+) -> Result<Vec<Vec<String>>, GitAiError> {
+    let mut affected_files = Vec::new();
+    let mut pathspecs: Vec<String> = Vec::new();
+    for reverted_commit in reverted_commits {
+        let mut files: Vec<String> = match reverted_parent(repo, reverted_commit, mainline)? {
+            Some(parent_sha) => repo.diff_changed_files(&parent_sha, reverted_commit)?,
+            None => repo
+                .list_commit_files(reverted_commit, None)?
+                .into_iter()
+                .collect(),
+        };
+        files.sort();
+        for file in &files {
+            if !pathspecs.contains(file) {
+                pathspecs.push(file.clone());
+            }
+        }
+        affected_files.push(files);
+    }
+
+    if pathspecs.is_empty() {
+        return Ok(affected_files);
+    }
+
+    let staged_files = repo.get_all_staged_files_content(&pathspecs)?;
+    let reverted_va = attributions_after_revert(
+        repo,
+        head_sha,
+        reverted_commits,
+        mainline,
+        &pathspecs,
+        staged_files,
+    )?;
+
+    // Pass same SHA for parent and commit to get empty diff (no committed hunks)
+    let (_authorship_log, initial_attributions) =
+        reverted_va.to_authorship_log_and_initial_working_log(repo, head_sha, head_sha, None)?;
+
+    // Keep INITIAL entries for files the revert didn't touch
+    let working_log = repo.storage.working_log_for_base_commit(head_sha);
+    let existing = working_log.read_initial_attributions();
+    let mut files = existing.files;
+    files.retain(|file, _| !pathspecs.contains(file));
+    files.extend(initial_attributions.files);
+    let mut prompts = existing.prompts;
+    prompts.extend(initial_attributions.prompts);
+    working_log.write_initial_attributions(files, prompts)?;
+
+    Ok(affected_files)
+}
+
+/// Attributions for the state left by reverting `reverted_commits` on top of `base_sha`
+///
+/// Lines present before the revert keep their attribution. Lines the revert restores are
+/// looked up in each reverted commit's parent, where they last existed.
+fn attributions_after_revert(
+    repo: &Repository,
+    base_sha: &str,
+    reverted_commits: &[String],
+    mainline: Option<usize>,
+    pathspecs: &[String],
+    final_state: HashMap<String, String>,
+) -> Result<crate::authorship::virtual_attribution::VirtualAttributions, GitAiError> {
+    use crate::authorship::virtual_attribution::{
+        VirtualAttributions, merge_attributions_favoring_first,
+    };
+
+    let repo_clone = repo.clone();
+    let base_clone = base_sha.to_string();
+    let mut merged_va = smol::block_on(async {
+        VirtualAttributions::new_for_base_commit(repo_clone, base_clone, pathspecs, None).await
+    })?;
+
+    for reverted_commit in reverted_commits {
+        // Reverting a root commit only removes lines, so there is nothing to restore
+        let parent_va = match reverted_parent(repo, reverted_commit, mainline)? {
+            Some(parent_sha) => {
+                let repo_clone = repo.clone();
+                smol::block_on(async {
+                    VirtualAttributions::new_for_base_commit(
+                        repo_clone, parent_sha, pathspecs, None,
+                    )
+                    .await
+                })?
+            }
+            None => VirtualAttributions::new(
+                repo.clone(),
+                reverted_commit.clone(),
+                HashMap::new(),
+                HashMap::new(),
+                merged_va.timestamp(),
+            ),
+        };
+
+        merged_va = merge_attributions_favoring_first(merged_va, parent_va, final_state.clone())?;
+    }
+
+    Ok(merged_va)
+}
+
+/// The parent a revert undoes a commit against
+///
+/// That's the `-m/--mainline` parent for a merge and the only parent otherwise, or None for a
+/// root commit.
+fn reverted_parent(
+    repo: &Repository,
+    reverted_commit: &str,
+    mainline: Option<usize>,
+) -> Result<Option<String>, GitAiError> {
+    let commit = repo.find_commit(reverted_commit.to_string())?;
+    let parent_index = mainline.map_or(0, |parent_number| parent_number.saturating_sub(1));
+    Ok(commit.parent(parent_index).ok().map(|parent| parent.id()))
+}
+
 /// Get file contents from a commit tree for specified pathspecs
 fn get_committed_files_content(
     repo: &Repository,
@@ -773,6 +1066,133 @@ fn get_committed_files_content(
     Ok(files)
 }
 
+/// Move an authorship log from a commit onto the commit a history rewrite replaced it with
+///
+/// Used after repository-wide rewrites like `git filter-repo` or BFG, where every commit gets
+/// a new SHA and paths may have been renamed or removed. Each path goes through
+/// `path_renames` (`(old, new)` prefixes) first. A path that still isn't in the new commit is
+/// followed to the one file there with identical content, and dropped when there is none.
+/// Files whose content was rewritten are remapped with the same attribution transform the
+/// rebase rewrites use, as long as the old commit hasn't been pruned yet.
+pub fn remap_authorship_log(
+    repo: &Repository,
+    log: &AuthorshipLog,
+    old_commit: &str,
+    new_commit: &str,
+    path_renames: &[(String, String)],
+) -> Result<AuthorshipLog, GitAiError> {
+    let new_blobs = repo.list_tree_blobs(new_commit)?;
+    // filter-repo usually prunes the old commits, which leaves only the explicit renames
+    let old_blobs = repo.list_tree_blobs(old_commit).unwrap_or_default();
+
+    let mut remapped = log.clone();
+    remapped.attestations.clear();
+    remapped.metadata.base_commit_sha = new_commit.to_string();
+    // Any signature covered the old commit
+    remapped.metadata.signature = None;
+
+    let mut new_paths: HashMap<&str, Option<String>> = HashMap::new();
+    let mut rewritten_files = Vec::new();
+    for file in &log.attestations {
+        let new_path = rewritten_path(&file.file_path, path_renames, &old_blobs, &new_blobs);
+        new_paths.insert(&file.file_path, new_path.clone());
+        let Some(new_path) = new_path else {
+            debug_log(&format!(
+                "{} is gone from {}, dropping its attributions",
+                file.file_path, new_commit
+            ));
+            continue;
+        };
+
+        let mut moved = file.clone();
+        moved.file_path = new_path.clone();
+        match (old_blobs.get(&file.file_path), new_blobs.get(&new_path)) {
+            (Some(old_blob), Some(new_blob)) if old_blob != new_blob => {
+                let read = |path: &str, commit: &str| {
+                    repo.get_file_content(path, commit)
+                        .map(|bytes| String::from_utf8_lossy(&bytes).to_string())
+                };
+                let old_content = read(&file.file_path, old_commit)?;
+                let new_content = read(&new_path, new_commit)?;
+                moved
+                    .entries
+                    .iter_mut()
+                    .for_each(|entry| entry.anchors.clear());
+                rewritten_files.push((moved, old_content, new_content));
+            }
+            _ => remapped.attestations.push(moved),
+        }
+    }
+
+    if !rewritten_files.is_empty() {
+        remapped.attestations.extend(remap_attestations_to_content(
+            repo,
+            new_commit,
+            rewritten_files,
+        )?);
+    }
+
+    // Deleted lines point into the parent, so only the path moves
+    remapped.metadata.deletions = log
+        .metadata
+        .deletions
+        .iter()
+        .filter_map(|(path, by_hash)| {
+            let new_path = match new_paths.get(path.as_str()) {
+                Some(new_path) => new_path.clone()?,
+                None => rename_path(path, path_renames),
+            };
+            Some((new_path, by_hash.clone()))
+        })
+        .collect();
+
+    Ok(remapped)
+}
+
+/// Where a file from the old commit lives in the rewritten one, if anywhere
+fn rewritten_path(
+    path: &str,
+    path_renames: &[(String, String)],
+    old_blobs: &HashMap<String, String>,
+    new_blobs: &HashMap<String, String>,
+) -> Option<String> {
+    let renamed = rename_path(path, path_renames);
+    if new_blobs.contains_key(&renamed) {
+        return Some(renamed);
+    }
+
+    // Follow the content when exactly one file in the new commit has it
+    let old_blob = old_blobs.get(path)?;
+    let mut matches = new_blobs
+        .iter()
+        .filter(|(_, blob)| *blob == old_blob)
+        .map(|(path, _)| path);
+    let found = matches.next()?;
+    matches.next().is_none().then(|| found.clone())
+}
+
+/// Apply the first matching `(old, new)` rename, where `old` is a file or directory prefix
+fn rename_path(path: &str, path_renames: &[(String, String)]) -> String {
+    for (old, new) in path_renames {
+        let old_dir = old.trim_end_matches('/');
+        if path == old_dir {
+            return new.trim_end_matches('/').to_string();
+        }
+        if let Some(rest) = path
+            .strip_prefix(old_dir)
+            .and_then(|rest| rest.strip_prefix('/'))
+        {
+            let new_dir = new.trim_end_matches('/');
+            return if new_dir.is_empty() {
+                rest.to_string()
+            } else {
+                format!("{}/{}", new_dir, rest)
+            };
+        }
+    }
+    path.to_string()
+}
+
 pub fn rewrite_authorship_after_commit_amend(
     repo: &Repository,
     original_commit: &str,
@@ -1118,8 +1538,58 @@ pub fn filter_pathspecs_to_ai_touched_files(
         .collect())
 }
 
+/// Carry attestations from each file's original content onto its final content
+///
+/// Each item is a file's attestation with the content its ranges refer to and the content
+/// they should be moved onto. Lines that didn't survive lose their attribution.
+pub(crate) fn remap_attestations_to_content(
+    repo: &Repository,
+    base_commit: &str,
+    files: Vec<(FileAttestation, String, String)>,
+) -> Result<Vec<FileAttestation>, GitAiError> {
+    use crate::authorship::attribution_tracker::{
+        LineAttribution, line_attributions_to_attributions,
+    };
+    use crate::authorship::virtual_attribution::VirtualAttributions;
+
+    let mut attributions = HashMap::new();
+    let mut file_contents = HashMap::new();
+    let mut final_state = HashMap::new();
+
+    for (file, original_content, final_content) in files {
+        let mut line_attrs = Vec::new();
+        for entry in &file.entries {
+            for range in &entry.line_ranges {
+                line_attrs.push(LineAttribution::new(
+                    range.start(),
+                    range.end(),
+                    entry.hash.clone(),
+                    None,
+                ));
+            }
+        }
+        let char_attrs = line_attributions_to_attributions(&line_attrs, &original_content, 0);
+        attributions.insert(file.file_path.clone(), (char_attrs, line_attrs));
+        file_contents.insert(file.file_path.clone(), original_content);
+        final_state.insert(file.file_path, final_content);
+    }
+
+    let original_va = VirtualAttributions::new(
+        repo.clone(),
+        base_commit.to_string(),
+        attributions,
+        file_contents,
+        0,
+    );
+    Ok(
+        transform_attributions_to_final_state(&original_va, final_state, None)?
+            .to_authorship_log()?
+            .attestations,
+    )
+}
+
 /// Transform VirtualAttributions to match a new final state (single-source variant)
-fn transform_attributions_to_final_state(
+pub(crate) fn transform_attributions_to_final_state(
     source_va: &crate::authorship::virtual_attribution::VirtualAttributions,
     final_state: HashMap<String, String>,
     original_head_state: Option<&crate::authorship::virtual_attribution::VirtualAttributions>,
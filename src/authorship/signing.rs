@@ -0,0 +1,318 @@
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::error::GitAiError;
+use crate::git::repository::Repository;
+use crate::utils::debug_log;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Namespace passed to `ssh-keygen -Y`, so a note signature can't be replayed as a commit or file signature
+pub const SIGNATURE_NAMESPACE: &str = "git-ai-authorship";
+
+/// Git config key holding the SSH key used to sign notes (same semantics as `user.signingKey`)
+pub const SIGNING_KEY_CONFIG: &str = "gitai.signingKey";
+
+/// Git config key holding the allowed-signers file used by `git-ai verify`
+pub const ALLOWED_SIGNERS_CONFIG: &str = "gitai.allowedSignersFile";
+
+/// Outcome of checking the signature on a single note
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureStatus {
+    Unsigned,
+    Valid { principal: String },
+    Invalid { reason: String },
+}
+
+/// SSH key configured for signing notes in this repository, if any
+pub fn configured_signing_key(repo: &Repository) -> Option<String> {
+    repo.config_get_str(SIGNING_KEY_CONFIG)
+        .ok()
+        .flatten()
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+}
+
+/// Allowed-signers file for this repository, falling back to git's own `gpg.ssh.allowedSignersFile`
+pub fn configured_allowed_signers(repo: &Repository) -> Option<PathBuf> {
+    [ALLOWED_SIGNERS_CONFIG, "gpg.ssh.allowedSignersFile"]
+        .iter()
+        .find_map(|key| repo.config_get_str(key).ok().flatten())
+        .map(|path| expand_home(path.trim()))
+}
+
+/// Sign a serialized note if a signing key is configured, otherwise return it untouched.
+///
+/// Signing failures are logged and the note is written unsigned: a broken key setup
+/// shouldn't lose authorship data, and `git-ai verify` will flag the missing signature.
+pub fn sign_note_if_configured(repo: &Repository, note_content: &str) -> String {
+    let Some(signing_key) = configured_signing_key(repo) else {
+        return note_content.to_string();
+    };
+
+    let mut log = match AuthorshipLog::deserialize_from_string(note_content) {
+        Ok(log) => log,
+        Err(e) => {
+            debug_log(&format!("Not signing note that failed to parse: {}", e));
+            return note_content.to_string();
+        }
+    };
+
+    let signed = sign_authorship_log(&mut log, &expand_home(&signing_key)).and_then(|_| {
+        log.serialize_to_string()
+            .map_err(|_| GitAiError::Generic("Failed to serialize signed note".to_string()))
+    });
+
+    match signed {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!(
+                "Warning: failed to sign authorship note, writing it unsigned: {}",
+                e
+            );
+            note_content.to_string()
+        }
+    }
+}
+
+/// The exact bytes covered by a note's signature: the note serialized without its signature
+pub fn signed_payload(log: &AuthorshipLog) -> Result<String, GitAiError> {
+    let mut unsigned = log.clone();
+    unsigned.metadata.signature = None;
+    unsigned
+        .serialize_to_string()
+        .map_err(|_| GitAiError::Generic("Failed to serialize authorship log".to_string()))
+}
+
+/// Sign the log with `ssh-keygen -Y sign`, storing the armored signature in its metadata
+pub fn sign_authorship_log(log: &mut AuthorshipLog, key_path: &Path) -> Result<(), GitAiError> {
+    let payload = signed_payload(log)?;
+    let output = run_ssh_keygen(
+        &[
+            "-Y".to_string(),
+            "sign".to_string(),
+            "-n".to_string(),
+            SIGNATURE_NAMESPACE.to_string(),
+            "-f".to_string(),
+            key_path.to_string_lossy().to_string(),
+        ],
+        payload.as_bytes(),
+    )?;
+
+    if !output.status.success() {
+        return Err(GitAiError::Generic(format!(
+            "ssh-keygen failed to sign note: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let signature = String::from_utf8(output.stdout)?.trim().to_string();
+    if signature.is_empty() {
+        return Err(GitAiError::Generic(
+            "ssh-keygen produced an empty signature".to_string(),
+        ));
+    }
+
+    log.metadata.signature = Some(signature);
+    Ok(())
+}
+
+/// Check a note's signature against an allowed-signers file
+///
+/// Mirrors what git does for SSH-signed commits: look up the principal for the signing key
+/// with `find-principals`, then verify the payload against that principal.
+pub fn verify_authorship_log(
+    log: &AuthorshipLog,
+    allowed_signers: &Path,
+) -> Result<SignatureStatus, GitAiError> {
+    let Some(signature) = log.metadata.signature.as_ref() else {
+        return Ok(SignatureStatus::Unsigned);
+    };
+
+    let payload = signed_payload(log)?;
+    let signature_file = SignatureFile::write(signature)?;
+    let allowed_signers = allowed_signers.to_string_lossy().to_string();
+    let signature_path = signature_file.path.to_string_lossy().to_string();
+
+    let output = run_ssh_keygen(
+        &[
+            "-Y".to_string(),
+            "find-principals".to_string(),
+            "-f".to_string(),
+            allowed_signers.clone(),
+            "-s".to_string(),
+            signature_path.clone(),
+        ],
+        &[],
+    )?;
+    let principal = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+        .unwrap_or_default();
+    if !output.status.success() || principal.is_empty() {
+        return Ok(SignatureStatus::Invalid {
+            reason: "signing key is not in the allowed signers file".to_string(),
+        });
+    }
+
+    let output = run_ssh_keygen(
+        &[
+            "-Y".to_string(),
+            "verify".to_string(),
+            "-n".to_string(),
+            SIGNATURE_NAMESPACE.to_string(),
+            "-f".to_string(),
+            allowed_signers,
+            "-I".to_string(),
+            principal.clone(),
+            "-s".to_string(),
+            signature_path,
+        ],
+        payload.as_bytes(),
+    )?;
+
+    if output.status.success() {
+        Ok(SignatureStatus::Valid { principal })
+    } else {
+        Ok(SignatureStatus::Invalid {
+            reason: "signature does not match note contents".to_string(),
+        })
+    }
+}
+
+fn run_ssh_keygen(args: &[String], stdin_data: &[u8]) -> Result<std::process::Output, GitAiError> {
+    let mut child = Command::new("ssh-keygen")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| GitAiError::Generic(format!("Failed to run ssh-keygen: {}", e)))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(stdin_data).map_err(GitAiError::IoError)?;
+    }
+
+    child.wait_with_output().map_err(GitAiError::IoError)
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .map(|home| home.join(rest))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    }
+}
+
+/// `ssh-keygen -Y verify` only reads signatures from a file, so stage it in the temp dir
+struct SignatureFile {
+    path: PathBuf,
+}
+
+impl SignatureFile {
+    fn write(signature: &str) -> Result<Self, GitAiError> {
+        let path = std::env::temp_dir().join(format!("git-ai-note-{}.sig", uuid::Uuid::new_v4()));
+        std::fs::write(&path, format!("{}\n", signature))?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for SignatureFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::authorship_log::LineRange;
+    use crate::authorship::authorship_log_serialization::AttestationEntry;
+
+    fn generate_key(dir: &Path) -> PathBuf {
+        let key_path = dir.join("id_ed25519");
+        let status = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-C", "test", "-f"])
+            .arg(&key_path)
+            .status()
+            .expect("ssh-keygen should be installed");
+        assert!(status.success());
+        key_path
+    }
+
+    fn allowed_signers_for(dir: &Path, key_path: &Path, principal: &str) -> PathBuf {
+        let public_key = std::fs::read_to_string(key_path.with_extension("pub")).unwrap();
+        let allowed_signers = dir.join("allowed_signers");
+        std::fs::write(
+            &allowed_signers,
+            format!(
+                "{} namespaces=\"{}\" {}",
+                principal, SIGNATURE_NAMESPACE, public_key
+            ),
+        )
+        .unwrap();
+        allowed_signers
+    }
+
+    fn sample_log() -> AuthorshipLog {
+        let mut log = AuthorshipLog::new();
+        log.metadata.base_commit_sha = "abc123".to_string();
+        log.get_or_create_file("src/main.rs")
+            .add_entry(AttestationEntry::new(
+                "0123456789abcdef".to_string(),
+                vec![LineRange::Range(1, 5)],
+            ));
+        log
+    }
+
+    #[test]
+    fn test_sign_and_verify_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let key_path = generate_key(dir.path());
+        let allowed_signers = allowed_signers_for(dir.path(), &key_path, "dev@example.com");
+
+        let mut log = sample_log();
+        sign_authorship_log(&mut log, &key_path).unwrap();
+        assert!(log.metadata.signature.is_some());
+
+        // Signature must survive the round trip through the note text
+        let serialized = log.serialize_to_string().unwrap();
+        let parsed = AuthorshipLog::deserialize_from_string(&serialized).unwrap();
+        assert_eq!(
+            verify_authorship_log(&parsed, &allowed_signers).unwrap(),
+            SignatureStatus::Valid {
+                principal: "dev@example.com".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_detects_tampering_and_unknown_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let key_path = generate_key(dir.path());
+        let allowed_signers = allowed_signers_for(dir.path(), &key_path, "dev@example.com");
+
+        let mut log = sample_log();
+        assert_eq!(
+            verify_authorship_log(&log, &allowed_signers).unwrap(),
+            SignatureStatus::Unsigned
+        );
+
+        sign_authorship_log(&mut log, &key_path).unwrap();
+        let mut tampered = log.clone();
+        tampered.attestations[0].entries[0].line_ranges = vec![LineRange::Range(1, 50)];
+        assert!(matches!(
+            verify_authorship_log(&tampered, &allowed_signers).unwrap(),
+            SignatureStatus::Invalid { .. }
+        ));
+
+        let other_dir = tempfile::tempdir().unwrap();
+        let other_key = generate_key(other_dir.path());
+        let other_signers = allowed_signers_for(other_dir.path(), &other_key, "other@example.com");
+        assert!(matches!(
+            verify_authorship_log(&log, &other_signers).unwrap(),
+            SignatureStatus::Invalid { .. }
+        ));
+    }
+}
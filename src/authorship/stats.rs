@@ -1,15 +1,18 @@
-use crate::authorship::diff_ai_accepted::diff_ai_accepted_stats;
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::diff_ai_accepted::{ai_accepted_stats_for_lines, diff_ai_accepted_stats};
+use crate::authorship::range_authorship::should_ignore_file;
 use crate::authorship::transcript::Message;
 use crate::error::GitAiError;
 use crate::git::refs::get_authorship;
 use crate::git::repository::Repository;
 use crate::utils::debug_log;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 
 const EMPTY_TREE_HASH: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct ToolModelHeadlineStats {
     #[serde(default)]
     pub ai_additions: u32, // Number of lines committed with AI attribution (full and/or mixed)
@@ -25,7 +28,7 @@ pub struct ToolModelHeadlineStats {
     pub time_waiting_for_ai: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct CommitStats {
     #[serde(default)]
     pub human_additions: u32, // Number of lines committed with human attribution (full and/or mixed)
@@ -45,6 +48,8 @@ pub struct CommitStats {
     pub git_diff_deleted_lines: u32,
     #[serde(default)]
     pub git_diff_added_lines: u32,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub merge_resolution: bool, // Merge commit: additions only count lines that differ from every parent
     #[serde(default)]
     pub tool_model_breakdown: BTreeMap<String, ToolModelHeadlineStats>,
 }
@@ -54,6 +59,7 @@ pub fn stats_command(
     commit_sha: Option<&str>,
     json: bool,
     ignore_patterns: &[String],
+    recurse_submodules: bool,
 ) -> Result<(), GitAiError> {
     let (target, refname) = if let Some(sha) = commit_sha {
         // Validate that the commit exists using revparse_single
@@ -81,7 +87,11 @@ pub fn stats_command(
         target, refname
     ));
 
-    let stats = stats_for_commit_stats(repo, &target, ignore_patterns)?;
+    let stats = if recurse_submodules {
+        commit_stats_with_submodules(repo, &target, ignore_patterns)?
+    } else {
+        stats_for_commit_stats(repo, &target, ignore_patterns)?
+    };
 
     if json {
         let json_str = serde_json::to_string(&stats)?;
@@ -99,6 +109,16 @@ pub fn write_stats_to_terminal(stats: &CommitStats, print: bool) -> String {
     // Set maximum bar width to 40 characters
     let bar_width: usize = 40;
 
+    // Merge commits only count the lines written while resolving them
+    if stats.merge_resolution {
+        let merge_msg = format!("     \x1b[90m{:^40}\x1b[0m", "(merge resolution)");
+        output.push_str(&merge_msg);
+        output.push('\n');
+        if print {
+            println!("{}", merge_msg);
+        }
+    }
+
     // Handle deletion-only commits (no additions)
     if stats.git_diff_added_lines == 0 && stats.git_diff_deleted_lines > 0 {
         // Show gray bar for deletion-only commit
@@ -446,6 +466,114 @@ pub fn write_stats_to_markdown(stats: &CommitStats) -> String {
     output
 }
 
+/// Compute a superproject's stats with the work done inside its submodules folded in
+///
+/// `superproject_stats` computes the superproject's own stats for the given ignore patterns;
+/// the submodules' gitlinks are ignored there, since their lines are counted from the
+/// submodule commits `from..to` moved each one across, using the submodule's own notes.
+pub fn stats_with_submodules<F>(
+    repo: &Repository,
+    from: Option<&str>,
+    to: &str,
+    ignore_patterns: &[String],
+    superproject_stats: F,
+) -> Result<CommitStats, GitAiError>
+where
+    F: FnOnce(&[String]) -> Result<CommitStats, GitAiError>,
+{
+    let changes = repo.diff_submodule_commits(from, to)?;
+
+    let mut patterns = ignore_patterns.to_vec();
+    patterns.extend(changes.iter().map(|(path, _, _)| path.clone()));
+    let mut stats = superproject_stats(&patterns)?;
+
+    for (path, old_commit, new_commit) in changes {
+        let submodule = match repo.open_submodule(&path) {
+            Ok(submodule) => submodule,
+            Err(e) => {
+                debug_log(&format!("Skipping submodule {} in stats: {}", path, e));
+                continue;
+            }
+        };
+
+        let mut args = submodule.global_args_for_exec();
+        args.push("rev-list".to_string());
+        args.push(new_commit.clone());
+        if let Some(old_commit) = &old_commit {
+            args.push(format!("^{}", old_commit));
+        }
+        let commits = match crate::git::repository::exec_git(&args) {
+            Ok(output) => String::from_utf8(output.stdout)?,
+            Err(e) => {
+                // The submodule hasn't fetched the recorded commits
+                debug_log(&format!("Skipping submodule {} in stats: {}", path, e));
+                continue;
+            }
+        };
+
+        for commit_sha in commits.lines() {
+            match commit_stats_with_submodules(&submodule, commit_sha, ignore_patterns) {
+                Ok(commit_stats) => add_commit_stats(&mut stats, &commit_stats),
+                Err(e) => debug_log(&format!(
+                    "Skipping {} in submodule {} in stats: {}",
+                    commit_sha, path, e
+                )),
+            }
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Stats for a single commit, including the submodule commits it moved its gitlinks across
+fn commit_stats_with_submodules(
+    repo: &Repository,
+    commit_sha: &str,
+    ignore_patterns: &[String],
+) -> Result<CommitStats, GitAiError> {
+    let parents: Vec<String> = repo
+        .find_commit(commit_sha.to_string())?
+        .parents()
+        .map(|parent| parent.id())
+        .collect();
+    // A merge's submodule changes are counted on the commits that made them
+    if parents.len() > 1 {
+        return stats_for_commit_stats(repo, commit_sha, ignore_patterns);
+    }
+    stats_with_submodules(
+        repo,
+        parents.first().map(String::as_str),
+        commit_sha,
+        ignore_patterns,
+        |patterns| stats_for_commit_stats(repo, commit_sha, patterns),
+    )
+}
+
+/// Add one set of stats to a running total
+pub fn add_commit_stats(total: &mut CommitStats, stats: &CommitStats) {
+    total.human_additions += stats.human_additions;
+    total.mixed_additions += stats.mixed_additions;
+    total.ai_additions += stats.ai_additions;
+    total.ai_accepted += stats.ai_accepted;
+    total.total_ai_additions += stats.total_ai_additions;
+    total.total_ai_deletions += stats.total_ai_deletions;
+    total.time_waiting_for_ai += stats.time_waiting_for_ai;
+    total.git_diff_deleted_lines += stats.git_diff_deleted_lines;
+    total.git_diff_added_lines += stats.git_diff_added_lines;
+    for (tool_model, tool_stats) in &stats.tool_model_breakdown {
+        let total_tool_stats = total
+            .tool_model_breakdown
+            .entry(tool_model.clone())
+            .or_default();
+        total_tool_stats.ai_additions += tool_stats.ai_additions;
+        total_tool_stats.mixed_additions += tool_stats.mixed_additions;
+        total_tool_stats.ai_accepted += tool_stats.ai_accepted;
+        total_tool_stats.total_ai_additions += tool_stats.total_ai_additions;
+        total_tool_stats.total_ai_deletions += tool_stats.total_ai_deletions;
+        total_tool_stats.time_waiting_for_ai += tool_stats.time_waiting_for_ai;
+    }
+}
+
 /// Calculate commit stats from an authorship log
 /// This helper can work with both fetched and in-memory authorship logs
 pub fn stats_from_authorship_log(
@@ -466,6 +594,7 @@ pub fn stats_from_authorship_log(
         tool_model_breakdown: BTreeMap::new(),
         git_diff_deleted_lines,
         git_diff_added_lines,
+        merge_resolution: false,
     };
 
     // Process authorship log if present
@@ -534,19 +663,58 @@ pub fn stats_for_commit_stats(
     commit_sha: &str,
     ignore_patterns: &[String],
 ) -> Result<CommitStats, GitAiError> {
-    // Step 1: get the diff between this commit and its parent ON refname (if more than one parent)
+    commit_stats(repo, commit_sha, ignore_patterns, false)
+}
+
+/// Like `stats_for_commit_stats`, but prompts only count when they wrote lines in a file
+/// that isn't ignored, so the prompt totals and waiting time cover the same files as the
+/// line counts. Used to split a commit's stats across directories.
+pub fn stats_for_commit_files(
+    repo: &Repository,
+    commit_sha: &str,
+    ignore_patterns: &[String],
+) -> Result<CommitStats, GitAiError> {
+    commit_stats(repo, commit_sha, ignore_patterns, true)
+}
+
+/// Drop the attestations of ignored files and the prompts only they referenced
+fn scope_log_to_files(mut log: AuthorshipLog, ignore_patterns: &[String]) -> AuthorshipLog {
+    log.attestations
+        .retain(|file| !should_ignore_file(&file.file_path, ignore_patterns));
+    let referenced: HashSet<&str> = log
+        .attestations
+        .iter()
+        .flat_map(|file| file.entries.iter().map(|entry| entry.hash.as_str()))
+        .collect();
+    log.metadata
+        .prompts
+        .retain(|hash, _| referenced.contains(hash.as_str()));
+    log
+}
+
+fn commit_stats(
+    repo: &Repository,
+    commit_sha: &str,
+    ignore_patterns: &[String],
+    scope_prompts: bool,
+) -> Result<CommitStats, GitAiError> {
+    let commit_obj = repo.revparse_single(commit_sha)?.peel_to_commit()?;
+    let parents: Vec<String> = commit_obj.parents().map(|parent| parent.id()).collect();
+    if parents.len() > 1 {
+        return merge_resolution_stats(repo, commit_sha, &parents, ignore_patterns, scope_prompts);
+    }
+
+    // Step 1: get the diff between this commit and its parent
     // If initial than everything is additions
     // We want the count here git shows +111 -55
     let (git_diff_added_lines, git_diff_deleted_lines) =
         get_git_diff_stats(repo, commit_sha, ignore_patterns)?;
 
     // Step 2: get parent SHA for diff-based accepted counts
-    let commit_obj = repo.revparse_single(commit_sha)?.peel_to_commit()?;
-    let parent_sha = if commit_obj.parent_count()? == 0 {
-        EMPTY_TREE_HASH.to_string()
-    } else {
-        commit_obj.parent(0)?.id()
-    };
+    let parent_sha = parents
+        .first()
+        .cloned()
+        .unwrap_or_else(|| EMPTY_TREE_HASH.to_string());
 
     let diff_ai_stats = diff_ai_accepted_stats(
         repo,
@@ -557,7 +725,10 @@ pub fn stats_for_commit_stats(
     )?;
 
     // Step 3: get the authorship log for this commit
-    let authorship_log = get_authorship(repo, commit_sha);
+    let mut authorship_log = get_authorship(repo, commit_sha);
+    if scope_prompts {
+        authorship_log = authorship_log.map(|log| scope_log_to_files(log, ignore_patterns));
+    }
 
     // Step 4: Calculate stats from authorship log with diff-based accepted counts
     Ok(stats_from_authorship_log(
@@ -569,6 +740,46 @@ pub fn stats_for_commit_stats(
     ))
 }
 
+/// Stats for a merge commit, counting only the lines written while resolving it
+///
+/// Lines that came unchanged from a parent are counted on the commits that wrote them.
+fn merge_resolution_stats(
+    repo: &Repository,
+    commit_sha: &str,
+    parents: &[String],
+    ignore_patterns: &[String],
+    scope_prompts: bool,
+) -> Result<CommitStats, GitAiError> {
+    let mut resolution_lines = repo.merge_resolution_lines(commit_sha, parents)?;
+    resolution_lines.retain(|file, _| !should_ignore_file(file, ignore_patterns));
+    let resolved_line_count = resolution_lines
+        .values()
+        .map(|lines| lines.len() as u32)
+        .sum();
+
+    let diff_ai_stats = ai_accepted_stats_for_lines(
+        repo,
+        resolution_lines,
+        commit_sha,
+        Some(&parents[0]),
+        ignore_patterns,
+    )?;
+
+    let mut authorship_log = get_authorship(repo, commit_sha);
+    if scope_prompts {
+        authorship_log = authorship_log.map(|log| scope_log_to_files(log, ignore_patterns));
+    }
+    let mut stats = stats_from_authorship_log(
+        authorship_log.as_ref(),
+        resolved_line_count,
+        0,
+        diff_ai_stats.total_ai_accepted,
+        &diff_ai_stats.per_tool_model,
+    );
+    stats.merge_resolution = true;
+    Ok(stats)
+}
+
 /// Get git diff statistics between commit and its parent
 pub fn get_git_diff_stats(
     repo: &Repository,
@@ -703,6 +914,7 @@ mod tests {
             total_ai_additions: 100,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            merge_resolution: false,
         };
 
         let mixed_output = write_stats_to_terminal(&stats, true);
@@ -720,6 +932,7 @@ mod tests {
             total_ai_additions: 100,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            merge_resolution: false,
         };
 
         let ai_only_output = write_stats_to_terminal(&ai_stats, true);
@@ -737,6 +950,7 @@ mod tests {
             total_ai_additions: 0,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            merge_resolution: false,
         };
 
         let human_only_output = write_stats_to_terminal(&human_stats, true);
@@ -754,6 +968,7 @@ mod tests {
             total_ai_additions: 100,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            merge_resolution: false,
         };
 
         let minimal_human_output = write_stats_to_terminal(&minimal_human_stats, true);
@@ -771,6 +986,7 @@ mod tests {
             total_ai_additions: 0,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            merge_resolution: false,
         };
 
         let deletion_only_output = write_stats_to_terminal(&deletion_only_stats, true);
@@ -791,6 +1007,7 @@ mod tests {
             total_ai_additions: 100,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            merge_resolution: false,
         };
 
         let mixed_output = write_stats_to_markdown(&stats);
@@ -808,6 +1025,7 @@ mod tests {
             total_ai_additions: 100,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            merge_resolution: false,
         };
 
         let ai_only_output = write_stats_to_markdown(&ai_stats);
@@ -825,6 +1043,7 @@ mod tests {
             total_ai_additions: 0,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            merge_resolution: false,
         };
 
         let human_only_output = write_stats_to_markdown(&human_stats);
@@ -842,6 +1061,7 @@ mod tests {
             total_ai_additions: 100,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            merge_resolution: false,
         };
 
         let minimal_human_output = write_stats_to_markdown(&minimal_human_stats);
@@ -859,6 +1079,7 @@ mod tests {
             total_ai_additions: 0,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            merge_resolution: false,
         };
 
         let deletion_only_output = write_stats_to_markdown(&deletion_only_stats);
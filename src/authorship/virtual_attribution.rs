@@ -150,16 +150,17 @@ impl VirtualAttributions {
         repo: &Repository,
         prompt_id: &str,
     ) -> Result<(String, crate::authorship::authorship_log::PromptRecord), GitAiError> {
-        // Use git grep to search for the prompt ID in authorship notes
-        let shas = crate::git::refs::grep_ai_notes(repo, &format!("\"{}\"", prompt_id))
-            .unwrap_or_default();
-
-        // Check the most recent commit with this prompt ID
-        if let Some(latest_sha) = shas.first()
-            && let Ok(log) = crate::git::refs::get_reference_as_authorship_log_v3(repo, latest_sha)
-            && let Some(prompt) = log.metadata.prompts.get(prompt_id)
-        {
-            return Ok((latest_sha.clone(), prompt.clone()));
+        // Use git grep to search for the prompt ID in authorship notes. The bare ID also
+        // matches attestation lines, which stay plain text when the metadata is compressed.
+        let shas = crate::git::refs::grep_ai_notes(repo, prompt_id).unwrap_or_default();
+
+        // Take the most recent commit whose note carries this prompt
+        for sha in &shas {
+            if let Ok(log) = crate::git::refs::get_reference_as_authorship_log_v3(repo, sha)
+                && let Some(prompt) = log.metadata.prompts.get(prompt_id)
+            {
+                return Ok((sha.clone(), prompt.clone()));
+            }
         }
 
         Err(GitAiError::Generic(format!(
@@ -1,7 +1,9 @@
 use crate::authorship::attribution_tracker::{Attribution, LineAttribution};
 use crate::authorship::authorship_log_serialization::GIT_AI_VERSION;
 use crate::authorship::transcript::AiTranscript;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fmt;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -20,6 +22,19 @@ pub struct WorkingLogEntry {
     pub attributions: Vec<Attribution>,
     #[serde(default)]
     pub line_attributions: Vec<LineAttribution>,
+    /// Lines an AI checkpoint removed from the file, by their line number in the file's
+    /// previous checkpoint. Matched against the commit's diff to attribute deletions to prompts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deleted_lines: Vec<DeletedLine>,
+}
+
+/// A line removed by an AI checkpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeletedLine {
+    /// Line number in the file's content before the checkpoint
+    pub line: u32,
+    /// `deleted_line_hash` of the line, to check matches against
+    pub hash: String,
 }
 
 impl WorkingLogEntry {
@@ -35,11 +50,21 @@ impl WorkingLogEntry {
             blob_sha,
             attributions,
             line_attributions,
+            deleted_lines: Vec::new(),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Short content hash of a deleted line, ignoring its line ending
+///
+/// Working logs keep these instead of the deleted text, which would otherwise sit on disk
+/// until the next commit.
+pub fn deleted_line_hash(line: &str) -> String {
+    let digest = Sha256::digest(line.trim_end_matches(['\r', '\n']).as_bytes());
+    format!("{:x}", digest)[..16].to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct AgentId {
     pub tool: String, // e.g., "cursor", "windsurf"
     pub id: String,   // id in their domain
@@ -189,6 +214,15 @@ mod tests {
         assert!(deserialized.agent_id.is_none());
     }
 
+    #[test]
+    fn test_deleted_line_hash_ignores_line_endings() {
+        let hash = deleted_line_hash("fn main() {}");
+        assert_eq!(hash.len(), 16);
+        assert_eq!(deleted_line_hash("fn main() {}\n"), hash);
+        assert_eq!(deleted_line_hash("fn main() {}\r\n"), hash);
+        assert_ne!(deleted_line_hash("fn main() { }"), hash);
+    }
+
     #[test]
     fn test_log_array_serialization() {
         let entry1 = WorkingLogEntry::new(
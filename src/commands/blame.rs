@@ -1,15 +1,17 @@
+use crate::authorship::anchors::reanchor_file_at_commit;
 use crate::authorship::authorship_log::PromptRecord;
-use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::authorship_log_serialization::{FileAttestation, lookup_foreign_prompt};
 use crate::authorship::working_log::CheckpointKind;
 use crate::error::GitAiError;
-use crate::git::refs::get_reference_as_authorship_log_v3;
+use crate::git::refs::{get_authorship_attestations, get_authorship_prompts};
 use crate::git::repository::Repository;
 use crate::git::repository::{exec_git, exec_git_stdin};
 #[cfg(windows)]
 use crate::utils::normalize_to_posix;
 use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use schemars::JsonSchema;
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::io::{self, IsTerminal, Write};
 use std::sync::LazyLock;
@@ -141,6 +143,9 @@ pub struct GitAiBlameOptions {
     // When true, a single git blame hunk may be split into multiple hunks
     // if different lines were authored by different humans working with AI
     pub split_hunks_by_ai_author: bool,
+
+    // Blame files inside submodules against the submodule's own history
+    pub recurse_submodules: bool,
 }
 
 impl Default for GitAiBlameOptions {
@@ -185,6 +190,7 @@ impl Default for GitAiBlameOptions {
             json: false,
             mark_unknown: false,
             split_hunks_by_ai_author: true,
+            recurse_submodules: false,
         }
     }
 }
@@ -348,7 +354,7 @@ impl Repository {
         }
 
         // Step 2: Overlay AI authorship information
-        let (line_authors, prompt_records, authorship_logs, prompt_commits) =
+        let (line_authors, prompt_records, commit_attestations, prompt_commits) =
             overlay_ai_authorship(self, &all_blame_hunks, &relative_file_path, &options)?;
 
         if options.no_output {
@@ -360,7 +366,7 @@ impl Repository {
             output_json_format(
                 &line_authors,
                 &prompt_records,
-                &authorship_logs,
+                &commit_attestations,
                 &prompt_commits,
                 &relative_file_path,
             )?;
@@ -701,44 +707,22 @@ impl Repository {
         file_path: &str,
         options: &GitAiBlameOptions,
     ) -> Result<Vec<BlameHunk>, GitAiError> {
-        // Cache authorship logs by commit SHA to avoid repeated lookups
-        let mut commit_authorship_cache: HashMap<String, Option<AuthorshipLog>> = HashMap::new();
-        // Cache for foreign prompts to avoid repeated grepping
-        let mut foreign_prompts_cache: HashMap<String, Option<PromptRecord>> = HashMap::new();
+        let mut notes = BlameNotes::load(self, &hunks, file_path);
 
         let mut result_hunks: Vec<BlameHunk> = Vec::new();
 
         for hunk in hunks {
-            // Get or fetch the authorship log for this commit
-            let authorship_log = if let Some(cached) = commit_authorship_cache.get(&hunk.commit_sha)
-            {
-                cached.clone()
-            } else {
-                let authorship = get_reference_as_authorship_log_v3(self, &hunk.commit_sha).ok();
-                commit_authorship_cache.insert(hunk.commit_sha.clone(), authorship.clone());
-                authorship
-            };
-
             // If we have an authorship log, look up human_author for each line
-            if let Some(ref authorship_log) = authorship_log {
+            if notes.has_note(&hunk.commit_sha) {
                 // Collect human_author for each line in this hunk
                 let num_lines = hunk.range.1 - hunk.range.0 + 1;
                 let mut line_authors: Vec<Option<String>> = Vec::with_capacity(num_lines as usize);
 
                 for i in 0..num_lines {
                     let orig_line_num = hunk.orig_range.0 + i;
-
-                    let human_author = if let Some((_author, _prompt_hash, Some(prompt_record))) =
-                        authorship_log.get_line_attribution(
-                            self,
-                            file_path,
-                            orig_line_num,
-                            &mut foreign_prompts_cache,
-                        ) {
-                        prompt_record.human_author.clone()
-                    } else {
-                        None
-                    };
+                    let human_author = notes
+                        .line_attribution(&hunk.commit_sha, file_path, orig_line_num)
+                        .and_then(|(_, prompt_record)| prompt_record.human_author);
                     line_authors.push(human_author);
                 }
 
@@ -795,83 +779,153 @@ impl Repository {
     }
 }
 
-#[allow(clippy::type_complexity)]
+/// Authorship notes for the commits a blame touches
+///
+/// Only the attestation section of each note is read up front. Prompt metadata is
+/// loaded afterwards, once per commit, and only for hashes covering blamed lines.
+struct BlameNotes<'a> {
+    repo: &'a Repository,
+    attestations: HashMap<String, Option<Vec<FileAttestation>>>,
+    prompts: HashMap<String, BTreeMap<String, PromptRecord>>,
+    // Cache for foreign prompts to avoid repeated grepping
+    foreign_prompts_cache: HashMap<String, Option<PromptRecord>>,
+}
+
+impl<'a> BlameNotes<'a> {
+    fn load(repo: &'a Repository, hunks: &[BlameHunk], file_path: &str) -> Self {
+        let mut attestations: HashMap<String, Option<Vec<FileAttestation>>> = HashMap::new();
+        let mut hashes_by_commit: HashMap<String, HashSet<String>> = HashMap::new();
+
+        for hunk in hunks {
+            let commit_attestations =
+                attestations
+                    .entry(hunk.commit_sha.clone())
+                    .or_insert_with(|| {
+                        let mut commit_attestations =
+                            get_authorship_attestations(repo, &hunk.commit_sha)?;
+                        // Confirm anchored lines still match before trusting their line numbers
+                        if let Some(file_attestation) = commit_attestations
+                            .iter_mut()
+                            .find(|f| f.file_path == file_path)
+                        {
+                            reanchor_file_at_commit(repo, &hunk.commit_sha, file_attestation);
+                        }
+                        Some(commit_attestations)
+                    });
+            let Some(file_attestation) = commit_attestations
+                .as_ref()
+                .and_then(|a| a.iter().find(|f| f.file_path == file_path))
+            else {
+                continue;
+            };
+            for orig_line_num in hunk.orig_range.0..=hunk.orig_range.1 {
+                hashes_by_commit
+                    .entry(hunk.commit_sha.clone())
+                    .or_default()
+                    .extend(file_attestation.hashes_for_line(orig_line_num));
+            }
+        }
+
+        let prompts = hashes_by_commit
+            .into_iter()
+            .filter(|(_, hashes)| !hashes.is_empty())
+            .map(|(commit_sha, hashes)| {
+                let prompts =
+                    get_authorship_prompts(repo, &commit_sha, &hashes).unwrap_or_default();
+                (commit_sha, prompts)
+            })
+            .collect();
+
+        Self {
+            repo,
+            attestations,
+            prompts,
+            foreign_prompts_cache: HashMap::new(),
+        }
+    }
+
+    fn has_note(&self, commit_sha: &str) -> bool {
+        matches!(self.attestations.get(commit_sha), Some(Some(_)))
+    }
+
+    /// Prompt hash and record for a line as of `commit_sha`, using its original line number
+    ///
+    /// The latest entry covering the line wins; earlier entries are used if its prompt
+    /// can't be found in this note or any other.
+    fn line_attribution(
+        &mut self,
+        commit_sha: &str,
+        file_path: &str,
+        orig_line_num: u32,
+    ) -> Option<(String, PromptRecord)> {
+        let candidates = self
+            .attestations
+            .get(commit_sha)?
+            .as_ref()?
+            .iter()
+            .find(|f| f.file_path == file_path)?
+            .hashes_for_line(orig_line_num);
+
+        candidates.into_iter().find_map(|hash| {
+            self.prompts
+                .get(commit_sha)
+                .and_then(|prompts| prompts.get(&hash).cloned())
+                .or_else(|| {
+                    lookup_foreign_prompt(self.repo, &hash, &mut self.foreign_prompts_cache)
+                })
+                .map(|record| (hash, record))
+        })
+    }
+
+    fn into_attestations(self) -> Vec<Vec<FileAttestation>> {
+        self.attestations.into_values().flatten().collect()
+    }
+}
+
+/// Line authors, prompt records, the attestations seen, and prompt_hash -> commit_shas
+type AiAuthorshipOverlay = (
+    HashMap<u32, String>,
+    HashMap<String, PromptRecord>,
+    Vec<Vec<FileAttestation>>,
+    HashMap<String, Vec<String>>,
+);
+
 fn overlay_ai_authorship(
     repo: &Repository,
     blame_hunks: &[BlameHunk],
     file_path: &str,
     options: &GitAiBlameOptions,
-) -> Result<
-    (
-        HashMap<u32, String>,
-        HashMap<String, PromptRecord>,
-        Vec<AuthorshipLog>,
-        HashMap<String, Vec<String>>, // prompt_hash -> commit_shas
-    ),
-    GitAiError,
-> {
+) -> Result<AiAuthorshipOverlay, GitAiError> {
     let mut line_authors: HashMap<u32, String> = HashMap::new();
     let mut prompt_records: HashMap<String, PromptRecord> = HashMap::new();
     // Track which commits contain each prompt hash
-    let mut prompt_commits: HashMap<String, std::collections::HashSet<String>> = HashMap::new();
+    let mut prompt_commits: HashMap<String, HashSet<String>> = HashMap::new();
 
-    // Group hunks by commit SHA to avoid repeated lookups
-    let mut commit_authorship_cache: HashMap<String, Option<AuthorshipLog>> = HashMap::new();
-    // Cache for foreign prompts to avoid repeated grepping
-    let mut foreign_prompts_cache: HashMap<String, Option<PromptRecord>> = HashMap::new();
+    let mut notes = BlameNotes::load(repo, blame_hunks, file_path);
 
     for hunk in blame_hunks {
-        // Check if we've already looked up this commit's authorship
-        let authorship_log = if let Some(cached) = commit_authorship_cache.get(&hunk.commit_sha) {
-            cached.clone()
-        } else {
-            // Try to get authorship log for this commit
-            let authorship = get_reference_as_authorship_log_v3(repo, &hunk.commit_sha).ok();
-            commit_authorship_cache.insert(hunk.commit_sha.clone(), authorship.clone());
-            authorship
-        };
-
         // If we have AI authorship data, look up the author for lines in this hunk
-        if let Some(authorship_log) = authorship_log {
-            // Check each line in this hunk for AI authorship using compact schema
+        if notes.has_note(&hunk.commit_sha) {
             // IMPORTANT: Use the original line numbers from the commit, not the current line numbers
             let num_lines = hunk.range.1 - hunk.range.0 + 1;
             for i in 0..num_lines {
                 let current_line_num = hunk.range.0 + i;
                 let orig_line_num = hunk.orig_range.0 + i;
 
-                if let Some((author, prompt_hash, prompt)) = authorship_log.get_line_attribution(
-                    repo,
-                    file_path,
-                    orig_line_num,
-                    &mut foreign_prompts_cache,
-                ) {
-                    // If this line is AI-assisted, display the tool name; otherwise the human username
-                    if let Some(prompt_record) = prompt {
-                        let prompt_hash = prompt_hash.unwrap();
-                        // Track that this prompt hash appears in this commit
-                        prompt_commits
-                            .entry(prompt_hash.clone())
-                            .or_default()
-                            .insert(hunk.commit_sha.clone());
-                        if options.use_prompt_hashes_as_names {
-                            line_authors.insert(current_line_num, prompt_hash.clone());
-                        } else {
-                            line_authors
-                                .insert(current_line_num, prompt_record.agent_id.tool.clone());
-                        }
-                        prompt_records.insert(prompt_hash, prompt_record.clone());
+                if let Some((prompt_hash, prompt_record)) =
+                    notes.line_attribution(&hunk.commit_sha, file_path, orig_line_num)
+                {
+                    // Track that this prompt hash appears in this commit
+                    prompt_commits
+                        .entry(prompt_hash.clone())
+                        .or_default()
+                        .insert(hunk.commit_sha.clone());
+                    if options.use_prompt_hashes_as_names {
+                        line_authors.insert(current_line_num, prompt_hash.clone());
                     } else {
-                        // Has authorship log but line not AI = human-authored
-                        if options.return_human_authors_as_human {
-                            line_authors.insert(
-                                current_line_num,
-                                CheckpointKind::Human.to_str().to_string(),
-                            );
-                        } else {
-                            line_authors.insert(current_line_num, author.username.clone());
-                        }
+                        line_authors.insert(current_line_num, prompt_record.agent_id.tool.clone());
                     }
+                    prompt_records.insert(prompt_hash, prompt_record);
                 } else {
                     // Has authorship log but no attribution found = human-authored
                     if options.return_human_authors_as_human {
@@ -897,11 +951,8 @@ fn overlay_ai_authorship(
         }
     }
 
-    // Collect all authorship logs we've seen (for JSON output to find other files)
-    let authorship_logs: Vec<AuthorshipLog> = commit_authorship_cache
-        .into_iter()
-        .filter_map(|(_, log)| log)
-        .collect();
+    // Collect all attestations we've seen (for JSON output to find other files)
+    let commit_attestations = notes.into_attestations();
 
     // Convert HashSet to Vec and sort for deterministic output
     let prompt_commits_vec: HashMap<String, Vec<String>> = prompt_commits
@@ -916,37 +967,38 @@ fn overlay_ai_authorship(
     Ok((
         line_authors,
         prompt_records,
-        authorship_logs,
+        commit_attestations,
         prompt_commits_vec,
     ))
 }
 
 /// JSON output structure for blame
-#[derive(Debug, Serialize)]
-struct JsonBlameOutput {
-    lines: std::collections::BTreeMap<String, String>,
-    prompts: HashMap<String, PromptRecordWithOtherFiles>,
+#[derive(Debug, Serialize, JsonSchema)]
+pub(crate) struct JsonBlameOutput {
+    /// Line or line range ("12" or "12-20") mapped to the prompt hash that wrote it
+    pub(crate) lines: std::collections::BTreeMap<String, String>,
+    pub(crate) prompts: HashMap<String, PromptRecordWithOtherFiles>,
 }
 
 /// Read model that patches PromptRecord with other_files and commits fields
-#[derive(Debug, Serialize)]
-struct PromptRecordWithOtherFiles {
+#[derive(Debug, Serialize, JsonSchema)]
+pub(crate) struct PromptRecordWithOtherFiles {
     #[serde(flatten)]
-    prompt_record: PromptRecord,
-    other_files: Vec<String>,
-    commits: Vec<String>,
+    pub(crate) prompt_record: PromptRecord,
+    pub(crate) other_files: Vec<String>,
+    pub(crate) commits: Vec<String>,
 }
 
-/// Helper function to get all files touched by a prompt hash across authorship logs
+/// Helper function to get all files touched by a prompt hash across the attestations of each commit
 fn get_files_for_prompt_hash(
     prompt_hash: &str,
-    authorship_logs: &[AuthorshipLog],
+    commit_attestations: &[Vec<FileAttestation>],
     exclude_file: &str,
 ) -> Vec<String> {
-    let mut files = std::collections::HashSet::new();
+    let mut files = HashSet::new();
 
-    for log in authorship_logs {
-        for file_attestation in &log.attestations {
+    for attestations in commit_attestations {
+        for file_attestation in attestations {
             // Skip the file we're currently blaming
             if file_attestation.file_path == exclude_file {
                 continue;
@@ -972,7 +1024,7 @@ fn get_files_for_prompt_hash(
 fn output_json_format(
     line_authors: &HashMap<u32, String>,
     prompt_records: &HashMap<String, PromptRecord>,
-    authorship_logs: &[AuthorshipLog],
+    commit_attestations: &[Vec<FileAttestation>],
     prompt_commits: &HashMap<String, Vec<String>>,
     current_file: &str,
 ) -> Result<(), GitAiError> {
@@ -1031,7 +1083,7 @@ fn output_json_format(
         .iter()
         .filter(|(k, _)| referenced_prompt_ids.contains(k))
         .map(|(k, v)| {
-            let other_files = get_files_for_prompt_hash(k, authorship_logs, current_file);
+            let other_files = get_files_for_prompt_hash(k, commit_attestations, current_file);
             let commits = prompt_commits.get(k).cloned().unwrap_or_default();
             (
                 k.clone(),
@@ -1768,6 +1820,11 @@ pub fn parse_blame_args(args: &[String]) -> Result<(String, GitAiBlameOptions),
                 i += 1;
             }
 
+            "--recurse-submodules" => {
+                options.recurse_submodules = true;
+                i += 1;
+            }
+
             // File path (non-option argument)
             arg if !arg.starts_with('-') => {
                 if file_path.is_none() {
@@ -1793,6 +1850,58 @@ pub fn parse_blame_args(args: &[String]) -> Result<(String, GitAiBlameOptions),
     Ok((file_path, options))
 }
 
+/// Resolve a file inside a (possibly nested) submodule to the submodule's repository and
+/// the file's path within it
+///
+/// Revisions in `options` name superproject commits, so they're swapped for the submodule
+/// commits those revisions record. Files outside any submodule are returned unchanged.
+pub fn resolve_submodule_blame_target(
+    repo: Repository,
+    file_path: &str,
+    options: &mut GitAiBlameOptions,
+) -> Result<(Repository, String), GitAiError> {
+    let mut repo = repo;
+    let mut file_path = file_path.to_string();
+
+    loop {
+        let path = std::path::Path::new(&file_path);
+        let relative_path = if path.is_absolute() {
+            let canonical_path = path.canonicalize()?;
+            match canonical_path.strip_prefix(repo.canonical_workdir()) {
+                Ok(relative) => relative.to_string_lossy().to_string(),
+                Err(_) => return Ok((repo, file_path)),
+            }
+        } else {
+            file_path.clone()
+        };
+
+        let Some((submodule_path, inner_path)) = repo.submodule_containing(&relative_path)? else {
+            return Ok((repo, file_path));
+        };
+        if inner_path.is_empty() {
+            return Err(GitAiError::Generic(format!(
+                "'{}' is a submodule, not a file",
+                submodule_path
+            )));
+        }
+
+        let submodule = repo.open_submodule(&submodule_path)?;
+        for revision in [&mut options.newest_commit, &mut options.oldest_commit]
+            .into_iter()
+            .flatten()
+        {
+            let mut args = repo.global_args_for_exec();
+            args.push("rev-parse".to_string());
+            args.push(format!("{}:{}", revision, submodule_path));
+            let output = exec_git(&args)?;
+            *revision = String::from_utf8(output.stdout)?.trim().to_string();
+        }
+
+        repo = submodule;
+        file_path = inner_path;
+    }
+}
+
 fn parse_line_range(range_str: &str) -> Option<(u32, u32)> {
     if let Some(dash_pos) = range_str.find(',') {
         let start_str = &range_str[..dash_pos];
@@ -3,14 +3,14 @@ use crate::authorship::attribution_tracker::{
 };
 use crate::authorship::authorship_log::PromptRecord;
 use crate::authorship::authorship_log_serialization::generate_short_hash;
-use crate::authorship::imara_diff_utils::{LineChangeTag, compute_line_changes};
+use crate::authorship::imara_diff_utils::{LineChange, LineChangeTag, compute_line_changes};
 use crate::authorship::working_log::CheckpointKind;
-use crate::authorship::working_log::{Checkpoint, WorkingLogEntry};
+use crate::authorship::working_log::{Checkpoint, DeletedLine, WorkingLogEntry, deleted_line_hash};
 use crate::commands::blame::{GitAiBlameOptions, OLDEST_AI_BLAME_DATE};
 use crate::commands::checkpoint_agent::agent_presets::AgentRunResult;
 use crate::config::Config;
 use crate::error::GitAiError;
-use crate::git::repo_storage::{PersistedWorkingLog, RepoStorage};
+use crate::git::repo_storage::{PersistedWorkingLog, RenameCandidates, RepoStorage};
 use crate::git::repository::Repository;
 use crate::git::status::{EntryKind, StatusCode};
 use crate::utils::{debug_log, normalize_to_posix};
@@ -80,6 +80,197 @@ pub fn run(
     quiet: bool,
     agent_run_result: Option<AgentRunResult>,
     is_pre_commit: bool,
+) -> Result<(usize, usize, usize), GitAiError> {
+    // Pre-commit checkpoints only cover the repository being committed to
+    let mut agent_run_result = agent_run_result;
+    let submodule_totals = match agent_run_result.as_mut() {
+        Some(result) if !is_pre_commit => {
+            checkpoint_submodule_edits(repo, author, kind, show_working_log, reset, quiet, result)
+        }
+        _ => None,
+    };
+
+    let Some((sub_entries, sub_files, sub_checkpoints)) = submodule_totals else {
+        return checkpoint_repo(
+            repo,
+            author,
+            kind,
+            show_working_log,
+            reset,
+            quiet,
+            agent_run_result,
+            is_pre_commit,
+        );
+    };
+
+    // Everything the agent touched was inside submodules
+    let remaining_paths = agent_run_result.as_ref().and_then(|result| {
+        if result.checkpoint_kind == CheckpointKind::Human {
+            result.will_edit_filepaths.as_ref()
+        } else {
+            result.edited_filepaths.as_ref()
+        }
+    });
+    if remaining_paths.is_none_or(|paths| paths.is_empty()) {
+        return Ok((sub_entries, sub_files, sub_checkpoints));
+    }
+
+    let (entries, files, checkpoints) = checkpoint_repo(
+        repo,
+        author,
+        kind,
+        show_working_log,
+        reset,
+        quiet,
+        agent_run_result,
+        is_pre_commit,
+    )?;
+    Ok((
+        entries + sub_entries,
+        files + sub_files,
+        checkpoints + sub_checkpoints,
+    ))
+}
+
+/// Checkpoint the edited paths that lie inside submodules against each submodule's own
+/// repository, so they land in its working log rather than showing up as a gitlink change
+///
+/// Routed paths are removed from `agent_run_result`. A path naming a submodule itself stands
+/// for everything changed inside it. Returns None when no path was inside a submodule.
+#[allow(clippy::too_many_arguments)]
+fn checkpoint_submodule_edits(
+    repo: &Repository,
+    author: &str,
+    kind: CheckpointKind,
+    show_working_log: bool,
+    reset: bool,
+    quiet: bool,
+    agent_run_result: &mut AgentRunResult,
+) -> Option<(usize, usize, usize)> {
+    let workdir = repo.workdir().ok()?;
+    let submodules = repo.submodule_paths().ok()?;
+    if submodules.is_empty() {
+        return None;
+    }
+
+    let paths = if agent_run_result.checkpoint_kind == CheckpointKind::Human {
+        agent_run_result.will_edit_filepaths.as_mut()?
+    } else {
+        agent_run_result.edited_filepaths.as_mut()?
+    };
+
+    let mut submodule_paths: HashMap<String, Vec<String>> = HashMap::new();
+    paths.retain(|path| {
+        let relative = workdir_relative_path(repo, &workdir, path);
+        let Some(submodule) = submodules.iter().find(|submodule| {
+            relative == **submodule || relative.starts_with(&format!("{}/", submodule))
+        }) else {
+            return true;
+        };
+        let inner_paths = submodule_paths.entry(submodule.clone()).or_default();
+        if let Some(inner) = relative.strip_prefix(&format!("{}/", submodule)) {
+            inner_paths.push(inner.to_string());
+        }
+        false
+    });
+    if submodule_paths.is_empty() {
+        return None;
+    }
+
+    let mut totals = (0, 0, 0);
+    for (submodule, mut inner_paths) in submodule_paths {
+        let submodule_repo = match repo.open_submodule(&submodule) {
+            Ok(submodule_repo) => submodule_repo,
+            Err(e) => {
+                debug_log(&format!("Skipping edits in submodule {}: {}", submodule, e));
+                continue;
+            }
+        };
+        if inner_paths.is_empty() {
+            inner_paths = submodule_repo
+                .get_staged_and_unstaged_filenames()
+                .map(|files| files.into_iter().collect())
+                .unwrap_or_default();
+        }
+
+        let mut submodule_result = agent_run_result.clone();
+        submodule_result.repo_working_dir =
+            Some(workdir.join(&submodule).to_string_lossy().to_string());
+        if submodule_result.checkpoint_kind == CheckpointKind::Human {
+            submodule_result.will_edit_filepaths = Some(inner_paths);
+        } else {
+            submodule_result.edited_filepaths = Some(inner_paths);
+        }
+        // Dirty buffers are keyed by path; absolute keys resolve against the submodule's workdir
+        submodule_result.dirty_files = agent_run_result.dirty_files.as_ref().map(|dirty_files| {
+            dirty_files
+                .iter()
+                .map(|(path, content)| {
+                    let absolute = if std::path::Path::new(path).is_absolute() {
+                        path.clone()
+                    } else {
+                        workdir.join(path).to_string_lossy().to_string()
+                    };
+                    (absolute, content.clone())
+                })
+                .collect()
+        });
+
+        debug_log(&format!("Checkpointing edits in submodule {}", submodule));
+        match run(
+            &submodule_repo,
+            author,
+            kind,
+            show_working_log,
+            reset,
+            quiet,
+            Some(submodule_result),
+            false,
+        ) {
+            Ok((entries, files, checkpoints)) => {
+                totals.0 += entries;
+                totals.1 += files;
+                totals.2 += checkpoints;
+            }
+            Err(e) => debug_log(&format!(
+                "Checkpoint in submodule {} failed: {}",
+                submodule, e
+            )),
+        }
+    }
+
+    Some(totals)
+}
+
+/// A path from an agent as a posix path relative to the workdir
+fn workdir_relative_path(repo: &Repository, workdir: &std::path::Path, path: &str) -> String {
+    let path_buf = std::path::Path::new(path);
+    if !path_buf.is_absolute() {
+        let path = normalize_to_posix(path);
+        return path.strip_prefix("./").unwrap_or(&path).to_string();
+    }
+    if let Ok(relative) = path_buf.strip_prefix(workdir) {
+        return normalize_to_posix(&relative.to_string_lossy());
+    }
+    let canonical = path_buf
+        .canonicalize()
+        .unwrap_or_else(|_| path_buf.to_path_buf());
+    match canonical.strip_prefix(repo.canonical_workdir()) {
+        Ok(relative) => normalize_to_posix(&relative.to_string_lossy()),
+        Err(_) => normalize_to_posix(path),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn checkpoint_repo(
+    repo: &Repository,
+    author: &str,
+    kind: CheckpointKind,
+    show_working_log: bool,
+    reset: bool,
+    quiet: bool,
+    agent_run_result: Option<AgentRunResult>,
+    is_pre_commit: bool,
 ) -> Result<(usize, usize, usize), GitAiError> {
     let checkpoint_start = Instant::now();
     debug_log("[BENCHMARK] Starting checkpoint run");
@@ -140,6 +331,11 @@ pub fn run(
         working_log.set_dirty_files(Some(dirty_files));
     }
 
+    // Follow files renamed since the last checkpoint before reading the working log
+    if !reset {
+        carry_attributions_across_renames(repo, &base_commit, &working_log);
+    }
+
     // Get the current timestamp in milliseconds since the Unix epoch
     let ts = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -545,6 +741,152 @@ fn get_status_of_files(
     Ok(files)
 }
 
+/// Move the working log entries of renamed files over to their new paths
+///
+/// A file the working log knows about that is gone from disk is matched against the new and
+/// untracked files with git's rename detection. This covers `git mv` as well as renames done
+/// in an editor, which would otherwise leave the new file looking entirely human. Detection
+/// only runs when the missing or new files differ from the previous checkpoint's.
+fn carry_attributions_across_renames(
+    repo: &Repository,
+    base_commit: &str,
+    working_log: &PersistedWorkingLog,
+) {
+    let initial = working_log.read_initial_attributions();
+    let Ok(mut checkpoints) = working_log.read_all_checkpoints() else {
+        return;
+    };
+
+    let is_present = |file: &str| {
+        working_log
+            .dirty_files
+            .as_ref()
+            .is_some_and(|dirty_files| dirty_files.contains_key(file))
+            || std::path::Path::new(&working_log.to_repo_absolute_path(file)).exists()
+    };
+
+    let mut known_files: HashSet<String> = initial.files.keys().cloned().collect();
+    known_files.extend(
+        checkpoints
+            .iter()
+            .flat_map(|checkpoint| &checkpoint.entries)
+            .map(|entry| entry.file.clone()),
+    );
+    let mut missing: Vec<String> = known_files
+        .iter()
+        .filter(|file| !is_present(file))
+        .cloned()
+        .collect();
+    if missing.is_empty() {
+        return;
+    }
+    missing.sort();
+
+    let mut new: Vec<String> = match repo.get_new_filenames() {
+        Ok(files) => files
+            .into_iter()
+            .filter(|file| !known_files.contains(file))
+            .collect(),
+        Err(e) => {
+            debug_log(&format!(
+                "Failed to list new files for rename detection: {}",
+                e
+            ));
+            return;
+        }
+    };
+    new.sort();
+
+    let candidates = RenameCandidates { missing, new };
+    if working_log.read_rename_candidates().as_ref() == Some(&candidates) {
+        debug_log("Missing and new files unchanged since last checkpoint, skipping renames");
+        return;
+    }
+    if let Err(e) = working_log.write_rename_candidates(&candidates) {
+        debug_log(&format!("Failed to write rename candidates: {}", e));
+    }
+    if candidates.new.is_empty() {
+        return;
+    }
+
+    // Last known content of each file that has disappeared, from its newest checkpoint blob
+    // or, for files only carried in INITIAL, from the base commit
+    let mut last_contents: HashMap<String, String> = HashMap::new();
+    for entry in checkpoints
+        .iter()
+        .flat_map(|checkpoint| &checkpoint.entries)
+    {
+        if candidates.missing.contains(&entry.file)
+            && let Ok(content) = working_log.get_file_version(&entry.blob_sha)
+        {
+            last_contents.insert(entry.file.clone(), content);
+        }
+    }
+    let old_files: Vec<(String, String)> = candidates
+        .missing
+        .iter()
+        .filter_map(|file| {
+            let content = last_contents.remove(file).or_else(|| {
+                repo.get_file_content(file, base_commit)
+                    .ok()
+                    .map(|bytes| String::from_utf8_lossy(&bytes).to_string())
+            })?;
+            (!content.is_empty()).then(|| (file.clone(), content))
+        })
+        .collect();
+    let new_files: Vec<(String, String)> = candidates
+        .new
+        .iter()
+        .filter_map(|file| {
+            let content = working_log.read_current_file_content(file).ok()?;
+            Some((file.clone(), content))
+        })
+        .collect();
+
+    let renames: HashMap<String, String> = match repo.detect_renames(&old_files, &new_files) {
+        Ok(renames) => renames.into_iter().collect(),
+        Err(e) => {
+            debug_log(&format!("Rename detection failed: {}", e));
+            return;
+        }
+    };
+    if renames.is_empty() {
+        return;
+    }
+    for (old_path, new_path) in &renames {
+        debug_log(&format!(
+            "Carrying attributions from {} to renamed file {}",
+            old_path, new_path
+        ));
+    }
+
+    if initial.files.keys().any(|file| renames.contains_key(file)) {
+        let files = initial
+            .files
+            .into_iter()
+            .map(|(file, attributions)| {
+                let file = renames.get(&file).cloned().unwrap_or(file);
+                (file, attributions)
+            })
+            .collect();
+        if let Err(e) = working_log.write_initial_attributions(files, initial.prompts) {
+            debug_log(&format!("Failed to write INITIAL attributions: {}", e));
+        }
+    }
+
+    for entry in checkpoints
+        .iter_mut()
+        .flat_map(|checkpoint| checkpoint.entries.iter_mut())
+    {
+        if let Some(new_path) = renames.get(&entry.file) {
+            entry.file = new_path.clone();
+        }
+    }
+    if let Err(e) = working_log.write_all_checkpoints(&checkpoints) {
+        debug_log(&format!("Failed to write renamed checkpoints: {}", e));
+    }
+}
+
 /// Get all files that should be tracked, including those from previous checkpoints and INITIAL attributions
 ///
 fn get_all_tracked_files(
@@ -1162,29 +1504,46 @@ fn make_entry_for_file(
 
     // Compute line stats while we already have both contents in memory
     let stats_start = Instant::now();
-    let line_stats = compute_file_line_stats(previous_content, content);
+    let changes = compute_line_changes(previous_content, content);
+    let line_stats = compute_file_line_stats(&changes);
     debug_log(&format!(
         "[BENCHMARK]   compute_file_line_stats for {} took {:?}",
         file_path,
         stats_start.elapsed()
     ));
 
-    let entry = WorkingLogEntry::new(
+    let mut entry = WorkingLogEntry::new(
         file_path.to_string(),
         blob_sha.to_string(),
         new_attributions,
         line_attributions,
     );
 
+    // Only AI deletions are attributed; anything unclaimed at commit time is human
+    if author_id != CheckpointKind::Human.to_str() {
+        let mut old_line = 0u32;
+        for change in &changes {
+            match change.tag() {
+                LineChangeTag::Equal => old_line += 1,
+                LineChangeTag::Delete => {
+                    old_line += 1;
+                    entry.deleted_lines.push(DeletedLine {
+                        line: old_line,
+                        hash: deleted_line_hash(change.value()),
+                    });
+                }
+                LineChangeTag::Insert => {}
+            }
+        }
+    }
+
     Ok((entry, line_stats))
 }
 
-/// Compute line statistics for a single file by diffing previous and current content
-fn compute_file_line_stats(previous_content: &str, current_content: &str) -> FileLineStats {
+/// Compute line statistics for a single file from the line diff of its previous and current content
+fn compute_file_line_stats(changes: &[LineChange]) -> FileLineStats {
     let mut stats = FileLineStats::default();
 
-    // Use imara_diff to count line changes (matches git's diff algorithm)
-    let changes = compute_line_changes(previous_content, current_content);
     for change in changes {
         match change.tag() {
             LineChangeTag::Insert => {
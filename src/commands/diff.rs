@@ -1,9 +1,16 @@
 use crate::authorship::authorship_log::{LineRange, PromptRecord};
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::imara_diff_utils::{DiffOp, capture_diff_slices};
+use crate::authorship::virtual_attribution::VirtualAttributions;
+use crate::authorship::working_log::CheckpointKind;
 use crate::commands::blame::GitAiBlameOptions;
+use crate::commands::checkpoint;
 use crate::error::GitAiError;
+use crate::git::refs::{get_authorship_deletions, get_authorship_prompts};
 use crate::git::repository::{Repository, exec_git};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize, Serializer};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::IsTerminal;
 
 // ============================================================================
@@ -14,6 +21,8 @@ use std::io::IsTerminal;
 pub enum DiffSpec {
     SingleCommit(String),      // SHA
     TwoCommit(String, String), // start..end
+    WorkingTree,               // HEAD..working tree
+    Index,                     // HEAD..index (--cached)
 }
 
 pub enum DiffFormat {
@@ -41,7 +50,7 @@ pub struct DiffLineKey {
 }
 
 /// JSON output format for git-ai diff --json
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DiffJson {
     /// Per-file diff information with annotations
     pub files: BTreeMap<String, FileDiffJson>,
@@ -50,16 +59,35 @@ pub struct DiffJson {
 }
 
 /// Per-file diff information in JSON output
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FileDiffJson {
     /// Annotations mapping prompt hash to line ranges
     /// Line ranges are serialized as JSON tuples: [start, end] or single number
     #[serde(serialize_with = "serialize_annotations")]
+    #[schemars(with = "BTreeMap<String, Vec<JsonLineRange>>")]
     pub annotations: BTreeMap<String, Vec<LineRange>>,
     /// The unified diff for this file
     pub diff: String,
     /// The base content of the file (before changes)
     pub base_content: String,
+    /// Deleted lines mapping prompt hash to old-side line ranges
+    /// Only available when diffing a single commit against its parent
+    #[serde(
+        default,
+        serialize_with = "serialize_annotations",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    #[schemars(with = "BTreeMap<String, Vec<JsonLineRange>>")]
+    pub deletions: BTreeMap<String, Vec<LineRange>>,
+}
+
+/// Schema of a line range as written by `serialize_annotations`
+#[allow(dead_code)]
+#[derive(JsonSchema)]
+#[serde(untagged)]
+enum JsonLineRange {
+    Single(u32),
+    Range(u32, u32),
 }
 
 #[derive(Debug, Hash, Eq, PartialEq, Clone)]
@@ -80,13 +108,6 @@ pub enum Attribution {
 // ============================================================================
 
 pub fn handle_diff(repo: &Repository, args: &[String]) -> Result<(), GitAiError> {
-    if args.is_empty() {
-        eprintln!("Error: diff requires a commit or commit range argument");
-        eprintln!("Usage: git-ai diff <commit>");
-        eprintln!("       git-ai diff <commit1>..<commit2>");
-        std::process::exit(1);
-    }
-
     let (spec, format) = parse_diff_args(args)?;
     let output = execute_diff(repo, spec, format)?;
     print!("{}", output);
@@ -99,13 +120,29 @@ pub fn handle_diff(repo: &Repository, args: &[String]) -> Result<(), GitAiError>
 // ============================================================================
 
 pub fn parse_diff_args(args: &[String]) -> Result<(DiffSpec, DiffFormat), GitAiError> {
-    let arg = &args[0];
-
     let format = if args.iter().any(|arg| arg == "--json") {
         DiffFormat::Json
     } else {
         DiffFormat::GitCompatibleTerminal
     };
+    let cached = args
+        .iter()
+        .any(|arg| arg == "--cached" || arg == "--staged");
+
+    // Without a commit, diff the uncommitted changes against HEAD
+    let Some(arg) = args.iter().find(|arg| !arg.starts_with("--")) else {
+        let spec = if cached {
+            DiffSpec::Index
+        } else {
+            DiffSpec::WorkingTree
+        };
+        return Ok((spec, format));
+    };
+    if cached {
+        return Err(GitAiError::Generic(
+            "--cached can't be combined with a commit".to_string(),
+        ));
+    }
 
     // Check for commit range (start..end)
     if arg.contains("..") {
@@ -137,6 +174,8 @@ pub fn execute_diff(
 ) -> Result<String, GitAiError> {
     // Resolve commits to get from/to SHAs
     let (from_commit, to_commit) = match spec {
+        DiffSpec::WorkingTree => return execute_uncommitted_diff(repo, false, format),
+        DiffSpec::Index => return execute_uncommitted_diff(repo, true, format),
         DiffSpec::TwoCommit(start, end) => {
             // Resolve both commits
             let from = resolve_commit(repo, &start)?;
@@ -164,8 +203,67 @@ pub fn execute_diff(
             serde_json::to_string(&diff_json)
                 .map_err(|e| GitAiError::Generic(format!("Failed to serialize JSON: {}", e)))?
         }
+        DiffFormat::GitCompatibleTerminal => format_annotated_diff(
+            repo,
+            &[from_commit.clone(), to_commit.clone()],
+            &attributions,
+        )?,
+    };
+
+    Ok(output)
+}
+
+/// Diff the working tree (or the index, when `cached`) against HEAD
+///
+/// Uncommitted lines have no note to blame yet, so added lines are annotated from the
+/// working log. Untracked files are left out, as they are by `git diff`.
+fn execute_uncommitted_diff(
+    repo: &Repository,
+    cached: bool,
+    format: DiffFormat,
+) -> Result<String, GitAiError> {
+    // Before the first commit, everything is diffed against the empty tree
+    let head = resolve_commit(repo, "HEAD").unwrap_or_else(|_| EMPTY_TREE.to_string());
+    let mut diff_args = Vec::new();
+    if cached {
+        diff_args.push("--cached".to_string());
+    }
+    diff_args.push(head.clone());
+
+    let hunks = get_diff_hunks(repo, &diff_args)?;
+    let (attributions, annotations, prompts) =
+        working_log_attributions(repo, &head, &hunks, cached)?;
+
+    let output = match format {
+        DiffFormat::Json => {
+            let mut file_diffs = get_diff_split_by_file(repo, &diff_args)?;
+            let mut files = BTreeMap::new();
+            for hunk in &hunks {
+                if files.contains_key(&hunk.file_path) {
+                    continue;
+                }
+                let base_content = match repo.get_file_content(&hunk.file_path, &head) {
+                    Ok(bytes) => String::from_utf8(bytes).unwrap_or_default(),
+                    Err(_) => String::new(), // File is new since HEAD
+                };
+                files.insert(
+                    hunk.file_path.clone(),
+                    FileDiffJson {
+                        annotations: annotations
+                            .get(&hunk.file_path)
+                            .cloned()
+                            .unwrap_or_default(),
+                        diff: file_diffs.remove(&hunk.file_path).unwrap_or_default(),
+                        base_content,
+                        deletions: BTreeMap::new(),
+                    },
+                );
+            }
+            serde_json::to_string(&DiffJson { files, prompts })
+                .map_err(|e| GitAiError::Generic(format!("Failed to serialize JSON: {}", e)))?
+        }
         DiffFormat::GitCompatibleTerminal => {
-            format_annotated_diff(repo, &from_commit, &to_commit, &attributions)?
+            format_annotated_diff(repo, &diff_args, &attributions)?
         }
     };
 
@@ -176,6 +274,9 @@ pub fn execute_diff(
 // Commit Resolution
 // ============================================================================
 
+/// The empty tree, diffed against when there is no parent or HEAD
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
 fn resolve_commit(repo: &Repository, rev: &str) -> Result<String, GitAiError> {
     let mut args = repo.global_args_for_exec();
     args.push("rev-parse".to_string());
@@ -216,14 +317,14 @@ fn resolve_parent(repo: &Repository, commit: &str) -> Result<String, GitAiError>
 
             if sha.is_empty() {
                 // No parent, this is initial commit - use empty tree
-                Ok("4b825dc642cb6eb9a060e54bf8d69288fbee4904".to_string())
+                Ok(EMPTY_TREE.to_string())
             } else {
                 Ok(sha)
             }
         }
         Err(_) => {
             // No parent, this is initial commit - use empty tree hash
-            Ok("4b825dc642cb6eb9a060e54bf8d69288fbee4904".to_string())
+            Ok(EMPTY_TREE.to_string())
         }
     }
 }
@@ -237,12 +338,16 @@ pub fn get_diff_with_line_numbers(
     from: &str,
     to: &str,
 ) -> Result<Vec<DiffHunk>, GitAiError> {
+    get_diff_hunks(repo, &[from.to_string(), to.to_string()])
+}
+
+/// Diff hunks for `git diff <diff_args>`, e.g. two commits or `--cached HEAD`
+fn get_diff_hunks(repo: &Repository, diff_args: &[String]) -> Result<Vec<DiffHunk>, GitAiError> {
     let mut args = repo.global_args_for_exec();
     args.push("diff".to_string());
     args.push("-U0".to_string()); // No context lines, just changes
     args.push("--no-color".to_string());
-    args.push(from.to_string());
-    args.push(to.to_string());
+    args.extend(diff_args.iter().cloned());
 
     let output = exec_git(&args)?;
     let diff_text = String::from_utf8(output.stdout)
@@ -443,9 +548,224 @@ pub fn overlay_diff_attributions(
         }
     }
 
+    // Deleted lines are attributed from the note, not blame
+    if let Some(note) = deletions_note(repo, from_commit, to_commit) {
+        for file_path in note.metadata.deletions.keys() {
+            for (hash, ranges) in note.deletions_for_file(file_path) {
+                let Some(prompt) = note.metadata.prompts.get(&hash) else {
+                    continue;
+                };
+                for line in ranges.iter().flat_map(|range| range.expand()) {
+                    let key = DiffLineKey {
+                        file: file_path.clone(),
+                        line,
+                        side: LineSide::Old,
+                    };
+                    attributions.insert(key, Attribution::Ai(prompt.agent_id.tool.clone()));
+                }
+            }
+        }
+    }
+
     Ok(attributions)
 }
 
+/// The deletion attributions of a diff, with the prompts that made them
+///
+/// Deletions are recorded against a commit's parent, so they only apply when
+/// `from_commit` is the parent of `to_commit`. Only the deletions and their prompts are
+/// read from the note.
+fn deletions_note(repo: &Repository, from_commit: &str, to_commit: &str) -> Option<AuthorshipLog> {
+    if resolve_parent(repo, to_commit).ok()? != from_commit {
+        return None;
+    }
+    let deletions = get_authorship_deletions(repo, to_commit).filter(|d| !d.is_empty())?;
+    let hashes: HashSet<String> = deletions
+        .values()
+        .flat_map(|by_hash| by_hash.keys().cloned())
+        .collect();
+
+    let mut note = AuthorshipLog::new();
+    note.metadata.deletions = deletions;
+    note.metadata.prompts = get_authorship_prompts(repo, to_commit, &hashes).unwrap_or_default();
+    Some(note)
+}
+
+/// Attribute uncommitted added lines from the working log
+///
+/// The working tree is checkpointed first, as `status` does, so the working log's line numbers
+/// are those of the current working tree. With `cached` the index content is then mapped onto
+/// the working tree; staged lines edited again since have no attribution.
+/// Returns the per-line attributions, the AI lines per file keyed by prompt hash, and the
+/// prompts they came from.
+#[allow(clippy::type_complexity)]
+fn working_log_attributions(
+    repo: &Repository,
+    head: &str,
+    hunks: &[DiffHunk],
+    cached: bool,
+) -> Result<
+    (
+        HashMap<DiffLineKey, Attribution>,
+        BTreeMap<String, BTreeMap<String, Vec<LineRange>>>,
+        BTreeMap<String, PromptRecord>,
+    ),
+    GitAiError,
+> {
+    let mut attributions = HashMap::new();
+    let mut annotations: BTreeMap<String, BTreeMap<String, Vec<LineRange>>> = BTreeMap::new();
+    let mut prompts = BTreeMap::new();
+
+    let mut lines_by_file: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+    for hunk in hunks {
+        if !hunk.added_lines.is_empty() {
+            lines_by_file
+                .entry(hunk.file_path.clone())
+                .or_default()
+                .extend(&hunk.added_lines);
+        }
+    }
+    if lines_by_file.is_empty() {
+        return Ok((attributions, annotations, prompts));
+    }
+
+    let human_author = repo.config_get_str("user.name").ok().flatten();
+    let human_name = human_author
+        .clone()
+        .unwrap_or_else(|| CheckpointKind::Human.to_str().to_string());
+
+    let checkpoint_author = human_author
+        .as_deref()
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or("unknown");
+    let _ = checkpoint::run(
+        repo,
+        checkpoint_author,
+        CheckpointKind::Human,
+        false,
+        false,
+        true,
+        None,
+        false,
+    );
+    let va =
+        VirtualAttributions::from_just_working_log(repo.clone(), head.to_string(), human_author)?;
+    let working_prompts: HashMap<&str, &PromptRecord> = va
+        .prompts()
+        .iter()
+        .filter_map(|(hash, records)| {
+            records
+                .values()
+                .next()
+                .map(|record| (hash.as_str(), record))
+        })
+        .collect();
+
+    let files: Vec<String> = lines_by_file.keys().cloned().collect();
+    let staged_contents = if cached {
+        repo.get_all_staged_files_content(&files)?
+    } else {
+        HashMap::new()
+    };
+
+    for (file_path, lines) in lines_by_file {
+        // Working tree line -> author id
+        let tracked = va.get_line_attributions(&file_path).is_some();
+        let mut authors: HashMap<u32, &str> = HashMap::new();
+        for line_attr in va.get_line_attributions(&file_path).into_iter().flatten() {
+            for line in line_attr.start_line..=line_attr.end_line {
+                authors.insert(line, &line_attr.author_id);
+            }
+        }
+
+        let index_lines = if cached {
+            let working_content = match va.get_file_content(&file_path) {
+                Some(content) => content.clone(),
+                None => repo
+                    .workdir()
+                    .ok()
+                    .and_then(|workdir| std::fs::read_to_string(workdir.join(&file_path)).ok())
+                    .unwrap_or_default(),
+            };
+            let staged_content = staged_contents
+                .get(&file_path)
+                .map(String::as_str)
+                .unwrap_or_default();
+            Some(map_unchanged_lines(staged_content, &working_content))
+        } else {
+            None
+        };
+
+        let mut ai_lines: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+        for line in lines {
+            let working_line = match &index_lines {
+                Some(mapping) => mapping.get(&line).copied(),
+                None => Some(line),
+            };
+            let author = working_line.map(|working_line| authors.get(&working_line));
+            let attribution = match author {
+                Some(Some(&author_id)) if working_prompts.contains_key(author_id) => {
+                    let prompt = working_prompts[author_id];
+                    prompts.insert(author_id.to_string(), prompt.clone());
+                    ai_lines
+                        .entry(author_id.to_string())
+                        .or_default()
+                        .push(line);
+                    Attribution::Ai(prompt.agent_id.tool.clone())
+                }
+                // Like a commit with a note, a file the working log tracks is human
+                // wherever AI didn't write it
+                Some(_) if tracked => Attribution::Human(human_name.clone()),
+                // Files without working log entries, and staged lines edited again since
+                _ => Attribution::NoData,
+            };
+            attributions.insert(
+                DiffLineKey {
+                    file: file_path.clone(),
+                    line,
+                    side: LineSide::New,
+                },
+                attribution,
+            );
+        }
+
+        if !ai_lines.is_empty() {
+            annotations.insert(
+                file_path,
+                ai_lines
+                    .into_iter()
+                    .map(|(hash, lines)| (hash, LineRange::compress_lines(&lines)))
+                    .collect(),
+            );
+        }
+    }
+
+    Ok((attributions, annotations, prompts))
+}
+
+/// Map each line of `old` that survives unchanged in `new` to its line number there
+fn map_unchanged_lines(old: &str, new: &str) -> HashMap<u32, u32> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let mut mapping = HashMap::new();
+    for op in capture_diff_slices(&old_lines, &new_lines) {
+        if let DiffOp::Equal {
+            old_index,
+            new_index,
+            len,
+        } = op
+        {
+            for offset in 0..len {
+                mapping.insert(
+                    (old_index + offset + 1) as u32,
+                    (new_index + offset + 1) as u32,
+                );
+            }
+        }
+    }
+    mapping
+}
+
 /// Convert a sorted list of line numbers to contiguous ranges
 /// e.g., [1, 2, 3, 5, 6, 10] -> [(1, 3), (5, 6), (10, 10)]
 fn lines_to_ranges(lines: &[u32]) -> Vec<(u32, u32)> {
@@ -489,9 +809,11 @@ fn build_diff_json(
 ) -> Result<DiffJson, GitAiError> {
     let mut files: BTreeMap<String, FileDiffJson> = BTreeMap::new();
     let mut all_prompts: BTreeMap<String, PromptRecord> = BTreeMap::new();
+    let deletions = deletions_note(repo, from_commit, to_commit);
 
     // Get the full diff output and split by file
-    let file_diffs = get_diff_split_by_file(repo, from_commit, to_commit)?;
+    let file_diffs =
+        get_diff_split_by_file(repo, &[from_commit.to_string(), to_commit.to_string()])?;
 
     // Get unique files from hunks
     let mut unique_files: Vec<String> = hunks.iter().map(|h| h.file_path.clone()).collect();
@@ -518,12 +840,26 @@ fn build_diff_json(
             Err(_) => String::new(), // File didn't exist in from_commit (new file)
         };
 
+        // Get the lines AI prompts deleted from this file
+        let file_deletions = deletions
+            .as_ref()
+            .map(|note| note.deletions_for_file(file_path))
+            .unwrap_or_default();
+        if let Some(note) = &deletions {
+            for hash in file_deletions.keys() {
+                if let Some(prompt_record) = note.metadata.prompts.get(hash) {
+                    all_prompts.insert(hash.clone(), prompt_record.clone());
+                }
+            }
+        }
+
         files.insert(
             file_path.clone(),
             FileDiffJson {
                 annotations: file_annotations.0,
                 diff,
                 base_content,
+                deletions: file_deletions,
             },
         );
     }
@@ -537,14 +873,12 @@ fn build_diff_json(
 /// Get the unified diff split by file path
 fn get_diff_split_by_file(
     repo: &Repository,
-    from_commit: &str,
-    to_commit: &str,
+    diff_args: &[String],
 ) -> Result<HashMap<String, String>, GitAiError> {
     let mut args = repo.global_args_for_exec();
     args.push("diff".to_string());
     args.push("--no-color".to_string());
-    args.push(from_commit.to_string());
-    args.push(to_commit.to_string());
+    args.extend(diff_args.iter().cloned());
 
     let output = exec_git(&args)?;
     let diff_text = String::from_utf8(output.stdout)
@@ -691,16 +1025,14 @@ fn collect_file_annotations(
 #[allow(clippy::if_same_then_else)]
 pub fn format_annotated_diff(
     repo: &Repository,
-    from_commit: &str,
-    to_commit: &str,
+    diff_args: &[String],
     attributions: &HashMap<DiffLineKey, Attribution>,
 ) -> Result<String, GitAiError> {
     // Execute git diff with normal context
     let mut args = repo.global_args_for_exec();
     args.push("diff".to_string());
     args.push("--no-color".to_string());
-    args.push(from_commit.to_string());
-    args.push(to_commit.to_string());
+    args.extend(diff_args.iter().cloned());
 
     let output = exec_git(&args)?;
     let diff_text = String::from_utf8(output.stdout)
@@ -1038,6 +1370,29 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_diff_args_uncommitted() {
+        let (spec, format) = parse_diff_args(&[]).unwrap();
+        assert!(matches!(spec, DiffSpec::WorkingTree));
+        assert!(matches!(format, DiffFormat::GitCompatibleTerminal));
+
+        let args = vec!["--cached".to_string(), "--json".to_string()];
+        let (spec, format) = parse_diff_args(&args).unwrap();
+        assert!(matches!(spec, DiffSpec::Index));
+        assert!(matches!(format, DiffFormat::Json));
+
+        let args = vec!["--cached".to_string(), "abc123".to_string()];
+        assert!(parse_diff_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_map_unchanged_lines() {
+        let mapping = map_unchanged_lines("a\nb\nc\n", "new\na\nc\n");
+        assert_eq!(mapping.get(&1), Some(&2));
+        assert_eq!(mapping.get(&2), None);
+        assert_eq!(mapping.get(&3), Some(&3));
+    }
+
     #[test]
     fn test_parse_hunk_line_basic() {
         let line = "@@ -10,3 +15,5 @@ fn main() {";
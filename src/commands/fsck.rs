@@ -0,0 +1,664 @@
+use crate::authorship::anchors::reanchor_file_attestation;
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::commands::show::resolve_commits;
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::refs::{list_commits_with_notes, show_authorship_note};
+use crate::git::repository::{Repository, exec_git};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Handle the `fsck` command
+///
+/// Usage: `git-ai fsck [<rev|range>] [--json]`
+///
+/// Verifies that authorship notes are consistent with the commits they annotate.
+/// Without a revision, every commit that has a note under refs/notes/ai is checked.
+/// Exits with status 1 when any issue is found so it can gate CI.
+pub fn handle_fsck(args: &[String]) {
+    let parsed = match parse_args(args) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let commits = match &parsed.spec {
+        Some(spec) => resolve_commits(&repo, spec),
+        None => list_commits_with_notes(&repo),
+    };
+    let commits = match commits {
+        Ok(commits) => commits,
+        Err(e) => {
+            eprintln!("Failed to resolve commits: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let report = match fsck_commits(&repo, &commits) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("fsck failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if parsed.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).unwrap_or_else(|_| "{}".to_string())
+        );
+    } else {
+        print_report(&report);
+    }
+
+    if !report.issues.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+#[derive(Debug)]
+pub struct ParsedArgs {
+    pub spec: Option<String>,
+    pub json: bool,
+}
+
+pub fn parse_args(args: &[String]) -> Result<ParsedArgs, String> {
+    let mut spec: Option<String> = None;
+    let mut json = false;
+
+    for arg in args {
+        if arg == "--json" {
+            json = true;
+        } else if arg.starts_with('-') {
+            return Err(format!("Unknown option: {}", arg));
+        } else {
+            if spec.is_some() {
+                return Err("fsck accepts at most one revision or range".to_string());
+            }
+            spec = Some(arg.clone());
+        }
+    }
+
+    Ok(ParsedArgs { spec, json })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FsckIssueKind {
+    /// The note exists but can't be parsed as an authorship log
+    UnparseableNote,
+    /// The note is attached to an object that is not a commit in this repository
+    MissingCommit,
+    /// An attestation hash has no entry in `metadata.prompts`
+    MissingPrompt,
+    /// An attested file does not exist in the commit tree
+    MissingFile,
+    /// A line range points past the end of the file (or at line 0)
+    RangeOutOfBounds,
+    /// Line ranges in an entry are out of order or reversed
+    UnsortedRanges,
+    /// Line ranges overlap, within an entry or across prompts in the same file
+    OverlappingRanges,
+    /// `metadata.base_commit_sha` is not the annotated commit
+    BaseCommitMismatch,
+    /// Anchored lines no longer match their content hash
+    AnchorMismatch,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FsckIssue {
+    pub commit: String,
+    pub kind: FsckIssueKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FsckReport {
+    pub commits_checked: usize,
+    pub notes_checked: usize,
+    pub issues: Vec<FsckIssue>,
+}
+
+/// Check the authorship notes of every commit in `commits`
+/// Commits without a note are counted but otherwise skipped.
+pub fn fsck_commits(repo: &Repository, commits: &[String]) -> Result<FsckReport, GitAiError> {
+    let mut report = FsckReport::default();
+
+    for commit_sha in commits {
+        report.commits_checked += 1;
+
+        let Some(content) = show_authorship_note(repo, commit_sha) else {
+            continue;
+        };
+        report.notes_checked += 1;
+
+        let authorship_log = match AuthorshipLog::deserialize_from_string(&content) {
+            Ok(log) => log,
+            Err(e) => {
+                report.issues.push(FsckIssue {
+                    commit: commit_sha.clone(),
+                    kind: FsckIssueKind::UnparseableNote,
+                    file: None,
+                    hash: None,
+                    message: format!("note could not be parsed: {}", e),
+                });
+                continue;
+            }
+        };
+
+        let tree_files = match list_tree_files(repo, commit_sha) {
+            Ok(files) => files,
+            Err(_) => {
+                report.issues.push(FsckIssue {
+                    commit: commit_sha.clone(),
+                    kind: FsckIssueKind::MissingCommit,
+                    file: None,
+                    hash: None,
+                    message: "note is attached to a commit that does not exist".to_string(),
+                });
+                continue;
+            }
+        };
+
+        // Only read the blobs we actually need to bound-check
+        let mut contents: HashMap<String, String> = HashMap::new();
+        let mut unreadable: HashMap<String, String> = HashMap::new();
+        for file_attestation in &authorship_log.attestations {
+            let path = &file_attestation.file_path;
+            if tree_files.contains(path) && !contents.contains_key(path) {
+                match repo.get_file_content(path, commit_sha) {
+                    Ok(content) => {
+                        contents
+                            .insert(path.clone(), String::from_utf8_lossy(&content).into_owned());
+                    }
+                    // e.g. a submodule gitlink; reported below as a missing file
+                    Err(e) => {
+                        unreadable.insert(path.clone(), e.to_string());
+                    }
+                }
+            }
+        }
+        let line_counts: HashMap<String, u32> = contents
+            .iter()
+            .map(|(path, content)| (path.clone(), count_lines(content.as_bytes())))
+            .collect();
+
+        report.issues.extend(
+            check_authorship_log(commit_sha, &authorship_log, &line_counts)
+                .into_iter()
+                .map(|mut issue| {
+                    let error = issue.file.as_ref().and_then(|file| unreadable.get(file));
+                    if let (FsckIssueKind::MissingFile, Some(error)) = (issue.kind, error) {
+                        issue.message = format!("file could not be read: {}", error);
+                    }
+                    issue
+                }),
+        );
+        report
+            .issues
+            .extend(check_anchors(commit_sha, &authorship_log, &contents));
+    }
+
+    Ok(report)
+}
+
+/// Validate a parsed authorship log against the files of its commit
+///
+/// `line_counts` maps each attested file that exists in the commit tree to its line count.
+/// Files missing from the map are reported as missing from the tree.
+pub fn check_authorship_log(
+    commit_sha: &str,
+    authorship_log: &AuthorshipLog,
+    line_counts: &HashMap<String, u32>,
+) -> Vec<FsckIssue> {
+    let mut issues = Vec::new();
+    let issue = |kind: FsckIssueKind,
+                 file: Option<&str>,
+                 hash: Option<&str>,
+                 message: String|
+     -> FsckIssue {
+        FsckIssue {
+            commit: commit_sha.to_string(),
+            kind,
+            file: file.map(|f| f.to_string()),
+            hash: hash.map(|h| h.to_string()),
+            message,
+        }
+    };
+
+    if authorship_log.metadata.base_commit_sha != commit_sha {
+        issues.push(issue(
+            FsckIssueKind::BaseCommitMismatch,
+            None,
+            None,
+            format!(
+                "base_commit_sha is '{}'",
+                authorship_log.metadata.base_commit_sha
+            ),
+        ));
+    }
+
+    for file_attestation in &authorship_log.attestations {
+        let file = file_attestation.file_path.as_str();
+        let line_count = line_counts.get(file).copied();
+
+        if line_count.is_none() {
+            issues.push(issue(
+                FsckIssueKind::MissingFile,
+                Some(file),
+                None,
+                "file does not exist in the commit tree".to_string(),
+            ));
+        }
+
+        let mut reported_missing: HashSet<&str> = HashSet::new();
+        for entry in &file_attestation.entries {
+            let hash = entry.hash.as_str();
+            if !authorship_log.metadata.prompts.contains_key(hash) && reported_missing.insert(hash)
+            {
+                issues.push(issue(
+                    FsckIssueKind::MissingPrompt,
+                    Some(file),
+                    Some(hash),
+                    "attestation hash has no entry in metadata.prompts".to_string(),
+                ));
+            }
+
+            let mut previous: Option<(u32, u32)> = None;
+            for range in &entry.line_ranges {
+                let (start, end) = range_bounds(range);
+
+                if start > end {
+                    issues.push(issue(
+                        FsckIssueKind::UnsortedRanges,
+                        Some(file),
+                        Some(hash),
+                        format!("range {}-{} is reversed", start, end),
+                    ));
+                }
+
+                if let Some(count) = line_count
+                    && (start == 0 || start.max(end) > count)
+                {
+                    issues.push(issue(
+                        FsckIssueKind::RangeOutOfBounds,
+                        Some(file),
+                        Some(hash),
+                        format!(
+                            "range {} is outside the file ({} lines)",
+                            format_range(range),
+                            count
+                        ),
+                    ));
+                }
+
+                if let Some((prev_start, prev_end)) = previous {
+                    if start < prev_start {
+                        issues.push(issue(
+                            FsckIssueKind::UnsortedRanges,
+                            Some(file),
+                            Some(hash),
+                            format!(
+                                "range {} comes after {}-{}",
+                                format_range(range),
+                                prev_start,
+                                prev_end
+                            ),
+                        ));
+                    } else if start <= prev_end {
+                        issues.push(issue(
+                            FsckIssueKind::OverlappingRanges,
+                            Some(file),
+                            Some(hash),
+                            format!(
+                                "range {} overlaps {}-{}",
+                                format_range(range),
+                                prev_start,
+                                prev_end
+                            ),
+                        ));
+                    }
+                }
+                previous = Some((start, end));
+            }
+        }
+
+        // A line can only be attributed to one prompt
+        let mut claimed: Vec<(u32, u32, &str)> = file_attestation
+            .entries
+            .iter()
+            .flat_map(|entry| {
+                entry.line_ranges.iter().map(move |range| {
+                    let (start, end) = range_bounds(range);
+                    (start, end, entry.hash.as_str())
+                })
+            })
+            .collect();
+        claimed.sort();
+        let mut reported_pairs: HashSet<(&str, &str)> = HashSet::new();
+        let mut widest: Option<(u32, &str)> = None;
+        for (start, end, hash) in claimed {
+            if let Some((widest_end, widest_hash)) = widest
+                && widest_hash != hash
+                && start <= widest_end
+                && reported_pairs.insert((widest_hash.min(hash), widest_hash.max(hash)))
+            {
+                issues.push(issue(
+                    FsckIssueKind::OverlappingRanges,
+                    Some(file),
+                    Some(hash),
+                    format!("lines are also attributed to prompt {}", widest_hash),
+                ));
+            }
+            if widest.is_none_or(|(widest_end, _)| end > widest_end) {
+                widest = Some((end, hash));
+            }
+        }
+    }
+
+    issues
+}
+
+/// Compare the anchored ranges of a log against the files of its commit
+///
+/// `contents` maps each attested file that exists in the commit tree to its content.
+/// Ranges whose lines moved report where they are now, so the note can be fixed by hand.
+pub fn check_anchors(
+    commit_sha: &str,
+    authorship_log: &AuthorshipLog,
+    contents: &HashMap<String, String>,
+) -> Vec<FsckIssue> {
+    let mut issues = Vec::new();
+    for file_attestation in &authorship_log.attestations {
+        let Some(content) = contents.get(&file_attestation.file_path) else {
+            continue;
+        };
+        let check = reanchor_file_attestation(&mut file_attestation.clone(), content);
+        let moved = check.moved.into_iter().map(|(hash, from, to)| {
+            let message = format!(
+                "lines {} no longer match their anchor; the anchored content is at {}",
+                format_range(&from),
+                format_range(&to)
+            );
+            (hash, message)
+        });
+        let lost = check.lost.into_iter().map(|(hash, from)| {
+            let message = format!(
+                "lines {} no longer match their anchor; the anchored content is gone",
+                format_range(&from)
+            );
+            (hash, message)
+        });
+        for (hash, message) in moved.chain(lost) {
+            issues.push(FsckIssue {
+                commit: commit_sha.to_string(),
+                kind: FsckIssueKind::AnchorMismatch,
+                file: Some(file_attestation.file_path.clone()),
+                hash: Some(hash),
+                message,
+            });
+        }
+    }
+    issues
+}
+
+fn print_report(report: &FsckReport) {
+    for issue in &report.issues {
+        let short_sha = &issue.commit[..issue.commit.len().min(7)];
+        let mut location = String::new();
+        if let Some(file) = &issue.file {
+            location.push_str(&format!(" {}", file));
+        }
+        if let Some(hash) = &issue.hash {
+            location.push_str(&format!(" [{}]", hash));
+        }
+        println!(
+            "{} {}{}: {}",
+            short_sha,
+            issue_kind_label(issue.kind),
+            location,
+            issue.message
+        );
+    }
+
+    if report.issues.is_empty() {
+        println!(
+            "Checked {} commit(s), {} note(s): no problems found",
+            report.commits_checked, report.notes_checked
+        );
+    } else {
+        println!(
+            "Checked {} commit(s), {} note(s): {} problem(s) found",
+            report.commits_checked,
+            report.notes_checked,
+            report.issues.len()
+        );
+    }
+}
+
+fn issue_kind_label(kind: FsckIssueKind) -> &'static str {
+    match kind {
+        FsckIssueKind::UnparseableNote => "unparseable-note",
+        FsckIssueKind::MissingCommit => "missing-commit",
+        FsckIssueKind::MissingPrompt => "missing-prompt",
+        FsckIssueKind::MissingFile => "missing-file",
+        FsckIssueKind::RangeOutOfBounds => "range-out-of-bounds",
+        FsckIssueKind::UnsortedRanges => "unsorted-ranges",
+        FsckIssueKind::OverlappingRanges => "overlapping-ranges",
+        FsckIssueKind::BaseCommitMismatch => "base-commit-mismatch",
+        FsckIssueKind::AnchorMismatch => "anchor-mismatch",
+    }
+}
+
+fn range_bounds(range: &LineRange) -> (u32, u32) {
+    match range {
+        LineRange::Single(line) => (*line, *line),
+        LineRange::Range(start, end) => (*start, *end),
+    }
+}
+
+fn format_range(range: &LineRange) -> String {
+    match range {
+        LineRange::Single(line) => line.to_string(),
+        LineRange::Range(start, end) => format!("{}-{}", start, end),
+    }
+}
+
+/// Count lines the same way blame does: a trailing line without a newline still counts
+fn count_lines(content: &[u8]) -> u32 {
+    if content.is_empty() {
+        return 0;
+    }
+    let newlines = content.iter().filter(|b| **b == b'\n').count() as u32;
+    if content.ends_with(b"\n") {
+        newlines
+    } else {
+        newlines + 1
+    }
+}
+
+/// List all file paths in a commit's tree
+fn list_tree_files(repo: &Repository, commit_sha: &str) -> Result<HashSet<String>, GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.push("ls-tree".to_string());
+    args.push("-r".to_string());
+    args.push("-z".to_string());
+    args.push("--name-only".to_string());
+    args.push(format!("{}^{{commit}}", commit_sha));
+
+    let output = exec_git(&args)?;
+    Ok(output
+        .stdout
+        .split(|&b| b == 0)
+        .filter(|bytes| !bytes.is_empty())
+        .filter_map(|bytes| String::from_utf8(bytes.to_vec()).ok())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::authorship_log::PromptRecord;
+    use crate::authorship::authorship_log_serialization::{AttestationEntry, FileAttestation};
+    use crate::authorship::working_log::AgentId;
+
+    fn prompt() -> PromptRecord {
+        PromptRecord {
+            agent_id: AgentId {
+                tool: "mock_ai".to_string(),
+                id: "session".to_string(),
+                model: "test-model".to_string(),
+            },
+            human_author: None,
+            messages: vec![],
+            total_additions: 0,
+            total_deletions: 0,
+            accepted_lines: 0,
+            overriden_lines: 0,
+            messages_url: None,
+        }
+    }
+
+    fn log_with_entries(entries: Vec<AttestationEntry>) -> AuthorshipLog {
+        let mut log = AuthorshipLog::new();
+        log.metadata.base_commit_sha = "abc".to_string();
+        log.metadata.prompts.insert("p1".to_string(), prompt());
+        log.metadata.prompts.insert("p2".to_string(), prompt());
+        let mut file = FileAttestation::new("a.txt".to_string());
+        for entry in entries {
+            file.add_entry(entry);
+        }
+        log.attestations.push(file);
+        log
+    }
+
+    fn kinds(issues: &[FsckIssue]) -> Vec<FsckIssueKind> {
+        issues.iter().map(|i| i.kind).collect()
+    }
+
+    #[test]
+    fn test_clean_log_has_no_issues() {
+        let log = log_with_entries(vec![
+            AttestationEntry::new("p1".to_string(), vec![LineRange::Range(1, 3)]),
+            AttestationEntry::new("p2".to_string(), vec![LineRange::Single(5)]),
+        ]);
+        let counts = HashMap::from([("a.txt".to_string(), 5)]);
+        assert!(check_authorship_log("abc", &log, &counts).is_empty());
+    }
+
+    #[test]
+    fn test_detects_missing_prompt_file_and_base_mismatch() {
+        let log = log_with_entries(vec![AttestationEntry::new(
+            "missing".to_string(),
+            vec![LineRange::Single(1)],
+        )]);
+        let issues = check_authorship_log("def", &log, &HashMap::new());
+        assert_eq!(
+            kinds(&issues),
+            vec![
+                FsckIssueKind::BaseCommitMismatch,
+                FsckIssueKind::MissingFile,
+                FsckIssueKind::MissingPrompt,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detects_out_of_bounds_ranges() {
+        let log = log_with_entries(vec![AttestationEntry::new(
+            "p1".to_string(),
+            vec![LineRange::Single(0), LineRange::Range(2, 9)],
+        )]);
+        let counts = HashMap::from([("a.txt".to_string(), 4)]);
+        let issues = check_authorship_log("abc", &log, &counts);
+        assert_eq!(
+            kinds(&issues),
+            vec![
+                FsckIssueKind::RangeOutOfBounds,
+                FsckIssueKind::RangeOutOfBounds
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detects_unsorted_and_overlapping_ranges() {
+        let log = log_with_entries(vec![
+            AttestationEntry::new(
+                "p1".to_string(),
+                vec![
+                    LineRange::Range(5, 7),
+                    LineRange::Single(2),
+                    LineRange::Range(2, 3),
+                ],
+            ),
+            AttestationEntry::new("p2".to_string(), vec![LineRange::Single(6)]),
+        ]);
+        let counts = HashMap::from([("a.txt".to_string(), 10)]);
+        let issues = check_authorship_log("abc", &log, &counts);
+        assert_eq!(
+            kinds(&issues),
+            vec![
+                FsckIssueKind::UnsortedRanges,
+                FsckIssueKind::OverlappingRanges,
+                FsckIssueKind::OverlappingRanges,
+            ]
+        );
+        assert_eq!(issues[2].hash.as_deref(), Some("p2"));
+    }
+
+    #[test]
+    fn test_detects_drifted_anchors() {
+        let mut log = log_with_entries(vec![AttestationEntry::new(
+            "p1".to_string(),
+            vec![LineRange::Single(1), LineRange::Single(3)],
+        )]);
+        crate::authorship::anchors::anchor_file_attestation(
+            &mut log.attestations[0],
+            "ai one\nhuman\nai two\n",
+        );
+
+        let unchanged =
+            HashMap::from([("a.txt".to_string(), "ai one\nhuman\nai two\n".to_string())]);
+        assert!(check_anchors("abc", &log, &unchanged).is_empty());
+
+        // As if the note had been copied onto a commit where the file differs
+        let other = HashMap::from([("a.txt".to_string(), "new\nai one\nhuman\n".to_string())]);
+        let issues = check_anchors("abc", &log, &other);
+        assert_eq!(
+            kinds(&issues),
+            vec![FsckIssueKind::AnchorMismatch, FsckIssueKind::AnchorMismatch]
+        );
+        assert!(
+            issues[0].message.contains("is at 2"),
+            "{}",
+            issues[0].message
+        );
+        assert!(
+            issues[1].message.contains("is gone"),
+            "{}",
+            issues[1].message
+        );
+    }
+
+    #[test]
+    fn test_count_lines() {
+        assert_eq!(count_lines(b""), 0);
+        assert_eq!(count_lines(b"a\nb\n"), 2);
+        assert_eq!(count_lines(b"a\nb"), 2);
+    }
+}
@@ -1,6 +1,9 @@
+use crate::authorship::grouped_stats::{
+    GroupBy, GroupedStatsFormat, format_grouped_stats, grouped_range_stats,
+};
 use crate::authorship::internal_db::InternalDatabase;
 use crate::authorship::range_authorship;
-use crate::authorship::stats::stats_command;
+use crate::authorship::stats::{stats_command, stats_with_submodules};
 use crate::authorship::working_log::{AgentId, CheckpointKind};
 use crate::commands;
 use crate::commands::checkpoint_agent::agent_presets::{
@@ -72,6 +75,36 @@ pub fn handle_git_ai(args: &[String]) {
         "show" => {
             commands::show::handle_show(&args[1..]);
         }
+        "fsck" => {
+            commands::fsck::handle_fsck(&args[1..]);
+        }
+        "verify" => {
+            commands::verify::handle_verify(&args[1..]);
+        }
+        "migrate-notes" => {
+            commands::migrate_notes::handle_migrate_notes(&args[1..]);
+        }
+        "remap" => {
+            commands::remap::handle_remap(&args[1..]);
+        }
+        "recover" => {
+            commands::recover::handle_recover(&args[1..]);
+        }
+        "log" => {
+            commands::log::handle_log(&args[1..]);
+        }
+        "jj-sync" => {
+            commands::jj_sync::handle_jj_sync(&args[1..]);
+        }
+        "report" => {
+            commands::report::handle_report(&args[1..]);
+        }
+        "ownership" => {
+            commands::ownership::handle_ownership(&args[1..]);
+        }
+        "schema" => {
+            commands::schema::handle_schema(&args[1..]);
+        }
         "checkpoint" => {
             if !allowed_repository {
                 eprintln!(
@@ -188,14 +221,76 @@ fn print_help() {
     eprintln!("    --reset                     Reset working log");
     eprintln!("    mock_ai [pathspecs...]      Test preset accepting optional file pathspecs");
     eprintln!("  blame <file>       Git blame with AI authorship overlay");
-    eprintln!("  diff <commit|range>  Show diff with AI authorship annotations");
+    eprintln!("    --recurse-submodules  Blame files inside submodules against the submodule");
+    eprintln!("  diff [commit|range]  Show diff with AI authorship annotations");
+    eprintln!(
+        "                          Diffs uncommitted changes against HEAD when no commit is given"
+    );
     eprintln!("    <commit>              Diff from commit's parent to commit");
     eprintln!("    <commit1>..<commit2>  Diff between two commits");
+    eprintln!("    --cached              Diff staged changes against HEAD");
+    eprintln!("    --json                Output in JSON format");
     eprintln!("  stats [commit]     Show AI authorship statistics for a commit");
     eprintln!("    --json                 Output in JSON format");
+    eprintln!("    --recurse-submodules   Include the submodule commits each gitlink moved across");
+    eprintln!(
+        "    --group-by <key>       Split range stats by author, tool, model, week, month or directory"
+    );
+    eprintln!("    --csv                  Output grouped stats as CSV");
     eprintln!("  status             Show uncommitted AI authorship status (debug)");
     eprintln!("    --json                 Output in JSON format");
+    eprintln!("    --recurse-submodules   Include uncommitted work inside submodules");
     eprintln!("  show <rev|range>   Display authorship logs for a revision or range");
+    eprintln!("  log [rev|range] [-- paths]  List commits with their AI, mixed and human lines");
+    eprintln!("                        Lists the last 100 commits on HEAD when no range is given");
+    eprintln!("    --tool <tool>          Only commits an agent or tool worked on");
+    eprintln!("    --model <model>        Only commits a model worked on (substring match)");
+    eprintln!("    --min-ai-percent <n>   Only commits with at least n% AI lines");
+    eprintln!("    --author <pattern>     Only commits by matching authors");
+    eprintln!("    -n <count>             Limit the number of commits checked");
+    eprintln!("    --json                 Output in JSON format");
+    eprintln!(
+        "  report <range>     Write a self-contained HTML report of AI authorship in a range"
+    );
+    eprintln!(
+        "                        AI share per week, directories, top prompts and tools/models"
+    );
+    eprintln!("    --out <file>           Output file (default: git-ai-report.html)");
+    eprintln!(
+        "  ownership [rev] [-- paths]  Show AI, human and unknown lines per directory at a revision"
+    );
+    eprintln!("    --depth <n>            Directory levels shown in the tree (default: 2)");
+    eprintln!("    --json                 Output every directory and file as JSON");
+    eprintln!("  fsck [rev|range]   Verify authorship notes against the commits they annotate");
+    eprintln!("                        Checks all noted commits when no revision is given");
+    eprintln!("    --json                 Output in JSON format");
+    eprintln!("  verify [rev|range] Check SSH signatures on authorship notes");
+    eprintln!("                        Sign notes by setting gitai.signingKey to an SSH key");
+    eprintln!(
+        "    --allowed-signers <file>  Allowed signers file (default: gitai.allowedSignersFile)"
+    );
+    eprintln!("    --json                 Output in JSON format");
+    eprintln!("  migrate-notes [rev|range]  Rewrite legacy authorship notes in canonical form");
+    eprintln!("                        Migrates all notes when no revision is given");
+    eprintln!("    --dry-run             Show a diff of each note without changing anything");
+    eprintln!(
+        "  remap [commit-map] Move authorship notes onto commits rewritten by filter-repo or BFG"
+    );
+    eprintln!("                        Reads .git/filter-repo/commit-map when no map is given");
+    eprintln!("    --path-rename <old>:<new>  Rename paths in the notes like the rewrite did");
+    eprintln!("    --dry-run             List the notes that would be remapped");
+    eprintln!(
+        "  recover [rev|range]  Rebuild notes for rebased or squashed commits that lost them"
+    );
+    eprintln!(
+        "                        Matches by patch-id, then by added lines; checks the last 100 commits by default"
+    );
+    eprintln!("    --dry-run             Report matches without writing notes");
+    eprintln!("  jj-sync            Carry authorship notes across commits jj rewrote");
+    eprintln!("                        Run after jj operations in a colocated repository");
+    eprintln!("    --dry-run             List the notes that would be synced");
+    eprintln!("  schema [name]      Print the JSON Schema for a git-ai output format");
+    eprintln!("                        Lists available schemas when no name is given");
     eprintln!("  show-prompt <id>   Display a prompt record by its ID");
     eprintln!("    --commit <rev>        Look in a specific commit only");
     eprintln!(
@@ -241,6 +336,15 @@ fn print_help() {
     eprintln!("  version, -v, --version     Print the git-ai version");
     eprintln!("  help, -h, --help           Show this help message");
     eprintln!();
+    eprintln!("Patches:");
+    eprintln!(
+        "  Set gitai.formatPatchAuthorship to embed authorship in the files git format-patch writes"
+    );
+    eprintln!(
+        "                        (-o, --output, --numbered-files); --stdout output carries none"
+    );
+    eprintln!("  git am restores it from mailbox files named on its command line, not from stdin");
+    eprintln!();
     std::process::exit(0);
 }
 
@@ -788,6 +892,19 @@ fn handle_ai_blame(args: &[String]) {
         }
     };
 
+    // Files inside a submodule are blamed against the submodule's own history and notes
+    let (repo, file_path) = if options.recurse_submodules {
+        match commands::blame::resolve_submodule_blame_target(repo, &file_path, &mut options) {
+            Ok(target) => target,
+            Err(e) => {
+                eprintln!("Failed to resolve submodule for {}: {}", file_path, e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        (repo, file_path)
+    };
+
     // Auto-detect ignore-revs-file if not explicitly provided, not disabled via --no-ignore-revs-file,
     // and git version supports --ignore-revs-file (git >= 2.23)
     if options.ignore_revs_file.is_none()
@@ -871,6 +988,9 @@ fn handle_stats(args: &[String]) {
     let mut commit_sha = None;
     let mut commit_range: Option<CommitRange> = None;
     let mut ignore_patterns: Vec<String> = Vec::new();
+    let mut recurse_submodules = false;
+    let mut group_by: Option<GroupBy> = None;
+    let mut csv_output = false;
 
     let mut i = 0;
     while i < args.len() {
@@ -879,6 +999,31 @@ fn handle_stats(args: &[String]) {
                 json_output = true;
                 i += 1;
             }
+            "--csv" => {
+                csv_output = true;
+                i += 1;
+            }
+            "--group-by" => {
+                let Some(value) = args.get(i + 1) else {
+                    eprintln!("--group-by requires author, tool, model, week, month or directory");
+                    std::process::exit(1);
+                };
+                match GroupBy::parse(value) {
+                    Some(parsed) => group_by = Some(parsed),
+                    None => {
+                        eprintln!(
+                            "Unknown --group-by '{}', expected author, tool, model, week, month or directory",
+                            value
+                        );
+                        std::process::exit(1);
+                    }
+                }
+                i += 2;
+            }
+            "--recurse-submodules" => {
+                recurse_submodules = true;
+                i += 1;
+            }
             "--ignore" => {
                 // Collect all arguments after --ignore until we hit another flag or commit SHA
                 // This supports shell glob expansion: `--ignore *.lock` expands to `--ignore Cargo.lock package.lock`
@@ -945,9 +1090,54 @@ fn handle_stats(args: &[String]) {
         }
     }
 
+    if group_by.is_some() && (commit_range.is_none() || recurse_submodules) {
+        eprintln!(
+            "--group-by needs a commit range and can't be combined with --recurse-submodules"
+        );
+        std::process::exit(1);
+    }
+    if csv_output && group_by.is_none() {
+        eprintln!("--csv is only supported with --group-by");
+        std::process::exit(1);
+    }
+
     // Handle commit range if detected
+    if let (Some(range), Some(group_by)) = (commit_range.clone(), group_by) {
+        let format = if json_output {
+            GroupedStatsFormat::Json
+        } else if csv_output {
+            GroupedStatsFormat::Csv
+        } else {
+            GroupedStatsFormat::Table
+        };
+        match grouped_range_stats(range, group_by, &ignore_patterns) {
+            Ok(grouped) => print!("{}", format_grouped_stats(&grouped, format)),
+            Err(e) => {
+                eprintln!("Grouped range stats failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
     if let Some(range) = commit_range {
-        match range_authorship::range_authorship(range, false, &ignore_patterns) {
+        let result = if recurse_submodules {
+            let (start, end) = (range.start_oid.clone(), range.end_oid.clone());
+            let mut range_result = None;
+            stats_with_submodules(&repo, Some(&start), &end, &ignore_patterns, |patterns| {
+                let stats = range_authorship::range_authorship(range, false, patterns)?;
+                let range_stats = stats.range_stats.clone();
+                range_result = Some(stats);
+                Ok(range_stats)
+            })
+            .map(|range_stats| {
+                let mut stats = range_result.expect("range stats were computed");
+                stats.range_stats = range_stats;
+                stats
+            })
+        } else {
+            range_authorship::range_authorship(range, false, &ignore_patterns)
+        };
+        match result {
             Ok(stats) => {
                 if json_output {
                     let json_str = serde_json::to_string(&stats).unwrap();
@@ -964,7 +1154,13 @@ fn handle_stats(args: &[String]) {
         return;
     }
 
-    if let Err(e) = stats_command(&repo, commit_sha.as_deref(), json_output, &ignore_patterns) {
+    if let Err(e) = stats_command(
+        &repo,
+        commit_sha.as_deref(),
+        json_output,
+        &ignore_patterns,
+        recurse_submodules,
+    ) {
         match e {
             crate::error::GitAiError::Generic(msg) if msg.starts_with("No commit found:") => {
                 eprintln!("{}", msg);
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::authorship::virtual_attribution::VirtualAttributions;
 use crate::commands::hooks::checkout_hooks;
@@ -7,11 +7,15 @@ use crate::commands::hooks::clone_hooks;
 use crate::commands::hooks::commit_hooks;
 use crate::commands::hooks::fetch_hooks;
 use crate::commands::hooks::merge_hooks;
+use crate::commands::hooks::patch_hooks;
 use crate::commands::hooks::push_hooks;
 use crate::commands::hooks::rebase_hooks;
 use crate::commands::hooks::reset_hooks;
+use crate::commands::hooks::restore_hooks::{self, DiscardSnapshot};
+use crate::commands::hooks::revert_hooks;
 use crate::commands::hooks::stash_hooks;
 use crate::commands::hooks::switch_hooks;
+use crate::commands::hooks::worktree_hooks;
 use crate::config;
 use crate::git::cli_parser::{ParsedGitInvocation, parse_git_cli_args};
 use crate::git::find_repository;
@@ -30,10 +34,11 @@ use std::os::unix::process::CommandExt;
 use std::os::unix::process::ExitStatusExt;
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
+use std::path::PathBuf;
 use std::process::Command;
 #[cfg(unix)]
 use std::sync::atomic::{AtomicI32, Ordering};
-use std::time::Instant;
+use std::time::{Instant, SystemTime};
 
 #[cfg(unix)]
 static CHILD_PGID: AtomicI32 = AtomicI32::new(0);
@@ -92,6 +97,12 @@ pub struct CommandHooksContext {
     /// VirtualAttributions captured before a pull --rebase --autostash operation.
     /// Used to preserve uncommitted AI attributions that git's internal stash would lose.
     pub stashed_va: Option<VirtualAttributions>,
+    /// Messages `git am` is about to apply, read before it consumes them.
+    pub am_patches: Option<Vec<String>>,
+    /// Patch files in the `git format-patch` output directory before it ran, with their mtimes.
+    pub existing_patch_files: Option<HashMap<PathBuf, Option<SystemTime>>>,
+    /// Attributed files as they were before `git restore`, `git clean` or `git checkout -p`.
+    pub discard_snapshot: Option<DiscardSnapshot>,
 }
 
 pub fn handle_git(args: &[String]) {
@@ -138,6 +149,9 @@ pub fn handle_git(args: &[String]) {
             stash_sha: None,
             push_authorship_handle: None,
             stashed_va: None,
+            am_patches: None,
+            existing_patch_files: None,
+            discard_snapshot: None,
         };
 
         let repository = repository_option.as_mut().unwrap();
@@ -329,6 +343,15 @@ fn run_pre_command_hooks(
                     command_hooks_context,
                 );
             }
+            Some("revert") => {
+                revert_hooks::pre_revert_hook(parsed_args, repository, command_hooks_context);
+            }
+            Some("am") => {
+                patch_hooks::pre_am_hook(parsed_args, repository, command_hooks_context);
+            }
+            Some("format-patch") => {
+                patch_hooks::pre_format_patch_hook(parsed_args, repository, command_hooks_context);
+            }
             Some("push") => {
                 command_hooks_context.push_authorship_handle =
                     push_hooks::push_pre_command_hook(parsed_args, repository);
@@ -350,6 +373,9 @@ fn run_pre_command_hooks(
             Some("checkout") => {
                 checkout_hooks::pre_checkout_hook(parsed_args, repository, command_hooks_context);
             }
+            Some("restore") | Some("clean") => {
+                restore_hooks::pre_restore_hook(parsed_args, repository, command_hooks_context);
+            }
             Some("switch") => {
                 switch_hooks::pre_switch_hook(parsed_args, repository, command_hooks_context);
             }
@@ -425,6 +451,24 @@ fn run_post_command_hooks(
                 exit_status,
                 repository,
             ),
+            Some("revert") => revert_hooks::post_revert_hook(
+                command_hooks_context,
+                parsed_args,
+                exit_status,
+                repository,
+            ),
+            Some("am") => patch_hooks::post_am_hook(
+                command_hooks_context,
+                parsed_args,
+                exit_status,
+                repository,
+            ),
+            Some("format-patch") => patch_hooks::post_format_patch_hook(
+                command_hooks_context,
+                parsed_args,
+                repository,
+                exit_status,
+            ),
             Some("stash") => {
                 let config = config::Config::get();
 
@@ -445,6 +489,9 @@ fn run_post_command_hooks(
                     command_hooks_context,
                 );
             }
+            Some("restore") | Some("clean") => {
+                restore_hooks::post_restore_hook(repository, command_hooks_context);
+            }
             Some("switch") => {
                 switch_hooks::post_switch_hook(
                     parsed_args,
@@ -453,6 +500,9 @@ fn run_post_command_hooks(
                     command_hooks_context,
                 );
             }
+            Some("worktree") => {
+                worktree_hooks::post_worktree_hook(parsed_args, repository, exit_status);
+            }
             _ => {}
         }
     }));
@@ -1,6 +1,9 @@
 use crate::authorship::virtual_attribution::{VirtualAttributions, restore_stashed_va};
 use crate::commands::git_handlers::CommandHooksContext;
 use crate::commands::hooks::commit_hooks::get_commit_default_author;
+use crate::commands::hooks::restore_hooks::{
+    post_restore_hook, pre_restore_hook, prune_working_log_files,
+};
 use crate::git::cli_parser::ParsedGitInvocation;
 use crate::git::repository::Repository;
 use crate::utils::debug_log;
@@ -12,6 +15,12 @@ pub fn pre_checkout_hook(
 ) {
     repository.require_pre_command_head();
 
+    // `checkout -p` discards hunks chosen interactively, like `restore -p`
+    if is_patch_checkout(parsed_args) {
+        pre_restore_hook(parsed_args, repository, command_hooks_context);
+        return;
+    }
+
     // If --merge is used, we need to capture VirtualAttributions before the checkout
     // because the merge might shift lines around
     if is_merge_checkout(parsed_args) && has_uncommitted_changes(repository) {
@@ -27,6 +36,11 @@ fn is_force_checkout(parsed_args: &ParsedGitInvocation) -> bool {
         .any(|arg| arg == "-f" || arg == "--force")
 }
 
+/// Check if checkout uses -p/--patch to pick hunks to discard.
+fn is_patch_checkout(parsed_args: &ParsedGitInvocation) -> bool {
+    parsed_args.has_command_flag("--patch") || parsed_args.has_command_flag("-p")
+}
+
 /// Check if checkout uses --merge flag that merges local changes.
 fn is_merge_checkout(parsed_args: &ParsedGitInvocation) -> bool {
     parsed_args.has_command_flag("--merge") || parsed_args.has_command_flag("-m")
@@ -85,6 +99,12 @@ pub fn post_checkout_hook(
     exit_status: std::process::ExitStatus,
     command_hooks_context: &mut CommandHooksContext,
 ) {
+    // Patch checkout (git checkout -p) - drop the hunks that were discarded
+    if command_hooks_context.discard_snapshot.is_some() {
+        post_restore_hook(repository, command_hooks_context);
+        return;
+    }
+
     if !exit_status.success() {
         debug_log("Checkout failed, skipping working log handling");
         return;
@@ -151,32 +171,9 @@ pub fn post_checkout_hook(
 
 /// Remove attributions for specific files from working log (pathspec checkout case).
 fn remove_attributions_for_pathspecs(repository: &Repository, head: &str, pathspecs: &[String]) {
-    let working_log = repository.storage.working_log_for_base_commit(head);
-
-    // Filter INITIAL attributions
-    let initial = working_log.read_initial_attributions();
-    if !initial.files.is_empty() {
-        let filtered_files = initial
-            .files
-            .into_iter()
-            .filter(|(file, _)| !matches_any_pathspec(file, pathspecs))
-            .collect();
-        let _ = working_log.write_initial_attributions(filtered_files, initial.prompts);
-    }
-
-    // Filter checkpoints
-    if let Ok(checkpoints) = working_log.read_all_checkpoints() {
-        let filtered: Vec<_> = checkpoints
-            .into_iter()
-            .map(|mut cp| {
-                cp.entries
-                    .retain(|entry| !matches_any_pathspec(&entry.file, pathspecs));
-                cp
-            })
-            .filter(|cp| !cp.entries.is_empty())
-            .collect();
-        let _ = working_log.write_all_checkpoints(&filtered);
-    }
+    prune_working_log_files(repository, head, |file| {
+        matches_any_pathspec(file, pathspecs)
+    });
 }
 
 fn matches_any_pathspec(file: &str, pathspecs: &[String]) -> bool {
@@ -1,5 +1,6 @@
 use crate::authorship::pre_commit;
 use crate::commands::git_handlers::CommandHooksContext;
+use crate::commands::hooks::revert_hooks;
 use crate::git::cli_parser::{ParsedGitInvocation, is_dry_run};
 use crate::git::repository::Repository;
 use crate::git::rewrite_log::RewriteLogEvent;
@@ -16,6 +17,9 @@ pub fn commit_pre_command_hook(
     // store HEAD context for post-command hook
     repository.require_pre_command_head();
 
+    // Committing a resolved `git revert --no-commit` needs the reverted lines' attributions
+    revert_hooks::prepare_resolved_revert(repository);
+
     let default_author = get_commit_default_author(repository, &parsed_args.command_args);
 
     // Run pre-commit logic
@@ -4,8 +4,12 @@ pub mod clone_hooks;
 pub mod commit_hooks;
 pub mod fetch_hooks;
 pub mod merge_hooks;
+pub mod patch_hooks;
 pub mod push_hooks;
 pub mod rebase_hooks;
 pub mod reset_hooks;
+pub mod restore_hooks;
+pub mod revert_hooks;
 pub mod stash_hooks;
 pub mod switch_hooks;
+pub mod worktree_hooks;
@@ -0,0 +1,345 @@
+use crate::authorship::patch_authorship::{
+    PATCH_AUTHORSHIP_CONFIG, attach_patch_authorship, embed_authorship_in_patch,
+    patch_source_commit, patch_subject, split_mbox,
+};
+use crate::authorship::rebase_authorship::walk_commits_to_base;
+use crate::commands::git_handlers::CommandHooksContext;
+use crate::git::cli_parser::ParsedGitInvocation;
+use crate::git::refs::get_reference_as_authorship_log_v3;
+use crate::git::repository::{Repository, invocation_dir};
+use crate::utils::debug_log;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Note the patch files already in the output directory before `git format-patch` runs
+///
+/// The post-hook only embeds authorship in patches this invocation wrote, so files left
+/// behind by earlier runs keep their content.
+pub fn pre_format_patch_hook(
+    parsed_args: &ParsedGitInvocation,
+    repository: &Repository,
+    command_hooks_context: &mut CommandHooksContext,
+) {
+    if !format_patch_authorship_enabled(repository) {
+        return;
+    }
+    if let Some((dir, names)) = patch_output_dir(parsed_args) {
+        command_hooks_context.existing_patch_files = Some(
+            list_patch_files(&dir, &names)
+                .into_iter()
+                .map(|path| {
+                    let modified = modified_time(&path);
+                    (path, modified)
+                })
+                .collect(),
+        );
+    }
+}
+
+/// Embed each commit's authorship log in the patches `git format-patch` wrote
+///
+/// Only runs when `gitai.formatPatchAuthorship` is enabled. Covers numbered patches in the
+/// output directory and a single `--output` file; patches written to stdout can't be
+/// rewritten and are left alone.
+pub fn post_format_patch_hook(
+    context: &CommandHooksContext,
+    parsed_args: &ParsedGitInvocation,
+    repository: &Repository,
+    exit_status: std::process::ExitStatus,
+) {
+    if !exit_status.success() || !format_patch_authorship_enabled(repository) {
+        return;
+    }
+
+    let args = &parsed_args.command_args;
+    if args.iter().any(|arg| arg == "--stdout") {
+        debug_log("format-patch wrote to stdout, not embedding authorship");
+        return;
+    }
+
+    let patch_files = match option_value(args, "--output", None) {
+        Some(output) => vec![invocation_dir(&parsed_args.global_args).join(output)],
+        None => {
+            let Some((dir, names)) = patch_output_dir(parsed_args) else {
+                return;
+            };
+            let existing = context.existing_patch_files.as_ref();
+            list_patch_files(&dir, &names)
+                .into_iter()
+                .filter(|path| {
+                    existing
+                        .and_then(|existing| existing.get(path))
+                        .is_none_or(|modified| *modified != modified_time(path))
+                })
+                .collect()
+        }
+    };
+
+    for patch_file in patch_files {
+        if let Err(e) = embed_authorship_in_file(repository, &patch_file) {
+            debug_log(&format!(
+                "Failed to embed authorship in {}: {}",
+                patch_file.display(),
+                e
+            ));
+        }
+    }
+}
+
+fn format_patch_authorship_enabled(repository: &Repository) -> bool {
+    repository
+        .config_get_bool(PATCH_AUTHORSHIP_CONFIG)
+        .ok()
+        .flatten()
+        .unwrap_or(false)
+}
+
+/// How format-patch names the patches it writes to a directory
+enum PatchFileNames {
+    /// `0001-subject.patch`, ending in `--suffix`
+    Suffix(String),
+    /// `1`, `2`, ... with `--numbered-files`, which ignores `--suffix`
+    Numbered,
+}
+
+impl PatchFileNames {
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            PatchFileNames::Suffix(suffix) => name.ends_with(suffix.as_str()),
+            PatchFileNames::Numbered => {
+                !name.is_empty() && name.chars().all(|c| c.is_ascii_digit())
+            }
+        }
+    }
+}
+
+/// The directory format-patch writes numbered patches to and how they're named
+///
+/// None when the patches go to stdout or to a single `--output` file, which the post-hook
+/// handles on its own.
+fn patch_output_dir(parsed_args: &ParsedGitInvocation) -> Option<(PathBuf, PatchFileNames)> {
+    let args = &parsed_args.command_args;
+    if args.iter().any(|arg| arg == "--stdout") || option_value(args, "--output", None).is_some() {
+        return None;
+    }
+    let base_dir = invocation_dir(&parsed_args.global_args);
+    let dir = option_value(args, "--output-directory", Some("-o"))
+        .map(|dir| base_dir.join(dir))
+        .unwrap_or(base_dir);
+    let names = if args.iter().any(|arg| arg == "--numbered-files") {
+        PatchFileNames::Numbered
+    } else {
+        PatchFileNames::Suffix(option_value(args, "--suffix", None).unwrap_or(".patch".to_string()))
+    };
+    Some((dir, names))
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+fn embed_authorship_in_file(repository: &Repository, path: &Path) -> std::io::Result<()> {
+    let content = std::fs::read_to_string(path)?;
+    let mut changed = false;
+    let mut output = String::with_capacity(content.len());
+
+    for message in split_mbox(&content) {
+        let embedded = patch_source_commit(message)
+            .and_then(|sha| get_reference_as_authorship_log_v3(repository, &sha).ok())
+            .and_then(|mut log| {
+                // The signature only vouches for the note on the original commit
+                log.metadata.signature = None;
+                log.serialize_to_string().ok()
+            })
+            .and_then(|note| embed_authorship_in_patch(message, &note));
+        match embedded {
+            Some(embedded) => {
+                changed = true;
+                output.push_str(&embedded);
+            }
+            None => output.push_str(message),
+        }
+    }
+
+    if changed {
+        std::fs::write(path, output)?;
+        debug_log(&format!("Embedded authorship in {}", path.display()));
+    }
+    Ok(())
+}
+
+/// Patch files format-patch may have written to `dir`, recognised by their mbox header
+fn list_patch_files(dir: &Path, names: &PatchFileNames) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .is_some_and(|name| names.matches(&name.to_string_lossy()))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Value of `--long <value>`, `--long=<value>` or `-s <value>`/`-s<value>`
+fn option_value(args: &[String], long: &str, short: Option<&str>) -> Option<String> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == long || Some(arg.as_str()) == short {
+            return args.next().cloned();
+        }
+        if let Some(value) = arg
+            .strip_prefix(long)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(value.to_string());
+        }
+        if let Some(short) = short
+            && let Some(value) = arg.strip_prefix(short)
+            && !value.is_empty()
+            && !arg.starts_with("--")
+        {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+/// Collect the messages `git am` is about to apply, to pair with the commits it makes
+///
+/// Only mailboxes named on the command line are read; a mailbox piped to `git am` on stdin
+/// belongs to git and is applied without authorship.
+pub fn pre_am_hook(
+    parsed_args: &ParsedGitInvocation,
+    repository: &mut Repository,
+    command_hooks_context: &mut CommandHooksContext,
+) {
+    debug_log("=== AM PRE-COMMAND HOOK ===");
+
+    // Capture HEAD before any patches are applied
+    repository.require_pre_command_head();
+
+    let rebase_apply = repository.path().join("rebase-apply");
+    let messages = if rebase_apply.is_dir() {
+        // Continuing a stopped `git am`: the remaining messages were split out already
+        pending_am_messages(&rebase_apply)
+    } else {
+        let base_dir = invocation_dir(&parsed_args.global_args);
+        let input_files = am_input_files(&parsed_args.command_args);
+        if input_files.is_empty() {
+            debug_log("git am reads its mailbox from stdin, not carrying authorship");
+        }
+        let mut messages = Vec::new();
+        for path in input_files {
+            match std::fs::read_to_string(base_dir.join(&path)) {
+                Ok(content) => messages.extend(split_mbox(&content).into_iter().map(String::from)),
+                Err(e) => debug_log(&format!("Failed to read patch {}: {}", path, e)),
+            }
+        }
+        messages
+    };
+
+    debug_log(&format!("git am will apply {} messages", messages.len()));
+    command_hooks_context.am_patches = Some(messages);
+}
+
+pub fn post_am_hook(
+    context: &CommandHooksContext,
+    _parsed_args: &ParsedGitInvocation,
+    exit_status: std::process::ExitStatus,
+    repository: &mut Repository,
+) {
+    debug_log("=== AM POST-COMMAND HOOK ===");
+    debug_log(&format!("Exit status: {}", exit_status));
+
+    let messages = match &context.am_patches {
+        Some(messages) if !messages.is_empty() => messages,
+        _ => return,
+    };
+    let Some(original_head) = repository.pre_command_base_commit.clone() else {
+        return;
+    };
+    let Ok(new_head) = repository.head().and_then(|head| head.target()) else {
+        return;
+    };
+    if new_head == original_head {
+        return;
+    }
+
+    // Patches applied before a conflict stopped `git am` still get their authorship
+    let mut new_commits = match walk_commits_to_base(repository, &new_head, &original_head) {
+        Ok(commits) => commits,
+        Err(e) => {
+            debug_log(&format!("✗ Failed to find applied commits: {}", e));
+            return;
+        }
+    };
+    new_commits.reverse();
+
+    // Pair commits with messages in order, skipping messages whose subject doesn't
+    // match (patches that were skipped or turned out empty)
+    let mut remaining = messages.iter();
+    for commit_sha in new_commits {
+        let summary = repository
+            .find_commit(commit_sha.clone())
+            .and_then(|commit| commit.summary())
+            .unwrap_or_default();
+        let Some(message) = remaining
+            .by_ref()
+            .find(|message| patch_subject(message).as_deref() == Some(summary.trim()))
+        else {
+            debug_log(&format!("No patch message matches commit {}", commit_sha));
+            break;
+        };
+
+        match attach_patch_authorship(repository, message, &commit_sha) {
+            Ok(true) => debug_log(&format!("✓ Attached patch authorship to {}", commit_sha)),
+            Ok(false) => {}
+            Err(e) => debug_log(&format!(
+                "✗ Failed to attach patch authorship to {}: {}",
+                commit_sha, e
+            )),
+        }
+    }
+}
+
+/// The messages of a stopped `git am` that haven't been committed yet, in order
+fn pending_am_messages(rebase_apply: &Path) -> Vec<String> {
+    let read_number = |name: &str| -> Option<usize> {
+        std::fs::read_to_string(rebase_apply.join(name))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    };
+    let (Some(next), Some(last)) = (read_number("next"), read_number("last")) else {
+        return Vec::new();
+    };
+    (next..=last)
+        .filter_map(|n| std::fs::read_to_string(rebase_apply.join(format!("{:04}", n))).ok())
+        .collect()
+}
+
+/// Mailbox files named on a `git am` command line
+fn am_input_files(args: &[String]) -> Vec<String> {
+    let mut files = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            // Options that take a separate value
+            "--patch-format" | "--directory" | "--exclude" | "--include" | "--resolvemsg" => {
+                args.next();
+            }
+            _ if arg.starts_with('-') => {}
+            _ => files.push(arg.clone()),
+        }
+    }
+    files
+}
@@ -57,8 +57,11 @@ pub fn push_pre_command_hook(
 
         crate::observability::spawn_background_flush();
 
-        // Spawn CAS flush if prompt_storage is "default" (CAS upload mode)
-        if crate::config::Config::get().prompt_storage() == "default" {
+        // Spawn CAS flush if prompt_storage is "default" (CAS upload mode), or if oversized
+        // transcripts are offloaded from notes
+        if crate::config::Config::get().prompt_storage() == "default"
+            || crate::authorship::post_commit::note_messages_budget(repository).is_some()
+        {
             crate::commands::flush_cas::spawn_background_cas_flush();
         }
 
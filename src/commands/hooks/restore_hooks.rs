@@ -0,0 +1,208 @@
+use crate::authorship::virtual_attribution::{
+    VirtualAttributions, merge_attributions_favoring_first,
+};
+use crate::authorship::working_log::CheckpointKind;
+use crate::commands::git_handlers::CommandHooksContext;
+use crate::commands::hooks::commit_hooks::get_commit_default_author;
+use crate::git::cli_parser::ParsedGitInvocation;
+use crate::git::repository::Repository;
+use crate::utils::debug_log;
+use std::collections::{HashMap, HashSet};
+
+/// The attributed files' state before a command that can discard working tree edits
+pub struct DiscardSnapshot {
+    head: String,
+    va: VirtualAttributions,
+    /// Working tree content of each attributed file, None if it didn't exist
+    contents: HashMap<String, Option<String>>,
+}
+
+/// Capture attributions before `git restore`, `git checkout -p` or `git clean`
+///
+/// The command decides which edits go, so rather than interpreting its arguments the post
+/// hook compares each attributed file with what was on disk here.
+pub fn pre_restore_hook(
+    parsed_args: &ParsedGitInvocation,
+    repository: &mut Repository,
+    command_hooks_context: &mut CommandHooksContext,
+) {
+    let Some(head) = repository.head().ok().and_then(|h| h.target().ok()) else {
+        return;
+    };
+
+    // Record edits made since the last checkpoint so they aren't mistaken for discarded ones
+    let human_author = get_commit_default_author(repository, &parsed_args.command_args);
+    let _ = crate::commands::checkpoint::run(
+        repository,
+        &human_author,
+        CheckpointKind::Human,
+        false,
+        false,
+        true,
+        None,
+        true,
+    );
+
+    let va = match VirtualAttributions::from_just_working_log(
+        repository.clone(),
+        head.clone(),
+        Some(human_author),
+    ) {
+        Ok(va) => va,
+        Err(e) => {
+            debug_log(&format!("Failed to build VirtualAttributions: {}", e));
+            return;
+        }
+    };
+    if va.attributions.is_empty() {
+        return;
+    }
+
+    let Ok(workdir) = repository.workdir() else {
+        return;
+    };
+    let contents = va
+        .files()
+        .into_iter()
+        .map(|file| {
+            let content = std::fs::read_to_string(workdir.join(&file)).ok();
+            (file, content)
+        })
+        .collect();
+
+    command_hooks_context.discard_snapshot = Some(DiscardSnapshot { head, va, contents });
+}
+
+/// Drop the attributions of edits the command discarded
+///
+/// Files that were removed or brought back to HEAD lose their entries in the working log
+/// and INITIAL. Files that were only partly restored keep the attributions of the lines
+/// that survived, remapped onto the new content and stored as INITIAL attributions.
+pub fn post_restore_hook(
+    repository: &mut Repository,
+    command_hooks_context: &mut CommandHooksContext,
+) {
+    // An interrupted `-p` session may still have discarded some hunks, so the exit
+    // status doesn't matter; only what changed on disk does
+    let Some(snapshot) = command_hooks_context.discard_snapshot.take() else {
+        return;
+    };
+    let Ok(workdir) = repository.workdir() else {
+        return;
+    };
+
+    let mut discarded: HashSet<String> = HashSet::new();
+    let mut remaining_contents: HashMap<String, String> = HashMap::new();
+    for (file, before) in &snapshot.contents {
+        let after = std::fs::read_to_string(workdir.join(file)).ok();
+        if &after == before {
+            continue;
+        }
+        discarded.insert(file.clone());
+
+        let Some(after) = after else {
+            continue;
+        };
+        let head_content = repository
+            .get_file_content(file, &snapshot.head)
+            .ok()
+            .map(|bytes| String::from_utf8_lossy(&bytes).to_string());
+        if head_content.as_deref() != Some(after.as_str()) {
+            remaining_contents.insert(file.clone(), after);
+        }
+    }
+
+    if discarded.is_empty() {
+        debug_log("No attributed files were changed, working log left as is");
+        return;
+    }
+    debug_log(&format!(
+        "Dropping discarded edits from {} file(s), {} partly restored",
+        discarded.len(),
+        remaining_contents.len()
+    ));
+
+    prune_working_log_files(repository, &snapshot.head, |file| discarded.contains(file));
+
+    if !remaining_contents.is_empty() {
+        remap_partly_restored_files(repository, snapshot, remaining_contents);
+    }
+}
+
+/// Move the surviving attributions of partly restored files onto their new content
+fn remap_partly_restored_files(
+    repository: &Repository,
+    snapshot: DiscardSnapshot,
+    remaining_contents: HashMap<String, String>,
+) {
+    let DiscardSnapshot { head, va, .. } = snapshot;
+    let empty = VirtualAttributions::new(
+        repository.clone(),
+        head.clone(),
+        HashMap::new(),
+        HashMap::new(),
+        va.timestamp(),
+    );
+    let pathspecs: HashSet<String> = remaining_contents.keys().cloned().collect();
+
+    let remapped =
+        merge_attributions_favoring_first(va, empty, remaining_contents).and_then(|merged| {
+            merged.to_authorship_log_and_initial_working_log(
+                repository,
+                &head,
+                &head,
+                Some(&pathspecs),
+            )
+        });
+    let (_, remapped_initial) = match remapped {
+        Ok(result) => result,
+        Err(e) => {
+            debug_log(&format!("Failed to remap partly restored files: {}", e));
+            return;
+        }
+    };
+
+    let working_log = repository.storage.working_log_for_base_commit(&head);
+    let mut initial = working_log.read_initial_attributions();
+    for (file, line_attributions) in remapped_initial.files {
+        if !line_attributions.is_empty() {
+            initial.files.insert(file, line_attributions);
+        }
+    }
+    initial.prompts.extend(remapped_initial.prompts);
+    if let Err(e) = working_log.write_initial_attributions(initial.files, initial.prompts) {
+        debug_log(&format!("Failed to write INITIAL attributions: {}", e));
+    }
+}
+
+/// Remove the files matching `should_prune` from the INITIAL attributions and checkpoints
+pub fn prune_working_log_files<F>(repository: &Repository, head: &str, should_prune: F)
+where
+    F: Fn(&str) -> bool,
+{
+    let working_log = repository.storage.working_log_for_base_commit(head);
+
+    // Filter INITIAL attributions
+    let initial = working_log.read_initial_attributions();
+    if !initial.files.is_empty() {
+        let filtered_files = initial
+            .files
+            .into_iter()
+            .filter(|(file, _)| !should_prune(file))
+            .collect();
+        let _ = working_log.write_initial_attributions(filtered_files, initial.prompts);
+    }
+
+    // Filter checkpoints
+    if let Ok(checkpoints) = working_log.read_all_checkpoints() {
+        let filtered: Vec<_> = checkpoints
+            .into_iter()
+            .map(|mut cp| {
+                cp.entries.retain(|entry| !should_prune(&entry.file));
+                cp
+            })
+            .filter(|cp| !cp.entries.is_empty())
+            .collect();
+        let _ = working_log.write_all_checkpoints(&filtered);
+    }
+}
@@ -0,0 +1,467 @@
+use crate::authorship::rebase_authorship::{
+    prepare_working_log_after_revert, rewrite_authorship_after_revert, walk_commits_to_base,
+};
+use crate::commands::git_handlers::CommandHooksContext;
+use crate::git::cli_parser::ParsedGitInvocation;
+use crate::git::repository::{Repository, exec_git};
+use crate::git::rewrite_log::{
+    RevertAbortEvent, RevertMixedEvent, RevertStartEvent, RewriteLogEvent,
+};
+use crate::utils::debug_log;
+
+/// What a `git revert` invocation does to the revert sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RevertAction {
+    Start,
+    Continue,
+    Skip,
+    Abort,
+    Quit,
+}
+
+pub fn pre_revert_hook(
+    parsed_args: &ParsedGitInvocation,
+    repository: &mut Repository,
+    _command_hooks_context: &mut CommandHooksContext,
+) {
+    debug_log("=== REVERT PRE-COMMAND HOOK ===");
+
+    let action = revert_action(&parsed_args.command_args);
+    if action != RevertAction::Start {
+        debug_log(&format!(
+            "Revert {:?}, will use the recorded RevertStart event in post-hook",
+            action
+        ));
+        return;
+    }
+
+    // git refuses to start a revert while another one is stopped on a conflict
+    if is_revert_in_progress(repository) {
+        debug_log("Revert already in progress, not recording a new start");
+        return;
+    }
+
+    let original_head = match repository.head().and_then(|head| head.target()) {
+        Ok(target) => target,
+        Err(e) => {
+            debug_log(&format!("Could not read HEAD for new revert: {}", e));
+            return;
+        }
+    };
+
+    // Resolve the commits being reverted now, since relative refs like HEAD~1 will
+    // point somewhere else once the revert commits exist
+    let reverted_commits = parse_revert_commits(repository, &parsed_args.command_args);
+    let mainline = parse_mainline(&parsed_args.command_args);
+    debug_log(&format!(
+        "Reverting {} commits from {} (mainline {:?}): {:?}",
+        reverted_commits.len(),
+        original_head,
+        mainline,
+        reverted_commits
+    ));
+
+    // A multi-commit revert can stop on a conflict and finish in a later `--continue`,
+    // so the run is recorded in the rewrite log rather than in this invocation's context
+    let start_event = RewriteLogEvent::revert_start(RevertStartEvent::new(
+        original_head,
+        reverted_commits,
+        mainline,
+        is_no_commit(&parsed_args.command_args),
+    ));
+    match repository.storage.append_rewrite_event(start_event) {
+        Ok(_) => debug_log("✓ Logged RevertStart event"),
+        Err(e) => debug_log(&format!("✗ Failed to log RevertStart event: {}", e)),
+    }
+}
+
+pub fn post_revert_hook(
+    _context: &CommandHooksContext,
+    parsed_args: &ParsedGitInvocation,
+    exit_status: std::process::ExitStatus,
+    repository: &mut Repository,
+) {
+    debug_log("=== REVERT POST-COMMAND HOOK ===");
+    debug_log(&format!("Exit status: {}", exit_status));
+
+    let Some(start) = find_active_revert_start(repository) else {
+        debug_log("No active RevertStart event, skipping authorship handling");
+        return;
+    };
+    let action = revert_action(&parsed_args.command_args);
+
+    if action == RevertAction::Abort {
+        // HEAD is back where the revert started, so there is nothing to carry
+        log_revert_abort(repository, &start.original_head);
+        return;
+    }
+
+    // `--no-commit` leaves REVERT_HEAD behind even when it succeeds, and `--quit` ends the
+    // run while keeping the commits made so far
+    let no_commit = action == RevertAction::Start && is_no_commit(&parsed_args.command_args);
+    if !no_commit && action != RevertAction::Quit && is_revert_in_progress(repository) {
+        // Conflicts stop the revert; `--continue` or `--skip` picks the rest up
+        debug_log("⏸ Revert still in progress, waiting for completion");
+        return;
+    }
+
+    if no_commit && !exit_status.success() && is_revert_in_progress(repository) {
+        // The conflict is resolved before the next commit, which prepares the working log
+        debug_log("⏸ Uncommitted revert stopped on a conflict, waiting for it to be resolved");
+        return;
+    }
+
+    if !exit_status.success() {
+        debug_log("Revert failed, skipping authorship handling");
+        log_revert_abort(repository, &start.original_head);
+        return;
+    }
+
+    // `--quit` keeps a stopped `--no-commit` revert's changes without committing them
+    let uncommitted = no_commit
+        || (action == RevertAction::Quit
+            && start.no_commit
+            && repository.head().and_then(|head| head.target()).ok()
+                == Some(start.original_head.clone()));
+    if uncommitted {
+        process_uncommitted_revert(
+            repository,
+            &start.original_head,
+            &start.reverted_commits,
+            start.mainline,
+        );
+    } else {
+        process_completed_revert(
+            repository,
+            &start.original_head,
+            &start.reverted_commits,
+            start.mainline,
+        );
+    }
+}
+
+/// Prepare the working log for a `--no-commit` revert that stopped on a conflict
+///
+/// Runs before `git commit`, once the conflict is resolved and the reverted lines are
+/// staged. A `git revert --continue` instead makes the revert commit itself and is handled
+/// in the post-hook.
+pub fn prepare_resolved_revert(repository: &mut Repository) {
+    if !repository.path().join("REVERT_HEAD").exists() {
+        return;
+    }
+    let Some(start) = find_active_revert_start(repository) else {
+        return;
+    };
+    if !start.no_commit || has_unmerged_paths(repository) {
+        return;
+    }
+    if repository.head().and_then(|head| head.target()).ok() != Some(start.original_head.clone()) {
+        debug_log("HEAD moved since the revert started, not preparing its working log");
+        return;
+    }
+
+    debug_log("Conflicted revert resolved, preparing its working log");
+    process_uncommitted_revert(
+        repository,
+        &start.original_head,
+        &start.reverted_commits,
+        start.mainline,
+    );
+}
+
+/// Carry attributions for a revert that created commits
+///
+/// Each new commit is matched to the commit it undoes through the "This reverts commit"
+/// line git writes. When every named commit got a revert commit, the order they were named
+/// on the command line is the fallback; after a `--skip` or `--quit` it would misalign.
+fn process_completed_revert(
+    repository: &mut Repository,
+    original_head: &str,
+    reverted_commits: &[String],
+    mainline: Option<usize>,
+) {
+    let new_head = match repository.head().and_then(|head| head.target()) {
+        Ok(target) => target,
+        Err(e) => {
+            debug_log(&format!("✗ Failed to get HEAD: {}", e));
+            return;
+        }
+    };
+
+    if new_head == original_head {
+        debug_log("Revert resulted in no new commits");
+        return;
+    }
+
+    let mut new_commits = match walk_commits_to_base(repository, &new_head, original_head) {
+        Ok(commits) => commits,
+        Err(e) => {
+            debug_log(&format!("✗ Failed to find revert commits: {}", e));
+            return;
+        }
+    };
+    new_commits.reverse();
+    let ordered_fallback = new_commits.len() == reverted_commits.len();
+
+    for (idx, revert_commit) in new_commits.iter().enumerate() {
+        let Some(reverted_commit) = reverted_commit_from_message(repository, revert_commit)
+            .or_else(|| {
+                reverted_commits
+                    .get(idx)
+                    .filter(|_| ordered_fallback)
+                    .cloned()
+            })
+        else {
+            debug_log(&format!(
+                "Could not tell which commit {} reverts, skipping",
+                revert_commit
+            ));
+            continue;
+        };
+
+        let (success, affected_files) = match rewrite_authorship_after_revert(
+            repository,
+            &reverted_commit,
+            revert_commit,
+            mainline,
+        ) {
+            Ok(files) => {
+                debug_log(&format!(
+                    "✓ Rewrote authorship for revert of {} in {}",
+                    reverted_commit, revert_commit
+                ));
+                (true, files)
+            }
+            Err(e) => {
+                debug_log(&format!(
+                    "✗ Failed to rewrite authorship for revert of {}: {}",
+                    reverted_commit, e
+                ));
+                (false, Vec::new())
+            }
+        };
+
+        log_revert_event(
+            repository,
+            RevertMixedEvent::new(
+                reverted_commit,
+                Some(revert_commit.clone()),
+                success,
+                affected_files,
+            ),
+        );
+    }
+}
+
+/// Carry attributions for `git revert --no-commit` into the working log
+fn process_uncommitted_revert(
+    repository: &mut Repository,
+    head_sha: &str,
+    reverted_commits: &[String],
+    mainline: Option<usize>,
+) {
+    if reverted_commits.is_empty() {
+        debug_log("No reverted commits recorded, skipping authorship handling");
+        return;
+    }
+
+    let (success, affected_files) =
+        match prepare_working_log_after_revert(repository, head_sha, reverted_commits, mainline) {
+            Ok(files) => {
+                debug_log(&format!(
+                    "✓ Prepared working log for uncommitted revert of {} commits",
+                    reverted_commits.len()
+                ));
+                (true, files)
+            }
+            Err(e) => {
+                debug_log(&format!(
+                    "✗ Failed to prepare working log after revert: {}",
+                    e
+                ));
+                (false, vec![Vec::new(); reverted_commits.len()])
+            }
+        };
+
+    for (reverted_commit, files) in reverted_commits.iter().zip(affected_files) {
+        log_revert_event(
+            repository,
+            RevertMixedEvent::new(reverted_commit.clone(), None, success, files),
+        );
+    }
+}
+
+fn log_revert_event(repository: &Repository, event: RevertMixedEvent) {
+    match repository
+        .storage
+        .append_rewrite_event(RewriteLogEvent::revert_mixed(event))
+    {
+        Ok(_) => debug_log("✓ Logged RevertMixed event"),
+        Err(e) => debug_log(&format!("✗ Failed to log RevertMixed event: {}", e)),
+    }
+}
+
+fn log_revert_abort(repository: &Repository, original_head: &str) {
+    let event = RewriteLogEvent::revert_abort(RevertAbortEvent::new(original_head.to_string()));
+    match repository.storage.append_rewrite_event(event) {
+        Ok(_) => debug_log("✓ Logged RevertAbort event"),
+        Err(e) => debug_log(&format!("✗ Failed to log RevertAbort event: {}", e)),
+    }
+}
+
+/// The most recent RevertStart event, unless the run it started has already ended
+fn find_active_revert_start(repository: &Repository) -> Option<RevertStartEvent> {
+    let events = repository.storage.read_rewrite_events().ok()?;
+
+    // Events are newest-first
+    for event in events {
+        match event {
+            RewriteLogEvent::RevertMixed { .. } | RewriteLogEvent::RevertAbort { .. } => {
+                return None;
+            }
+            RewriteLogEvent::RevertStart { revert_start } => return Some(revert_start),
+            _ => continue,
+        }
+    }
+
+    None
+}
+
+fn is_revert_in_progress(repository: &Repository) -> bool {
+    repository.path().join("REVERT_HEAD").exists() || repository.path().join("sequencer").exists()
+}
+
+fn has_unmerged_paths(repository: &Repository) -> bool {
+    let mut args = repository.global_args_for_exec();
+    args.push("ls-files".to_string());
+    args.push("--unmerged".to_string());
+    exec_git(&args).map_or(true, |output| !output.stdout.is_empty())
+}
+
+fn revert_action(args: &[String]) -> RevertAction {
+    args.iter()
+        .find_map(|arg| match arg.as_str() {
+            "--continue" => Some(RevertAction::Continue),
+            "--skip" => Some(RevertAction::Skip),
+            "--abort" => Some(RevertAction::Abort),
+            "--quit" => Some(RevertAction::Quit),
+            _ => None,
+        })
+        .unwrap_or(RevertAction::Start)
+}
+
+fn is_no_commit(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == "-n" || arg == "--no-commit")
+}
+
+/// Parent number from `-m <n>`, `-m<n>`, `--mainline <n>` or `--mainline=<n>`
+fn parse_mainline(args: &[String]) -> Option<usize> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = match arg.as_str() {
+            "-m" | "--mainline" => iter.next().map(String::as_str),
+            _ => arg
+                .strip_prefix("--mainline=")
+                .or_else(|| arg.strip_prefix("-m")),
+        };
+        if let Some(parent_number) = value.and_then(|value| value.parse().ok()) {
+            return Some(parent_number);
+        }
+    }
+    None
+}
+
+/// Read the reverted commit from the "This reverts commit <sha>." line of a revert commit
+fn reverted_commit_from_message(repository: &Repository, revert_commit: &str) -> Option<String> {
+    let body = repository
+        .find_commit(revert_commit.to_string())
+        .ok()?
+        .body()
+        .ok()?;
+    body.lines().find_map(|line| {
+        let sha = line
+            .trim()
+            .strip_prefix("This reverts commit ")?
+            .trim_end_matches('.');
+        (sha.len() == 40 && sha.chars().all(|c| c.is_ascii_hexdigit())).then(|| sha.to_string())
+    })
+}
+
+/// Parse revert commit arguments
+/// Handles:
+/// - Single commit: `git revert A`
+/// - Multiple commits: `git revert A B C`
+/// - Ranges: `git revert A..C`, reverted newest first like git does
+fn parse_revert_commits(repository: &Repository, args: &[String]) -> Vec<String> {
+    let mut commits = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        let arg = &args[i];
+
+        if arg.starts_with('-') {
+            // Skip option values for flags that take arguments
+            if matches!(
+                arg.as_str(),
+                "-m" | "--mainline" | "-s" | "--strategy" | "-X" | "--strategy-option"
+            ) {
+                i += 2;
+                continue;
+            }
+            i += 1;
+            continue;
+        }
+
+        let mut rev_args = repository.global_args_for_exec();
+        rev_args.push("rev-list".to_string());
+        if !arg.contains("..") {
+            rev_args.push("--no-walk".to_string());
+        }
+        rev_args.push(arg.clone());
+
+        if let Ok(output) = exec_git(&rev_args)
+            && let Ok(stdout) = String::from_utf8(output.stdout)
+        {
+            commits.extend(
+                stdout
+                    .lines()
+                    .map(|line| line.trim().to_string())
+                    .filter(|line| !line.is_empty()),
+            );
+        }
+
+        i += 1;
+    }
+
+    commits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_revert_action() {
+        assert_eq!(revert_action(&args(&["HEAD"])), RevertAction::Start);
+        assert_eq!(
+            revert_action(&args(&["--continue"])),
+            RevertAction::Continue
+        );
+        assert_eq!(revert_action(&args(&["--skip"])), RevertAction::Skip);
+        assert_eq!(revert_action(&args(&["--abort"])), RevertAction::Abort);
+        assert_eq!(revert_action(&args(&["--quit"])), RevertAction::Quit);
+    }
+
+    #[test]
+    fn test_parse_mainline() {
+        assert_eq!(parse_mainline(&args(&["-m", "2", "abc"])), Some(2));
+        assert_eq!(parse_mainline(&args(&["-m1", "abc"])), Some(1));
+        assert_eq!(parse_mainline(&args(&["--mainline", "2", "abc"])), Some(2));
+        assert_eq!(parse_mainline(&args(&["--mainline=2", "abc"])), Some(2));
+        assert_eq!(parse_mainline(&args(&["--no-edit", "abc"])), None);
+    }
+}
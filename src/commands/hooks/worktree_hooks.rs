@@ -0,0 +1,61 @@
+use crate::git::cli_parser::ParsedGitInvocation;
+use crate::git::repository::{Repository, find_repository_in_path, invocation_dir};
+use crate::utils::debug_log;
+
+/// Set up git-ai state for worktrees created with `git worktree add`
+///
+/// Working logs, INITIAL attributions and the rewrite log live under each worktree's own
+/// git dir (`.git/worktrees/<name>/ai`), so two worktrees on the same base commit never
+/// share them. `git worktree move` keeps that directory and `git worktree remove`/`prune`
+/// delete it, so only `add` needs handling here.
+pub fn post_worktree_hook(
+    parsed_args: &ParsedGitInvocation,
+    repository: &Repository,
+    exit_status: std::process::ExitStatus,
+) {
+    if !exit_status.success() {
+        return;
+    }
+
+    if parsed_args.command_args.first().map(String::as_str) != Some("add") {
+        return;
+    }
+
+    let Some(worktree_path) = extract_worktree_add_path(&parsed_args.command_args[1..]) else {
+        debug_log("failed to extract path from worktree add; skipping worktree setup");
+        return;
+    };
+    let worktree_path = invocation_dir(&parsed_args.global_args).join(worktree_path);
+
+    // Opening the worktree creates its storage under its own git dir
+    match find_repository_in_path(&worktree_path.to_string_lossy()) {
+        Ok(worktree) => debug_log(&format!(
+            "Initialized git-ai state for worktree {} in {} (shared git dir {})",
+            worktree_path.display(),
+            worktree.path().display(),
+            repository.common_dir().display()
+        )),
+        Err(e) => debug_log(&format!(
+            "failed to open new worktree at {}: {}",
+            worktree_path.display(),
+            e
+        )),
+    }
+}
+
+/// The `<path>` argument of `git worktree add [options] <path> [<commit-ish>]`
+fn extract_worktree_add_path(args: &[String]) -> Option<&String> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            // Options that take a separate value
+            "-b" | "-B" | "--reason" => {
+                args.next();
+            }
+            "--" => return args.next(),
+            _ if arg.starts_with('-') => {}
+            _ => return Some(arg),
+        }
+    }
+    None
+}
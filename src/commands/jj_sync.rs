@@ -0,0 +1,373 @@
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::rebase_authorship::remap_authorship_log;
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::refs::{list_commits_with_notes, notes_add_batch, show_authorship_note};
+use crate::git::repository::{Repository, exec_git_stdin};
+use crate::utils::debug_log;
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Change id -> the commits it pointed at, more than one when the change is divergent
+pub type ChangeMap = BTreeMap<String, Vec<String>>;
+
+/// Handle the `jj-sync` command
+///
+/// Usage: `git-ai jj-sync [--dry-run]`
+///
+/// jj rewrites commits in colocated repositories without going through the git CLI, so none
+/// of the git hooks see it. This compares jj's current change id -> commit mapping with the
+/// one recorded by the previous sync and moves the notes of every rewritten commit onto its
+/// replacement, along with any working log based on it. Commits jj rewrote before the first
+/// sync are found through the `change-id` header jj writes into git commits. Run it after jj
+/// operations, e.g. from a shell alias or periodically.
+pub fn handle_jj_sync(args: &[String]) {
+    let parsed = match parse_args(args) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let current = match read_jj_changes(&repo) {
+        Ok(current) => current,
+        Err(e) => {
+            eprintln!("Failed to read jj changes: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let previous = read_sync_state(&repo);
+
+    let plan = match plan_jj_sync(&repo, &previous, &current) {
+        Ok(plan) => plan,
+        Err(e) => {
+            eprintln!("Failed to match rewritten commits: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    for (commit_sha, error) in &plan.failed {
+        eprintln!("Skipping {}: {}", short_sha(commit_sha), error);
+    }
+
+    if parsed.dry_run {
+        for (old_sha, new_sha, _) in &plan.remaps {
+            println!("{} -> {}", short_sha(old_sha), short_sha(new_sha));
+        }
+        println!("Would sync {} note(s)", plan.remaps.len());
+        return;
+    }
+
+    if !plan.remaps.is_empty() {
+        let updates: Vec<(String, String)> = plan
+            .remaps
+            .iter()
+            .map(|(_, new_sha, note)| (new_sha.clone(), note.clone()))
+            .collect();
+        if let Err(e) = notes_add_batch(&repo, &updates, "Synced by 'git-ai jj-sync'") {
+            eprintln!("Failed to update refs/notes/ai: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    for (old_sha, new_sha) in &plan.rewritten {
+        if let Err(e) = repo.storage.rename_working_log(old_sha, new_sha) {
+            debug_log(&format!(
+                "Failed to move working log {} to {}: {}",
+                old_sha, new_sha, e
+            ));
+        }
+    }
+
+    if let Err(e) = write_sync_state(&repo, &current) {
+        eprintln!("Failed to save jj sync state: {}", e);
+        std::process::exit(1);
+    }
+
+    println!(
+        "Synced {} note(s) across {} rewritten commit(s)",
+        plan.remaps.len(),
+        plan.rewritten.len()
+    );
+}
+
+#[derive(Debug)]
+pub struct ParsedArgs {
+    pub dry_run: bool,
+}
+
+pub fn parse_args(args: &[String]) -> Result<ParsedArgs, String> {
+    let mut dry_run = false;
+
+    for arg in args {
+        if arg == "--dry-run" {
+            dry_run = true;
+        } else {
+            return Err(format!("Unknown option: {}", arg));
+        }
+    }
+
+    Ok(ParsedArgs { dry_run })
+}
+
+fn short_sha(sha: &str) -> &str {
+    &sha[..sha.len().min(7)]
+}
+
+/// Ask jj for the commit behind every visible change
+fn read_jj_changes(repo: &Repository) -> Result<ChangeMap, GitAiError> {
+    let workdir = repo.workdir()?;
+    if !workdir.join(".jj").is_dir() {
+        return Err(GitAiError::Generic(format!(
+            "{} is not a colocated jj repository",
+            workdir.display()
+        )));
+    }
+
+    // --ignore-working-copy keeps jj from snapshotting, so syncing never creates operations
+    let output = Command::new("jj")
+        .arg("-R")
+        .arg(&workdir)
+        .args([
+            "log",
+            "--no-graph",
+            "--ignore-working-copy",
+            "--color=never",
+            "-r",
+            "all()",
+            "-T",
+            r#"change_id ++ " " ++ commit_id ++ "\n""#,
+        ])
+        .output()
+        .map_err(|e| GitAiError::Generic(format!("Failed to run jj: {}", e)))?;
+    if !output.status.success() {
+        return Err(GitAiError::Generic(format!(
+            "jj log failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(parse_jj_log(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse "<change id> <commit id>" lines, skipping jj's all-zero root commit
+pub fn parse_jj_log(output: &str) -> ChangeMap {
+    let mut changes = ChangeMap::new();
+    for line in output.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(change_id), Some(commit)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        if commit.chars().all(|c| c == '0') {
+            continue;
+        }
+        let commits = changes.entry(change_id.to_string()).or_default();
+        if !commits.iter().any(|c| c == commit) {
+            commits.push(commit.to_string());
+        }
+    }
+    changes
+}
+
+fn sync_state_path(repo: &Repository) -> PathBuf {
+    repo.path().join("ai").join("jj_changes")
+}
+
+/// The mapping saved by the previous sync, empty before the first one
+fn read_sync_state(repo: &Repository) -> ChangeMap {
+    std::fs::read_to_string(sync_state_path(repo))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_sync_state(repo: &Repository, changes: &ChangeMap) -> Result<(), GitAiError> {
+    let path = sync_state_path(repo);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string(changes)?)?;
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+pub struct JjSyncPlan {
+    /// (old commit, new commit, rewritten note)
+    pub remaps: Vec<(String, String, String)>,
+    /// Every (old commit, new commit) pair jj rewrote since the previous sync
+    pub rewritten: Vec<(String, String)>,
+    /// Commits whose note couldn't be carried over, with the reason
+    pub failed: Vec<(String, String)>,
+}
+
+/// Work out which commits jj rewrote and their notes, without writing anything
+///
+/// A change whose commit differs from the previous sync was rewritten. For notes on commits
+/// jj rewrote before any sync, the noted commits' `change-id` headers stand in for the
+/// previous mapping.
+pub fn plan_jj_sync(
+    repo: &Repository,
+    previous: &ChangeMap,
+    current: &ChangeMap,
+) -> Result<JjSyncPlan, GitAiError> {
+    let noted: HashSet<String> = list_commits_with_notes(repo)?.into_iter().collect();
+    let mut predecessors = previous.clone();
+    for (change_id, commit) in change_id_headers(repo, &noted)? {
+        let commits = predecessors.entry(change_id).or_default();
+        if !commits.contains(&commit) {
+            commits.push(commit);
+        }
+    }
+
+    let mut plan = JjSyncPlan::default();
+    for (change_id, commits) in current {
+        let Some(old_commits) = predecessors.get(change_id) else {
+            continue;
+        };
+        // The previous sync's commits come first, then any noted commits found by header
+        let old_commits: Vec<&String> = old_commits
+            .iter()
+            .filter(|c| !commits.contains(c))
+            .collect();
+        let Some(old_commit) = old_commits.first() else {
+            continue;
+        };
+
+        for new_commit in commits {
+            if previous.contains_key(change_id) {
+                plan.rewritten
+                    .push(((*old_commit).clone(), new_commit.clone()));
+            }
+            if noted.contains(new_commit) {
+                continue;
+            }
+            let Some(noted_commit) = old_commits.iter().find(|c| noted.contains(**c)) else {
+                continue;
+            };
+            match remap_note(repo, noted_commit, new_commit) {
+                Ok(note) => plan
+                    .remaps
+                    .push(((*noted_commit).clone(), new_commit.clone(), note)),
+                Err(e) => plan.failed.push(((*noted_commit).clone(), e.to_string())),
+            }
+        }
+    }
+
+    Ok(plan)
+}
+
+fn remap_note(repo: &Repository, old_commit: &str, new_commit: &str) -> Result<String, GitAiError> {
+    let note = show_authorship_note(repo, old_commit)
+        .ok_or_else(|| GitAiError::Generic("note could not be read".to_string()))?;
+    let log = AuthorshipLog::deserialize_from_string(&note)
+        .map_err(|e| GitAiError::Generic(format!("note could not be parsed: {}", e)))?;
+    remap_authorship_log(repo, &log, old_commit, new_commit, &[])?
+        .serialize_to_string()
+        .map_err(|_| GitAiError::Generic("Failed to serialize authorship log".to_string()))
+}
+
+/// (change id, commit) for every given commit that still exists and carries jj's
+/// `change-id` header
+fn change_id_headers(
+    repo: &Repository,
+    commits: &HashSet<String>,
+) -> Result<Vec<(String, String)>, GitAiError> {
+    if commits.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut args = repo.global_args_for_exec();
+    args.push("cat-file".to_string());
+    args.push("--batch".to_string());
+    let input: String = commits.iter().map(|c| format!("{}\n", c)).collect();
+    let output = exec_git_stdin(&args, input.as_bytes())?;
+
+    Ok(parse_change_id_headers(&output.stdout))
+}
+
+/// Parse `git cat-file --batch` output into (change id, commit) pairs
+pub fn parse_change_id_headers(data: &[u8]) -> Vec<(String, String)> {
+    let mut headers = Vec::new();
+    let mut rest = data;
+    while let Some(newline) = rest.iter().position(|b| *b == b'\n') {
+        let info = String::from_utf8_lossy(&rest[..newline]).to_string();
+        rest = &rest[newline + 1..];
+
+        // "<sha> <type> <size>", or "<sha> missing"
+        let fields: Vec<&str> = info.split_whitespace().collect();
+        let [sha, object_type, size] = fields[..] else {
+            continue;
+        };
+        let Ok(size) = size.parse::<usize>() else {
+            continue;
+        };
+        let body = &rest[..size.min(rest.len())];
+        rest = &rest[(size + 1).min(rest.len())..];
+        if object_type != "commit" {
+            continue;
+        }
+
+        // Headers end at the first blank line
+        let body = String::from_utf8_lossy(body);
+        let change_id = body
+            .lines()
+            .take_while(|line| !line.is_empty())
+            .find_map(|line| line.strip_prefix("change-id "));
+        if let Some(change_id) = change_id {
+            headers.push((change_id.trim().to_string(), sha.to_string()));
+        }
+    }
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_jj_log() {
+        let sha_a = "a".repeat(40);
+        let sha_b = "b".repeat(40);
+        let output = format!(
+            "kkkk {}\nzzzz {}\nkkkk {}\nzzzz {}\n\n",
+            sha_a,
+            sha_b,
+            sha_b,
+            "0".repeat(40)
+        );
+        let changes = parse_jj_log(&output);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes["kkkk"], vec![sha_a, sha_b.clone()]);
+        assert_eq!(changes["zzzz"], vec![sha_b]);
+    }
+
+    #[test]
+    fn test_parse_change_id_headers() {
+        let with_header = "tree 1234\nauthor A <a@a> 0 +0000\nchange-id wvutsrq\n\nMessage\nchange-id not a header\n";
+        let without_header = "tree 1234\n\nchange-id in message\n";
+        let data = format!(
+            "{} commit {}\n{}\n{} commit {}\n{}\n{} missing\n",
+            "a".repeat(40),
+            with_header.len(),
+            with_header,
+            "b".repeat(40),
+            without_header.len(),
+            without_header,
+            "c".repeat(40)
+        );
+        assert_eq!(
+            parse_change_id_headers(data.as_bytes()),
+            vec![("wvutsrq".to_string(), "a".repeat(40))]
+        );
+    }
+}
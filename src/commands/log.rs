@@ -0,0 +1,331 @@
+use crate::authorship::stats::{CommitStats, stats_for_commit_files, stats_for_commit_stats};
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::repository::{Repository, exec_git};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Commits shown when no range is given
+const DEFAULT_COMMIT_LIMIT: usize = 100;
+
+/// Handle the `log` command
+///
+/// Usage: `git-ai log [<rev|range>] [--tool <tool>] [--model <model>] [--min-ai-percent <n>]
+/// [--author <pattern>] [-n <count>] [--json] [-- <paths>...]`
+///
+/// Lists commits newest first with their AI, mixed and human line counts and the tool/model
+/// pairs that worked on them. Without a range, the last 100 commits on HEAD are listed. With
+/// paths, the counts only cover the files matching them.
+pub fn handle_log(args: &[String]) {
+    let parsed = match parse_args(args) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let entries = match log_entries(&repo, &parsed) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Failed to compute log: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if parsed.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&entries).unwrap_or_else(|_| "[]".to_string())
+        );
+    } else {
+        print_log(&entries);
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ParsedArgs {
+    pub spec: Option<String>,
+    pub paths: Vec<String>,
+    pub tool: Option<String>,
+    pub model: Option<String>,
+    pub min_ai_percent: Option<f64>,
+    pub author: Option<String>,
+    pub max_count: Option<usize>,
+    pub json: bool,
+}
+
+pub fn parse_args(args: &[String]) -> Result<ParsedArgs, String> {
+    let mut parsed = ParsedArgs::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = |name: &str| {
+            inline_value
+                .clone()
+                .or_else(|| args.next().cloned())
+                .ok_or_else(|| format!("{} requires a value", name))
+        };
+
+        match flag {
+            "--json" => parsed.json = true,
+            "--tool" => parsed.tool = Some(value("--tool")?),
+            "--model" => parsed.model = Some(value("--model")?),
+            "--author" => parsed.author = Some(value("--author")?),
+            "--min-ai-percent" => {
+                let percent = value("--min-ai-percent")?;
+                parsed.min_ai_percent = Some(
+                    percent
+                        .trim_end_matches('%')
+                        .parse::<f64>()
+                        .map_err(|_| format!("Invalid --min-ai-percent '{}'", percent))?,
+                );
+            }
+            "-n" | "--max-count" => {
+                let count = value(flag)?;
+                parsed.max_count = Some(
+                    count
+                        .parse::<usize>()
+                        .map_err(|_| format!("Invalid {} '{}'", flag, count))?,
+                );
+            }
+            "--" => {
+                parsed.paths.extend(args.by_ref().cloned());
+            }
+            _ if arg.starts_with('-') => return Err(format!("Unknown option: {}", arg)),
+            _ => {
+                if parsed.spec.is_some() {
+                    return Err("log accepts at most one revision or range".to_string());
+                }
+                parsed.spec = Some(arg.clone());
+            }
+        }
+    }
+
+    Ok(parsed)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogEntry {
+    pub commit: String,
+    pub author: String,
+    /// Author date, ISO 8601
+    pub date: String,
+    pub subject: String,
+    /// AI lines (accepted and mixed) as a share of the lines the commit added in the
+    /// requested paths
+    pub ai_percent: f64,
+    pub stats: CommitStats,
+}
+
+/// AI lines as a percentage of the commit's added lines
+pub fn ai_percent(stats: &CommitStats) -> f64 {
+    if stats.git_diff_added_lines == 0 {
+        return 0.0;
+    }
+    (stats.ai_additions as f64 / stats.git_diff_added_lines as f64 * 100.0).min(100.0)
+}
+
+/// Whether any tool/model pair in the commit matches the filters
+///
+/// Tools must match exactly, models by substring, both ignoring case.
+pub fn matches_tool_model(stats: &CommitStats, tool: Option<&str>, model: Option<&str>) -> bool {
+    if tool.is_none() && model.is_none() {
+        return true;
+    }
+    stats.tool_model_breakdown.keys().any(|key| {
+        let (key_tool, key_model) = key.split_once("::").unwrap_or((key, ""));
+        tool.is_none_or(|tool| key_tool.eq_ignore_ascii_case(tool))
+            && model.is_none_or(|model| key_model.to_lowercase().contains(&model.to_lowercase()))
+    })
+}
+
+/// Compute the stats of every commit in range and keep the ones matching the filters
+pub fn log_entries(repo: &Repository, parsed: &ParsedArgs) -> Result<Vec<LogEntry>, GitAiError> {
+    let mut entries = Vec::new();
+    for (commit, author, date, subject) in list_commits(repo, parsed)? {
+        let stats = if parsed.paths.is_empty() {
+            stats_for_commit_stats(repo, &commit, &[])?
+        } else {
+            let patterns = outside_paths_patterns(repo, &commit, &parsed.paths)?;
+            stats_for_commit_files(repo, &commit, &patterns)?
+        };
+        if !matches_tool_model(&stats, parsed.tool.as_deref(), parsed.model.as_deref()) {
+            continue;
+        }
+        let ai_percent = ai_percent(&stats);
+        if parsed
+            .min_ai_percent
+            .is_some_and(|min_percent| ai_percent < min_percent)
+        {
+            continue;
+        }
+        entries.push(LogEntry {
+            commit,
+            author,
+            date,
+            subject,
+            ai_percent,
+            stats,
+        });
+    }
+    Ok(entries)
+}
+
+/// Ignore patterns for the files a commit changed outside the pathspecs `paths`
+///
+/// Anchored exact paths, so a file elsewhere with the same name isn't ignored too.
+fn outside_paths_patterns(
+    repo: &Repository,
+    commit: &str,
+    paths: &[String],
+) -> Result<Vec<String>, GitAiError> {
+    let pathspecs: HashSet<String> = paths.iter().cloned().collect();
+    let inside = repo.list_commit_files(commit, Some(&pathspecs))?;
+    Ok(repo
+        .list_commit_files(commit, None)?
+        .into_iter()
+        .filter(|file| !inside.contains(file))
+        .map(|file| format!("/{}", glob::Pattern::escape(&file)))
+        .collect())
+}
+
+/// (sha, author, date, subject) of each commit, newest first
+fn list_commits(
+    repo: &Repository,
+    parsed: &ParsedArgs,
+) -> Result<Vec<(String, String, String, String)>, GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.push("log".to_string());
+    args.push("-z".to_string());
+    args.push("--format=%H%x1f%an <%ae>%x1f%aI%x1f%s".to_string());
+    if let Some(author) = &parsed.author {
+        args.push(format!("--author={}", author));
+    }
+    match (&parsed.spec, parsed.max_count) {
+        (_, Some(count)) => args.push(format!("--max-count={}", count)),
+        (None, None) => args.push(format!("--max-count={}", DEFAULT_COMMIT_LIMIT)),
+        (Some(_), None) => {}
+    }
+    args.push(parsed.spec.clone().unwrap_or_else(|| "HEAD".to_string()));
+    if !parsed.paths.is_empty() {
+        args.push("--".to_string());
+        args.extend(parsed.paths.iter().cloned());
+    }
+
+    let output = exec_git(&args)?;
+    Ok(String::from_utf8(output.stdout)?
+        .split('\0')
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').splitn(4, '\x1f');
+            let sha = fields.next().filter(|sha| !sha.is_empty())?;
+            Some((
+                sha.to_string(),
+                fields.next()?.to_string(),
+                fields.next()?.to_string(),
+                fields.next().unwrap_or_default().to_string(),
+            ))
+        })
+        .collect())
+}
+
+fn print_log(entries: &[LogEntry]) {
+    if entries.is_empty() {
+        println!("No matching commits");
+        return;
+    }
+
+    for entry in entries {
+        let stats = &entry.stats;
+        println!(
+            "\x1b[33m{}\x1b[0m {} {}",
+            &entry.commit[..entry.commit.len().min(7)],
+            entry.date.get(..10).unwrap_or(&entry.date),
+            entry.subject
+        );
+        println!("        \x1b[90m{}\x1b[0m", entry.author);
+
+        let pure_human = stats.human_additions.saturating_sub(stats.mixed_additions);
+        let mut summary = format!(
+            "        {} ai, {} mixed, {} human ({:.0}% AI)",
+            stats.ai_accepted, stats.mixed_additions, pure_human, entry.ai_percent
+        );
+        if stats.merge_resolution {
+            summary.push_str(", merge resolution");
+        }
+        println!("{}", summary);
+
+        if !stats.tool_model_breakdown.is_empty() {
+            let tools: Vec<String> = stats
+                .tool_model_breakdown
+                .keys()
+                .map(|key| key.replace("::", "/"))
+                .collect();
+            println!("        {}", tools.join(", "));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::stats::ToolModelHeadlineStats;
+
+    #[test]
+    fn test_parse_args() {
+        let args: Vec<String> = [
+            "main..HEAD",
+            "--tool=cursor",
+            "--min-ai-percent",
+            "50%",
+            "--author",
+            "alice",
+            "--json",
+            "--",
+            "src",
+            "docs",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let parsed = parse_args(&args).unwrap();
+        assert_eq!(parsed.spec.as_deref(), Some("main..HEAD"));
+        assert_eq!(parsed.tool.as_deref(), Some("cursor"));
+        assert_eq!(parsed.min_ai_percent, Some(50.0));
+        assert_eq!(parsed.author.as_deref(), Some("alice"));
+        assert!(parsed.json);
+        assert_eq!(parsed.paths, vec!["src".to_string(), "docs".to_string()]);
+        assert!(parse_args(&["--min-ai-percent".to_string(), "lots".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_matches_tool_model() {
+        let mut stats = CommitStats::default();
+        stats.tool_model_breakdown.insert(
+            "cursor::claude-4-sonnet".to_string(),
+            ToolModelHeadlineStats::default(),
+        );
+        assert!(matches_tool_model(&stats, None, None));
+        assert!(matches_tool_model(&stats, Some("Cursor"), None));
+        assert!(matches_tool_model(&stats, Some("cursor"), Some("sonnet")));
+        assert!(!matches_tool_model(&stats, Some("claude"), None));
+        assert!(!matches_tool_model(&stats, Some("cursor"), Some("gpt")));
+        assert!(!matches_tool_model(
+            &CommitStats::default(),
+            Some("cursor"),
+            None
+        ));
+    }
+}
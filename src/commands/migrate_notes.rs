@@ -0,0 +1,366 @@
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log_serialization::{
+    AUTHORSHIP_LOG_VERSION, AttestationEntry, AuthorshipLog, FileAttestation, GIT_AI_VERSION,
+    decode_note, generate_short_hash,
+};
+use crate::authorship::imara_diff_utils::{LineChangeTag, compute_line_changes};
+use crate::commands::show::resolve_commits;
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::refs::{list_commits_with_notes, notes_add_batch, show_authorship_note};
+use crate::git::repository::Repository;
+use std::collections::{BTreeMap, HashMap};
+
+/// Length of the session hashes written by versions before `generate_short_hash` went to 16 chars
+const LEGACY_HASH_LEN: usize = 7;
+
+/// Handle the `migrate-notes` command
+///
+/// Usage: `git-ai migrate-notes [<rev|range>] [--dry-run]`
+///
+/// Rewrites authorship notes into canonical `authorship/3.0.0` form: 16-char prompt
+/// hashes, normalized line ranges, and a current `git_ai_version`. All rewritten notes
+/// land in a single commit on refs/notes/ai. Without a revision, every note is migrated.
+pub fn handle_migrate_notes(args: &[String]) {
+    let parsed = match parse_args(args) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let commits = match &parsed.spec {
+        Some(spec) => resolve_commits(&repo, spec),
+        None => list_commits_with_notes(&repo),
+    };
+    let commits = match commits {
+        Ok(commits) => commits,
+        Err(e) => {
+            eprintln!("Failed to resolve commits: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let plan = match plan_migration(&repo, &commits) {
+        Ok(plan) => plan,
+        Err(e) => {
+            eprintln!("Failed to migrate notes: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    for (commit_sha, error) in &plan.unparseable {
+        eprintln!(
+            "Skipping {}: note could not be parsed: {}",
+            &commit_sha[..commit_sha.len().min(7)],
+            error
+        );
+    }
+
+    if parsed.dry_run {
+        for migration in &plan.migrations {
+            print_note_diff(migration);
+        }
+        println!(
+            "Would migrate {} of {} note(s)",
+            plan.migrations.len(),
+            plan.notes_checked
+        );
+        return;
+    }
+
+    if plan.migrations.is_empty() {
+        println!("All {} note(s) are already up to date", plan.notes_checked);
+        return;
+    }
+
+    let updates: Vec<(String, String)> = plan
+        .migrations
+        .iter()
+        .map(|m| (m.commit_sha.clone(), m.new_content.clone()))
+        .collect();
+    if let Err(e) = notes_add_batch(&repo, &updates, "Migrated by 'git-ai migrate-notes'") {
+        eprintln!("Failed to update refs/notes/ai: {}", e);
+        std::process::exit(1);
+    }
+
+    println!(
+        "Migrated {} of {} note(s)",
+        plan.migrations.len(),
+        plan.notes_checked
+    );
+}
+
+#[derive(Debug)]
+pub struct ParsedArgs {
+    pub spec: Option<String>,
+    pub dry_run: bool,
+}
+
+pub fn parse_args(args: &[String]) -> Result<ParsedArgs, String> {
+    let mut spec: Option<String> = None;
+    let mut dry_run = false;
+
+    for arg in args {
+        if arg == "--dry-run" {
+            dry_run = true;
+        } else if arg.starts_with('-') {
+            return Err(format!("Unknown option: {}", arg));
+        } else {
+            if spec.is_some() {
+                return Err("migrate-notes accepts at most one revision or range".to_string());
+            }
+            spec = Some(arg.clone());
+        }
+    }
+
+    Ok(ParsedArgs { spec, dry_run })
+}
+
+/// A note whose canonical form differs from what is stored
+#[derive(Debug, Clone)]
+pub struct NoteMigration {
+    pub commit_sha: String,
+    pub old_content: String,
+    pub new_content: String,
+}
+
+#[derive(Debug, Default)]
+pub struct MigrationPlan {
+    pub notes_checked: usize,
+    pub migrations: Vec<NoteMigration>,
+    /// Notes that couldn't be parsed, with the parse error; these are left untouched
+    pub unparseable: Vec<(String, String)>,
+}
+
+/// Work out the canonical form of every note in `commits` without writing anything
+pub fn plan_migration(repo: &Repository, commits: &[String]) -> Result<MigrationPlan, GitAiError> {
+    let mut plan = MigrationPlan::default();
+    let mut parsed: Vec<(String, String, AuthorshipLog)> = Vec::new();
+
+    for commit_sha in commits {
+        let Some(content) = show_authorship_note(repo, commit_sha) else {
+            continue;
+        };
+        plan.notes_checked += 1;
+        // Compare and diff against the plain-text form so compressed notes that are
+        // otherwise canonical aren't rewritten
+        let content = match decode_note(&content) {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                plan.unparseable.push((commit_sha.clone(), e.to_string()));
+                continue;
+            }
+        };
+        match AuthorshipLog::deserialize_from_string(&content) {
+            Ok(log) => parsed.push((commit_sha.clone(), content, log)),
+            Err(e) => plan.unparseable.push((commit_sha.clone(), e.to_string())),
+        }
+    }
+
+    // Attestations can reference prompts stored in another commit's note (after a rebase
+    // or squash), so resolve legacy hashes against every prompt in the range
+    let mut legacy_hashes: HashMap<String, String> = HashMap::new();
+    for (_, _, log) in &parsed {
+        for record in log.metadata.prompts.values() {
+            let canonical = generate_short_hash(&record.agent_id.id, &record.agent_id.tool);
+            legacy_hashes.insert(canonical[..LEGACY_HASH_LEN].to_string(), canonical);
+        }
+    }
+
+    for (commit_sha, old_content, log) in parsed {
+        let migrated = migrate_authorship_log(&log, &legacy_hashes);
+        let new_content = migrated
+            .serialize_to_string()
+            .map_err(|_| GitAiError::Generic("Failed to serialize authorship log".to_string()))?;
+        if new_content != old_content {
+            plan.migrations.push(NoteMigration {
+                commit_sha,
+                old_content,
+                new_content,
+            });
+        }
+    }
+
+    Ok(plan)
+}
+
+/// Rewrite a log into canonical form
+///
+/// Prompts are re-keyed by `generate_short_hash` of their agent id, attestation hashes
+/// follow (using `legacy_hashes` for prompts that live in other notes), entries that
+/// end up sharing a hash are merged, and every entry's ranges (deletions included) are normalized.
+/// Any signature is dropped since it no longer matches; the writer re-signs if configured.
+pub fn migrate_authorship_log(
+    log: &AuthorshipLog,
+    legacy_hashes: &HashMap<String, String>,
+) -> AuthorshipLog {
+    let mut renamed: HashMap<&str, String> = HashMap::new();
+    let mut prompts = BTreeMap::new();
+    for (hash, record) in &log.metadata.prompts {
+        let canonical = generate_short_hash(&record.agent_id.id, &record.agent_id.tool);
+        renamed.insert(hash.as_str(), canonical.clone());
+        prompts.entry(canonical).or_insert_with(|| record.clone());
+    }
+
+    let canonical_hash = |hash: &str| -> String {
+        if let Some(canonical) = renamed.get(hash) {
+            return canonical.clone();
+        }
+        if hash.len() == LEGACY_HASH_LEN
+            && let Some(canonical) = legacy_hashes.get(hash)
+        {
+            return canonical.clone();
+        }
+        hash.to_string()
+    };
+
+    let mut migrated = log.clone();
+    migrated.metadata.schema_version = AUTHORSHIP_LOG_VERSION.to_string();
+    migrated.metadata.git_ai_version = Some(GIT_AI_VERSION.to_string());
+    migrated.metadata.prompts = prompts;
+    migrated.metadata.signature = None;
+    migrated.attestations = log
+        .attestations
+        .iter()
+        .map(|file| {
+            let mut entries: Vec<AttestationEntry> = Vec::new();
+            for entry in &file.entries {
+                let hash = canonical_hash(&entry.hash);
+                match entries.iter_mut().find(|e| e.hash == hash) {
+                    Some(existing) => existing.line_ranges.extend(entry.line_ranges.clone()),
+                    None => entries.push(AttestationEntry::new(hash, entry.line_ranges.clone())),
+                }
+            }
+            for entry in &mut entries {
+                entry.line_ranges = LineRange::normalize(&entry.line_ranges);
+            }
+            FileAttestation {
+                file_path: file.file_path.clone(),
+                entries,
+            }
+        })
+        .collect();
+    migrated.metadata.deletions.clear();
+    for file_path in log.metadata.deletions.keys() {
+        for (hash, ranges) in log.deletions_for_file(file_path) {
+            migrated.record_deletions(file_path, &canonical_hash(&hash), &ranges);
+        }
+    }
+
+    migrated
+}
+
+fn print_note_diff(migration: &NoteMigration) {
+    println!("--- a/{}", migration.commit_sha);
+    println!("+++ b/{}", migration.commit_sha);
+    for change in compute_line_changes(&migration.old_content, &migration.new_content) {
+        let prefix = match change.tag() {
+            LineChangeTag::Delete => '-',
+            LineChangeTag::Insert => '+',
+            LineChangeTag::Equal => continue,
+        };
+        println!("{}{}", prefix, change.value().trim_end_matches('\n'));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::authorship_log::PromptRecord;
+    use crate::authorship::working_log::AgentId;
+
+    fn prompt(tool: &str, id: &str) -> PromptRecord {
+        PromptRecord {
+            agent_id: AgentId {
+                tool: tool.to_string(),
+                id: id.to_string(),
+                model: "test-model".to_string(),
+            },
+            human_author: None,
+            messages: vec![],
+            total_additions: 0,
+            total_deletions: 0,
+            accepted_lines: 0,
+            overriden_lines: 0,
+            messages_url: None,
+        }
+    }
+
+    #[test]
+    fn test_migrates_legacy_hashes_and_ranges() {
+        let canonical = generate_short_hash("session-1", "cursor");
+        let legacy = canonical[..LEGACY_HASH_LEN].to_string();
+        let foreign = generate_short_hash("session-2", "claude");
+        let foreign_legacy = foreign[..LEGACY_HASH_LEN].to_string();
+
+        let mut log = AuthorshipLog::new();
+        log.metadata.schema_version = "authorship/2.0.0".to_string();
+        log.metadata.git_ai_version = None;
+        log.metadata
+            .prompts
+            .insert(legacy.clone(), prompt("cursor", "session-1"));
+        let file = log.get_or_create_file("src/main.rs");
+        file.add_entry(AttestationEntry::new(
+            legacy.clone(),
+            vec![LineRange::Range(5, 7), LineRange::Single(1)],
+        ));
+        file.add_entry(AttestationEntry::new(
+            legacy.clone(),
+            vec![LineRange::Single(8)],
+        ));
+        file.add_entry(AttestationEntry::new(
+            foreign_legacy.clone(),
+            vec![LineRange::Single(20)],
+        ));
+
+        let legacy_hashes = HashMap::from([(foreign_legacy, foreign.clone())]);
+        let migrated = migrate_authorship_log(&log, &legacy_hashes);
+
+        assert_eq!(migrated.metadata.schema_version, AUTHORSHIP_LOG_VERSION);
+        assert_eq!(
+            migrated.metadata.git_ai_version.as_deref(),
+            Some(GIT_AI_VERSION)
+        );
+        assert_eq!(
+            migrated.metadata.prompts.keys().collect::<Vec<_>>(),
+            vec![&canonical]
+        );
+        assert_eq!(
+            migrated.attestations[0].entries,
+            vec![
+                AttestationEntry::new(
+                    canonical,
+                    vec![LineRange::Single(1), LineRange::Range(5, 8)]
+                ),
+                AttestationEntry::new(foreign, vec![LineRange::Single(20)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_canonical_log_is_unchanged() {
+        let canonical = generate_short_hash("session-1", "cursor");
+        let mut log = AuthorshipLog::new();
+        log.metadata
+            .prompts
+            .insert(canonical.clone(), prompt("cursor", "session-1"));
+        log.get_or_create_file("src/main.rs")
+            .add_entry(AttestationEntry::new(
+                canonical,
+                vec![LineRange::Single(1), LineRange::Range(3, 4)],
+            ));
+
+        let migrated = migrate_authorship_log(&log, &HashMap::new());
+        assert_eq!(migrated, log);
+    }
+}
@@ -8,15 +8,24 @@ pub mod exchange_nonce;
 pub mod flush_cas;
 pub mod flush_logs;
 pub mod flush_metrics_db;
+pub mod fsck;
 pub mod git_ai_handlers;
 pub mod git_handlers;
 pub mod hooks;
 pub mod install_hooks;
+pub mod jj_sync;
+pub mod log;
 pub mod login;
 pub mod logout;
+pub mod migrate_notes;
+pub mod ownership;
 pub mod personal_dashboard;
 pub mod prompt_picker;
 pub mod prompts_db;
+pub mod recover;
+pub mod remap;
+pub mod report;
+pub mod schema;
 pub mod share;
 pub mod share_tui;
 pub mod show;
@@ -25,3 +34,4 @@ pub mod squash_authorship;
 pub mod status;
 pub mod sync_prompts;
 pub mod upgrade;
+pub mod verify;
@@ -0,0 +1,488 @@
+use crate::commands::blame::GitAiBlameOptions;
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::refs::AI_AUTHORSHIP_REFNAME;
+use crate::git::repository::Repository;
+use crate::utils::debug_log;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Directory depth shown in the tree view by default
+const DEFAULT_DEPTH: usize = 2;
+
+/// Handle the `ownership` command
+///
+/// Usage: `git-ai ownership [<rev>] [--depth <n>] [--json] [-- <paths>...]`
+///
+/// Blames every file in the tree at `rev` (HEAD by default) and counts the surviving lines
+/// written by AI, by humans, and in commits without authorship notes, rolled up per directory.
+pub fn handle_ownership(args: &[String]) {
+    let parsed = match parse_args(args) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let ownership = match compute_ownership(&repo, &parsed.rev, &parsed.paths) {
+        Ok(ownership) => ownership,
+        Err(e) => {
+            eprintln!("Failed to compute ownership: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if parsed.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&ownership).unwrap_or_else(|_| "{}".to_string())
+        );
+    } else {
+        print!("{}", format_tree(&ownership, parsed.depth));
+    }
+}
+
+#[derive(Debug)]
+pub struct ParsedArgs {
+    pub rev: String,
+    pub paths: Vec<String>,
+    pub depth: usize,
+    pub json: bool,
+}
+
+pub fn parse_args(args: &[String]) -> Result<ParsedArgs, String> {
+    let mut rev: Option<String> = None;
+    let mut paths = Vec::new();
+    let mut depth = DEFAULT_DEPTH;
+    let mut json = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--json" {
+            json = true;
+        } else if arg == "--depth" || arg.starts_with("--depth=") {
+            let value = match arg.strip_prefix("--depth=") {
+                Some(value) => value.to_string(),
+                None => args.next().ok_or("--depth requires a value")?.clone(),
+            };
+            depth = value
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid --depth '{}'", value))?;
+        } else if arg == "--" {
+            paths.extend(args.by_ref().cloned());
+        } else if arg.starts_with('-') {
+            return Err(format!("Unknown option: {}", arg));
+        } else {
+            if rev.is_some() {
+                return Err("ownership accepts at most one revision".to_string());
+            }
+            rev = Some(arg.clone());
+        }
+    }
+
+    Ok(ParsedArgs {
+        rev: rev.unwrap_or_else(|| "HEAD".to_string()),
+        paths,
+        depth,
+        json,
+    })
+}
+
+/// Surviving lines by who wrote them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineOwnership {
+    pub ai: u32,
+    pub human: u32,
+    /// Lines from commits without an authorship note
+    pub unknown: u32,
+}
+
+impl LineOwnership {
+    fn add(&mut self, other: &LineOwnership) {
+        self.ai += other.ai;
+        self.human += other.human;
+        self.unknown += other.unknown;
+    }
+
+    /// AI lines as a percentage of the lines with known authorship
+    pub fn ai_percent(&self) -> Option<f64> {
+        let known = self.ai + self.human;
+        (known > 0).then(|| self.ai as f64 / known as f64 * 100.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Ownership {
+    pub commit: String,
+    pub total: LineOwnership,
+    /// Every directory containing a blamed file, "." for the root
+    pub directories: BTreeMap<String, LineOwnership>,
+    pub files: BTreeMap<String, LineOwnership>,
+    /// Binary files, and files blame couldn't read
+    pub skipped: Vec<String>,
+}
+
+/// Whether a path is one of the pathspecs or below one of them
+pub fn path_matches(path: &str, pathspecs: &[String]) -> bool {
+    pathspecs.is_empty()
+        || pathspecs.iter().any(|spec| {
+            let spec = spec.trim_start_matches("./").trim_end_matches('/');
+            spec.is_empty()
+                || spec == "."
+                || path == spec
+                || path.starts_with(&format!("{}/", spec))
+        })
+}
+
+/// The directories a file rolls up into, from the root down
+pub fn parent_directories(path: &str) -> Vec<String> {
+    let mut directories = vec![".".to_string()];
+    let mut end = 0;
+    while let Some(offset) = path[end..].find('/') {
+        end += offset;
+        directories.push(path[..end].to_string());
+        end += 1;
+    }
+    directories
+}
+
+/// Blame every file at `rev` and roll the line ownership up per directory
+///
+/// Files are blamed concurrently. Results are cached per path and blob in `.git/ai`, and the
+/// cache is dropped whenever the notes ref moves, since new notes change the attribution.
+pub fn compute_ownership(
+    repo: &Repository,
+    rev: &str,
+    pathspecs: &[String],
+) -> Result<Ownership, GitAiError> {
+    let commit = repo.revparse_single(rev)?.peel_to_commit()?.id();
+    let notes_commit = repo
+        .revparse_single(&format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME))
+        .map(|notes| notes.id())
+        .unwrap_or_default();
+
+    let tree_blobs = repo.list_tree_blobs(&commit)?;
+    let mut blobs: Vec<(String, String)> = tree_blobs
+        .iter()
+        .filter(|(path, _)| path_matches(path, pathspecs))
+        .map(|(path, blob)| (path.clone(), blob.clone()))
+        .collect();
+    blobs.sort();
+
+    let mut cache = OwnershipCache::load(repo, &notes_commit);
+    cache.retain_tree(&tree_blobs);
+    let missing: Vec<(String, String)> = blobs
+        .iter()
+        .filter(|(path, blob)| cache.get(path, blob).is_none())
+        .cloned()
+        .collect();
+    let blamed = smol::block_on(blame_files_concurrent(repo, &commit, missing));
+    for (path, blob, result) in blamed {
+        // Failures aren't cached, so the next run tries the file again
+        match result {
+            Ok(lines) => cache.insert(path, blob, lines),
+            Err(e) => debug_log(&format!("Skipping {} in ownership: {}", path, e)),
+        }
+    }
+    cache.save(repo);
+
+    let mut ownership = Ownership {
+        commit,
+        total: LineOwnership::default(),
+        directories: BTreeMap::new(),
+        files: BTreeMap::new(),
+        skipped: Vec::new(),
+    };
+    for (path, blob) in blobs {
+        let Some(Some(lines)) = cache.get(&path, &blob) else {
+            ownership.skipped.push(path);
+            continue;
+        };
+        ownership.total.add(&lines);
+        for directory in parent_directories(&path) {
+            ownership
+                .directories
+                .entry(directory)
+                .or_default()
+                .add(&lines);
+        }
+        ownership.files.insert(path, lines);
+    }
+    Ok(ownership)
+}
+
+/// Blame files concurrently, with each file's result from `blame_file`
+async fn blame_files_concurrent(
+    repo: &Repository,
+    commit: &str,
+    files: Vec<(String, String)>,
+) -> Vec<(String, String, Result<Option<LineOwnership>, GitAiError>)> {
+    const MAX_CONCURRENT: usize = 16;
+
+    let semaphore = Arc::new(smol::lock::Semaphore::new(MAX_CONCURRENT));
+    let mut tasks = Vec::new();
+
+    for (path, blob) in files {
+        let repo = repo.clone();
+        let commit = commit.to_string();
+        let semaphore = Arc::clone(&semaphore);
+
+        let task = smol::spawn(async move {
+            // Acquire semaphore permit to limit concurrency
+            let _permit = semaphore.acquire().await;
+
+            // Wrap blocking git operations in smol::unblock
+            smol::unblock(move || {
+                let lines = blame_file(&repo, &commit, &path, &blob);
+                (path, blob, lines)
+            })
+            .await
+        });
+
+        tasks.push(task);
+    }
+
+    futures::future::join_all(tasks).await
+}
+
+/// Count a file's lines by author, using the same note overlay as `git-ai blame`
+///
+/// Returns `None` for binary files.
+fn blame_file(
+    repo: &Repository,
+    commit: &str,
+    path: &str,
+    blob: &str,
+) -> Result<Option<LineOwnership>, GitAiError> {
+    let content = repo.find_blob(blob.to_string())?.content()?;
+    if content.is_empty() {
+        return Ok(Some(LineOwnership::default()));
+    }
+    // Same heuristic as git: a NUL in the first 8000 bytes means binary
+    if content.iter().take(8000).any(|&b| b == 0) {
+        return Ok(None);
+    }
+
+    let options = GitAiBlameOptions {
+        newest_commit: Some(commit.to_string()),
+        use_prompt_hashes_as_names: true,
+        return_human_authors_as_human: true,
+        mark_unknown: true,
+        no_output: true,
+        ..Default::default()
+    };
+    let (line_authors, prompt_records) = repo.blame(path, &options)?;
+
+    let mut lines = LineOwnership::default();
+    for author in line_authors.values() {
+        if prompt_records.contains_key(author) {
+            lines.ai += 1;
+        } else if author == "Unknown" {
+            lines.unknown += 1;
+        } else {
+            lines.human += 1;
+        }
+    }
+    Ok(Some(lines))
+}
+
+/// Per-file results from earlier runs, valid while the notes ref stays put
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct OwnershipCache {
+    notes_commit: String,
+    /// Keyed by "<blob> <path>"; `None` marks binary files
+    files: HashMap<String, Option<LineOwnership>>,
+}
+
+impl OwnershipCache {
+    fn path(repo: &Repository) -> std::path::PathBuf {
+        repo.path().join("ai").join("ownership_cache")
+    }
+
+    fn load(repo: &Repository, notes_commit: &str) -> Self {
+        std::fs::read(Self::path(repo))
+            .ok()
+            .and_then(|data| serde_json::from_slice::<OwnershipCache>(&data).ok())
+            .filter(|cache| cache.notes_commit == notes_commit)
+            .unwrap_or_else(|| OwnershipCache {
+                notes_commit: notes_commit.to_string(),
+                files: HashMap::new(),
+            })
+    }
+
+    fn get(&self, path: &str, blob: &str) -> Option<Option<LineOwnership>> {
+        self.files.get(&format!("{} {}", blob, path)).copied()
+    }
+
+    fn insert(&mut self, path: String, blob: String, lines: Option<LineOwnership>) {
+        self.files.insert(format!("{} {}", blob, path), lines);
+    }
+
+    /// Drop files whose blob is no longer at their path in `tree_blobs`, so the cache only
+    /// grows with the tree
+    fn retain_tree(&mut self, tree_blobs: &HashMap<String, String>) {
+        self.files.retain(|key, _| {
+            key.split_once(' ')
+                .is_some_and(|(blob, path)| tree_blobs.get(path).is_some_and(|b| b == blob))
+        });
+    }
+
+    /// Best effort; a failed write only costs the next run its cache
+    fn save(&self, repo: &Repository) {
+        let path = Self::path(repo);
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(data) = serde_json::to_vec(self) {
+            let _ = std::fs::write(path, data);
+        }
+    }
+}
+
+/// Render directories (and files) down to `depth` levels as an indented tree
+pub fn format_tree(ownership: &Ownership, depth: usize) -> String {
+    let mut output = format!(
+        "{:>8}  {:>8}  {:>8}  {:>5}  path\n",
+        "ai", "human", "unknown", "ai %"
+    );
+    let row = |output: &mut String, lines: &LineOwnership, name: &str, level: usize| {
+        let percent = lines
+            .ai_percent()
+            .map(|percent| format!("{:.0}%", percent))
+            .unwrap_or_else(|| "-".to_string());
+        output.push_str(&format!(
+            "{:>8}  {:>8}  {:>8}  {:>5}  {}{}\n",
+            lines.ai,
+            lines.human,
+            lines.unknown,
+            percent,
+            "  ".repeat(level),
+            name
+        ));
+    };
+
+    row(&mut output, &ownership.total, ".", 0);
+
+    // Merge directories and files into one sorted listing; directories sort before their
+    // contents because "a" < "a/..."
+    let mut entries: Vec<(&String, &LineOwnership, bool)> = ownership
+        .directories
+        .iter()
+        .filter(|(path, _)| path.as_str() != ".")
+        .map(|(path, lines)| (path, lines, true))
+        .chain(
+            ownership
+                .files
+                .iter()
+                .map(|(path, lines)| (path, lines, false)),
+        )
+        .collect();
+    entries.sort_by(|a, b| a.0.split('/').cmp(b.0.split('/')));
+
+    for (path, lines, is_directory) in entries {
+        let level = path.matches('/').count() + 1;
+        if level > depth {
+            continue;
+        }
+        let name = path.rsplit('/').next().unwrap_or(path);
+        let name = if is_directory {
+            format!("{}/", name)
+        } else {
+            name.to_string()
+        };
+        row(&mut output, lines, &name, level);
+    }
+
+    if !ownership.skipped.is_empty() {
+        output.push_str(&format!(
+            "Skipped {} binary or unreadable file(s)\n",
+            ownership.skipped.len()
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_helpers() {
+        assert_eq!(
+            parent_directories("src/commands/log.rs"),
+            vec![".", "src", "src/commands"]
+        );
+        assert_eq!(parent_directories("README.md"), vec!["."]);
+
+        let specs = vec!["src/".to_string()];
+        assert!(path_matches("src/main.rs", &specs));
+        assert!(!path_matches("srcs/main.rs", &specs));
+        assert!(path_matches("anything", &[]));
+    }
+
+    #[test]
+    fn test_cache_keeps_only_blobs_in_tree() {
+        let mut cache = OwnershipCache::default();
+        cache.insert("a.rs".to_string(), "aaa".to_string(), None);
+        cache.insert("a.rs".to_string(), "old".to_string(), None);
+        cache.insert("gone.rs".to_string(), "ggg".to_string(), None);
+        cache.insert(
+            "dir/b c.rs".to_string(),
+            "bbb".to_string(),
+            Some(LineOwnership::default()),
+        );
+
+        cache.retain_tree(&HashMap::from([
+            ("a.rs".to_string(), "aaa".to_string()),
+            ("dir/b c.rs".to_string(), "bbb".to_string()),
+        ]));
+
+        assert_eq!(cache.files.len(), 2);
+        assert_eq!(cache.get("a.rs", "aaa"), Some(None));
+        assert_eq!(cache.get("a.rs", "old"), None);
+        assert_eq!(cache.get("gone.rs", "ggg"), None);
+        assert!(cache.get("dir/b c.rs", "bbb").is_some());
+    }
+
+    #[test]
+    fn test_format_tree() {
+        let lines = |ai, human| LineOwnership {
+            ai,
+            human,
+            unknown: 0,
+        };
+        let ownership = Ownership {
+            commit: "abc".to_string(),
+            total: lines(3, 2),
+            directories: BTreeMap::from([
+                (".".to_string(), lines(3, 2)),
+                ("src".to_string(), lines(3, 1)),
+            ]),
+            files: BTreeMap::from([
+                ("README.md".to_string(), lines(0, 1)),
+                ("src/main.rs".to_string(), lines(3, 1)),
+            ]),
+            skipped: Vec::new(),
+        };
+
+        let tree = format_tree(&ownership, 2);
+        let rows: Vec<&str> = tree.lines().map(|line| line.trim_start()).collect();
+        assert_eq!(rows.len(), 5);
+        assert!(rows[1].ends_with("60%  ."));
+        assert!(rows[2].ends_with(" 0%    README.md"), "{}", tree);
+        assert!(rows[3].ends_with("75%    src/"), "{}", tree);
+        assert!(rows[4].ends_with("75%      main.rs"), "{}", tree);
+
+        assert_eq!(format_tree(&ownership, 1).lines().count(), 4);
+    }
+}
@@ -0,0 +1,554 @@
+use crate::authorship::rebase_authorship::rewrite_authorship_after_rebase_v2;
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::refs::{list_commits_with_notes, show_authorship_note};
+use crate::git::repository::{Repository, exec_git, exec_git_stdin};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Commits checked when no range is given
+const DEFAULT_COMMIT_LIMIT: usize = 100;
+
+/// Commits per `git patch-id` call, small enough that its output never fills the pipe
+/// while we're still writing patches to it
+const PATCH_ID_BATCH_SIZE: usize = 256;
+
+/// Share of a noted commit's added lines that must show up in a commit for it to count
+/// as part of that commit
+const CONTAINED_THRESHOLD: f64 = 0.8;
+
+/// Share of a commit's added lines the matched noted commits must account for
+const COVERED_THRESHOLD: f64 = 0.5;
+
+/// Fewest distinctive lines a noted commit must add to be matched by similarity; smaller
+/// commits are contained in almost anything
+const MIN_CANDIDATE_LINES: usize = 3;
+
+/// Starts of import lines, which too many unrelated commits share to tell them apart
+const IMPORT_PREFIXES: &[&str] = &[
+    "use ", "import ", "from ", "#include", "require ", "require(", "package ",
+];
+
+/// How a commit without a note was matched to the noted commits it came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    /// Same `git patch-id --stable`, as after a clean rebase
+    PatchId,
+    /// The added lines mostly come from the noted commits, as after a squash or a rebase
+    /// that had to resolve conflicts
+    Similarity,
+}
+
+impl MatchKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MatchKind::PatchId => "patch-id",
+            MatchKind::Similarity => "similarity",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecoveryMatch {
+    pub commit: String,
+    /// Noted commits the commit was rebuilt from, newest first
+    pub originals: Vec<String>,
+    pub kind: MatchKind,
+}
+
+#[derive(Debug, Default)]
+pub struct RecoveryPlan {
+    pub matches: Vec<RecoveryMatch>,
+    /// Commits without a note that nothing matched
+    pub unmatched: Vec<String>,
+}
+
+/// Handle the `recover` command
+///
+/// Usage: `git-ai recover [<rev|range>] [--dry-run]`
+///
+/// Finds commits without authorship notes, typically rebased or squashed by a hosting
+/// platform, matches them to noted commits that still exist locally, and rebuilds their
+/// notes the way a local rebase would. Without a range, the last 100 commits on HEAD are
+/// checked.
+pub fn handle_recover(args: &[String]) {
+    let parsed = match parse_args(args) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let plan = match plan_recovery(&repo, parsed.spec.as_deref()) {
+        Ok(plan) => plan,
+        Err(e) => {
+            eprintln!("Failed to match commits: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let total = plan.matches.len() + plan.unmatched.len();
+    if total == 0 {
+        println!("Every commit in range already has an authorship note");
+        return;
+    }
+
+    let mut recovered = Vec::new();
+    let mut failed = Vec::new();
+    for recovery in &plan.matches {
+        if parsed.dry_run {
+            recovered.push(recovery);
+            continue;
+        }
+        match rebuild_note(&repo, recovery) {
+            Ok(()) => recovered.push(recovery),
+            Err(e) => failed.push((recovery.commit.clone(), e.to_string())),
+        }
+    }
+
+    println!(
+        "{} {} of {} commit(s) without notes",
+        if parsed.dry_run {
+            "Would recover"
+        } else {
+            "Recovered"
+        },
+        recovered.len(),
+        total
+    );
+    for recovery in &recovered {
+        let originals: Vec<&str> = recovery
+            .originals
+            .iter()
+            .map(|sha| short_sha(sha))
+            .collect();
+        println!(
+            "  {} <- {} ({})",
+            short_sha(&recovery.commit),
+            originals.join(", "),
+            recovery.kind.as_str()
+        );
+    }
+
+    if !plan.unmatched.is_empty() || !failed.is_empty() {
+        println!("Could not recover:");
+        for commit in &plan.unmatched {
+            println!("  {} no matching noted commit", short_sha(commit));
+        }
+        for (commit, error) in &failed {
+            println!("  {} {}", short_sha(commit), error);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParsedArgs {
+    pub spec: Option<String>,
+    pub dry_run: bool,
+}
+
+pub fn parse_args(args: &[String]) -> Result<ParsedArgs, String> {
+    let mut spec: Option<String> = None;
+    let mut dry_run = false;
+
+    for arg in args {
+        if arg == "--dry-run" {
+            dry_run = true;
+        } else if arg.starts_with('-') {
+            return Err(format!("Unknown option: {}", arg));
+        } else {
+            if spec.is_some() {
+                return Err("recover accepts at most one revision or range".to_string());
+            }
+            spec = Some(arg.clone());
+        }
+    }
+
+    Ok(ParsedArgs { spec, dry_run })
+}
+
+fn short_sha(sha: &str) -> &str {
+    &sha[..sha.len().min(7)]
+}
+
+/// Match every commit in range that has no note, without writing anything
+pub fn plan_recovery(repo: &Repository, spec: Option<&str>) -> Result<RecoveryPlan, GitAiError> {
+    let targets: Vec<String> = commits_in_range(repo, spec)?
+        .into_iter()
+        .filter(|commit| show_authorship_note(repo, commit).is_none())
+        .collect();
+    if targets.is_empty() {
+        return Ok(RecoveryPlan::default());
+    }
+
+    let target_set: HashSet<&String> = targets.iter().collect();
+    let candidates: Vec<String> = existing_commits(repo, &list_commits_with_notes(repo)?)?
+        .into_iter()
+        .filter(|commit| !target_set.contains(commit))
+        .collect();
+
+    let mut plan = RecoveryPlan::default();
+    if candidates.is_empty() {
+        plan.unmatched = targets;
+        return Ok(plan);
+    }
+
+    // Clean rebases keep the patch, so patch-ids settle most commits cheaply
+    let target_ids = patch_ids(repo, &targets)?;
+    let mut candidates_by_id: HashMap<String, Vec<String>> = HashMap::new();
+    for (commit, patch_id) in patch_ids(repo, &candidates)? {
+        candidates_by_id.entry(patch_id).or_default().push(commit);
+    }
+
+    let mut unmatched = Vec::new();
+    for target in &targets {
+        match target_ids
+            .get(target)
+            .and_then(|patch_id| candidates_by_id.get(patch_id))
+        {
+            Some(originals) => plan.matches.push(RecoveryMatch {
+                commit: target.clone(),
+                originals: newest_first(repo, originals)?,
+                kind: MatchKind::PatchId,
+            }),
+            None => unmatched.push(target.clone()),
+        }
+    }
+
+    if !unmatched.is_empty() {
+        let candidate_lines = added_lines(repo, &candidates)?;
+        let target_lines = added_lines(repo, &unmatched)?;
+        for target in unmatched {
+            // A noted commit already in the target's history can't be what it was rebuilt from
+            let originals = target_lines
+                .get(&target)
+                .map(|lines| {
+                    match_by_similarity(lines, &candidate_lines, |candidate| {
+                        is_ancestor(repo, candidate, &target)
+                    })
+                })
+                .unwrap_or_default();
+            if originals.is_empty() {
+                plan.unmatched.push(target);
+            } else {
+                plan.matches.push(RecoveryMatch {
+                    commit: target,
+                    originals: newest_first(repo, &originals)?,
+                    kind: MatchKind::Similarity,
+                });
+            }
+        }
+    }
+
+    Ok(plan)
+}
+
+/// Rebuild a commit's note from the noted commits it was matched to
+fn rebuild_note(repo: &Repository, recovery: &RecoveryMatch) -> Result<(), GitAiError> {
+    let mut original_commits = recovery.originals.clone();
+    original_commits.reverse();
+    rewrite_authorship_after_rebase_v2(
+        repo,
+        &recovery.originals[0],
+        &original_commits,
+        std::slice::from_ref(&recovery.commit),
+        "",
+    )?;
+
+    if show_authorship_note(repo, &recovery.commit).is_none() {
+        return Err(GitAiError::Generic(
+            "matched commits carry no AI attributions for its files".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Non-merge commits in `spec`, oldest first
+fn commits_in_range(repo: &Repository, spec: Option<&str>) -> Result<Vec<String>, GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.push("rev-list".to_string());
+    args.push("--reverse".to_string());
+    args.push("--no-merges".to_string());
+    match spec {
+        Some(spec) if spec.contains("..") => args.push(spec.to_string()),
+        Some(spec) => {
+            args.push("--no-walk".to_string());
+            args.push(spec.to_string());
+        }
+        None => {
+            args.push(format!("--max-count={}", DEFAULT_COMMIT_LIMIT));
+            args.push("HEAD".to_string());
+        }
+    }
+
+    let output = exec_git(&args)?;
+    Ok(String::from_utf8(output.stdout)?
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect())
+}
+
+/// The commits that are still in the object database; notes outlive pruned commits
+fn existing_commits(repo: &Repository, commits: &[String]) -> Result<Vec<String>, GitAiError> {
+    if commits.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut args = repo.global_args_for_exec();
+    args.push("cat-file".to_string());
+    args.push("--batch-check".to_string());
+    let output = exec_git_stdin(&args, format!("{}\n", commits.join("\n")).as_bytes())?;
+
+    // Each line is "<sha> <type> <size>" or "<sha> missing"
+    Ok(String::from_utf8(output.stdout)?
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let sha = parts.next()?;
+            (parts.next() == Some("commit")).then(|| sha.to_string())
+        })
+        .collect())
+}
+
+/// `git patch-id --stable` for each commit, keyed by commit
+fn patch_ids(repo: &Repository, commits: &[String]) -> Result<HashMap<String, String>, GitAiError> {
+    let mut ids = HashMap::new();
+    for batch in commits.chunks(PATCH_ID_BATCH_SIZE) {
+        let patches = commit_patches(repo, batch, &[])?;
+
+        let mut args = repo.global_args_for_exec();
+        args.push("patch-id".to_string());
+        args.push("--stable".to_string());
+        let output = exec_git_stdin(&args, &patches)?;
+
+        // Each line is "<patch id> <commit>"
+        for line in String::from_utf8(output.stdout)?.lines() {
+            if let Some((patch_id, commit)) = line.split_once(' ') {
+                ids.insert(commit.trim().to_string(), patch_id.to_string());
+            }
+        }
+    }
+    Ok(ids)
+}
+
+/// `git log -p` output for exactly these commits, each introduced by "commit <sha>"
+fn commit_patches(
+    repo: &Repository,
+    commits: &[String],
+    extra_args: &[&str],
+) -> Result<Vec<u8>, GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.push("log".to_string());
+    args.push("--no-walk=unsorted".to_string());
+    args.push("--stdin".to_string());
+    args.push("-p".to_string());
+    args.push("--no-color".to_string());
+    args.push("--no-ext-diff".to_string());
+    args.push("--format=commit %H".to_string());
+    args.extend(extra_args.iter().map(|arg| arg.to_string()));
+    let output = exec_git_stdin(&args, format!("{}\n", commits.join("\n")).as_bytes())?;
+    Ok(output.stdout)
+}
+
+/// The distinctive lines each commit adds, as (file, trimmed line), keyed by commit
+fn added_lines(
+    repo: &Repository,
+    commits: &[String],
+) -> Result<BTreeMap<String, HashSet<(String, String)>>, GitAiError> {
+    let mut lines = BTreeMap::new();
+    for batch in commits.chunks(PATCH_ID_BATCH_SIZE) {
+        let patches = commit_patches(repo, batch, &["-U0"])?;
+        lines.extend(parse_added_lines(&String::from_utf8_lossy(&patches)));
+    }
+    Ok(lines)
+}
+
+fn parse_added_lines(patches: &str) -> BTreeMap<String, HashSet<(String, String)>> {
+    let mut lines: BTreeMap<String, HashSet<(String, String)>> = BTreeMap::new();
+    let mut commit: Option<String> = None;
+    let mut file: Option<String> = None;
+
+    for line in patches.lines() {
+        if let Some(sha) = line.strip_prefix("commit ")
+            && sha.len() >= 40
+            && sha.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            commit = Some(sha.to_string());
+            file = None;
+            lines.entry(sha.to_string()).or_default();
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            file = path.strip_prefix("b/").map(|path| path.to_string());
+        } else if let Some(added) = line.strip_prefix('+')
+            && let (Some(commit), Some(file)) = (&commit, &file)
+            && !is_trivial_line(added.trim())
+        {
+            lines
+                .entry(commit.clone())
+                .or_default()
+                .insert((file.clone(), added.trim().to_string()));
+        }
+    }
+
+    lines
+}
+
+/// Lines that say nothing about where a commit came from: blank lines, lines of only
+/// punctuation such as braces, and imports
+fn is_trivial_line(line: &str) -> bool {
+    !line.chars().any(char::is_alphanumeric)
+        || IMPORT_PREFIXES
+            .iter()
+            .any(|prefix| line.starts_with(prefix))
+}
+
+/// Noted commits whose added lines are mostly in `target`, provided together they account
+/// for enough of what `target` adds
+///
+/// `excluded` is only asked about candidates that would otherwise match.
+fn match_by_similarity(
+    target: &HashSet<(String, String)>,
+    candidates: &BTreeMap<String, HashSet<(String, String)>>,
+    excluded: impl Fn(&str) -> bool,
+) -> Vec<String> {
+    if target.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matched = Vec::new();
+    let mut covered: HashSet<&(String, String)> = HashSet::new();
+    for (candidate, lines) in candidates {
+        if lines.len() < MIN_CANDIDATE_LINES {
+            continue;
+        }
+        let shared: Vec<&(String, String)> = lines.intersection(target).collect();
+        if shared.len() as f64 / lines.len() as f64 >= CONTAINED_THRESHOLD && !excluded(candidate) {
+            matched.push(candidate.clone());
+            covered.extend(shared);
+        }
+    }
+
+    if (covered.len() as f64 / target.len() as f64) < COVERED_THRESHOLD {
+        return Vec::new();
+    }
+    matched
+}
+
+fn is_ancestor(repo: &Repository, ancestor: &str, descendant: &str) -> bool {
+    let mut args = repo.global_args_for_exec();
+    args.push("merge-base".to_string());
+    args.push("--is-ancestor".to_string());
+    args.push(ancestor.to_string());
+    args.push(descendant.to_string());
+    exec_git(&args).is_ok()
+}
+
+/// Order commits newest first, so the first one's history includes the others where it can
+fn newest_first(repo: &Repository, commits: &[String]) -> Result<Vec<String>, GitAiError> {
+    // Matches are a handful of commits, so counting ancestors pairwise is cheap enough
+    let mut ranked: Vec<(usize, &String)> = commits
+        .iter()
+        .map(|commit| {
+            let ancestors = commits
+                .iter()
+                .filter(|other| *other != commit && is_ancestor(repo, other, commit))
+                .count();
+            (ancestors, commit)
+        })
+        .collect();
+    ranked.sort_by_key(|(ancestors, _)| std::cmp::Reverse(*ancestors));
+    Ok(ranked
+        .into_iter()
+        .map(|(_, commit)| commit.clone())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(entries: &[(&str, &str)]) -> HashSet<(String, String)> {
+        entries
+            .iter()
+            .map(|(file, line)| (file.to_string(), line.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_added_lines() {
+        let sha_a = "a".repeat(40);
+        let sha_b = "b".repeat(40);
+        let patches = format!(
+            "commit {}\n\ndiff --git a/x.txt b/x.txt\n--- a/x.txt\n+++ b/x.txt\n@@ -1,0 +2,5 @@\n+one\n+   \n+    }};\n+use std::fs;\n+import os\n-gone\ncommit {}\n\ndiff --git a/y.txt b/y.txt\nnew file mode 100644\n--- /dev/null\n+++ b/y.txt\n@@ -0,0 +1 @@\n+  two  \n",
+            sha_a, sha_b
+        );
+        let parsed = parse_added_lines(&patches);
+        assert_eq!(parsed[&sha_a], lines(&[("x.txt", "one")]));
+        assert_eq!(parsed[&sha_b], lines(&[("y.txt", "two")]));
+    }
+
+    #[test]
+    fn test_match_by_similarity_finds_squashed_commits() {
+        let squashed = lines(&[
+            ("a.rs", "one"),
+            ("a.rs", "two"),
+            ("a.rs", "three"),
+            ("b.rs", "four"),
+            ("b.rs", "five"),
+            ("b.rs", "six"),
+        ]);
+        let mut candidates = BTreeMap::new();
+        candidates.insert(
+            "first".to_string(),
+            lines(&[("a.rs", "one"), ("a.rs", "two"), ("a.rs", "three")]),
+        );
+        candidates.insert(
+            "second".to_string(),
+            lines(&[("b.rs", "four"), ("b.rs", "five"), ("b.rs", "six")]),
+        );
+        candidates.insert(
+            "unrelated".to_string(),
+            lines(&[("a.rs", "one"), ("c.rs", "x"), ("c.rs", "y")]),
+        );
+        assert_eq!(
+            match_by_similarity(&squashed, &candidates, |_| false),
+            vec!["first".to_string(), "second".to_string()]
+        );
+
+        // Candidates in the target's own history are left out
+        assert_eq!(
+            match_by_similarity(&squashed, &candidates, |candidate| candidate == "second"),
+            vec!["first".to_string()]
+        );
+
+        // Too little of the commit is explained by what matched
+        let mostly_new = lines(&[
+            ("a.rs", "one"),
+            ("a.rs", "two"),
+            ("a.rs", "three"),
+            ("a.rs", "new 1"),
+            ("a.rs", "new 2"),
+            ("a.rs", "new 3"),
+            ("a.rs", "new 4"),
+        ]);
+        let mut candidates = BTreeMap::new();
+        candidates.insert(
+            "first".to_string(),
+            lines(&[("a.rs", "one"), ("a.rs", "two"), ("a.rs", "three")]),
+        );
+        assert!(match_by_similarity(&mostly_new, &candidates, |_| false).is_empty());
+
+        // Commits adding too few lines are contained in too much to be matched
+        let small = lines(&[("a.rs", "one"), ("a.rs", "new 1")]);
+        let mut candidates = BTreeMap::new();
+        candidates.insert("tiny".to_string(), lines(&[("a.rs", "one")]));
+        assert!(match_by_similarity(&small, &candidates, |_| false).is_empty());
+    }
+}
@@ -0,0 +1,244 @@
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::rebase_authorship::remap_authorship_log;
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::refs::{notes_add_batch, show_authorship_note};
+use crate::git::repository::Repository;
+use std::path::PathBuf;
+
+/// Handle the `remap` command
+///
+/// Usage: `git-ai remap [<commit-map>] [--path-rename <old>:<new>]... [--dry-run]`
+///
+/// Rewrites authorship notes onto the commits a history rewrite replaced their commits with.
+/// The map has one "<old sha> <new sha>" pair per line, like the `commit-map` `git filter-repo`
+/// writes (the default) or BFG's `object-id-map.old-new.txt`. All rewritten notes land in a
+/// single commit on refs/notes/ai; the old notes stay until `git notes --ref=ai prune`.
+pub fn handle_remap(args: &[String]) {
+    let parsed = match parse_args(args) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let map_path = parsed
+        .map_path
+        .clone()
+        .unwrap_or_else(|| default_commit_map(&repo));
+    let commit_map = match std::fs::read_to_string(&map_path) {
+        Ok(content) => parse_commit_map(&content),
+        Err(e) => {
+            eprintln!("Failed to read commit map {}: {}", map_path.display(), e);
+            std::process::exit(1);
+        }
+    };
+
+    let plan = plan_remap(&repo, &commit_map, &parsed.path_renames);
+
+    for (commit_sha, error) in &plan.failed {
+        eprintln!(
+            "Skipping {}: {}",
+            &commit_sha[..commit_sha.len().min(7)],
+            error
+        );
+    }
+
+    if parsed.dry_run {
+        for (old_sha, new_sha, _) in &plan.remaps {
+            println!("{} -> {}", &old_sha[..7], &new_sha[..7]);
+        }
+        println!(
+            "Would remap {} note(s) across {} rewritten commit(s)",
+            plan.remaps.len(),
+            commit_map.len()
+        );
+        return;
+    }
+
+    if plan.remaps.is_empty() {
+        println!(
+            "No notes to remap across {} rewritten commit(s)",
+            commit_map.len()
+        );
+        return;
+    }
+
+    let updates: Vec<(String, String)> = plan
+        .remaps
+        .iter()
+        .map(|(_, new_sha, note)| (new_sha.clone(), note.clone()))
+        .collect();
+    if let Err(e) = notes_add_batch(&repo, &updates, "Remapped by 'git-ai remap'") {
+        eprintln!("Failed to update refs/notes/ai: {}", e);
+        std::process::exit(1);
+    }
+
+    println!(
+        "Remapped {} note(s) across {} rewritten commit(s)",
+        plan.remaps.len(),
+        commit_map.len()
+    );
+}
+
+#[derive(Debug)]
+pub struct ParsedArgs {
+    pub map_path: Option<PathBuf>,
+    pub path_renames: Vec<(String, String)>,
+    pub dry_run: bool,
+}
+
+pub fn parse_args(args: &[String]) -> Result<ParsedArgs, String> {
+    let mut map_path: Option<PathBuf> = None;
+    let mut path_renames = Vec::new();
+    let mut dry_run = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--dry-run" {
+            dry_run = true;
+        } else if arg == "--path-rename" || arg.starts_with("--path-rename=") {
+            let value = match arg.strip_prefix("--path-rename=") {
+                Some(value) => value.to_string(),
+                None => args
+                    .next()
+                    .cloned()
+                    .ok_or("--path-rename requires <old>:<new>")?,
+            };
+            let (old, new) = value.split_once(':').ok_or_else(|| {
+                format!("Invalid --path-rename '{}', expected <old>:<new>", value)
+            })?;
+            path_renames.push((old.to_string(), new.to_string()));
+        } else if arg.starts_with('-') {
+            return Err(format!("Unknown option: {}", arg));
+        } else {
+            if map_path.is_some() {
+                return Err("remap accepts at most one commit map".to_string());
+            }
+            map_path = Some(PathBuf::from(arg));
+        }
+    }
+
+    Ok(ParsedArgs {
+        map_path,
+        path_renames,
+        dry_run,
+    })
+}
+
+/// Where `git filter-repo` leaves the map of the last rewrite
+fn default_commit_map(repo: &Repository) -> PathBuf {
+    repo.common_dir().join("filter-repo").join("commit-map")
+}
+
+/// Parse "<old sha> <new sha>" lines into pairs
+///
+/// Header lines are skipped, as are commits that didn't change and commits the rewrite
+/// removed, which filter-repo maps to the all-zero id.
+pub fn parse_commit_map(content: &str) -> Vec<(String, String)> {
+    let is_sha =
+        |s: &str| (s.len() == 40 || s.len() == 64) && s.chars().all(|c| c.is_ascii_hexdigit());
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let old = parts.next()?;
+            let new = parts.next()?;
+            (is_sha(old) && is_sha(new) && old != new && new.chars().any(|c| c != '0'))
+                .then(|| (old.to_lowercase(), new.to_lowercase()))
+        })
+        .collect()
+}
+
+#[derive(Debug, Default)]
+pub struct RemapPlan {
+    /// (old commit, new commit, rewritten note)
+    pub remaps: Vec<(String, String, String)>,
+    /// Old commits whose note couldn't be remapped, with the reason
+    pub failed: Vec<(String, String)>,
+}
+
+/// Work out the rewritten note for every mapped commit that has one, without writing anything
+pub fn plan_remap(
+    repo: &Repository,
+    commit_map: &[(String, String)],
+    path_renames: &[(String, String)],
+) -> RemapPlan {
+    let mut plan = RemapPlan::default();
+
+    for (old_sha, new_sha) in commit_map {
+        let Some(note) = show_authorship_note(repo, old_sha) else {
+            continue;
+        };
+
+        let remapped = AuthorshipLog::deserialize_from_string(&note)
+            .map_err(|e| GitAiError::Generic(format!("note could not be parsed: {}", e)))
+            .and_then(|log| remap_authorship_log(repo, &log, old_sha, new_sha, path_renames))
+            .and_then(|log| {
+                log.serialize_to_string().map_err(|_| {
+                    GitAiError::Generic("Failed to serialize authorship log".to_string())
+                })
+            });
+        match remapped {
+            Ok(note) => plan.remaps.push((old_sha.clone(), new_sha.clone(), note)),
+            Err(e) => plan.failed.push((old_sha.clone(), e.to_string())),
+        }
+    }
+
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commit_map() {
+        let old = "1".repeat(40);
+        let new = "2".repeat(40);
+        let unchanged = "3".repeat(40);
+        let removed = "4".repeat(40);
+        let content = format!(
+            "old                                      new\n{} {}\n{} {}\n{} {}\n",
+            old,
+            new,
+            unchanged,
+            unchanged,
+            removed,
+            "0".repeat(40)
+        );
+        assert_eq!(parse_commit_map(&content), vec![(old, new)]);
+    }
+
+    #[test]
+    fn test_parse_path_renames() {
+        let args: Vec<String> = [
+            "--path-rename",
+            "src/:lib/",
+            "--path-rename=a.txt:b.txt",
+            "map",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let parsed = parse_args(&args).unwrap();
+        assert_eq!(
+            parsed.path_renames,
+            vec![
+                ("src/".to_string(), "lib/".to_string()),
+                ("a.txt".to_string(), "b.txt".to_string())
+            ]
+        );
+        assert_eq!(parsed.map_path, Some(PathBuf::from("map")));
+        assert!(parse_args(&["--path-rename".to_string(), "nocolon".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,635 @@
+use crate::authorship::authorship_log::PromptRecord;
+use crate::authorship::grouped_stats::{GroupBy, GroupedRangeStats, grouped_range_stats};
+use crate::authorship::internal_db::InternalDatabase;
+use crate::authorship::range_authorship::{RangeAuthorshipStats, range_authorship};
+use crate::authorship::stats::CommitStats;
+use crate::authorship::transcript::Message;
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::refs::get_authorship;
+use crate::git::repository::{CommitRange, Repository};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+/// Prompts listed in the report
+const TOP_PROMPT_COUNT: usize = 20;
+
+/// Characters of a prompt's first message shown in the report
+const PROMPT_SNIPPET_LENGTH: usize = 120;
+
+const CHART_WIDTH: f64 = 720.0;
+const CHART_HEIGHT: f64 = 180.0;
+const TREEMAP_HEIGHT: f64 = 320.0;
+
+/// Handle the `report` command
+///
+/// Usage: `git-ai report <range> [--out <file>]`
+///
+/// Writes a self-contained HTML report for the range: AI share per week, a treemap of
+/// top-level directories, the prompts with the most accepted lines and the tool/model
+/// breakdown. Everything is computed from the notes and the local prompt database, and the
+/// page loads nothing from the network.
+pub fn handle_report(args: &[String]) {
+    let parsed = match parse_args(args) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let Some((start, end)) = parsed.range.split_once("..") else {
+        eprintln!("Invalid commit range format. Expected: <commit>..<commit>");
+        std::process::exit(1);
+    };
+    let range =
+        match CommitRange::new_infer_refname(&repo, start.to_string(), end.to_string(), None) {
+            Ok(range) => range,
+            Err(e) => {
+                eprintln!("Failed to create commit range: {}", e);
+                std::process::exit(1);
+            }
+        };
+
+    let html = match build_report(&repo, range, &parsed.range) {
+        Ok(html) => html,
+        Err(e) => {
+            eprintln!("Failed to build report: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(e) = std::fs::write(&parsed.out, html) {
+        eprintln!("Failed to write {}: {}", parsed.out.display(), e);
+        std::process::exit(1);
+    }
+    println!("Wrote report to {}", parsed.out.display());
+}
+
+#[derive(Debug)]
+pub struct ParsedArgs {
+    pub range: String,
+    pub out: PathBuf,
+}
+
+pub fn parse_args(args: &[String]) -> Result<ParsedArgs, String> {
+    let mut range: Option<String> = None;
+    let mut out = PathBuf::from("git-ai-report.html");
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--out" || arg == "-o" {
+            out = PathBuf::from(args.next().ok_or("--out requires a file")?);
+        } else if let Some(value) = arg.strip_prefix("--out=") {
+            out = PathBuf::from(value);
+        } else if arg.starts_with('-') {
+            return Err(format!("Unknown option: {}", arg));
+        } else {
+            if range.is_some() {
+                return Err("report accepts one commit range".to_string());
+            }
+            range = Some(arg.clone());
+        }
+    }
+
+    Ok(ParsedArgs {
+        range: range.ok_or("report requires a commit range, e.g. v1.0..HEAD")?,
+        out,
+    })
+}
+
+/// A prompt's contribution to the range
+#[derive(Debug, Clone)]
+pub struct PromptSummary {
+    pub id: String,
+    pub tool: String,
+    pub model: String,
+    pub human_author: Option<String>,
+    pub accepted_lines: u32,
+    pub overriden_lines: u32,
+    pub snippet: String,
+}
+
+/// Compute everything the report shows and render it
+pub fn build_report(
+    repo: &Repository,
+    range: CommitRange,
+    title: &str,
+) -> Result<String, GitAiError> {
+    let commits = range.all_commits();
+    let weekly = grouped_range_stats(range.clone(), GroupBy::Week, &[])?;
+    let directories = grouped_range_stats(range.clone(), GroupBy::Directory, &[])?;
+    let summary = range_authorship(range, false, &[])?;
+    let prompts = top_prompts(repo, &commits);
+
+    Ok(render_report(
+        title,
+        &summary,
+        &weekly,
+        &directories,
+        &prompts,
+    ))
+}
+
+/// The prompts in the range's notes, most accepted lines first
+///
+/// A prompt that spans several commits is summed across them. Its text comes from the note,
+/// or from the local prompt database when the note doesn't carry the messages.
+fn top_prompts(repo: &Repository, commits: &[String]) -> Vec<PromptSummary> {
+    let mut prompts: BTreeMap<String, (PromptRecord, u32, u32)> = BTreeMap::new();
+    for commit in commits {
+        let Some(log) = get_authorship(repo, commit) else {
+            continue;
+        };
+        for (id, record) in log.metadata.prompts {
+            let entry = prompts.entry(id).or_insert_with(|| (record.clone(), 0, 0));
+            entry.1 += record.accepted_lines;
+            entry.2 += record.overriden_lines;
+        }
+    }
+
+    let mut summaries: Vec<PromptSummary> = prompts
+        .into_iter()
+        .map(|(id, (record, accepted_lines, overriden_lines))| {
+            let snippet = first_message_snippet(&record.messages)
+                .or_else(|| {
+                    let db = InternalDatabase::global().ok()?;
+                    let db = db.lock().ok()?;
+                    let db_record = db.get_prompt(&id).ok()??;
+                    Some(db_record.first_message_snippet(PROMPT_SNIPPET_LENGTH))
+                })
+                .unwrap_or_else(|| "(No messages)".to_string());
+            PromptSummary {
+                id,
+                tool: record.agent_id.tool,
+                model: record.agent_id.model,
+                human_author: record.human_author,
+                accepted_lines,
+                overriden_lines,
+                snippet,
+            }
+        })
+        .collect();
+    summaries.sort_by(|a, b| {
+        b.accepted_lines
+            .cmp(&a.accepted_lines)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    summaries.truncate(TOP_PROMPT_COUNT);
+    summaries
+}
+
+fn first_message_snippet(messages: &[Message]) -> Option<String> {
+    let text = messages.iter().find_map(|message| match message {
+        Message::User { text, .. } => Some(text),
+        _ => None,
+    })?;
+    if text.chars().count() <= PROMPT_SNIPPET_LENGTH {
+        return Some(text.clone());
+    }
+    let truncated: String = text.chars().take(PROMPT_SNIPPET_LENGTH).collect();
+    Some(format!("{}...", truncated))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+}
+
+/// Lay out a squarified treemap, one rectangle per weight
+///
+/// Weights should be sorted largest first; rectangles come back in the same order.
+pub fn squarify(weights: &[f64], bounds: Rect) -> Vec<Rect> {
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        return vec![
+            Rect {
+                x: bounds.x,
+                y: bounds.y,
+                w: 0.0,
+                h: 0.0,
+            };
+            weights.len()
+        ];
+    }
+
+    let scale = bounds.w * bounds.h / total;
+    let areas: Vec<f64> = weights.iter().map(|weight| weight * scale).collect();
+    let worst = |row: &[f64], side: f64| {
+        let sum: f64 = row.iter().sum();
+        let max = row.iter().cloned().fold(f64::MIN, f64::max);
+        let min = row.iter().cloned().fold(f64::MAX, f64::min);
+        if sum <= 0.0 || min <= 0.0 {
+            return f64::MAX;
+        }
+        (side * side * max / (sum * sum)).max(sum * sum / (side * side * min))
+    };
+
+    let mut rects = Vec::with_capacity(areas.len());
+    let mut free = bounds;
+    let mut start = 0;
+    while start < areas.len() {
+        // Grow the row while that makes its rectangles squarer
+        let side = free.w.min(free.h);
+        let mut end = start + 1;
+        while end < areas.len()
+            && worst(&areas[start..=end], side) <= worst(&areas[start..end], side)
+        {
+            end += 1;
+        }
+
+        let row_area: f64 = areas[start..end].iter().sum();
+        if free.w >= free.h {
+            // A column along the left edge
+            let width = if free.h > 0.0 { row_area / free.h } else { 0.0 };
+            let mut y = free.y;
+            for area in &areas[start..end] {
+                let h = if width > 0.0 { area / width } else { 0.0 };
+                rects.push(Rect {
+                    x: free.x,
+                    y,
+                    w: width,
+                    h,
+                });
+                y += h;
+            }
+            free.x += width;
+            free.w -= width;
+        } else {
+            // A row along the top edge
+            let height = if free.w > 0.0 { row_area / free.w } else { 0.0 };
+            let mut x = free.x;
+            for area in &areas[start..end] {
+                let w = if height > 0.0 { area / height } else { 0.0 };
+                rects.push(Rect {
+                    x,
+                    y: free.y,
+                    w,
+                    h: height,
+                });
+                x += w;
+            }
+            free.y += height;
+            free.h -= height;
+        }
+        start = end;
+    }
+    rects
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Share of the added lines that were AI, mixed and purely human
+fn line_shares(stats: &CommitStats) -> (f64, f64, f64) {
+    let pure_human = stats.human_additions.saturating_sub(stats.mixed_additions);
+    let total = (stats.ai_accepted + stats.mixed_additions + pure_human) as f64;
+    if total == 0.0 {
+        return (0.0, 0.0, 0.0);
+    }
+    (
+        stats.ai_accepted as f64 / total,
+        stats.mixed_additions as f64 / total,
+        pure_human as f64 / total,
+    )
+}
+
+const STYLE: &str = "
+body { font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Helvetica, Arial, sans-serif; margin: 2rem auto; max-width: 60rem; color: #1f2328; padding: 0 1rem; }
+h1 { font-size: 1.6rem; margin-bottom: 0.2rem; }
+h2 { font-size: 1.2rem; margin-top: 2.2rem; border-bottom: 1px solid #d0d7de; padding-bottom: 0.3rem; }
+.subtitle { color: #656d76; margin-top: 0; }
+.cards { display: flex; flex-wrap: wrap; gap: 0.8rem; }
+.card { border: 1px solid #d0d7de; border-radius: 6px; padding: 0.6rem 1rem; min-width: 8rem; }
+.card .value { font-size: 1.4rem; font-weight: 600; }
+.card .label { color: #656d76; font-size: 0.85rem; }
+table { border-collapse: collapse; width: 100%; font-size: 0.9rem; }
+th, td { text-align: left; padding: 0.35rem 0.5rem; border-bottom: 1px solid #eaeef2; vertical-align: top; }
+td.num, th.num { text-align: right; font-variant-numeric: tabular-nums; }
+.legend span { display: inline-block; margin-right: 1rem; font-size: 0.85rem; }
+.swatch { display: inline-block; width: 0.8rem; height: 0.8rem; margin-right: 0.3rem; vertical-align: middle; }
+.bar { background: #8250df; height: 0.7rem; border-radius: 2px; }
+.empty { color: #656d76; font-style: italic; }
+code { font-size: 0.85rem; }
+";
+
+const AI_COLOR: &str = "#8250df";
+const MIXED_COLOR: &str = "#d8b9ff";
+const HUMAN_COLOR: &str = "#afb8c1";
+
+fn render_report(
+    title: &str,
+    summary: &RangeAuthorshipStats,
+    weekly: &GroupedRangeStats,
+    directories: &GroupedRangeStats,
+    prompts: &[PromptSummary],
+) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>git-ai report {}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>AI authorship report</h1>\n<p class=\"subtitle\"><code>{}</code></p>\n",
+        escape_html(title),
+        STYLE,
+        escape_html(title)
+    );
+
+    render_summary(&mut html, summary);
+    render_weekly_chart(&mut html, weekly);
+    render_treemap(&mut html, directories);
+    render_prompts(&mut html, prompts);
+    render_tool_models(&mut html, &summary.range_stats);
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn render_summary(html: &mut String, summary: &RangeAuthorshipStats) {
+    let stats = &summary.range_stats;
+    let ai_percent = if stats.git_diff_added_lines > 0 {
+        (stats.ai_additions as f64 / stats.git_diff_added_lines as f64 * 100.0).min(100.0)
+    } else {
+        0.0
+    };
+    let cards = [
+        (
+            summary.authorship_stats.total_commits.to_string(),
+            "commits",
+        ),
+        (
+            summary.authorship_stats.commits_with_authorship.to_string(),
+            "commits with notes",
+        ),
+        (stats.git_diff_added_lines.to_string(), "lines added"),
+        (format!("{:.0}%", ai_percent), "AI share"),
+        (stats.ai_accepted.to_string(), "AI lines accepted"),
+        (stats.mixed_additions.to_string(), "AI lines edited"),
+        (
+            format!("{}m", stats.time_waiting_for_ai / 60),
+            "waiting for AI",
+        ),
+    ];
+    html.push_str("<div class=\"cards\">\n");
+    for (value, label) in cards {
+        let _ = writeln!(
+            html,
+            "<div class=\"card\"><div class=\"value\">{}</div><div class=\"label\">{}</div></div>",
+            value, label
+        );
+    }
+    html.push_str("</div>\n");
+}
+
+fn render_legend(html: &mut String) {
+    let _ = writeln!(
+        html,
+        "<p class=\"legend\"><span><i class=\"swatch\" style=\"background:{}\"></i>AI</span><span><i class=\"swatch\" style=\"background:{}\"></i>AI, edited</span><span><i class=\"swatch\" style=\"background:{}\"></i>Human</span></p>",
+        AI_COLOR, MIXED_COLOR, HUMAN_COLOR
+    );
+}
+
+fn render_weekly_chart(html: &mut String, weekly: &GroupedRangeStats) {
+    html.push_str("<h2>AI share over time</h2>\n");
+    if weekly.groups.is_empty() {
+        html.push_str("<p class=\"empty\">No commits in range</p>\n");
+        return;
+    }
+    render_legend(html);
+
+    let label_height = 20.0;
+    let slot = CHART_WIDTH / weekly.groups.len() as f64;
+    let bar_width = (slot * 0.8).max(1.0);
+    // Label at most ~12 weeks so they don't overlap
+    let label_every = weekly.groups.len().div_ceil(12);
+    let _ = writeln!(
+        html,
+        "<svg width=\"{}\" height=\"{}\" role=\"img\" aria-label=\"AI share per week\">",
+        CHART_WIDTH,
+        CHART_HEIGHT + label_height
+    );
+    for (i, group) in weekly.groups.iter().enumerate() {
+        let x = i as f64 * slot + (slot - bar_width) / 2.0;
+        let (ai, mixed, human) = line_shares(&group.stats);
+        let mut y = CHART_HEIGHT;
+        for (share, color) in [(ai, AI_COLOR), (mixed, MIXED_COLOR), (human, HUMAN_COLOR)] {
+            let height = share * CHART_HEIGHT;
+            y -= height;
+            let _ = writeln!(
+                html,
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"><title>{}: {:.0}% AI, {:.0}% edited, {:.0}% human ({} lines, {} commits)</title></rect>",
+                x,
+                y,
+                bar_width,
+                height,
+                color,
+                escape_html(&group.key),
+                ai * 100.0,
+                mixed * 100.0,
+                human * 100.0,
+                group.stats.git_diff_added_lines,
+                group.commits
+            );
+        }
+        if i % label_every == 0 {
+            let _ = writeln!(
+                html,
+                "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"10\" fill=\"#656d76\">{}</text>",
+                i as f64 * slot,
+                CHART_HEIGHT + 14.0,
+                escape_html(&group.key)
+            );
+        }
+    }
+    html.push_str("</svg>\n");
+}
+
+fn render_treemap(html: &mut String, directories: &GroupedRangeStats) {
+    html.push_str("<h2>Directories</h2>\n");
+    let mut groups: Vec<_> = directories
+        .groups
+        .iter()
+        .filter(|group| group.stats.git_diff_added_lines > 0)
+        .collect();
+    if groups.is_empty() {
+        html.push_str("<p class=\"empty\">No added lines in range</p>\n");
+        return;
+    }
+    groups.sort_by(|a, b| {
+        b.stats
+            .git_diff_added_lines
+            .cmp(&a.stats.git_diff_added_lines)
+            .then_with(|| a.key.cmp(&b.key))
+    });
+    html.push_str(
+        "<p class=\"legend\">Area is lines added, color is the share written by AI</p>\n",
+    );
+
+    let weights: Vec<f64> = groups
+        .iter()
+        .map(|group| group.stats.git_diff_added_lines as f64)
+        .collect();
+    let rects = squarify(
+        &weights,
+        Rect {
+            x: 0.0,
+            y: 0.0,
+            w: CHART_WIDTH,
+            h: TREEMAP_HEIGHT,
+        },
+    );
+    let _ = writeln!(
+        html,
+        "<svg width=\"{}\" height=\"{}\" role=\"img\" aria-label=\"Lines added per directory\">",
+        CHART_WIDTH, TREEMAP_HEIGHT
+    );
+    for (group, rect) in groups.iter().zip(rects) {
+        let (ai, mixed, _) = line_shares(&group.stats);
+        // From grey (all human) to purple (all AI)
+        let share = ai + mixed;
+        let mix = |from: u8, to: u8| (from as f64 + (to as f64 - from as f64) * share) as u8;
+        let fill = format!(
+            "#{:02x}{:02x}{:02x}",
+            mix(0xea, 0x82),
+            mix(0xee, 0x50),
+            mix(0xf2, 0xdf)
+        );
+        let text_fill = if share > 0.5 { "#ffffff" } else { "#1f2328" };
+        let _ = writeln!(
+            html,
+            "<g><rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\" stroke=\"#ffffff\" stroke-width=\"2\"><title>{}: {} lines added, {:.0}% AI</title></rect>",
+            rect.x,
+            rect.y,
+            rect.w,
+            rect.h,
+            fill,
+            escape_html(&group.key),
+            group.stats.git_diff_added_lines,
+            share * 100.0
+        );
+        if rect.w > 40.0 && rect.h > 18.0 {
+            let _ = writeln!(
+                html,
+                "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"12\" fill=\"{}\">{} ({:.0}%)</text>",
+                rect.x + 6.0,
+                rect.y + 16.0,
+                text_fill,
+                escape_html(&group.key),
+                share * 100.0
+            );
+        }
+        html.push_str("</g>\n");
+    }
+    html.push_str("</svg>\n");
+}
+
+fn render_prompts(html: &mut String, prompts: &[PromptSummary]) {
+    html.push_str("<h2>Top prompts by accepted lines</h2>\n");
+    if prompts.is_empty() {
+        html.push_str("<p class=\"empty\">No prompts recorded in range</p>\n");
+        return;
+    }
+    html.push_str("<table>\n<tr><th>Prompt</th><th>Tool / model</th><th>Author</th><th class=\"num\">Accepted</th><th class=\"num\">Edited</th></tr>\n");
+    for prompt in prompts {
+        let _ = writeln!(
+            html,
+            "<tr><td><code>{}</code><br>{}</td><td>{} / {}</td><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+            escape_html(&prompt.id),
+            escape_html(&prompt.snippet),
+            escape_html(&prompt.tool),
+            escape_html(&prompt.model),
+            escape_html(prompt.human_author.as_deref().unwrap_or("")),
+            prompt.accepted_lines,
+            prompt.overriden_lines
+        );
+    }
+    html.push_str("</table>\n");
+}
+
+fn render_tool_models(html: &mut String, stats: &CommitStats) {
+    html.push_str("<h2>Tools and models</h2>\n");
+    if stats.tool_model_breakdown.is_empty() {
+        html.push_str("<p class=\"empty\">No AI lines in range</p>\n");
+        return;
+    }
+    let max_additions = stats
+        .tool_model_breakdown
+        .values()
+        .map(|tool_stats| tool_stats.ai_additions)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    html.push_str("<table>\n<tr><th>Tool</th><th>Model</th><th class=\"num\">AI lines</th><th class=\"num\">Accepted</th><th class=\"num\">Edited</th><th class=\"num\">Generated</th><th class=\"num\">Waiting</th><th></th></tr>\n");
+    for (tool_model, tool_stats) in &stats.tool_model_breakdown {
+        let (tool, model) = tool_model.split_once("::").unwrap_or((tool_model, ""));
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}m</td><td style=\"width:25%\"><div class=\"bar\" style=\"width:{:.0}%\"></div></td></tr>",
+            escape_html(tool),
+            escape_html(model),
+            tool_stats.ai_additions,
+            tool_stats.ai_accepted,
+            tool_stats.mixed_additions,
+            tool_stats.total_ai_additions,
+            tool_stats.time_waiting_for_ai / 60,
+            tool_stats.ai_additions as f64 / max_additions as f64 * 100.0
+        );
+    }
+    html.push_str("</table>\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_squarify_fills_bounds() {
+        let bounds = Rect {
+            x: 0.0,
+            y: 0.0,
+            w: 600.0,
+            h: 400.0,
+        };
+        let weights = [6.0, 6.0, 4.0, 3.0, 2.0, 2.0, 1.0];
+        let rects = squarify(&weights, bounds);
+        assert_eq!(rects.len(), weights.len());
+
+        let total: f64 = weights.iter().sum();
+        for (weight, rect) in weights.iter().zip(&rects) {
+            let expected = weight / total * bounds.w * bounds.h;
+            assert!((rect.w * rect.h - expected).abs() < 1e-6);
+            assert!(rect.x >= -1e-9 && rect.x + rect.w <= bounds.w + 1e-6);
+            assert!(rect.y >= -1e-9 && rect.y + rect.h <= bounds.h + 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html("<script>\"a\" & 'b'</script>"),
+            "&lt;script&gt;&quot;a&quot; &amp; &#39;b&#39;&lt;/script&gt;"
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use crate::authorship::authorship_log::PromptRecord;
+use crate::authorship::authorship_log_serialization::AuthorshipMetadata;
+use crate::authorship::stats::CommitStats;
+use crate::commands::blame::JsonBlameOutput;
+use crate::commands::diff::DiffJson;
+use schemars::generate::SchemaSettings;
+use schemars::{JsonSchema, Schema};
+
+/// Every exported schema as (name, what it describes)
+pub const SCHEMA_NAMES: &[(&str, &str)] = &[
+    (
+        "authorship-metadata",
+        "JSON metadata below the '---' divider of an authorship note",
+    ),
+    ("prompt-record", "A prompt session in the note metadata"),
+    ("diff", "Output of 'git-ai diff --json'"),
+    ("commit-stats", "Output of 'git-ai stats --json'"),
+    ("blame", "Output of 'git-ai blame --json'"),
+];
+
+/// Handle the `schema` command
+///
+/// Usage: `git-ai schema [<name>]`
+///
+/// Prints the JSON Schema for one of git-ai's serialized formats. Without a name,
+/// lists the available schemas. The same schemas are checked in under `schemas/`.
+pub fn handle_schema(args: &[String]) {
+    let Some(name) = args.first() else {
+        println!("Available schemas:");
+        for (name, description) in SCHEMA_NAMES {
+            println!("  {:<21} {}", name, description);
+        }
+        return;
+    };
+
+    if args.len() > 1 {
+        eprintln!("Error: schema accepts a single schema name");
+        std::process::exit(1);
+    }
+
+    match schema_for_name(name) {
+        Some(schema) => println!("{}", schema_to_string(&schema)),
+        None => {
+            eprintln!(
+                "Unknown schema: {}. Run 'git-ai schema' to list available schemas",
+                name
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Generate the schema registered under `name`
+pub fn schema_for_name(name: &str) -> Option<Schema> {
+    match name {
+        "authorship-metadata" => Some(output_schema::<AuthorshipMetadata>()),
+        "prompt-record" => Some(output_schema::<PromptRecord>()),
+        "diff" => Some(output_schema::<DiffJson>()),
+        "commit-stats" => Some(output_schema::<CommitStats>()),
+        "blame" => Some(output_schema::<JsonBlameOutput>()),
+        _ => None,
+    }
+}
+
+/// Pretty JSON exactly as printed by `git-ai schema` and stored under `schemas/`
+pub fn schema_to_string(schema: &Schema) -> String {
+    serde_json::to_string_pretty(schema).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// Schemas describe what git-ai writes, so optional fields follow `skip_serializing_if`
+fn output_schema<T: JsonSchema>() -> Schema {
+    SchemaSettings::draft2020_12()
+        .for_serialize()
+        .into_generator()
+        .into_root_schema_for::<T>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::authorship_log::LineRange;
+    use crate::authorship::authorship_log_serialization::AuthorshipLog;
+    use crate::authorship::stats::ToolModelHeadlineStats;
+    use crate::authorship::transcript::Message;
+    use crate::authorship::working_log::AgentId;
+    use crate::commands::blame::PromptRecordWithOtherFiles;
+    use crate::commands::diff::FileDiffJson;
+    use serde::Serialize;
+    use std::collections::{BTreeMap, HashMap};
+    use std::path::PathBuf;
+
+    fn schema_path(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("schemas")
+            .join(format!("{}.schema.json", name))
+    }
+
+    fn sample_prompt() -> PromptRecord {
+        PromptRecord {
+            agent_id: AgentId {
+                tool: "cursor".to_string(),
+                id: "session-1".to_string(),
+                model: "test-model".to_string(),
+            },
+            human_author: Some("Test User <test@example.com>".to_string()),
+            messages: vec![
+                Message::user("Add a helper".to_string(), None),
+                Message::assistant("Done".to_string(), Some("2025-01-01T00:00:00Z".to_string())),
+                Message::tool_use("edit".to_string(), serde_json::json!({"path": "a.rs"})),
+            ],
+            total_additions: 3,
+            total_deletions: 1,
+            accepted_lines: 2,
+            overriden_lines: 1,
+            messages_url: Some("https://example.com/cas/abc".to_string()),
+        }
+    }
+
+    fn assert_valid<T: Serialize>(name: &str, value: &T) {
+        let schema = serde_json::to_value(schema_for_name(name).unwrap()).unwrap();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        let instance = serde_json::to_value(value).unwrap();
+        let errors: Vec<String> = validator
+            .iter_errors(&instance)
+            .map(|e| format!("{} at {}", e, e.instance_path))
+            .collect();
+        assert!(
+            errors.is_empty(),
+            "{} output doesn't match its schema: {:?}",
+            name,
+            errors
+        );
+    }
+
+    #[test]
+    fn test_shipped_schemas_are_up_to_date() {
+        for (name, _) in SCHEMA_NAMES {
+            let generated = schema_to_string(&schema_for_name(name).unwrap());
+            let path = schema_path(name);
+            let shipped = std::fs::read_to_string(&path).unwrap_or_default();
+            assert_eq!(
+                shipped.trim_end(),
+                generated,
+                "{} is out of date; regenerate it with 'git-ai schema {} > schemas/{}.schema.json'",
+                path.display(),
+                name,
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_serialized_output_matches_schemas() {
+        let mut log = AuthorshipLog::new();
+        log.metadata.base_commit_sha = "abc123".to_string();
+        log.metadata
+            .prompts
+            .insert("0123456789abcdef".to_string(), sample_prompt());
+        log.record_deletions("src/lib.rs", "0123456789abcdef", &[LineRange::Range(2, 4)]);
+        log.metadata.signature = Some("-----BEGIN SSH SIGNATURE-----".to_string());
+        assert_valid("authorship-metadata", &log.metadata);
+        assert_valid("prompt-record", &sample_prompt());
+
+        let diff = DiffJson {
+            files: BTreeMap::from([(
+                "src/lib.rs".to_string(),
+                FileDiffJson {
+                    annotations: BTreeMap::from([(
+                        "0123456789abcdef".to_string(),
+                        vec![LineRange::Single(1), LineRange::Range(3, 5)],
+                    )]),
+                    diff: "@@ -1 +1 @@\n-old\n+new\n".to_string(),
+                    base_content: "old\n".to_string(),
+                    deletions: BTreeMap::from([(
+                        "0123456789abcdef".to_string(),
+                        vec![LineRange::Single(1)],
+                    )]),
+                },
+            )]),
+            prompts: BTreeMap::from([("0123456789abcdef".to_string(), sample_prompt())]),
+        };
+        assert_valid("diff", &diff);
+
+        let stats = CommitStats {
+            ai_additions: 3,
+            tool_model_breakdown: BTreeMap::from([(
+                "cursor::test-model".to_string(),
+                ToolModelHeadlineStats::default(),
+            )]),
+            ..Default::default()
+        };
+        assert_valid("commit-stats", &stats);
+
+        let blame = JsonBlameOutput {
+            lines: BTreeMap::from([("1-3".to_string(), "0123456789abcdef".to_string())]),
+            prompts: HashMap::from([(
+                "0123456789abcdef".to_string(),
+                PromptRecordWithOtherFiles {
+                    prompt_record: sample_prompt(),
+                    other_files: vec!["src/main.rs".to_string()],
+                    commits: vec!["abc123".to_string()],
+                },
+            )]),
+        };
+        assert_valid("blame", &blame);
+    }
+
+    #[test]
+    fn test_unknown_schema_name() {
+        assert!(schema_for_name("nope").is_none());
+    }
+}
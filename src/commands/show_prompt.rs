@@ -1,4 +1,4 @@
-use crate::authorship::prompt_utils::find_prompt;
+use crate::authorship::prompt_utils::{find_prompt, resolve_cas_messages};
 use crate::git::find_repository;
 
 /// Handle the `show-prompt` command
@@ -30,7 +30,15 @@ pub fn handle_show_prompt(args: &[String]) {
         parsed.commit.as_deref(),
         parsed.offset,
     ) {
-        Ok((commit_sha, prompt_record)) => {
+        Ok((commit_sha, mut prompt_record)) => {
+            // Large transcripts may have been offloaded to CAS when the commit was made
+            if let Err(e) = resolve_cas_messages(&mut prompt_record) {
+                eprintln!(
+                    "Warning: could not load messages from {}: {}",
+                    prompt_record.messages_url.as_deref().unwrap_or_default(),
+                    e
+                );
+            }
             // Output the prompt as JSON, including the commit SHA for context
             let output = serde_json::json!({
                 "commit": commit_sha,
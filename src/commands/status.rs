@@ -1,4 +1,4 @@
-use crate::authorship::stats::{CommitStats, write_stats_to_terminal};
+use crate::authorship::stats::{CommitStats, add_commit_stats, write_stats_to_terminal};
 use crate::authorship::virtual_attribution::VirtualAttributions;
 use crate::authorship::working_log::CheckpointKind;
 use crate::commands::checkpoint;
@@ -6,6 +6,7 @@ use crate::error::GitAiError;
 use crate::git::find_repository;
 use crate::git::repo_storage::InitialAttributions;
 use crate::git::repository::Repository;
+use crate::utils::debug_log;
 use serde::Serialize;
 use std::collections::HashSet;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -17,6 +18,8 @@ struct CheckpointInfo {
     deletions: u32,
     tool_model: String,
     is_human: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    submodule: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -27,22 +30,25 @@ struct StatusOutput {
 
 pub fn handle_status(args: &[String]) {
     let mut json_output = false;
+    let mut recurse_submodules = false;
 
     let mut i = 0;
     while i < args.len() {
-        if args[i].as_str() == "--json" {
-            json_output = true;
+        match args[i].as_str() {
+            "--json" => json_output = true,
+            "--recurse-submodules" => recurse_submodules = true,
+            _ => {}
         }
         i += 1;
     }
 
-    if let Err(e) = run_status(json_output) {
+    if let Err(e) = run_status(json_output, recurse_submodules) {
         eprintln!("Error: {}", e);
         std::process::exit(1);
     }
 }
 
-fn run_status(json: bool) -> Result<(), GitAiError> {
+fn run_status(json: bool, recurse_submodules: bool) -> Result<(), GitAiError> {
     let repo = find_repository(&[])?;
 
     let default_user_name = match repo.config_get_str("user.name") {
@@ -50,24 +56,26 @@ fn run_status(json: bool) -> Result<(), GitAiError> {
         _ => "unknown".to_string(),
     };
 
-    let _ = checkpoint::run(
-        &repo,
-        &default_user_name,
-        CheckpointKind::Human,
-        false,
-        false,
-        true,
-        None,
-        false,
-    );
+    let head_sha = repo.head()?.target()?;
 
-    let head = repo.head()?;
-    let head_sha = head.target()?;
-
-    let working_log = repo.storage.working_log_for_base_commit(&head_sha);
-    let checkpoints = working_log.read_all_checkpoints()?;
+    let (mut stats, mut checkpoint_infos) = repo_status(&repo, &default_user_name, None)?;
+    if recurse_submodules {
+        for (submodule_path, submodule) in checked_out_submodules(&repo, "") {
+            // Submodules that haven't made a commit yet have nothing to compare against
+            match repo_status(&submodule, &default_user_name, Some(&submodule_path)) {
+                Ok((submodule_stats, submodule_checkpoints)) => {
+                    add_commit_stats(&mut stats, &submodule_stats);
+                    checkpoint_infos.extend(submodule_checkpoints);
+                }
+                Err(e) => debug_log(&format!(
+                    "Skipping status for submodule {}: {}",
+                    submodule_path, e
+                )),
+            }
+        }
+    }
 
-    if checkpoints.is_empty() {
+    if checkpoint_infos.is_empty() {
         if json {
             let output = StatusOutput {
                 stats: CommitStats::default(),
@@ -92,6 +100,78 @@ fn run_status(json: bool) -> Result<(), GitAiError> {
         return Ok(());
     }
 
+    if json {
+        let output = StatusOutput {
+            stats,
+            checkpoints: checkpoint_infos,
+        };
+        let json_str = serde_json::to_string(&output)?;
+        println!("{}", json_str);
+        return Ok(());
+    }
+
+    write_stats_to_terminal(&stats, true);
+
+    println!();
+    for cp in &checkpoint_infos {
+        let add_str = if cp.additions > 0 {
+            format!("+{}", cp.additions)
+        } else {
+            "0".to_string()
+        };
+        let del_str = if cp.deletions > 0 {
+            format!("-{}", cp.deletions)
+        } else {
+            "0".to_string()
+        };
+
+        let mut line = format!(
+            "{:<14} {:>5}  {:>5}  {}",
+            cp.time_ago, add_str, del_str, cp.tool_model
+        );
+        if let Some(submodule) = &cp.submodule {
+            line.push_str(&format!("  ({})", submodule));
+        }
+
+        if cp.is_human {
+            println!("\x1b[90m{}\x1b[0m", line);
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    Ok(())
+}
+
+/// Checkpoint a repository's working tree and summarize what changed since HEAD
+///
+/// Returns empty stats when no checkpoints have been recorded since the last commit.
+fn repo_status(
+    repo: &Repository,
+    default_user_name: &str,
+    submodule: Option<&str>,
+) -> Result<(CommitStats, Vec<CheckpointInfo>), GitAiError> {
+    let _ = checkpoint::run(
+        repo,
+        default_user_name,
+        CheckpointKind::Human,
+        false,
+        false,
+        true,
+        None,
+        false,
+    );
+
+    let head = repo.head()?;
+    let head_sha = head.target()?;
+
+    let working_log = repo.storage.working_log_for_base_commit(&head_sha);
+    let checkpoints = working_log.read_all_checkpoints()?;
+
+    if checkpoints.is_empty() {
+        return Ok((CommitStats::default(), Vec::new()));
+    }
+
     let mut checkpoint_infos = Vec::new();
 
     for checkpoint in checkpoints.iter().rev() {
@@ -104,7 +184,7 @@ fn run_status(json: bool) -> Result<(), GitAiError> {
             .agent_id
             .as_ref()
             .map(|a| format!("{} {}", capitalize(&a.tool), &a.model))
-            .unwrap_or_else(|| default_user_name.clone());
+            .unwrap_or_else(|| default_user_name.to_string());
 
         let is_human = checkpoint.kind == CheckpointKind::Human;
         checkpoint_infos.push(CheckpointInfo {
@@ -113,13 +193,14 @@ fn run_status(json: bool) -> Result<(), GitAiError> {
             deletions,
             tool_model,
             is_human,
+            submodule: submodule.map(str::to_string),
         });
     }
 
     let working_va = VirtualAttributions::from_just_working_log(
         repo.clone(),
         head_sha.clone(),
-        Some(default_user_name.clone()),
+        Some(default_user_name.to_string()),
     )?;
 
     let pathspecs: HashSet<String> = checkpoints
@@ -128,14 +209,14 @@ fn run_status(json: bool) -> Result<(), GitAiError> {
         .collect();
 
     let (authorship_log, initial) = working_va.to_authorship_log_and_initial_working_log(
-        &repo,
+        repo,
         &head_sha,
         &head_sha,
         Some(&pathspecs),
     )?;
 
     // Get actual git diff stats between HEAD and working directory (like post_commit does)
-    let (total_additions, total_deletions) = get_working_dir_diff_stats(&repo, Some(&pathspecs))?;
+    let (total_additions, total_deletions) = get_working_dir_diff_stats(repo, Some(&pathspecs))?;
 
     // For status (uncommitted changes), the AI attributions are in `initial` (uncommitted),
     // not in authorship_log.attestations (which is for committed changes).
@@ -149,44 +230,22 @@ fn run_status(json: bool) -> Result<(), GitAiError> {
         ai_accepted,
     );
 
-    if json {
-        let output = StatusOutput {
-            stats,
-            checkpoints: checkpoint_infos,
-        };
-        let json_str = serde_json::to_string(&output)?;
-        println!("{}", json_str);
-        return Ok(());
-    }
-
-    write_stats_to_terminal(&stats, true);
+    Ok((stats, checkpoint_infos))
+}
 
-    println!();
-    for cp in &checkpoint_infos {
-        let add_str = if cp.additions > 0 {
-            format!("+{}", cp.additions)
-        } else {
-            "0".to_string()
-        };
-        let del_str = if cp.deletions > 0 {
-            format!("-{}", cp.deletions)
-        } else {
-            "0".to_string()
+/// Every checked-out submodule, nested ones included, with its path from the top repository
+fn checked_out_submodules(repo: &Repository, prefix: &str) -> Vec<(String, Repository)> {
+    let mut submodules = Vec::new();
+    for path in repo.submodule_paths().unwrap_or_default() {
+        let Ok(submodule) = repo.open_submodule(&path) else {
+            continue;
         };
-
-        let line = format!(
-            "{:<14} {:>5}  {:>5}  {}",
-            cp.time_ago, add_str, del_str, cp.tool_model
-        );
-
-        if cp.is_human {
-            println!("\x1b[90m{}\x1b[0m", line);
-        } else {
-            println!("{}", line);
-        }
+        let display_path = format!("{}{}", prefix, path);
+        let nested = checked_out_submodules(&submodule, &format!("{}/", display_path));
+        submodules.push((display_path, submodule));
+        submodules.extend(nested);
     }
-
-    Ok(())
+    submodules
 }
 
 fn format_time_ago(timestamp: u64) -> String {
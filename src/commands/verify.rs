@@ -0,0 +1,278 @@
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::signing::{
+    SignatureStatus, configured_allowed_signers, verify_authorship_log,
+};
+use crate::commands::show::resolve_commits;
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::refs::{list_commits_with_notes, show_authorship_note};
+use crate::git::repository::Repository;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Handle the `verify` command
+///
+/// Usage: `git-ai verify [<rev|range>] [--allowed-signers <file>] [--json]`
+///
+/// Checks the SSH signatures on authorship notes against an allowed-signers file
+/// (`gitai.allowedSignersFile`, falling back to `gpg.ssh.allowedSignersFile`).
+/// Without a revision, every commit that has a note under refs/notes/ai is checked.
+/// Exits with status 1 when any note is unsigned or fails verification.
+pub fn handle_verify(args: &[String]) {
+    let parsed = match parse_args(args) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let Some(allowed_signers) = parsed
+        .allowed_signers
+        .clone()
+        .or_else(|| configured_allowed_signers(&repo))
+    else {
+        eprintln!(
+            "No allowed signers file configured. Pass --allowed-signers <file> or set gitai.allowedSignersFile"
+        );
+        std::process::exit(1);
+    };
+
+    let commits = match &parsed.spec {
+        Some(spec) => resolve_commits(&repo, spec),
+        None => list_commits_with_notes(&repo),
+    };
+    let commits = match commits {
+        Ok(commits) => commits,
+        Err(e) => {
+            eprintln!("Failed to resolve commits: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let report = match verify_commits(&repo, &commits, &allowed_signers) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("verify failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if parsed.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).unwrap_or_else(|_| "{}".to_string())
+        );
+    } else {
+        print_report(&report);
+    }
+
+    if report.has_failures() {
+        std::process::exit(1);
+    }
+}
+
+#[derive(Debug)]
+pub struct ParsedArgs {
+    pub spec: Option<String>,
+    pub allowed_signers: Option<PathBuf>,
+    pub json: bool,
+}
+
+pub fn parse_args(args: &[String]) -> Result<ParsedArgs, String> {
+    let mut spec: Option<String> = None;
+    let mut allowed_signers: Option<PathBuf> = None;
+    let mut json = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        let arg = &args[i];
+        if arg == "--json" {
+            json = true;
+        } else if arg == "--allowed-signers" {
+            if i + 1 >= args.len() {
+                return Err("--allowed-signers requires a value".to_string());
+            }
+            i += 1;
+            allowed_signers = Some(PathBuf::from(&args[i]));
+        } else if arg.starts_with('-') {
+            return Err(format!("Unknown option: {}", arg));
+        } else {
+            if spec.is_some() {
+                return Err("verify accepts at most one revision or range".to_string());
+            }
+            spec = Some(arg.clone());
+        }
+        i += 1;
+    }
+
+    Ok(ParsedArgs {
+        spec,
+        allowed_signers,
+        json,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyState {
+    /// Signature is valid and made by a key in the allowed signers file
+    Good,
+    /// Signature is present but invalid, or made by an unknown key
+    Bad,
+    /// The note carries no signature
+    Unsigned,
+    /// The note can't be parsed, so there is nothing to verify
+    Unparseable,
+    /// The commit has no authorship note
+    NoNote,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CommitVerification {
+    pub commit: String,
+    pub state: VerifyState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub principal: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VerifyReport {
+    pub commits: Vec<CommitVerification>,
+}
+
+impl VerifyReport {
+    /// Any note that exists but can't be trusted fails verification; commits without notes don't
+    pub fn has_failures(&self) -> bool {
+        self.commits.iter().any(|c| {
+            matches!(
+                c.state,
+                VerifyState::Bad | VerifyState::Unsigned | VerifyState::Unparseable
+            )
+        })
+    }
+}
+
+/// Verify the note signature of every commit in `commits`
+pub fn verify_commits(
+    repo: &Repository,
+    commits: &[String],
+    allowed_signers: &Path,
+) -> Result<VerifyReport, GitAiError> {
+    let mut report = VerifyReport::default();
+
+    for commit_sha in commits {
+        let mut result = CommitVerification {
+            commit: commit_sha.clone(),
+            state: VerifyState::NoNote,
+            principal: None,
+            reason: None,
+        };
+
+        if let Some(content) = show_authorship_note(repo, commit_sha) {
+            match AuthorshipLog::deserialize_from_string(&content) {
+                Ok(log) => match verify_authorship_log(&log, allowed_signers)? {
+                    SignatureStatus::Valid { principal } => {
+                        result.state = VerifyState::Good;
+                        result.principal = Some(principal);
+                    }
+                    SignatureStatus::Invalid { reason } => {
+                        result.state = VerifyState::Bad;
+                        result.reason = Some(reason);
+                    }
+                    SignatureStatus::Unsigned => result.state = VerifyState::Unsigned,
+                },
+                Err(e) => {
+                    result.state = VerifyState::Unparseable;
+                    result.reason = Some(e.to_string());
+                }
+            }
+        }
+
+        report.commits.push(result);
+    }
+
+    Ok(report)
+}
+
+fn print_report(report: &VerifyReport) {
+    for result in &report.commits {
+        let short_sha = &result.commit[..result.commit.len().min(7)];
+        match result.state {
+            VerifyState::Good => println!(
+                "{} good signature from {}",
+                short_sha,
+                result.principal.as_deref().unwrap_or("unknown")
+            ),
+            VerifyState::Bad => println!(
+                "{} BAD signature: {}",
+                short_sha,
+                result.reason.as_deref().unwrap_or("verification failed")
+            ),
+            VerifyState::Unsigned => println!("{} unsigned note", short_sha),
+            VerifyState::Unparseable => println!(
+                "{} unparseable note: {}",
+                short_sha,
+                result.reason.as_deref().unwrap_or("")
+            ),
+            VerifyState::NoNote => println!("{} no authorship note", short_sha),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let parsed = parse_args(&args(&[
+            "HEAD~3..HEAD",
+            "--allowed-signers",
+            "signers",
+            "--json",
+        ]))
+        .unwrap();
+        assert_eq!(parsed.spec.as_deref(), Some("HEAD~3..HEAD"));
+        assert_eq!(parsed.allowed_signers, Some(PathBuf::from("signers")));
+        assert!(parsed.json);
+
+        assert!(parse_args(&args(&["--allowed-signers"])).is_err());
+        assert!(parse_args(&args(&["HEAD", "HEAD~1"])).is_err());
+        assert!(parse_args(&args(&["--bogus"])).is_err());
+    }
+
+    #[test]
+    fn test_missing_notes_are_not_failures() {
+        let mut report = VerifyReport::default();
+        report.commits.push(CommitVerification {
+            commit: "abc".to_string(),
+            state: VerifyState::NoNote,
+            principal: None,
+            reason: None,
+        });
+        assert!(!report.has_failures());
+
+        report.commits.push(CommitVerification {
+            commit: "def".to_string(),
+            state: VerifyState::Unsigned,
+            principal: None,
+            reason: None,
+        });
+        assert!(report.has_failures());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::error::GitAiError;
-use crate::git::repository::{Repository, Tree, exec_git};
+use crate::git::repository::{Repository, Tree, exec_git, exec_git_stdin_with_env};
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
@@ -171,6 +171,107 @@ impl Repository {
 
         Ok(Diff { deltas })
     }
+
+    /// Pair files that disappeared with the new files git's rename detection says they became
+    ///
+    /// Both sides are given as (path, content). They are written to two throwaway trees so that
+    /// `git diff -M` can compare them the same way it compares commits, which works for files
+    /// that were never committed. The trees and blobs go to a temporary object directory that
+    /// is removed afterwards, so the repository's object store is left alone. Returns
+    /// (old path, new path) pairs.
+    pub fn detect_renames(
+        &self,
+        old_files: &[(String, String)],
+        new_files: &[(String, String)],
+    ) -> Result<Vec<(String, String)>, GitAiError> {
+        if old_files.is_empty() || new_files.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let scratch_dir = self
+            .path()
+            .join("ai")
+            .join(format!("rename_scratch_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(scratch_dir.join("objects"))?;
+        let env = vec![
+            (
+                "GIT_OBJECT_DIRECTORY".to_string(),
+                scratch_dir.join("objects").to_string_lossy().to_string(),
+            ),
+            (
+                "GIT_INDEX_FILE".to_string(),
+                scratch_dir.join("index").to_string_lossy().to_string(),
+            ),
+        ];
+
+        let result = (|| {
+            let old_tree = self.write_tree_from_contents(old_files, &env)?;
+            let new_tree = self.write_tree_from_contents(new_files, &env)?;
+
+            let mut args = self.global_args_for_exec();
+            args.push("diff".to_string());
+            args.push("--raw".to_string());
+            args.push("-z".to_string());
+            args.push("--no-abbrev".to_string());
+            args.push("-M".to_string());
+            args.push("--diff-filter=R".to_string());
+            args.push(old_tree);
+            args.push(new_tree);
+
+            let output = exec_git_stdin_with_env(&args, &env, &[])?;
+            parse_diff_raw(&output.stdout)
+        })();
+        let _ = std::fs::remove_dir_all(&scratch_dir);
+
+        let renames = result?
+            .into_iter()
+            .filter(|delta| delta.status() == DiffStatus::Renamed)
+            .filter_map(|delta| {
+                let old_path = delta.old_file().path()?.to_string_lossy().to_string();
+                let new_path = delta.new_file().path()?.to_string_lossy().to_string();
+                Some((old_path, new_path))
+            })
+            .collect();
+
+        Ok(renames)
+    }
+
+    /// Write a tree holding the given (path, content) files
+    ///
+    /// `env` points git at the scratch object directory and index `detect_renames` set up.
+    fn write_tree_from_contents(
+        &self,
+        files: &[(String, String)],
+        env: &[(String, String)],
+    ) -> Result<String, GitAiError> {
+        let mut index_info = String::new();
+        for (path, content) in files {
+            let mut args = self.global_args_for_exec();
+            args.push("hash-object".to_string());
+            args.push("-w".to_string());
+            args.push("--stdin".to_string());
+            let blob =
+                String::from_utf8(exec_git_stdin_with_env(&args, env, content.as_bytes())?.stdout)?;
+            index_info.push_str(&format!("100644 blob {}\t{}\n", blob.trim(), path));
+        }
+
+        // Both trees share the scratch index, so start from an empty one
+        let mut args = self.global_args_for_exec();
+        args.push("read-tree".to_string());
+        args.push("--empty".to_string());
+        exec_git_stdin_with_env(&args, env, &[])?;
+
+        let mut args = self.global_args_for_exec();
+        args.push("update-index".to_string());
+        args.push("--add".to_string());
+        args.push("--index-info".to_string());
+        exec_git_stdin_with_env(&args, env, index_info.as_bytes())?;
+
+        let mut args = self.global_args_for_exec();
+        args.push("write-tree".to_string());
+        let tree = String::from_utf8(exec_git_stdin_with_env(&args, env, &[])?.stdout)?;
+        Ok(tree.trim().to_string())
+    }
 }
 
 /// Parse the raw output from git diff --raw -z
@@ -178,8 +279,8 @@ impl Repository {
 /// Format (when using -z, NUL bytes separate fields):
 /// :<old_mode> <new_mode> <old_hash> <new_hash> <status>\0<path>\0
 ///
-/// For renames/copies:
-/// :<old_mode> <new_mode> <old_hash> <new_hash> R<score>\0<path>\0<old_path>\0
+/// For renames/copies, the source path comes first:
+/// :<old_mode> <new_mode> <old_hash> <new_hash> R<score>\0<old_path>\0<path>\0
 fn parse_diff_raw(data: &[u8]) -> Result<Vec<DiffDelta>, GitAiError> {
     let mut deltas = Vec::new();
     let mut parts = data
@@ -243,11 +344,11 @@ fn parse_diff_raw(data: &[u8]) -> Result<Vec<DiffDelta>, GitAiError> {
 
         // For renames and copies, there are two paths
         let (new_path, old_path) = if matches!(status, DiffStatus::Renamed | DiffStatus::Copied) {
-            let old_path_bytes = parts
+            let new_path_bytes = parts
                 .next()
-                .ok_or_else(|| GitAiError::Generic("Missing old path for rename/copy".into()))?;
-            let old_path_str = std::str::from_utf8(old_path_bytes)?;
-            (path.to_string(), Some(old_path_str.to_string()))
+                .ok_or_else(|| GitAiError::Generic("Missing new path for rename/copy".into()))?;
+            let new_path_str = std::str::from_utf8(new_path_bytes)?;
+            (new_path_str.to_string(), Some(path.to_string()))
         } else {
             (path.to_string(), None)
         };
@@ -305,7 +406,7 @@ mod tests {
         raw.extend_from_slice(b":100644 000000 1234567890abcdef1234567890abcdef12345678 0000000000000000000000000000000000000000 D\0src/old.rs\0");
 
         // Renamed file with 95% similarity
-        raw.extend_from_slice(b":100644 100644 abcdef1234567890abcdef1234567890abcdef12 abcdef1234567890abcdef1234567890abcdef12 R95\0src/renamed.rs\0src/original.rs\0");
+        raw.extend_from_slice(b":100644 100644 abcdef1234567890abcdef1234567890abcdef12 abcdef1234567890abcdef1234567890abcdef12 R95\0src/original.rs\0src/renamed.rs\0");
 
         let deltas = parse_diff_raw(&raw).expect("parse should succeed");
 
@@ -1,10 +1,21 @@
-use crate::authorship::authorship_log_serialization::{AUTHORSHIP_LOG_VERSION, AuthorshipLog};
+use crate::authorship::anchors::anchor_note_if_configured;
+use crate::authorship::authorship_log::PromptRecord;
+use crate::authorship::authorship_log_serialization::{
+    AUTHORSHIP_LOG_VERSION, AuthorshipLog, FileAttestation, encode_note_if_configured,
+    read_attestations, read_deletions, read_prompts_for_hashes, read_schema_version,
+};
+use crate::authorship::notes_merge::union_authorship_logs;
+use crate::authorship::signing::sign_note_if_configured;
 use crate::authorship::working_log::Checkpoint;
 use crate::error::GitAiError;
-use crate::git::repository::{Repository, exec_git, exec_git_stdin};
+use crate::git::repository::{
+    Repository, exec_git, exec_git_stdin, exec_git_stdin_with_env, spawn_git_stdout,
+};
 use crate::utils::debug_log;
 use serde_json;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::BufReader;
+use std::process::ChildStdout;
 
 // Modern refspecs without force to enable proper merging
 pub const AI_AUTHORSHIP_REFNAME: &str = "ai";
@@ -24,11 +35,127 @@ pub fn notes_add(
     args.push("-".to_string()); // Read note content from stdin
     args.push(commit_sha.to_string());
 
+    // Sign the note when the repo has a signing key configured, so every writer
+    // (post-commit, rebase, cherry-pick, amend) produces verifiable notes. Compression
+    // comes last so the signature covers the plain-text note, anchors included.
+    let note_content = anchor_note_if_configured(repo, commit_sha, note_content);
+    let note_content =
+        encode_note_if_configured(repo, &sign_note_if_configured(repo, &note_content));
+
     // Use stdin to provide the note content to avoid command line length limits
     exec_git_stdin(&args, note_content.as_bytes())?;
     Ok(())
 }
 
+/// Replace the notes of many commits with a single new commit on refs/notes/ai
+///
+/// `updates` pairs a commit SHA with its new note content. The new notes tree is built
+/// in a temporary index and the ref is moved with a compare-and-swap `update-ref`, so
+/// readers see either every update or none of them. Returns the new notes commit.
+pub fn notes_add_batch(
+    repo: &Repository,
+    updates: &[(String, String)],
+    message: &str,
+) -> Result<String, GitAiError> {
+    let notes_ref = format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME);
+    let old_notes_commit = if ref_exists(repo, &notes_ref) {
+        let mut args = repo.global_args_for_exec();
+        args.push("rev-parse".to_string());
+        args.push(notes_ref.clone());
+        Some(
+            String::from_utf8(exec_git(&args)?.stdout)?
+                .trim()
+                .to_string(),
+        )
+    } else {
+        None
+    };
+
+    // Existing notes may live under fan-out paths (ab/cdef...), so reuse their paths
+    let mut note_paths: HashMap<String, String> = HashMap::new();
+    if let Some(old_commit) = &old_notes_commit {
+        let mut args = repo.global_args_for_exec();
+        args.push("ls-tree".to_string());
+        args.push("-r".to_string());
+        args.push("--name-only".to_string());
+        args.push(old_commit.clone());
+        for path in String::from_utf8(exec_git(&args)?.stdout)?.lines() {
+            note_paths.insert(path.replace('/', ""), path.to_string());
+        }
+    }
+
+    let mut index_info = String::new();
+    for (commit_sha, content) in updates {
+        let content = anchor_note_if_configured(repo, commit_sha, content);
+        let content = encode_note_if_configured(repo, &sign_note_if_configured(repo, &content));
+        let mut args = repo.global_args_for_exec();
+        args.push("hash-object".to_string());
+        args.push("-w".to_string());
+        args.push("--stdin".to_string());
+        let blob = String::from_utf8(exec_git_stdin(&args, content.as_bytes())?.stdout)?;
+        let path = note_paths
+            .get(commit_sha)
+            .cloned()
+            .unwrap_or_else(|| commit_sha.clone());
+        index_info.push_str(&format!("100644 blob {}\t{}\n", blob.trim(), path));
+    }
+
+    let index_dir = repo.path().join("ai");
+    std::fs::create_dir_all(&index_dir)?;
+    let index_file = index_dir.join(format!("notes_index_{}", uuid::Uuid::new_v4()));
+    let env = vec![(
+        "GIT_INDEX_FILE".to_string(),
+        index_file.to_string_lossy().to_string(),
+    )];
+
+    let result = (|| {
+        if let Some(old_commit) = &old_notes_commit {
+            let mut args = repo.global_args_for_exec();
+            args.push("read-tree".to_string());
+            args.push(old_commit.clone());
+            exec_git_stdin_with_env(&args, &env, &[])?;
+        }
+
+        let mut args = repo.global_args_for_exec();
+        args.push("update-index".to_string());
+        args.push("--add".to_string());
+        args.push("--index-info".to_string());
+        exec_git_stdin_with_env(&args, &env, index_info.as_bytes())?;
+
+        let mut args = repo.global_args_for_exec();
+        args.push("write-tree".to_string());
+        let tree = String::from_utf8(exec_git_stdin_with_env(&args, &env, &[])?.stdout)?;
+        Ok::<String, GitAiError>(tree.trim().to_string())
+    })();
+    let _ = std::fs::remove_file(&index_file);
+    let tree = result?;
+
+    let mut args = repo.global_args_for_exec();
+    args.push("commit-tree".to_string());
+    args.push(tree);
+    if let Some(old_commit) = &old_notes_commit {
+        args.push("-p".to_string());
+        args.push(old_commit.clone());
+    }
+    args.push("-m".to_string());
+    args.push(message.to_string());
+    let new_notes_commit = String::from_utf8(exec_git(&args)?.stdout)?
+        .trim()
+        .to_string();
+
+    // Compare-and-swap: fail rather than clobber notes written while we were working
+    let mut args = repo.global_args_for_exec();
+    args.push("update-ref".to_string());
+    args.push("-m".to_string());
+    args.push(message.to_string());
+    args.push(notes_ref);
+    args.push(new_notes_commit.clone());
+    args.push(old_notes_commit.unwrap_or_default());
+    exec_git(&args)?;
+
+    Ok(new_notes_commit)
+}
+
 // Check which commits from the given list have authorship notes.
 // Uses git cat-file --batch-check to efficiently check multiple commits in one invocation.
 // Returns a Vec of CommitAuthorship for each commit.
@@ -141,6 +268,104 @@ pub fn get_authorship(repo: &Repository, commit_sha: &str) -> Option<AuthorshipL
     Some(authorship_log)
 }
 
+/// Stream a commit's note from `git notes show` into `read`
+///
+/// git is stopped once `read` returns, so whatever it leaves unread is never transferred.
+fn read_note_stream<T>(
+    repo: &Repository,
+    commit_sha: &str,
+    read: impl FnOnce(BufReader<ChildStdout>) -> Option<T>,
+) -> Option<T> {
+    let mut args = repo.global_args_for_exec();
+    args.push("notes".to_string());
+    args.push("--ref=ai".to_string());
+    args.push("show".to_string());
+    args.push(commit_sha.to_string());
+
+    let mut child = spawn_git_stdout(&args).ok()?;
+    let result = child
+        .stdout
+        .take()
+        .and_then(|stdout| read(BufReader::new(stdout)));
+
+    let _ = child.kill();
+    let _ = child.wait();
+
+    result
+}
+
+/// Read only the attestation section of a commit's note
+///
+/// Streams `git notes show` and stops at the `---` divider, reading just the start of the
+/// metadata for its schema version. Returns None if there is no note, it can't be parsed
+/// or it has another schema version.
+pub fn get_authorship_attestations(
+    repo: &Repository,
+    commit_sha: &str,
+) -> Option<Vec<FileAttestation>> {
+    read_note_stream(repo, commit_sha, |mut reader| {
+        let attestations = read_attestations(&mut reader).ok()?;
+        let version = read_schema_version(reader).ok()?;
+        if version != AUTHORSHIP_LOG_VERSION {
+            debug_log(&format!(
+                "Ignoring note on {} with unsupported version {}",
+                commit_sha, version
+            ));
+            return None;
+        }
+        Some(attestations)
+    })
+}
+
+/// Load the prompt records for `hashes` from a commit's note, skipping every other prompt
+///
+/// The note is streamed from git, so it is never held in memory as a whole.
+pub fn get_authorship_prompts(
+    repo: &Repository,
+    commit_sha: &str,
+    hashes: &HashSet<String>,
+) -> Option<BTreeMap<String, PromptRecord>> {
+    read_note_stream(repo, commit_sha, |reader| {
+        read_prompts_for_hashes(reader, hashes).ok()
+    })
+}
+
+/// Load only `metadata.deletions` from a commit's note, streaming it like
+/// `get_authorship_prompts`
+pub fn get_authorship_deletions(
+    repo: &Repository,
+    commit_sha: &str,
+) -> Option<BTreeMap<String, BTreeMap<String, String>>> {
+    read_note_stream(repo, commit_sha, |reader| read_deletions(reader).ok())
+}
+
+/// List every commit SHA that currently has a note under refs/notes/ai
+/// Uses `git notes list`, which also reports notes attached to commits that no longer exist
+pub fn list_commits_with_notes(repo: &Repository) -> Result<Vec<String>, GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.push("notes".to_string());
+    args.push(format!("--ref={}", AI_AUTHORSHIP_REFNAME));
+    args.push("list".to_string());
+
+    let output = match exec_git(&args) {
+        Ok(output) => output,
+        // No notes ref yet
+        Err(GitAiError::GitCliError { .. }) if !ref_exists(repo, "refs/notes/ai") => {
+            return Ok(Vec::new());
+        }
+        Err(e) => return Err(e),
+    };
+    let stdout = String::from_utf8(output.stdout)
+        .map_err(|_| GitAiError::Generic("Failed to parse git notes list output".to_string()))?;
+
+    // Each line is "<note blob sha> <annotated commit sha>"
+    Ok(stdout
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .map(|sha| sha.to_string())
+        .collect())
+}
+
 #[allow(dead_code)]
 pub fn get_reference_as_working_log(
     repo: &Repository,
@@ -220,8 +445,19 @@ pub fn ref_exists(repo: &Repository, ref_name: &str) -> bool {
 }
 
 /// Merge notes from a source ref into refs/notes/ai
-/// Uses the 'ours' strategy to combine notes without data loss
+///
+/// Notes changed on only one side merge as usual. Commits whose note was changed on
+/// both sides are first resolved with the 'ours' strategy, then replaced by the union of
+/// both authorship logs (see `union_authorship_logs`) in one follow-up notes commit.
+/// Conflicting notes that can't be parsed keep our version.
 pub fn merge_notes_from_ref(repo: &Repository, source_ref: &str) -> Result<(), GitAiError> {
+    let notes_ref = format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME);
+    let conflicts = if ref_exists(repo, &notes_ref) {
+        conflicting_notes(repo, &notes_ref, source_ref)?
+    } else {
+        Vec::new()
+    };
+
     let mut args = repo.global_args_for_exec();
     args.push("notes".to_string());
     args.push(format!("--ref={}", AI_AUTHORSHIP_REFNAME));
@@ -236,9 +472,105 @@ pub fn merge_notes_from_ref(repo: &Repository, source_ref: &str) -> Result<(), G
         source_ref
     ));
     exec_git(&args)?;
+
+    let mut updates: Vec<(String, String)> = Vec::new();
+    for (commit_sha, our_blob, their_blob) in conflicts {
+        let ours = AuthorshipLog::deserialize_from_string(&read_blob(repo, &our_blob)?);
+        let theirs = AuthorshipLog::deserialize_from_string(&read_blob(repo, &their_blob)?);
+        let (Ok(ours), Ok(theirs)) = (ours, theirs) else {
+            debug_log(&format!(
+                "Keeping our note for {}: conflicting note could not be parsed",
+                commit_sha
+            ));
+            continue;
+        };
+        let merged = union_authorship_logs(&ours, &theirs);
+        if let Ok(content) = merged.serialize_to_string() {
+            updates.push((commit_sha, content));
+        }
+    }
+
+    if !updates.is_empty() {
+        debug_log(&format!(
+            "Union-merging {} conflicting note(s) from {}",
+            updates.len(),
+            source_ref
+        ));
+        notes_add_batch(
+            repo,
+            &updates,
+            &format!("Union-merged authorship notes from {}", source_ref),
+        )?;
+    }
     Ok(())
 }
 
+/// Commits whose note differs between two notes refs and was changed on both sides
+/// since their merge base, as (commit, our blob, their blob)
+fn conflicting_notes(
+    repo: &Repository,
+    our_ref: &str,
+    their_ref: &str,
+) -> Result<Vec<(String, String, String)>, GitAiError> {
+    let ours = note_blobs(repo, our_ref)?;
+    let theirs = note_blobs(repo, their_ref)?;
+
+    let mut args = repo.global_args_for_exec();
+    args.push("merge-base".to_string());
+    args.push(our_ref.to_string());
+    args.push(their_ref.to_string());
+    // No merge base means unrelated histories; every differing note is a conflict
+    let base = match exec_git(&args) {
+        Ok(output) => note_blobs(repo, String::from_utf8(output.stdout)?.trim())?,
+        Err(_) => HashMap::new(),
+    };
+
+    let mut conflicts = Vec::new();
+    for (commit_sha, our_blob) in &ours {
+        let Some(their_blob) = theirs.get(commit_sha) else {
+            continue;
+        };
+        let base_blob = base.get(commit_sha);
+        if our_blob != their_blob && base_blob != Some(our_blob) && base_blob != Some(their_blob) {
+            conflicts.push((commit_sha.clone(), our_blob.clone(), their_blob.clone()));
+        }
+    }
+    conflicts.sort();
+    Ok(conflicts)
+}
+
+/// Map annotated commit SHA to note blob for a notes commit, undoing fan-out paths
+fn note_blobs(
+    repo: &Repository,
+    notes_commit: &str,
+) -> Result<HashMap<String, String>, GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.push("ls-tree".to_string());
+    args.push("-r".to_string());
+    args.push(notes_commit.to_string());
+    let output = String::from_utf8(exec_git(&args)?.stdout)?;
+
+    let mut blobs = HashMap::new();
+    for line in output.lines() {
+        // <mode> blob <oid>\t<path>
+        let Some((meta, path)) = line.split_once('\t') else {
+            continue;
+        };
+        if let Some(blob) = meta.split_whitespace().nth(2) {
+            blobs.insert(path.replace('/', ""), blob.to_string());
+        }
+    }
+    Ok(blobs)
+}
+
+fn read_blob(repo: &Repository, blob: &str) -> Result<String, GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.push("cat-file".to_string());
+    args.push("blob".to_string());
+    args.push(blob.to_string());
+    Ok(String::from_utf8(exec_git(&args)?.stdout)?)
+}
+
 /// Copy a ref to another location (used for initial setup of local notes from tracking ref)
 pub fn copy_ref(repo: &Repository, source_ref: &str, dest_ref: &str) -> Result<(), GitAiError> {
     let mut args = repo.global_args_for_exec();
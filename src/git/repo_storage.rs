@@ -20,6 +20,15 @@ pub struct InitialAttributions {
     pub prompts: HashMap<String, PromptRecord>,
 }
 
+/// Files the last checkpoint's rename detection compared, stored in the rename_candidates file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct RenameCandidates {
+    /// Files the working log knows about that are gone from disk, sorted
+    pub missing: Vec<String>,
+    /// New and untracked files the working log doesn't know about, sorted
+    pub new: Vec<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct RepoStorage {
     pub repo_path: PathBuf,
@@ -204,6 +213,11 @@ impl PersistedWorkingLog {
         let checkpoints_file = self.dir.join("checkpoints.jsonl");
         fs::write(&checkpoints_file, "")?;
 
+        let rename_candidates_file = self.dir.join("rename_candidates");
+        if rename_candidates_file.exists() {
+            fs::remove_file(&rename_candidates_file)?;
+        }
+
         Ok(())
     }
 
@@ -588,6 +602,23 @@ impl PersistedWorkingLog {
             }
         }
     }
+
+    /* rename detection */
+
+    /// Read the files the last rename detection compared, if it ran
+    pub fn read_rename_candidates(&self) -> Option<RenameCandidates> {
+        let content = fs::read_to_string(self.dir.join("rename_candidates")).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Record the files a rename detection compared
+    pub fn write_rename_candidates(&self, candidates: &RenameCandidates) -> Result<(), GitAiError> {
+        fs::write(
+            self.dir.join("rename_candidates"),
+            serde_json::to_string(candidates)?,
+        )?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
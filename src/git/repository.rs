@@ -13,7 +13,7 @@ use crate::utils::is_interactive_terminal;
 
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::process::{Command, Output};
+use std::process::{Child, Command, Output};
 
 #[cfg(windows)]
 use crate::utils::CREATE_NO_WINDOW;
@@ -852,6 +852,8 @@ impl<'a> Iterator for References<'a> {
 pub struct Repository {
     global_args: Vec<String>,
     git_dir: PathBuf,
+    /// Git dir shared by all worktrees; the same as git_dir outside linked worktrees
+    common_dir: PathBuf,
     pub storage: RepoStorage,
     pub pre_command_base_commit: Option<String>,
     pub pre_command_refname: Option<String>,
@@ -962,6 +964,13 @@ impl Repository {
         self.git_dir.as_path()
     }
 
+    // Returns the git dir shared by every worktree of this repository (config, refs, objects).
+    // For a linked worktree path() is its private .git/worktrees/<name> directory instead,
+    // which is where per-worktree state like working logs and the rewrite log lives.
+    pub fn common_dir(&self) -> &Path {
+        self.common_dir.as_path()
+    }
+
     // Get the path of the working directory for this repository.
     // If this repository is bare, then None is returned.
     pub fn workdir(&self) -> Result<PathBuf, GitAiError> {
@@ -1039,7 +1048,8 @@ impl Repository {
 
     /// Get the git config file for this repository and fallback to global config if not found.
     fn get_git_config_file(&self) -> Result<gix_config::File<'static>, GitAiError> {
-        match gix_config::File::from_git_dir(self.path().to_path_buf()) {
+        // Linked worktrees have no config of their own, so read the shared one
+        match gix_config::File::from_git_dir(self.common_dir().to_path_buf()) {
             Ok(git_config_file) => Ok(git_config_file),
             Err(e) => match gix_config::File::from_globals() {
                 Ok(system_config) => Ok(system_config),
@@ -1055,6 +1065,24 @@ impl Repository {
         }
     }
 
+    /// Get config value for a given key as a boolean, using git's true/yes/on/1 rules.
+    pub fn config_get_bool(&self, key: &str) -> Result<Option<bool>, GitAiError> {
+        let git_config_file = self.get_git_config_file()?;
+        git_config_file
+            .boolean(key)
+            .transpose()
+            .map_err(|e| GitAiError::Generic(format!("Invalid boolean for {}: {}", key, e)))
+    }
+
+    /// Get config value for a given key as an integer, honoring git's k/m/g suffixes.
+    pub fn config_get_int(&self, key: &str) -> Result<Option<i64>, GitAiError> {
+        let git_config_file = self.get_git_config_file()?;
+        git_config_file
+            .integer(key)
+            .transpose()
+            .map_err(|e| GitAiError::Generic(format!("Invalid integer for {}: {}", key, e)))
+    }
+
     /// Get all config values matching a regex pattern.
     ///
     /// Regular expression matching is currently case-sensitive
@@ -1748,6 +1776,164 @@ impl Repository {
         parse_diff_added_lines(&diff_output)
     }
 
+    /// List every file in a commit's tree with its blob id
+    /// Returns a HashMap of file paths to blob ids
+    pub fn list_tree_blobs(&self, commit_sha: &str) -> Result<HashMap<String, String>, GitAiError> {
+        let mut args = self.global_args_for_exec();
+        args.push("ls-tree".to_string());
+        args.push("-r".to_string());
+        args.push("-z".to_string());
+        args.push(commit_sha.to_string());
+
+        let output = exec_git(&args)?;
+
+        // Each entry is "<mode> <type> <oid>\t<path>"
+        Ok(output
+            .stdout
+            .split(|&b| b == 0)
+            .filter_map(|bytes| std::str::from_utf8(bytes).ok())
+            .filter_map(|entry| {
+                let (info, path) = entry.split_once('\t')?;
+                let mut parts = info.split_whitespace();
+                let object_type = parts.nth(1)?;
+                let oid = parts.next()?;
+                (object_type == "blob").then(|| (path.to_string(), oid.to_string()))
+            })
+            .collect())
+    }
+
+    /// List the submodules recorded in the index, as paths relative to the workdir
+    pub fn submodule_paths(&self) -> Result<Vec<String>, GitAiError> {
+        // Every submodule is declared in .gitmodules, so most repos can skip reading the index
+        let Ok(workdir) = self.workdir() else {
+            return Ok(Vec::new());
+        };
+        if !workdir.join(".gitmodules").exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut args = self.global_args_for_exec();
+        args.push("ls-files".to_string());
+        args.push("--stage".to_string());
+        args.push("-z".to_string());
+
+        let output = exec_git(&args)?;
+
+        // Each entry is "<mode> <oid> <stage>\t<path>"; submodules are gitlinks (mode 160000)
+        let mut paths: Vec<String> = output
+            .stdout
+            .split(|&b| b == 0)
+            .filter_map(|bytes| std::str::from_utf8(bytes).ok())
+            .filter_map(|entry| {
+                let (info, path) = entry.split_once('\t')?;
+                info.starts_with("160000 ").then(|| path.to_string())
+            })
+            .collect();
+        paths.dedup();
+        Ok(paths)
+    }
+
+    /// Find the submodule a workdir-relative path lies in
+    /// Returns the submodule's path and the path relative to the submodule's workdir,
+    /// which is empty when `path` names the submodule itself
+    pub fn submodule_containing(&self, path: &str) -> Result<Option<(String, String)>, GitAiError> {
+        let path = path.strip_prefix("./").unwrap_or(path);
+        Ok(self.submodule_paths()?.into_iter().find_map(|submodule| {
+            if path == submodule {
+                return Some((submodule, String::new()));
+            }
+            let inner = path.strip_prefix(&format!("{}/", submodule))?.to_string();
+            Some((submodule, inner))
+        }))
+    }
+
+    /// Open the checked-out submodule at a workdir-relative path
+    pub fn open_submodule(&self, submodule_path: &str) -> Result<Repository, GitAiError> {
+        let dir = self.workdir()?.join(submodule_path);
+        if !dir.join(".git").exists() {
+            return Err(GitAiError::Generic(format!(
+                "Submodule '{}' is not checked out",
+                submodule_path
+            )));
+        }
+        find_repository_in_path(&dir.to_string_lossy())
+    }
+
+    /// List the submodules whose recorded commit changed between two commits
+    /// Returns (submodule path, old commit, new commit); the old commit is None for
+    /// submodules added in `to`, and submodules removed in `to` are left out.
+    /// With no `from`, every submodule in `to` counts as added.
+    pub fn diff_submodule_commits(
+        &self,
+        from: Option<&str>,
+        to: &str,
+    ) -> Result<Vec<(String, Option<String>, String)>, GitAiError> {
+        let mut args = self.global_args_for_exec();
+        args.push("diff-tree".to_string());
+        args.push("-r".to_string());
+        args.push("-z".to_string());
+        args.push("--raw".to_string());
+        args.push("--no-renames".to_string());
+        match from {
+            Some(from) => args.push(from.to_string()),
+            None => args.push("--root".to_string()),
+        }
+        args.push(to.to_string());
+
+        let output = exec_git(&args)?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        // Records are ":<old mode> <new mode> <old oid> <new oid> <status>\0<path>\0"
+        let mut changes = Vec::new();
+        let mut fields = stdout.split('\0');
+        while let Some(info) = fields.next() {
+            let Some(info) = info.strip_prefix(':') else {
+                continue;
+            };
+            let Some(path) = fields.next() else {
+                break;
+            };
+            let parts: Vec<&str> = info.split_whitespace().collect();
+            if parts.len() < 4 || parts[1] != "160000" {
+                continue;
+            }
+            let old_commit = (parts[0] == "160000").then(|| parts[2].to_string());
+            changes.push((path.to_string(), old_commit, parts[3].to_string()));
+        }
+        Ok(changes)
+    }
+
+    /// Get the lines of a merge commit that differ from every one of its parents
+    /// Returns a HashMap of file paths to line numbers in the merge commit
+    ///
+    /// These are the lines written while resolving the merge; anything else came
+    /// unchanged from one of the parents.
+    pub fn merge_resolution_lines(
+        &self,
+        merge_sha: &str,
+        parents: &[String],
+    ) -> Result<HashMap<String, Vec<u32>>, GitAiError> {
+        let Some((first_parent, other_parents)) = parents.split_first() else {
+            return Ok(HashMap::new());
+        };
+
+        let mut resolution_lines = self.diff_added_lines(first_parent, merge_sha, None)?;
+        for parent in other_parents {
+            let pathspecs: HashSet<String> = resolution_lines.keys().cloned().collect();
+            let added_from_parent = self.diff_added_lines(parent, merge_sha, Some(&pathspecs))?;
+            resolution_lines.retain(|file, lines| {
+                let Some(parent_lines) = added_from_parent.get(file) else {
+                    return false;
+                };
+                let parent_lines: HashSet<u32> = parent_lines.iter().copied().collect();
+                lines.retain(|line| parent_lines.contains(line));
+                !lines.is_empty()
+            });
+        }
+
+        Ok(resolution_lines)
+    }
+
     /// Get list of changed files between two refs using `git diff --name-only`
     /// Returns a Vec of file paths that differ between the two refs
     pub fn diff_changed_files(
@@ -1856,6 +2042,20 @@ impl Repository {
     }
 }
 
+/// Directory a git command runs in, after applying any leading `-C` global args
+pub(crate) fn invocation_dir(global_args: &[String]) -> PathBuf {
+    let mut dir = std::env::current_dir().unwrap_or_default();
+    let mut args = global_args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "-C"
+            && let Some(path) = args.next()
+        {
+            dir = dir.join(path);
+        }
+    }
+    dir
+}
+
 pub fn find_repository(global_args: &[String]) -> Result<Repository, GitAiError> {
     let mut args = global_args.to_owned();
     args.push("rev-parse".to_string());
@@ -1865,7 +2065,14 @@ pub fn find_repository(global_args: &[String]) -> Result<Repository, GitAiError>
     args.push("--git-dir".to_string());
     args.push("--show-toplevel".to_string());
 
-    let output = exec_git(&args)?;
+    // --git-common-dir needs Git 2.5; older versions either fail or echo the flag back, and
+    // then the git dir stands in for it since they have no linked worktrees
+    let mut common_dir_args = args.clone();
+    common_dir_args.push("--git-common-dir".to_string());
+    let output = match exec_git(&common_dir_args) {
+        Ok(output) => output,
+        Err(_) => exec_git(&args)?,
+    };
     let both_dirs = String::from_utf8(output.stdout)?;
 
     let both_dirs = both_dirs.trim();
@@ -1888,6 +2095,19 @@ pub fn find_repository(global_args: &[String]) -> Result<Repository, GitAiError>
     } else {
         PathBuf::from(git_dir_str)
     };
+    // --git-common-dir differs from --git-dir only inside linked worktrees. Unlike --git-dir
+    // it can be relative to a subdirectory, so resolve it against where git actually ran.
+    let common_dir = match lines.get(2) {
+        Some(common_dir_str) if *common_dir_str != "--git-common-dir" => {
+            let common_dir = invocation_dir(global_args).join(common_dir_str);
+            if common_dir.canonicalize().ok() == git_dir.canonicalize().ok() {
+                git_dir.clone()
+            } else {
+                common_dir
+            }
+        }
+        _ => git_dir.clone(),
+    };
     if !git_dir.is_dir() {
         return Err(GitAiError::Generic(format!(
             "Git directory does not exist: {}",
@@ -1929,6 +2149,7 @@ pub fn find_repository(global_args: &[String]) -> Result<Repository, GitAiError>
         global_args,
         storage: RepoStorage::for_repo_path(&git_dir, &workdir),
         git_dir,
+        common_dir,
         pre_command_base_commit: None,
         pre_command_refname: None,
         pre_reset_target_commit: None,
@@ -1951,6 +2172,7 @@ pub fn from_bare_repository(git_dir: &Path) -> Result<Repository, GitAiError> {
         global_args,
         storage: RepoStorage::for_repo_path(git_dir, &workdir),
         git_dir: git_dir.to_path_buf(),
+        common_dir: git_dir.to_path_buf(),
         pre_command_base_commit: None,
         pre_command_refname: None,
         pre_reset_target_commit: None,
@@ -2121,6 +2343,26 @@ pub fn exec_git(args: &[String]) -> Result<Output, GitAiError> {
     Ok(output)
 }
 
+/// Helper to spawn a git command whose stdout is read incrementally by the caller
+///
+/// The caller owns the child and must wait on (or kill) it.
+pub fn spawn_git_stdout(args: &[String]) -> Result<Child, GitAiError> {
+    let mut cmd = Command::new(config::Config::get().git_cmd());
+    cmd.args(args)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null());
+
+    #[cfg(windows)]
+    {
+        if !is_interactive_terminal() {
+            cmd.creation_flags(CREATE_NO_WINDOW);
+        }
+    }
+
+    cmd.spawn().map_err(GitAiError::IoError)
+}
+
 /// Helper to execute a git command with data provided on stdin
 pub fn exec_git_stdin(args: &[String], stdin_data: &[u8]) -> Result<Output, GitAiError> {
     // TODO Make sure to handle process signals, etc.
@@ -2162,7 +2404,6 @@ pub fn exec_git_stdin(args: &[String], stdin_data: &[u8]) -> Result<Output, GitA
 }
 
 /// Helper to execute a git command with data provided on stdin and additional environment variables
-#[allow(dead_code)]
 pub fn exec_git_stdin_with_env(
     args: &[String],
     env: &[(String, String)],
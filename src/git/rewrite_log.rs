@@ -29,9 +29,15 @@ pub enum RewriteLogEvent {
     CherryPickAbort {
         cherry_pick_abort: CherryPickAbortEvent,
     },
+    RevertStart {
+        revert_start: RevertStartEvent,
+    },
     RevertMixed {
         revert_mixed: RevertMixedEvent,
     },
+    RevertAbort {
+        revert_abort: RevertAbortEvent,
+    },
     Reset {
         reset: ResetEvent,
     },
@@ -111,13 +117,24 @@ impl RewriteLogEvent {
         }
     }
 
-    #[allow(dead_code)]
+    pub fn revert_start(event: RevertStartEvent) -> Self {
+        Self::RevertStart {
+            revert_start: event,
+        }
+    }
+
     pub fn revert_mixed(event: RevertMixedEvent) -> Self {
         Self::RevertMixed {
             revert_mixed: event,
         }
     }
 
+    pub fn revert_abort(event: RevertAbortEvent) -> Self {
+        Self::RevertAbort {
+            revert_abort: event,
+        }
+    }
+
     #[allow(dead_code)]
     pub fn reset(event: ResetEvent) -> Self {
         Self::Reset { reset: event }
@@ -304,24 +321,71 @@ impl CherryPickAbortEvent {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevertStartEvent {
+    pub original_head: String,
+    pub reverted_commits: Vec<String>,
+    /// Parent number passed with `-m/--mainline` when reverting merges
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mainline: Option<usize>,
+    /// Started with `--no-commit`, so the reverted lines end up in the working tree
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_commit: bool,
+}
+
+impl RevertStartEvent {
+    pub fn new(
+        original_head: String,
+        reverted_commits: Vec<String>,
+        mainline: Option<usize>,
+        no_commit: bool,
+    ) -> Self {
+        Self {
+            original_head,
+            reverted_commits,
+            mainline,
+            no_commit,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RevertMixedEvent {
     pub reverted_commit: String,
+    /// Commit created by the revert, or None for `git revert --no-commit`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revert_commit: Option<String>,
     pub success: bool,
     pub affected_files: Vec<String>,
 }
 
 impl RevertMixedEvent {
-    #[allow(dead_code)]
-    pub fn new(reverted_commit: String, success: bool, affected_files: Vec<String>) -> Self {
+    pub fn new(
+        reverted_commit: String,
+        revert_commit: Option<String>,
+        success: bool,
+        affected_files: Vec<String>,
+    ) -> Self {
         Self {
             reverted_commit,
+            revert_commit,
             success,
             affected_files,
         }
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevertAbortEvent {
+    pub original_head: String,
+}
+
+impl RevertAbortEvent {
+    pub fn new(original_head: String) -> Self {
+        Self { original_head }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ResetKind {
@@ -109,6 +109,38 @@ impl Repository {
         Ok(filenames)
     }
 
+    /// Files that are new to the index or untracked, including ones staged as a rename target
+    pub fn get_new_filenames(&self) -> Result<HashSet<String>, GitAiError> {
+        let mut args = self.global_args_for_exec();
+        args.push("status".to_string());
+        args.push("--porcelain=v2".to_string());
+        args.push("--untracked-files=all".to_string());
+        args.push("-z".to_string());
+
+        let output = exec_git(&args)?;
+
+        if !output.status.success() {
+            return Err(GitAiError::Generic(format!(
+                "git status exited with status {}",
+                output.status
+            )));
+        }
+
+        let filenames: HashSet<String> = parse_porcelain_v2(&output.stdout)?
+            .into_iter()
+            .filter(|entry| {
+                entry.kind == EntryKind::Untracked
+                    || matches!(
+                        entry.staged,
+                        StatusCode::Added | StatusCode::Renamed | StatusCode::Copied
+                    )
+            })
+            .map(|entry| entry.path)
+            .collect();
+
+        Ok(filenames)
+    }
+
     pub fn status(
         &self,
         pathspecs: Option<&HashSet<String>>,
@@ -190,6 +190,13 @@ fn assert_diff_lines_exact(lines: &[DiffLine], expected: &[(&str, &str, Option<&
     }
 }
 
+/// Extract the first complete JSON object from mixed stdout/stderr output.
+fn extract_json_object(output: &str) -> String {
+    let start = output.find('{').unwrap_or(0);
+    let end = output.rfind('}').unwrap_or(output.len().saturating_sub(1));
+    output[start..=end].to_string()
+}
+
 #[test]
 fn test_diff_single_commit() {
     let repo = TestRepo::new();
@@ -612,14 +619,126 @@ fn test_diff_output_format() {
 }
 
 #[test]
-fn test_diff_error_on_no_args() {
+fn test_diff_uncommitted_changes() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("wip.rs");
+    file.set_contents(lines!["fn old() {}".human()]);
+    repo.stage_all_and_commit("Initial").unwrap();
+
+    // Uncommitted AI edit, then an uncommitted human line below it
+    file.set_contents(lines!["fn old() {}".human(), "fn agent() {}".ai()]);
+    let path = repo.path().join("wip.rs");
+    let content = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(&path, format!("{}\nfn mine() {{}}", content)).unwrap();
+
+    let output = repo
+        .git_ai(&["diff"])
+        .expect("git-ai diff without arguments should succeed");
+    let lines = parse_diff_output(&output);
+    assert_diff_lines_exact(
+        &lines,
+        &[
+            ("-", "fn old()", None),
+            ("+", "fn old()", None),
+            ("+", "fn agent()", Some("ai")),
+            ("+", "fn mine()", Some("human")),
+        ],
+    );
+
+    // Checkpointing the working tree logs to stderr in debug builds
+    let output = repo.git_ai(&["diff", "--json"]).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&extract_json_object(&output)).unwrap();
+    let annotations = json["files"]["wip.rs"]["annotations"]
+        .as_object()
+        .expect("uncommitted AI lines should be annotated");
+    assert_eq!(annotations.len(), 1);
+    let (hash, ranges) = annotations.iter().next().unwrap();
+    assert_eq!(ranges, &serde_json::json!([2]));
+    assert!(json["prompts"].get(hash).is_some());
+    assert_eq!(json["files"]["wip.rs"]["base_content"], "fn old() {}");
+}
+
+#[test]
+fn test_diff_cached_maps_staged_lines() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("staged.rs");
+    file.set_contents(lines!["fn base() {}".human()]);
+    repo.stage_all_and_commit("Initial").unwrap();
+
+    // Stage an AI line, then add an unstaged human line above it
+    file.set_contents(lines!["fn base() {}".human(), "fn staged() {}".ai()]);
+    repo.git(&["add", "staged.rs"]).unwrap();
+    let path = repo.path().join("staged.rs");
+    let content = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(&path, format!("fn unstaged() {{}}\n{}", content)).unwrap();
+
+    let output = repo.git_ai(&["diff", "--cached"]).unwrap();
+    assert!(!output.contains("unstaged"), "{}", output);
+    let lines = parse_diff_output(&output);
+    assert_diff_lines_exact(
+        &lines,
+        &[
+            ("-", "fn base()", None),
+            ("+", "fn base()", None),
+            ("+", "fn staged()", Some("ai")),
+        ],
+    );
+
+    // The working tree diff sees both lines
+    let output = repo.git_ai(&["diff"]).unwrap();
+    let lines = parse_diff_output(&output);
+    assert_diff_lines_exact(
+        &lines,
+        &[
+            ("-", "fn base()", None),
+            ("+", "fn unstaged()", Some("human")),
+            ("+", "fn base()", None),
+            ("+", "fn staged()", Some("ai")),
+        ],
+    );
+}
+
+#[test]
+fn test_diff_follows_edits_made_after_the_last_checkpoint() {
     let repo = TestRepo::new();
 
-    // Try to run diff without arguments
-    let result = repo.git_ai(&["diff"]);
+    let mut file = repo.filename("shift.rs");
+    file.set_contents(lines!["fn base() {}".human()]);
+    repo.stage_all_and_commit("Initial").unwrap();
 
-    // Should fail with error
-    assert!(result.is_err(), "git-ai diff without arguments should fail");
+    // The AI's checkpoint is the last one; the human lines above are only on disk
+    file.set_contents(lines![
+        "fn base() {}".human(),
+        "fn agent_one() {}".ai(),
+        "fn agent_two() {}".ai()
+    ]);
+    let path = repo.path().join("shift.rs");
+    let content = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(
+        &path,
+        format!("fn first() {{}}\nfn second() {{}}\n{}", content),
+    )
+    .unwrap();
+
+    let expected = [
+        ("-", "fn base()", None),
+        ("+", "fn first()", Some("human")),
+        ("+", "fn second()", Some("human")),
+        ("+", "fn base()", None),
+        ("+", "fn agent_one()", Some("ai")),
+        ("+", "fn agent_two()", Some("ai")),
+    ];
+    let output = repo.git_ai(&["diff"]).unwrap();
+    assert_diff_lines_exact(&parse_diff_output(&output), &expected);
+
+    // Staged content as well; stage, then edit again above the AI lines
+    repo.git(&["add", "shift.rs"]).unwrap();
+    let content = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(&path, format!("fn unstaged() {{}}\n{}", content)).unwrap();
+    let output = repo.git_ai(&["diff", "--cached"]).unwrap();
+    assert_diff_lines_exact(&parse_diff_output(&output), &expected);
 }
 
 #[test]
@@ -794,3 +913,161 @@ fn test_diff_range_multiple_commits() {
         "Should have attribution markers"
     );
 }
+
+#[test]
+fn test_diff_attributes_ai_deletions() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("cleanup.txt");
+    file.set_contents(lines![
+        "Keep 1".human(),
+        "Dead code 1".human(),
+        "Dead code 2".human(),
+        "Human removes".human(),
+        "Keep 2".human()
+    ]);
+    repo.stage_all_and_commit("Initial").unwrap();
+
+    // The AI removes the dead code, then a human removes another line
+    let path = repo.path().join("cleanup.txt");
+    std::fs::write(&path, "Keep 1\nHuman removes\nKeep 2").unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai"]).unwrap();
+    std::fs::write(&path, "Keep 1\nKeep 2").unwrap();
+    repo.git_ai(&["checkpoint"]).unwrap();
+    let commit = repo.stage_all_and_commit("Remove dead code").unwrap();
+
+    let output = repo
+        .git_ai(&["diff", &commit.commit_sha, "--json"])
+        .expect("git-ai diff --json should succeed");
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    let deletions = json["files"]["cleanup.txt"]["deletions"]
+        .as_object()
+        .expect("AI deletions should be reported");
+    assert_eq!(deletions.len(), 1);
+    let (hash, ranges) = deletions.iter().next().unwrap();
+    assert_eq!(ranges, &serde_json::json!([[2, 3]]));
+    assert!(json["prompts"].get(hash).is_some());
+
+    let output = repo.git_ai(&["diff", &commit.commit_sha]).unwrap();
+    let lines = parse_diff_output(&output);
+    assert_diff_lines_exact(
+        &lines,
+        &[
+            (" ", "Keep 1", None),
+            ("-", "Dead code 1", Some("ai")),
+            ("-", "Dead code 2", Some("ai")),
+            ("-", "Human removes", None),
+            (" ", "Keep 2", None),
+        ],
+    );
+    assert_eq!(lines[3].attribution, None);
+}
+
+#[test]
+fn test_diff_does_not_credit_human_deletions_of_matching_lines() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("cleanup.txt");
+    file.set_contents(lines![
+        "fn a() {".human(),
+        "}".human(),
+        "fn b() {".human(),
+        "    b();".human(),
+        "}".human()
+    ]);
+    repo.stage_all_and_commit("Initial").unwrap();
+
+    // The AI removes `a`, then a human removes the braces around `b`, including a `}`
+    // identical to the one the AI removed
+    let path = repo.path().join("cleanup.txt");
+    std::fs::write(&path, "fn b() {\n    b();\n}").unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai"]).unwrap();
+    std::fs::write(&path, "    b();").unwrap();
+    let commit = repo.stage_all_and_commit("Inline b").unwrap();
+
+    let output = repo
+        .git_ai(&["diff", &commit.commit_sha, "--json"])
+        .expect("git-ai diff --json should succeed");
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    let deletions = json["files"]["cleanup.txt"]["deletions"]
+        .as_object()
+        .expect("AI deletions should be reported");
+    assert_eq!(deletions.len(), 1);
+    let (_, ranges) = deletions.iter().next().unwrap();
+    assert_eq!(ranges, &serde_json::json!([[1, 2]]));
+
+    let output = repo.git_ai(&["diff", &commit.commit_sha]).unwrap();
+    let lines = parse_diff_output(&output);
+    assert_diff_lines_exact(
+        &lines,
+        &[
+            ("-", "fn a() {", Some("ai")),
+            ("-", "}", Some("ai")),
+            ("-", "fn b() {", None),
+            ("-", "b();", None),
+            ("-", "}", None),
+            ("+", "b();", Some("human")),
+        ],
+    );
+    assert_eq!(lines[4].attribution, None);
+}
+
+/// Commit the AI deleting two lines on `feature`, then add a line above them on the default
+/// branch, so the deleted lines move in the rewritten commit's parent
+fn setup_ai_deletion_on_feature(repo: &TestRepo) -> (String, String) {
+    let mut file = repo.filename("cleanup.txt");
+    file.set_contents(lines![
+        "Keep 1".human(),
+        "Dead code 1".human(),
+        "Dead code 2".human(),
+        "Keep 2".human()
+    ]);
+    repo.stage_all_and_commit("Initial").unwrap();
+    let default_branch = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+    let path = repo.path().join("cleanup.txt");
+    std::fs::write(&path, "Keep 1\nKeep 2").unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai"]).unwrap();
+    let feature_commit = repo.stage_all_and_commit("Remove dead code").unwrap();
+
+    repo.git(&["checkout", &default_branch]).unwrap();
+    std::fs::write(&path, "Header\nKeep 1\nDead code 1\nDead code 2\nKeep 2").unwrap();
+    repo.stage_all_and_commit("Add header").unwrap();
+
+    (default_branch, feature_commit.commit_sha)
+}
+
+fn assert_ai_deletions(repo: &TestRepo, expected: serde_json::Value) {
+    let head = repo.git(&["rev-parse", "HEAD"]).unwrap();
+    let output = repo.git_ai(&["diff", head.trim(), "--json"]).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    let deletions = json["files"]["cleanup.txt"]["deletions"]
+        .as_object()
+        .expect("AI deletions should survive the rewrite");
+    assert_eq!(deletions.len(), 1);
+    let (hash, ranges) = deletions.iter().next().unwrap();
+    assert_eq!(ranges, &expected);
+    assert!(json["prompts"].get(hash).is_some());
+}
+
+#[test]
+fn test_rebase_keeps_ai_deletions() {
+    let repo = TestRepo::new();
+    let (default_branch, _) = setup_ai_deletion_on_feature(&repo);
+
+    repo.git(&["checkout", "feature"]).unwrap();
+    repo.git(&["rebase", &default_branch]).unwrap();
+
+    assert_ai_deletions(&repo, serde_json::json!([[3, 4]]));
+}
+
+#[test]
+fn test_cherry_pick_keeps_ai_deletions() {
+    let repo = TestRepo::new();
+    let (_, feature_commit) = setup_ai_deletion_on_feature(&repo);
+
+    repo.git(&["cherry-pick", &feature_commit]).unwrap();
+
+    assert_ai_deletions(&repo, serde_json::json!([[3, 4]]));
+}
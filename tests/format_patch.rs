@@ -0,0 +1,191 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn patch_files(repo: &TestRepo) -> Vec<String> {
+    let mut files: Vec<String> = std::fs::read_dir(repo.path().join("patches"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path().to_string_lossy().to_string())
+        .collect();
+    files.sort();
+    files
+}
+
+/// A patch written with authorship enabled restores AI blame when applied with `git am`
+#[test]
+fn test_format_patch_and_am_carry_authorship() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "gitai.formatPatchAuthorship", "true"])
+        .unwrap();
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines!["Base".human(), "End".human()]);
+    repo.stage_all_and_commit("Base").unwrap();
+
+    file.insert_at(1, lines!["AI line 1".ai(), "AI line 2".ai()]);
+    repo.stage_all_and_commit("Add AI lines").unwrap();
+
+    repo.git(&["format-patch", "-1", "-o", "patches"]).unwrap();
+    let patches = patch_files(&repo);
+    assert_eq!(patches.len(), 1);
+    let patch = std::fs::read_to_string(&patches[0]).unwrap();
+    assert!(patch.contains("git-ai-authorship:"), "{}", patch);
+
+    repo.git_og(&["reset", "--hard", "HEAD~1"]).unwrap();
+    repo.git(&["am", &patches[0]]).unwrap();
+
+    file.assert_lines_and_blame(lines![
+        "Base".human(),
+        "AI line 1".ai(),
+        "AI line 2".ai(),
+        "End".human()
+    ]);
+    assert_eq!(repo.stats().unwrap().ai_additions, 2);
+}
+
+/// Patches that apply at an offset have their ranges remapped to the applied lines
+#[test]
+fn test_am_with_offset_remaps_authorship() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "gitai.formatPatchAuthorship", "true"])
+        .unwrap();
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines![
+        "Line 1".human(),
+        "Line 2".human(),
+        "Line 3".human(),
+        "Line 4".human(),
+        "End".human()
+    ]);
+    repo.stage_all_and_commit("Base").unwrap();
+
+    file.insert_at(4, lines!["AI line".ai()]);
+    repo.stage_all_and_commit("Add AI line").unwrap();
+
+    repo.git(&["format-patch", "-1", "-o", "patches"]).unwrap();
+    let patches = patch_files(&repo);
+
+    repo.git_og(&["reset", "--hard", "HEAD~1"]).unwrap();
+    file.set_contents(lines![
+        "New top 1".human(),
+        "New top 2".human(),
+        "Line 1".human(),
+        "Line 2".human(),
+        "Line 3".human(),
+        "Line 4".human(),
+        "End".human()
+    ]);
+    repo.stage_all_and_commit("Add lines at the top").unwrap();
+
+    repo.git(&["am", &patches[0]]).unwrap();
+
+    file.assert_lines_and_blame(lines![
+        "New top 1".human(),
+        "New top 2".human(),
+        "Line 1".human(),
+        "Line 2".human(),
+        "Line 3".human(),
+        "Line 4".human(),
+        "AI line".ai(),
+        "End".human()
+    ]);
+}
+
+/// Nothing is embedded unless the config opts in
+#[test]
+fn test_format_patch_without_config_embeds_nothing() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines!["Base".human(), "End".human()]);
+    repo.stage_all_and_commit("Base").unwrap();
+
+    file.insert_at(1, lines!["AI line".ai()]);
+    repo.stage_all_and_commit("Add AI line").unwrap();
+
+    repo.git(&["format-patch", "-1", "-o", "patches"]).unwrap();
+    let patches = patch_files(&repo);
+    assert_eq!(patches.len(), 1);
+    let patch = std::fs::read_to_string(&patches[0]).unwrap();
+    assert!(!patch.contains("git-ai-authorship:"));
+}
+
+/// Patches already in the output directory from an earlier run are left untouched
+#[test]
+fn test_format_patch_leaves_stale_patches_alone() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines!["Base".human(), "End".human()]);
+    repo.stage_all_and_commit("Base").unwrap();
+
+    file.insert_at(1, lines!["AI line 1".ai()]);
+    repo.stage_all_and_commit("Add first AI line").unwrap();
+
+    // An earlier export, written before authorship was turned on
+    repo.git(&["format-patch", "-1", "-o", "patches"]).unwrap();
+    let stale_path = repo.path().join("patches").join("0009-stale.patch");
+    std::fs::rename(&patch_files(&repo)[0], &stale_path).unwrap();
+    let stale = std::fs::read_to_string(&stale_path).unwrap();
+
+    repo.git(&["config", "gitai.formatPatchAuthorship", "true"])
+        .unwrap();
+    file.insert_at(2, lines!["AI line 2".ai()]);
+    repo.stage_all_and_commit("Add second AI line").unwrap();
+    repo.git(&["format-patch", "-1", "-o", "patches"]).unwrap();
+
+    let patches = patch_files(&repo);
+    assert_eq!(patches.len(), 2);
+    let fresh = std::fs::read_to_string(&patches[0]).unwrap();
+    assert!(fresh.contains("git-ai-authorship:"), "{}", fresh);
+    assert_eq!(std::fs::read_to_string(&stale_path).unwrap(), stale);
+}
+
+/// Authorship is embedded in a single `--output` file and in `--numbered-files` patches
+#[test]
+fn test_format_patch_embeds_in_output_file_and_numbered_files() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "gitai.formatPatchAuthorship", "true"])
+        .unwrap();
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines!["Base".human(), "End".human()]);
+    repo.stage_all_and_commit("Base").unwrap();
+
+    file.insert_at(1, lines!["AI line 1".ai()]);
+    repo.stage_all_and_commit("Add first AI line").unwrap();
+    file.insert_at(2, lines!["AI line 2".ai()]);
+    repo.stage_all_and_commit("Add second AI line").unwrap();
+
+    repo.git(&["format-patch", "-2", "--output", "series.mbox"])
+        .unwrap();
+    let series = std::fs::read_to_string(repo.path().join("series.mbox")).unwrap();
+    assert_eq!(
+        series.matches("git-ai-authorship:").count(),
+        2,
+        "{}",
+        series
+    );
+
+    repo.git(&["format-patch", "-2", "--numbered-files", "-o", "patches"])
+        .unwrap();
+    let patches = patch_files(&repo);
+    assert_eq!(patches.len(), 2);
+    for patch in &patches {
+        assert!(patch.ends_with('1') || patch.ends_with('2'), "{}", patch);
+        let content = std::fs::read_to_string(patch).unwrap();
+        assert!(content.contains("git-ai-authorship:"), "{}", content);
+    }
+
+    // The whole series applies from the --output file
+    repo.git_og(&["reset", "--hard", "HEAD~2"]).unwrap();
+    repo.git(&["am", "series.mbox"]).unwrap();
+    file.assert_lines_and_blame(lines![
+        "Base".human(),
+        "AI line 1".ai(),
+        "AI line 2".ai(),
+        "End".human()
+    ]);
+}
@@ -0,0 +1,198 @@
+#[macro_use]
+mod repos;
+
+use git_ai::commands::fsck::{FsckIssueKind, fsck_commits};
+use git_ai::git::find_repository_in_path;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn overwrite_note(repo: &TestRepo, commit_sha: &str, content: &str) {
+    repo.git_og(&["notes", "--ref=ai", "add", "-f", "-m", content, commit_sha])
+        .expect("failed to overwrite note");
+}
+
+fn issue_kinds(repo: &TestRepo, commit_sha: &str) -> Vec<FsckIssueKind> {
+    let gitai_repo = find_repository_in_path(repo.path().to_str().unwrap()).unwrap();
+    let report = fsck_commits(&gitai_repo, &[commit_sha.to_string()]).unwrap();
+    assert_eq!(report.notes_checked, 1);
+    report.issues.iter().map(|issue| issue.kind).collect()
+}
+
+#[test]
+fn fsck_reports_no_issues_for_fresh_notes() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("test.txt");
+
+    file.set_contents(lines!["Base".human(), "AI line 1".ai(), "AI line 2".ai()]);
+    repo.stage_all_and_commit("First commit").unwrap();
+
+    file.insert_at(3, lines!["AI line 3".ai()]);
+    repo.stage_all_and_commit("Second commit").unwrap();
+
+    let output = repo.git_ai(&["fsck"]).expect("fsck should succeed");
+    assert!(
+        output.contains("Checked 2 commit(s), 2 note(s): no problems found"),
+        "unexpected output: {}",
+        output
+    );
+}
+
+#[test]
+fn fsck_detects_corrupted_attestations() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("test.txt");
+
+    file.set_contents(lines!["Base".human(), "AI line 1".ai()]);
+    let commit = repo.stage_all_and_commit("First commit").unwrap();
+
+    // Simulate a buggy rewrite: a range past EOF plus an unknown hash on a file that doesn't exist
+    let hash = commit
+        .authorship_log
+        .metadata
+        .prompts
+        .keys()
+        .next()
+        .unwrap();
+    let corrupted = commit
+        .authorship_log
+        .serialize_to_string()
+        .unwrap()
+        .replacen(&format!("  {} 2\n", hash), &format!("  {} 2-40\n", hash), 1)
+        .replacen("---\n", "gone.txt\n  deadbeefdeadbeef 1\n---\n", 1);
+    overwrite_note(&repo, &commit.commit_sha, &corrupted);
+
+    repo.git_ai(&["fsck", "HEAD"])
+        .expect_err("fsck should exit non-zero when issues are found");
+
+    let kinds = issue_kinds(&repo, &commit.commit_sha);
+    assert_eq!(
+        kinds,
+        vec![
+            FsckIssueKind::RangeOutOfBounds,
+            FsckIssueKind::MissingFile,
+            FsckIssueKind::MissingPrompt,
+        ]
+    );
+}
+
+#[test]
+fn fsck_detects_base_commit_mismatch_and_unparseable_notes() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("test.txt");
+
+    file.set_contents(lines!["Base".human(), "AI line 1".ai()]);
+    let first = repo.stage_all_and_commit("First commit").unwrap();
+
+    file.insert_at(2, lines!["AI line 2".ai()]);
+    let second = repo.stage_all_and_commit("Second commit").unwrap();
+
+    // Copy the first note onto the second commit, like a stray `git notes copy`
+    let copied = first.authorship_log.serialize_to_string().unwrap();
+    overwrite_note(&repo, &second.commit_sha, &copied);
+    assert_eq!(
+        issue_kinds(&repo, &second.commit_sha),
+        vec![FsckIssueKind::BaseCommitMismatch]
+    );
+
+    overwrite_note(&repo, &first.commit_sha, "not an authorship log");
+    assert_eq!(
+        issue_kinds(&repo, &first.commit_sha),
+        vec![FsckIssueKind::UnparseableNote]
+    );
+}
+
+#[test]
+fn fsck_and_blame_reanchor_copied_notes() {
+    let repo = TestRepo::new();
+    repo.git_og(&["config", "gitai.noteAnchors", "true"])
+        .unwrap();
+
+    let mut file = repo.filename("test.txt");
+    file.set_contents(lines!["Base".human(), "AI line 1".ai(), "AI line 2".ai()]);
+    let commit = repo.stage_all_and_commit("First commit").unwrap();
+    let note = repo
+        .git_og(&["notes", "--ref=ai", "show", &commit.commit_sha])
+        .unwrap();
+    assert!(note.contains("\n~ "), "note should carry anchors: {}", note);
+    assert!(issue_kinds(&repo, &commit.commit_sha).is_empty());
+
+    // An unrelated commit where the same lines sit one line lower, carrying a copy of the note
+    repo.git_og(&["checkout", "--orphan", "other"]).unwrap();
+    std::fs::write(
+        repo.path().join("test.txt"),
+        "Header\nBase\nAI line 1\nAI line 2\n",
+    )
+    .unwrap();
+    repo.git_og(&["add", "test.txt"]).unwrap();
+    repo.git_og(&["commit", "-m", "Unrelated commit"]).unwrap();
+    let other = repo
+        .git_og(&["rev-parse", "HEAD"])
+        .unwrap()
+        .trim()
+        .to_string();
+    repo.git_og(&["notes", "--ref=ai", "copy", &commit.commit_sha, &other])
+        .unwrap();
+
+    assert_eq!(
+        issue_kinds(&repo, &other),
+        vec![
+            FsckIssueKind::BaseCommitMismatch,
+            FsckIssueKind::AnchorMismatch
+        ]
+    );
+
+    // Blame follows the content rather than the stale line numbers
+    let blame = repo.git_ai(&["blame", "test.txt"]).unwrap();
+    let ai_lines: Vec<&str> = blame
+        .lines()
+        .filter(|line| line.contains("mock_ai"))
+        .collect();
+    assert_eq!(ai_lines.len(), 2, "unexpected blame: {}", blame);
+    assert!(ai_lines[0].ends_with("AI line 1"));
+    assert!(ai_lines[1].ends_with("AI line 2"));
+}
+
+#[test]
+fn fsck_reports_unreadable_files_and_keeps_going() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("test.txt");
+    file.set_contents(lines!["Base".human(), "AI line 1".ai()]);
+    let first = repo.stage_all_and_commit("First commit").unwrap();
+
+    // A submodule gitlink can't be read as a file
+    repo.git_og(&[
+        "update-index",
+        "--add",
+        "--cacheinfo",
+        "160000,1234567890123456789012345678901234567890,vendor",
+    ])
+    .unwrap();
+    repo.git_og(&["commit", "-m", "Add submodule"]).unwrap();
+    let commit = repo
+        .git_og(&["rev-parse", "HEAD"])
+        .unwrap()
+        .trim()
+        .to_string();
+
+    let hash = first.authorship_log.metadata.prompts.keys().next().unwrap();
+    let note = first
+        .authorship_log
+        .serialize_to_string()
+        .unwrap()
+        .replace(&first.commit_sha, &commit)
+        .replacen("---\n", &format!("vendor\n  {} 1\n---\n", hash), 1);
+    overwrite_note(&repo, &commit, &note);
+
+    let gitai_repo = find_repository_in_path(repo.path().to_str().unwrap()).unwrap();
+    let report = fsck_commits(&gitai_repo, &[commit]).unwrap();
+    assert_eq!(report.issues.len(), 1, "{:?}", report.issues);
+    assert_eq!(report.issues[0].kind, FsckIssueKind::MissingFile);
+    assert_eq!(report.issues[0].file.as_deref(), Some("vendor"));
+    assert!(
+        report.issues[0]
+            .message
+            .starts_with("file could not be read"),
+        "{}",
+        report.issues[0].message
+    );
+}
@@ -0,0 +1,138 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use std::path::{Path, PathBuf};
+
+const CHANGE_ID: &str = "qpvuntsmwlqnyxzrkvrmsyxmmqrztoyz";
+
+fn head_sha(repo: &TestRepo) -> String {
+    repo.git_og(&["rev-parse", "HEAD"])
+        .unwrap()
+        .trim()
+        .to_string()
+}
+
+/// Make the repository look colocated and put a `jj` on PATH that reports the changes
+/// written to the returned file
+fn fake_jj(repo: &TestRepo) -> (PathBuf, String) {
+    std::fs::create_dir_all(repo.path().join(".jj")).unwrap();
+    std::fs::write(repo.path().join(".jj").join(".gitignore"), "/*\n").unwrap();
+
+    let bin_dir = repo.path().join(".git").join("fake-jj-bin");
+    std::fs::create_dir_all(&bin_dir).unwrap();
+    let changes_file = bin_dir.join("changes");
+    let script = bin_dir.join("jj");
+    std::fs::write(
+        &script,
+        format!("#!/bin/sh\ncat '{}'\n", changes_file.display()),
+    )
+    .unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    let path = format!(
+        "{}:{}",
+        bin_dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    (changes_file, path)
+}
+
+fn set_change(changes_file: &Path, commit: &str) {
+    std::fs::write(changes_file, format!("{} {}\n", CHANGE_ID, commit)).unwrap();
+}
+
+/// Rewriting a change in jj moves its note onto the new commit at the next sync
+#[cfg(unix)]
+#[test]
+fn test_jj_sync_carries_notes_to_rewritten_commits() {
+    let repo = TestRepo::new();
+    let (changes_file, path) = fake_jj(&repo);
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines!["Base".human(), "AI line".ai(), "End".human()]);
+    let original = repo.stage_all_and_commit("Add AI line").unwrap();
+
+    set_change(&changes_file, &original.commit_sha);
+    let output = repo
+        .git_ai_with_env(&["jj-sync"], &[("PATH", &path)])
+        .unwrap();
+    assert!(output.contains("Synced 0 note(s)"), "{}", output);
+
+    // jj describes the change, rewriting its commit behind git's back
+    repo.git_og(&["commit", "--amend", "-m", "Described in jj"])
+        .unwrap();
+    let rewritten = head_sha(&repo);
+    set_change(&changes_file, &rewritten);
+
+    let dry_run = repo
+        .git_ai_with_env(&["jj-sync", "--dry-run"], &[("PATH", &path)])
+        .unwrap();
+    assert!(dry_run.contains("Would sync 1 note(s)"), "{}", dry_run);
+    assert!(
+        repo.git_og(&["notes", "--ref=ai", "show", &rewritten])
+            .is_err()
+    );
+
+    let output = repo
+        .git_ai_with_env(&["jj-sync"], &[("PATH", &path)])
+        .unwrap();
+    assert!(
+        output.contains("Synced 1 note(s) across 1 rewritten commit(s)"),
+        "{}",
+        output
+    );
+    let note = repo
+        .git_og(&["notes", "--ref=ai", "show", &rewritten])
+        .unwrap();
+    assert!(note.contains(&format!("\"base_commit_sha\": \"{}\"", rewritten)));
+    file.assert_lines_and_blame(lines!["Base".human(), "AI line".ai(), "End".human()]);
+}
+
+/// Commits jj rewrote before the first sync are found through their `change-id` header
+#[cfg(unix)]
+#[test]
+fn test_jj_sync_uses_change_id_headers_before_first_sync() {
+    let repo = TestRepo::new();
+    let (changes_file, path) = fake_jj(&repo);
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines!["Base".human(), "AI line".ai(), "End".human()]);
+    repo.stage_all_and_commit("Add AI line").unwrap();
+
+    // Give HEAD a change-id header the way jj writes commits, keeping its note
+    let raw = repo.git_og(&["cat-file", "commit", "HEAD"]).unwrap();
+    let (headers, message) = raw.split_once("\n\n").unwrap();
+    let with_header = format!("{}\nchange-id {}\n\n{}", headers, CHANGE_ID, message);
+    let header_path = repo.path().join(".git").join("jj-commit");
+    std::fs::write(&header_path, with_header).unwrap();
+    let original = repo
+        .git_og(&[
+            "hash-object",
+            "-t",
+            "commit",
+            "-w",
+            header_path.to_str().unwrap(),
+        ])
+        .unwrap()
+        .trim()
+        .to_string();
+    repo.git_og(&["notes", "--ref=ai", "copy", "HEAD", &original])
+        .unwrap();
+    repo.git_og(&["reset", "--soft", &original]).unwrap();
+
+    repo.git_og(&["commit", "--amend", "-m", "Described in jj"])
+        .unwrap();
+    let rewritten = head_sha(&repo);
+    set_change(&changes_file, &rewritten);
+
+    let output = repo
+        .git_ai_with_env(&["jj-sync"], &[("PATH", &path)])
+        .unwrap();
+    assert!(output.contains("Synced 1 note(s)"), "{}", output);
+    file.assert_lines_and_blame(lines!["Base".human(), "AI line".ai(), "End".human()]);
+}
@@ -0,0 +1,119 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn parse_json(output: &str) -> Vec<serde_json::Value> {
+    let start = output.find('[').expect("log --json should print an array");
+    serde_json::from_str(&output[start..]).unwrap()
+}
+
+/// A repository with a human commit, an AI-heavy commit and a commit in docs/
+fn repo_with_history() -> (TestRepo, String, String) {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("src/main.rs");
+    file.set_contents(lines!["fn main() {".human(), "}".human()]);
+    let human = repo.stage_all_and_commit("Human start").unwrap();
+
+    file.set_contents(lines![
+        "fn main() {".human(),
+        "    println!(\"one\");".ai(),
+        "    println!(\"two\");".ai(),
+        "    println!(\"three\");".ai(),
+        "}".human()
+    ]);
+    let ai = repo.stage_all_and_commit("AI prints").unwrap();
+
+    let mut docs = repo.filename("docs/notes.md");
+    docs.set_contents(lines!["Notes".human()]);
+    repo.stage_all_and_commit("Docs").unwrap();
+
+    (repo, human.commit_sha, ai.commit_sha)
+}
+
+/// Every commit is listed newest first with its line counts and tools
+#[test]
+fn test_log_lists_commits_with_ai_share() {
+    let (repo, human, ai) = repo_with_history();
+
+    let output = repo.git_ai(&["log"]).unwrap();
+    assert!(output.contains("AI prints"), "{}", output);
+    assert!(
+        output.contains("3 ai, 0 mixed, 0 human (100% AI)"),
+        "{}",
+        output
+    );
+    assert!(output.contains("mock_ai/unknown"), "{}", output);
+
+    let entries = parse_json(&repo.git_ai(&["log", "--json"]).unwrap());
+    let commits: Vec<&str> = entries
+        .iter()
+        .map(|entry| entry["commit"].as_str().unwrap())
+        .collect();
+    assert_eq!(commits.len(), 3);
+    assert_eq!(commits[1], ai);
+    assert_eq!(commits[2], human);
+    assert_eq!(entries[1]["stats"]["ai_additions"], 3);
+    assert_eq!(entries[1]["ai_percent"], 100.0);
+}
+
+/// The filters narrow the list down to AI-heavy commits
+#[test]
+fn test_log_filters() {
+    let (repo, _human, ai) = repo_with_history();
+
+    for args in [
+        vec!["log", "--json", "--tool", "mock_ai"],
+        vec!["log", "--json", "--model", "unknown"],
+        vec!["log", "--json", "--min-ai-percent", "50"],
+        vec!["log", "--json", "--", "src"],
+    ] {
+        let entries = parse_json(&repo.git_ai(&args).unwrap());
+        let expected = if args.contains(&"src") { 2 } else { 1 };
+        assert_eq!(entries.len(), expected, "{:?}", args);
+        assert!(
+            entries.iter().any(|entry| entry["commit"] == ai.as_str()),
+            "{:?}",
+            args
+        );
+    }
+
+    let entries = parse_json(&repo.git_ai(&["log", "--json", "--tool", "cursor"]).unwrap());
+    assert!(entries.is_empty());
+    let entries = parse_json(
+        &repo
+            .git_ai(&["log", "--json", "--author", "nobody-matches-this"])
+            .unwrap(),
+    );
+    assert!(entries.is_empty());
+    let entries = parse_json(&repo.git_ai(&["log", "--json", "HEAD~1..HEAD"]).unwrap());
+    assert_eq!(entries.len(), 1);
+}
+
+/// With paths, the counts of a commit that also touched other files only cover those paths
+#[test]
+fn test_log_paths_limit_the_counts() {
+    let repo = TestRepo::new();
+
+    let mut code = repo.filename("src/main.rs");
+    code.set_contents(lines!["fn main() {}".ai(), "fn run() {}".ai()]);
+    let mut docs = repo.filename("docs/notes.md");
+    docs.set_contents(lines!["Notes".human(), "More notes".human()]);
+    repo.stage_all_and_commit("Code and docs").unwrap();
+
+    let whole = parse_json(&repo.git_ai(&["log", "--json"]).unwrap());
+    assert_eq!(whole[0]["stats"]["git_diff_added_lines"], 4);
+    assert_eq!(whole[0]["ai_percent"], 50.0);
+
+    let src = parse_json(&repo.git_ai(&["log", "--json", "--", "src"]).unwrap());
+    assert_eq!(src[0]["stats"]["git_diff_added_lines"], 2);
+    assert_eq!(src[0]["ai_percent"], 100.0);
+
+    let docs = parse_json(
+        &repo
+            .git_ai(&["log", "--json", "--min-ai-percent", "50", "--", "docs"])
+            .unwrap(),
+    );
+    assert!(docs.is_empty());
+}
@@ -0,0 +1,162 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+use git_ai::authorship::stats::CommitStats;
+
+/// `repo.stats()` expects a non-empty tool breakdown, which human-only merges don't have
+fn head_stats(repo: &TestRepo) -> CommitStats {
+    let output = repo.git_ai(&["stats", "--json"]).unwrap();
+    let json = output
+        .lines()
+        .find(|line| line.starts_with('{'))
+        .expect("stats should print JSON");
+    serde_json::from_str(json).unwrap()
+}
+
+/// Set up a conflict on "Line 3" between the default branch and `feature`, then start the merge
+fn start_conflicting_merge(repo: &TestRepo) {
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines![
+        "Line 1".human(),
+        "Line 2".human(),
+        "Line 3".human(),
+        "Line 4".human(),
+        "End".human()
+    ]);
+    repo.stage_all_and_commit("Base").unwrap();
+    let default_branch = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines![
+        "Line 1".human(),
+        "Line 2".human(),
+        "Line 3 feature".human(),
+        "Line 4".human(),
+        "Feature AI line".ai(),
+        "End".human()
+    ]);
+    repo.stage_all_and_commit("Feature changes").unwrap();
+
+    repo.git(&["checkout", &default_branch]).unwrap();
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines![
+        "Line 1".human(),
+        "Line 2".human(),
+        "Line 3 main".human(),
+        "Line 4".human(),
+        "End".human()
+    ]);
+    repo.stage_all_and_commit("Main changes").unwrap();
+
+    assert!(
+        repo.git(&["merge", "feature", "-m", "Merge feature"])
+            .is_err(),
+        "merge should conflict"
+    );
+}
+
+/// Lines an agent writes while resolving a conflict are attributed to the merge commit
+#[test]
+fn test_ai_conflict_resolution_is_attributed() {
+    let repo = TestRepo::new();
+    start_conflicting_merge(&repo);
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines![
+        "Line 1".human(),
+        "Line 2".human(),
+        "Line 3 resolved".ai(),
+        "Line 4".human(),
+        "Feature AI line".ai(),
+        "End".human()
+    ]);
+    repo.stage_all_and_commit("Merge feature").unwrap();
+
+    file.assert_lines_and_blame(lines![
+        "Line 1".human(),
+        "Line 2".human(),
+        "Line 3 resolved".ai(),
+        "Line 4".human(),
+        "Feature AI line".ai(),
+        "End".human()
+    ]);
+
+    // Only the resolved line belongs to the merge; the feature's AI line stays on its commit
+    let note = repo.git(&["notes", "--ref=ai", "show", "HEAD"]).unwrap();
+    let attestations = note.split("---").next().unwrap();
+    assert!(attestations.contains("file.txt"), "{}", note);
+    assert!(attestations.trim_end().ends_with(" 3"), "{}", note);
+
+    let stats = head_stats(&repo);
+    assert!(stats.merge_resolution);
+    assert_eq!(stats.git_diff_added_lines, 1);
+    assert_eq!(stats.ai_additions, 1);
+    assert_eq!(stats.human_additions, 0);
+}
+
+/// A conflict resolved by hand is counted as human resolution, not as an empty merge
+#[test]
+fn test_human_conflict_resolution_stats() {
+    let repo = TestRepo::new();
+    start_conflicting_merge(&repo);
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines![
+        "Line 1".human(),
+        "Line 2".human(),
+        "Line 3 resolved".human(),
+        "Line 4".human(),
+        "Feature AI line".human(),
+        "End".human()
+    ]);
+    repo.stage_all_and_commit("Merge feature").unwrap();
+
+    // Lines that came from the feature branch keep their original attribution
+    file.assert_lines_and_blame(lines![
+        "Line 1".human(),
+        "Line 2".human(),
+        "Line 3 resolved".human(),
+        "Line 4".human(),
+        "Feature AI line".ai(),
+        "End".human()
+    ]);
+
+    let stats = head_stats(&repo);
+    assert!(stats.merge_resolution);
+    assert_eq!(stats.git_diff_added_lines, 1);
+    assert_eq!(stats.human_additions, 1);
+    assert_eq!(stats.ai_additions, 0);
+}
+
+/// Clean merges have nothing to resolve
+#[test]
+fn test_clean_merge_has_no_resolution_lines() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines!["Line 1".human(), "End".human()]);
+    repo.stage_all_and_commit("Base").unwrap();
+    let default_branch = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+    let mut other = repo.filename("other.txt");
+    other.set_contents(lines!["AI line".ai(), "End".human()]);
+    repo.stage_all_and_commit("Feature changes").unwrap();
+
+    repo.git(&["checkout", &default_branch]).unwrap();
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines!["Line 1".human(), "Main line".human(), "End".human()]);
+    repo.stage_all_and_commit("Main changes").unwrap();
+
+    repo.git(&["merge", "--no-ff", "feature", "-m", "Merge feature"])
+        .unwrap();
+
+    let stats = head_stats(&repo);
+    assert!(stats.merge_resolution);
+    assert_eq!(stats.git_diff_added_lines, 0);
+    assert_eq!(stats.ai_additions, 0);
+    other.assert_lines_and_blame(lines!["AI line".ai(), "End".human()]);
+}
@@ -0,0 +1,84 @@
+#[macro_use]
+mod repos;
+
+use git_ai::authorship::authorship_log_serialization::{AUTHORSHIP_LOG_VERSION, AuthorshipLog};
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn read_note(repo: &TestRepo, commit_sha: &str) -> String {
+    repo.git_og(&["notes", "--ref=ai", "show", commit_sha])
+        .expect("note should exist")
+}
+
+/// Rewrite a commit's note the way pre-16-char versions of git-ai wrote it
+fn downgrade_note(repo: &TestRepo, commit_sha: &str) -> String {
+    let note = read_note(repo, commit_sha);
+    let log = AuthorshipLog::deserialize_from_string(&note).unwrap();
+    let mut legacy = note.replace(AUTHORSHIP_LOG_VERSION, "authorship/2.1.0");
+    for hash in log.metadata.prompts.keys() {
+        legacy = legacy.replace(hash.as_str(), &hash[..7]);
+    }
+    repo.git_og(&["notes", "--ref=ai", "add", "-f", "-m", &legacy, commit_sha])
+        .unwrap();
+    legacy
+}
+
+#[test]
+fn test_migrate_notes_rewrites_legacy_notes_in_one_commit() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("test.txt");
+
+    file.set_contents(lines!["Base".human(), "AI line 1".ai()]);
+    let first = repo.stage_all_and_commit("First commit").unwrap();
+    file.insert_at(2, lines!["AI line 2".ai()]);
+    let second = repo.stage_all_and_commit("Second commit").unwrap();
+
+    let first_legacy = downgrade_note(&repo, &first.commit_sha);
+    let second_legacy = downgrade_note(&repo, &second.commit_sha);
+
+    let dry_run = repo.git_ai(&["migrate-notes", "--dry-run"]).unwrap();
+    assert!(
+        dry_run.contains("Would migrate 2 of 2 note(s)"),
+        "unexpected output: {}",
+        dry_run
+    );
+    assert!(dry_run.contains(&format!("--- a/{}", first.commit_sha)));
+    assert!(dry_run.contains("-  \"schema_version\": \"authorship/2.1.0\","));
+    assert_eq!(read_note(&repo, &first.commit_sha), first_legacy);
+    assert_eq!(read_note(&repo, &second.commit_sha), second_legacy);
+
+    let notes_before = repo.git_og(&["rev-parse", "refs/notes/ai"]).unwrap();
+    let output = repo.git_ai(&["migrate-notes"]).unwrap();
+    assert!(
+        output.contains("Migrated 2 of 2 note(s)"),
+        "unexpected output: {}",
+        output
+    );
+
+    // Both notes were rewritten by a single commit on top of the old notes ref
+    let parent = repo.git_og(&["rev-parse", "refs/notes/ai^"]).unwrap();
+    assert_eq!(parent, notes_before);
+
+    for commit in [&first, &second] {
+        let migrated =
+            AuthorshipLog::deserialize_from_string(&read_note(&repo, &commit.commit_sha)).unwrap();
+        assert_eq!(migrated.metadata.schema_version, AUTHORSHIP_LOG_VERSION);
+        assert_eq!(
+            migrated.metadata.prompts.keys().collect::<Vec<_>>(),
+            commit
+                .authorship_log
+                .metadata
+                .prompts
+                .keys()
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(migrated.attestations, commit.authorship_log.attestations);
+    }
+
+    let again = repo.git_ai(&["migrate-notes"]).unwrap();
+    assert!(
+        again.contains("All 2 note(s) are already up to date"),
+        "unexpected output: {}",
+        again
+    );
+}
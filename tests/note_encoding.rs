@@ -0,0 +1,106 @@
+#[macro_use]
+mod repos;
+
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn read_note(repo: &TestRepo, commit_sha: &str) -> String {
+    repo.git_og(&["notes", "--ref=ai", "show", commit_sha])
+        .expect("note should exist")
+}
+
+#[test]
+fn test_compressed_notes_keep_attestations_readable() {
+    let repo = TestRepo::new();
+    repo.git_og(&["config", "gitai.noteEncoding", "zstd"])
+        .unwrap();
+
+    let mut file = repo.filename("test.txt");
+    file.set_contents(lines!["Base".human(), "AI line 1".ai(), "AI line 2".ai()]);
+    let commit = repo.stage_all_and_commit("First commit").unwrap();
+
+    let note = read_note(&repo, &commit.commit_sha);
+    let (attestations, metadata) = note.split_once("\n---\n").unwrap();
+    assert!(attestations.starts_with("test.txt\n"));
+    assert!(
+        metadata.starts_with("encoding: zstd+base64\n"),
+        "metadata should be compressed: {}",
+        metadata
+    );
+    assert!(!metadata.contains("schema_version"));
+
+    // The decoded note still carries the prompt that blame and show-prompt resolve
+    let prompt_id = commit
+        .authorship_log
+        .metadata
+        .prompts
+        .keys()
+        .next()
+        .unwrap()
+        .clone();
+    file.assert_lines_and_blame(lines!["Base".human(), "AI line 1".ai(), "AI line 2".ai()]);
+
+    let output = repo.git_ai(&["show-prompt", &prompt_id]).unwrap();
+    assert!(output.contains(&prompt_id), "unexpected output: {}", output);
+
+    let stats = repo.git_ai(&["stats", "--json"]).unwrap();
+    assert!(
+        stats.contains("\"ai_additions\":2"),
+        "unexpected stats: {}",
+        stats
+    );
+}
+
+#[test]
+fn test_plain_notes_are_the_default() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("test.txt");
+    file.set_contents(lines!["Base".human(), "AI line 1".ai(), "End".human()]);
+    let commit = repo.stage_all_and_commit("First commit").unwrap();
+
+    let note = read_note(&repo, &commit.commit_sha);
+    assert!(!note.contains("encoding: "));
+    assert!(note.contains("\"schema_version\""));
+
+    // Turning compression on later doesn't break reading the older plain notes
+    repo.git_og(&["config", "gitai.noteEncoding", "deflate"])
+        .unwrap();
+    file.insert_at(2, lines!["AI line 2".ai()]);
+    let second = repo.stage_all_and_commit("Second commit").unwrap();
+    assert!(read_note(&repo, &second.commit_sha).contains("\n---\nencoding: deflate+base64\n"));
+    file.assert_lines_and_blame(lines![
+        "Base".human(),
+        "AI line 1".ai(),
+        "AI line 2".ai(),
+        "End".human()
+    ]);
+}
+
+#[test]
+fn test_blame_ignores_notes_of_other_schema_versions() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("test.txt");
+    file.set_contents(lines!["Base".human(), "AI line 1".ai()]);
+    let commit = repo.stage_all_and_commit("First commit").unwrap();
+    let blame = repo.git_ai(&["blame", "test.txt"]).unwrap();
+    assert!(blame.contains("mock_ai"), "unexpected blame: {}", blame);
+
+    let other_version = commit
+        .authorship_log
+        .serialize_to_string()
+        .unwrap()
+        .replace("authorship/3.0.0", "authorship/9.0.0");
+    repo.git_og(&[
+        "notes",
+        "--ref=ai",
+        "add",
+        "-f",
+        "-m",
+        &other_version,
+        &commit.commit_sha,
+    ])
+    .unwrap();
+
+    let blame = repo.git_ai(&["blame", "test.txt"]).unwrap();
+    assert!(!blame.contains("mock_ai"), "unexpected blame: {}", blame);
+}
@@ -0,0 +1,69 @@
+#[macro_use]
+mod repos;
+
+use git_ai::authorship::authorship_log::LineRange;
+use git_ai::authorship::authorship_log_serialization::{AttestationEntry, AuthorshipLog};
+use git_ai::git::find_repository_in_path;
+use git_ai::git::refs::merge_notes_from_ref;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+/// Attribute an extra file to the commit's prompt in the note under `notes_ref`
+fn add_file_to_note(repo: &TestRepo, notes_ref: &str, commit_sha: &str, file_path: &str) {
+    let ref_arg = format!("--ref={}", notes_ref);
+    let note = repo
+        .git_og(&["notes", &ref_arg, "show", commit_sha])
+        .unwrap();
+    let mut log = AuthorshipLog::deserialize_from_string(&note).unwrap();
+    let hash = log.metadata.prompts.keys().next().unwrap().clone();
+    log.get_or_create_file(file_path)
+        .add_entry(AttestationEntry::new(hash, vec![LineRange::Range(1, 2)]));
+    let content = log.serialize_to_string().unwrap();
+    repo.git_og(&["notes", &ref_arg, "add", "-f", "-m", &content, commit_sha])
+        .unwrap();
+}
+
+#[test]
+fn test_conflicting_notes_are_union_merged() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("test.txt");
+    file.set_contents(lines!["Base".human(), "AI line 1".ai()]);
+    let commit = repo.stage_all_and_commit("First commit").unwrap();
+
+    // Simulate a remote whose copy of the note diverged from ours
+    repo.git_og(&["update-ref", "refs/notes/theirs", "refs/notes/ai"])
+        .unwrap();
+    add_file_to_note(&repo, "ai", &commit.commit_sha, "ours.txt");
+    add_file_to_note(&repo, "theirs", &commit.commit_sha, "theirs.txt");
+
+    let gitai_repo = find_repository_in_path(repo.path().to_str().unwrap()).unwrap();
+    merge_notes_from_ref(&gitai_repo, "refs/notes/theirs").unwrap();
+
+    let note = repo
+        .git_og(&["notes", "--ref=ai", "show", &commit.commit_sha])
+        .unwrap();
+    let merged = AuthorshipLog::deserialize_from_string(&note).unwrap();
+    let files: Vec<&str> = merged
+        .attestations
+        .iter()
+        .map(|f| f.file_path.as_str())
+        .collect();
+    assert_eq!(files, vec!["ours.txt", "test.txt", "theirs.txt"]);
+    assert_eq!(
+        merged.metadata.prompts.keys().collect::<Vec<_>>(),
+        commit
+            .authorship_log
+            .metadata
+            .prompts
+            .keys()
+            .collect::<Vec<_>>()
+    );
+
+    // Merging again is a no-op
+    let notes_head = repo.git_og(&["rev-parse", "refs/notes/ai"]).unwrap();
+    merge_notes_from_ref(&gitai_repo, "refs/notes/theirs").unwrap();
+    assert_eq!(
+        repo.git_og(&["rev-parse", "refs/notes/ai"]).unwrap(),
+        notes_head
+    );
+}
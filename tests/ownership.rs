@@ -0,0 +1,86 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn parse_json(output: &str) -> serde_json::Value {
+    let start = output
+        .find('{')
+        .expect("ownership --json should print an object");
+    serde_json::from_str(&output[start..]).unwrap()
+}
+
+/// Lines are counted per file and rolled up into every parent directory
+#[test]
+fn test_ownership_rolls_up_per_directory() {
+    let repo = TestRepo::new();
+
+    let mut readme = repo.filename("README.md");
+    readme.set_contents(lines!["# Project".human(), "Intro".human()]);
+    repo.stage_all_and_commit("Readme").unwrap();
+
+    let mut main = repo.filename("src/app/main.rs");
+    main.set_contents(lines!["fn main() {".ai(), "    run();".ai(), "}".ai()]);
+    repo.stage_all_and_commit("AI main").unwrap();
+
+    let mut lib = repo.filename("src/lib.rs");
+    lib.set_contents(lines!["pub fn run() {}".human()]);
+    repo.stage_all_and_commit("Human lib").unwrap();
+
+    let ownership = parse_json(&repo.git_ai(&["ownership", "--json"]).unwrap());
+    assert_eq!(ownership["files"]["src/app/main.rs"]["ai"], 3);
+    assert_eq!(ownership["files"]["src/lib.rs"]["human"], 1);
+    assert_eq!(ownership["files"]["README.md"]["human"], 2);
+    assert_eq!(ownership["directories"]["src/app"]["ai"], 3);
+    assert_eq!(ownership["directories"]["src"]["ai"], 3);
+    assert_eq!(ownership["directories"]["src"]["human"], 1);
+    assert_eq!(ownership["total"]["ai"], 3);
+    assert_eq!(ownership["total"]["human"], 3);
+    assert_eq!(ownership["total"]["unknown"], 0);
+
+    // A second run reads the cache and agrees
+    let cached = parse_json(&repo.git_ai(&["ownership", "--json"]).unwrap());
+    assert_eq!(cached["total"], ownership["total"]);
+
+    let tree = repo.git_ai(&["ownership"]).unwrap();
+    assert!(tree.contains("src/"), "{}", tree);
+    assert!(tree.contains("app/"), "{}", tree);
+    assert!(!tree.contains("main.rs"), "{}", tree);
+
+    let scoped = parse_json(
+        &repo
+            .git_ai(&["ownership", "--json", "--", "src/app"])
+            .unwrap(),
+    );
+    assert_eq!(scoped["total"]["ai"], 3);
+    assert_eq!(scoped["total"]["human"], 0);
+    assert!(scoped["files"].get("README.md").is_none());
+}
+
+/// Older revisions and commits without notes are reported as unknown
+#[test]
+fn test_ownership_at_revision_with_unknown_lines() {
+    let repo = TestRepo::new();
+
+    std::fs::write(repo.path().join("legacy.txt"), "old\ncode\n").unwrap();
+    repo.git_og(&["add", "legacy.txt"]).unwrap();
+    repo.git_og(&["commit", "-m", "Before git-ai"]).unwrap();
+    let first = repo
+        .git_og(&["rev-parse", "HEAD"])
+        .unwrap()
+        .trim()
+        .to_string();
+
+    let mut file = repo.filename("new.rs");
+    file.set_contents(lines!["fn new() {}".ai()]);
+    repo.stage_all_and_commit("AI file").unwrap();
+
+    let now = parse_json(&repo.git_ai(&["ownership", "--json"]).unwrap());
+    assert_eq!(now["total"]["unknown"], 2);
+    assert_eq!(now["total"]["ai"], 1);
+
+    let before = parse_json(&repo.git_ai(&["ownership", &first, "--json"]).unwrap());
+    assert_eq!(before["commit"], first.as_str());
+    assert_eq!(before["total"]["ai"], 0);
+    assert!(before["files"].get("new.rs").is_none());
+}
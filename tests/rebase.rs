@@ -1118,3 +1118,47 @@ cat {} > "$1"
         "function feature3() {}".ai()
     ]);
 }
+
+/// With anchors on, a rebase that shifts AI lines keeps them and re-anchors the new note
+#[test]
+fn test_rebase_with_note_anchors_keeps_shifted_lines() {
+    let repo = TestRepo::new();
+    repo.git_og(&["config", "gitai.noteAnchors", "true"])
+        .unwrap();
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines!["top".human(), "bottom".human()]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let default_branch = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+    file.set_contents(lines![
+        "top".human(),
+        "AI line 1".ai(),
+        "AI line 2".ai(),
+        "bottom".human()
+    ]);
+    repo.stage_all_and_commit("AI lines").unwrap();
+
+    repo.git(&["checkout", &default_branch]).unwrap();
+    file.set_contents(lines!["header".human(), "top".human(), "bottom".human()]);
+    repo.stage_all_and_commit("Add header").unwrap();
+
+    repo.git(&["checkout", "feature"]).unwrap();
+    repo.git(&["rebase", &default_branch]).unwrap();
+
+    file.assert_lines_and_blame(lines![
+        "header".human(),
+        "top".human(),
+        "AI line 1".ai(),
+        "AI line 2".ai(),
+        "bottom".human()
+    ]);
+    let note = repo.git_og(&["notes", "--ref=ai", "show", "HEAD"]).unwrap();
+    assert!(
+        note.contains("3-4"),
+        "note should follow the lines: {}",
+        note
+    );
+    assert!(note.contains("\n~ "), "note should carry anchors: {}", note);
+}
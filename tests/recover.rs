@@ -0,0 +1,152 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn has_note(repo: &TestRepo, commit: &str) -> bool {
+    repo.git_og(&["notes", "--ref=ai", "show", commit]).is_ok()
+}
+
+fn rev_parse(repo: &TestRepo, rev: &str) -> String {
+    repo.git_og(&["rev-parse", rev]).unwrap().trim().to_string()
+}
+
+/// Build a feature branch with two AI commits on top of a default branch that moved on
+fn diverged_feature_branch(repo: &TestRepo) -> String {
+    let mut base = repo.filename("base.txt");
+    base.set_contents(lines!["Base".human()]);
+    repo.stage_all_and_commit("Base").unwrap();
+    let default_branch = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+    let mut first = repo.filename("first.txt");
+    first.set_contents(lines!["First".human(), "AI first".ai(), "End".human()]);
+    repo.stage_all_and_commit("First feature commit").unwrap();
+    let mut second = repo.filename("second.txt");
+    second.set_contents(lines!["Second".human(), "AI second".ai(), "End".human()]);
+    repo.stage_all_and_commit("Second feature commit").unwrap();
+
+    repo.git(&["checkout", &default_branch]).unwrap();
+    let mut other = repo.filename("other.txt");
+    other.set_contents(lines!["Other work".human()]);
+    repo.stage_all_and_commit("Other work").unwrap();
+
+    default_branch
+}
+
+/// Commits rebased without git-ai (as a hosting platform would) get their notes back by patch-id
+#[test]
+fn test_recover_rebased_commits_by_patch_id() {
+    let repo = TestRepo::new();
+    let default_branch = diverged_feature_branch(&repo);
+
+    // Rebase a copy of the branch behind git-ai's back
+    repo.git_og(&["checkout", "-q", "-b", "server", "feature"])
+        .unwrap();
+    repo.git_og(&["rebase", "-q", &default_branch]).unwrap();
+    assert!(!has_note(&repo, "HEAD"));
+    assert!(!has_note(&repo, "HEAD~1"));
+
+    let range = format!("{}..server", default_branch);
+    let dry_run = repo.git_ai(&["recover", "--dry-run", &range]).unwrap();
+    assert!(
+        dry_run.contains("Would recover 2 of 2 commit(s)"),
+        "{}",
+        dry_run
+    );
+    assert!(!has_note(&repo, "HEAD"));
+
+    let output = repo.git_ai(&["recover", &range]).unwrap();
+    assert!(output.contains("Recovered 2 of 2 commit(s)"), "{}", output);
+    assert!(output.contains("(patch-id)"), "{}", output);
+
+    repo.filename("first.txt").assert_lines_and_blame(lines![
+        "First".human(),
+        "AI first".ai(),
+        "End".human()
+    ]);
+    repo.filename("second.txt").assert_lines_and_blame(lines![
+        "Second".human(),
+        "AI second".ai(),
+        "End".human()
+    ]);
+}
+
+/// A server-side squash is matched to the commits it squashed by their added lines
+#[test]
+fn test_recover_squashed_commit_by_similarity() {
+    let repo = TestRepo::new();
+    let default_branch = diverged_feature_branch(&repo);
+
+    repo.git_og(&["merge", "-q", "--squash", "feature"])
+        .unwrap();
+    repo.git_og(&["commit", "-q", "-m", "Squashed feature"])
+        .unwrap();
+    let squashed = rev_parse(&repo, "HEAD");
+    assert!(!has_note(&repo, &squashed));
+
+    let output = repo.git_ai(&["recover", &squashed]).unwrap();
+    assert!(output.contains("Recovered 1 of 1 commit(s)"), "{}", output);
+    assert!(output.contains("(similarity)"), "{}", output);
+    assert_eq!(repo.current_branch(), default_branch);
+
+    repo.filename("first.txt").assert_lines_and_blame(lines![
+        "First".human(),
+        "AI first".ai(),
+        "End".human()
+    ]);
+    repo.filename("second.txt").assert_lines_and_blame(lines![
+        "Second".human(),
+        "AI second".ai(),
+        "End".human()
+    ]);
+}
+
+/// Commits that don't match any noted commit are reported rather than guessed at
+#[test]
+fn test_recover_reports_unmatched_commits() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines!["Base".human(), "AI line".ai(), "End".human()]);
+    repo.stage_all_and_commit("Noted").unwrap();
+
+    std::fs::write(repo.path().join("unrelated.txt"), "Written elsewhere\n").unwrap();
+    repo.git_og(&["add", "unrelated.txt"]).unwrap();
+    repo.git_og(&["commit", "-q", "-m", "Unrelated"]).unwrap();
+    let unrelated = rev_parse(&repo, "HEAD");
+
+    let output = repo.git_ai(&["recover"]).unwrap();
+    assert!(output.contains("Recovered 0 of 1 commit(s)"), "{}", output);
+    assert!(output.contains("Could not recover:"), "{}", output);
+    assert!(output.contains(&unrelated[..7]), "{}", output);
+    assert!(!has_note(&repo, &unrelated));
+}
+
+/// A small noted commit whose only line also shows up in a commit isn't taken as its origin
+#[test]
+fn test_recover_ignores_one_line_noted_commits() {
+    let repo = TestRepo::new();
+    let mut base = repo.filename("base.txt");
+    base.set_contents(lines!["Base".human()]);
+    repo.stage_all_and_commit("Base").unwrap();
+    let default_branch = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "side"]).unwrap();
+    let mut small = repo.filename("small.txt");
+    small.set_contents(lines!["return value;".ai()]);
+    repo.stage_all_and_commit("One AI line").unwrap();
+    repo.git(&["checkout", &default_branch]).unwrap();
+
+    std::fs::write(
+        repo.path().join("unrelated.txt"),
+        "let value = compute();\nreturn value;\n",
+    )
+    .unwrap();
+    repo.git_og(&["add", "unrelated.txt"]).unwrap();
+    repo.git_og(&["commit", "-q", "-m", "Unrelated"]).unwrap();
+    let unrelated = rev_parse(&repo, "HEAD");
+
+    let output = repo.git_ai(&["recover", &unrelated]).unwrap();
+    assert!(output.contains("Recovered 0 of 1 commit(s)"), "{}", output);
+    assert!(!has_note(&repo, &unrelated));
+}
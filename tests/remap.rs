@@ -0,0 +1,168 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn head_sha(repo: &TestRepo) -> String {
+    repo.git_og(&["rev-parse", "HEAD"])
+        .unwrap()
+        .trim()
+        .to_string()
+}
+
+/// Write a commit map the way `git filter-repo` does, header and removed commits included
+fn write_commit_map(path: &std::path::Path, pairs: &[(&str, &str)]) {
+    let mut content = "old                                      new\n".to_string();
+    for (old, new) in pairs {
+        content.push_str(&format!("{} {}\n", old, new));
+    }
+    content.push_str(&format!("{} {}\n", "f".repeat(40), "0".repeat(40)));
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+/// Notes follow rewritten commits listed in filter-repo's default commit map
+#[test]
+fn test_remap_moves_notes_to_rewritten_commits() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines!["Base".human(), "AI line".ai(), "End".human()]);
+    let original = repo.stage_all_and_commit("Add AI line").unwrap();
+
+    // Reword without git-ai noticing, like a history rewrite would
+    repo.git_og(&["commit", "--amend", "-m", "Reworded"])
+        .unwrap();
+    let rewritten = head_sha(&repo);
+    assert!(
+        repo.git_og(&["notes", "--ref=ai", "show", &rewritten])
+            .is_err()
+    );
+
+    let map_path = repo
+        .path()
+        .join(".git")
+        .join("filter-repo")
+        .join("commit-map");
+    write_commit_map(&map_path, &[(&original.commit_sha, &rewritten)]);
+
+    let dry_run = repo.git_ai(&["remap", "--dry-run"]).unwrap();
+    assert!(dry_run.contains("Would remap 1 note(s)"), "{}", dry_run);
+    assert!(
+        repo.git_og(&["notes", "--ref=ai", "show", &rewritten])
+            .is_err()
+    );
+
+    let output = repo.git_ai(&["remap"]).unwrap();
+    assert!(output.contains("Remapped 1 note(s)"), "{}", output);
+
+    let note = repo
+        .git_og(&["notes", "--ref=ai", "show", &rewritten])
+        .unwrap();
+    assert!(note.contains(&format!("\"base_commit_sha\": \"{}\"", rewritten)));
+    file.assert_lines_and_blame(lines!["Base".human(), "AI line".ai(), "End".human()]);
+}
+
+/// `--path-rename` moves attestations along with the files
+#[test]
+fn test_remap_applies_path_renames() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("src/file.txt");
+    file.set_contents(lines!["Base".human(), "AI line".ai(), "End".human()]);
+    let original = repo.stage_all_and_commit("Add AI line").unwrap();
+
+    repo.git_og(&["mv", "src", "lib"]).unwrap();
+    repo.git_og(&["commit", "--amend", "-m", "Moved to lib"])
+        .unwrap();
+    let rewritten = head_sha(&repo);
+
+    let map_path = repo.path().join(".git").join("remap-test").join("map.txt");
+    write_commit_map(&map_path, &[(&original.commit_sha, &rewritten)]);
+    repo.git_ai(&[
+        "remap",
+        "--path-rename",
+        "src/:lib/",
+        map_path.to_str().unwrap(),
+    ])
+    .unwrap();
+
+    let note = repo
+        .git_og(&["notes", "--ref=ai", "show", &rewritten])
+        .unwrap();
+    assert!(note.starts_with("lib/file.txt"), "{}", note);
+
+    let mut moved = repo.filename("lib/file.txt");
+    moved.assert_lines_and_blame(lines!["Base".human(), "AI line".ai(), "End".human()]);
+}
+
+/// Without a rename, files are followed by content and removed files are dropped
+#[test]
+fn test_remap_follows_content_and_drops_removed_files() {
+    let repo = TestRepo::new();
+    let mut kept = repo.filename("kept.txt");
+    let mut removed = repo.filename("removed.txt");
+    kept.set_contents(lines!["Base".human(), "AI kept".ai(), "End".human()]);
+    removed.set_contents(lines!["Base".human(), "AI removed".ai(), "End".human()]);
+    let original = repo.stage_all_and_commit("Add AI lines").unwrap();
+
+    repo.git_og(&["mv", "kept.txt", "renamed.txt"]).unwrap();
+    repo.git_og(&["rm", "-q", "removed.txt"]).unwrap();
+    repo.git_og(&["commit", "--amend", "-m", "Filtered"])
+        .unwrap();
+    let rewritten = head_sha(&repo);
+
+    let map_path = repo
+        .path()
+        .join(".git")
+        .join("filter-repo")
+        .join("commit-map");
+    write_commit_map(&map_path, &[(&original.commit_sha, &rewritten)]);
+    repo.git_ai(&["remap"]).unwrap();
+
+    let note = repo
+        .git_og(&["notes", "--ref=ai", "show", &rewritten])
+        .unwrap();
+    let attestations = note.split("---").next().unwrap();
+    assert!(attestations.contains("renamed.txt"), "{}", note);
+    assert!(!attestations.contains("removed.txt"), "{}", note);
+}
+
+/// Files whose content the rewrite changed have their ranges remapped onto the new lines
+#[test]
+fn test_remap_remaps_rewritten_content() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines![
+        "Line 1".human(),
+        "password = hunter2".human(),
+        "AI line".ai(),
+        "End".human()
+    ]);
+    let original = repo.stage_all_and_commit("Add AI line").unwrap();
+
+    // Like BFG --replace-text: a line is rewritten and another is added above the AI line
+    std::fs::write(
+        repo.path().join("file.txt"),
+        "Header\nLine 1\npassword = ***REMOVED***\nAI line\nEnd",
+    )
+    .unwrap();
+    repo.git_og(&["add", "file.txt"]).unwrap();
+    repo.git_og(&["commit", "--amend", "-m", "Scrubbed"])
+        .unwrap();
+    let rewritten = head_sha(&repo);
+
+    let map_path = repo
+        .path()
+        .join(".git")
+        .join("filter-repo")
+        .join("commit-map");
+    write_commit_map(&map_path, &[(&original.commit_sha, &rewritten)]);
+    repo.git_ai(&["remap"]).unwrap();
+
+    file.assert_lines_and_blame(lines![
+        "Header".human(),
+        "Line 1".human(),
+        "password = ***REMOVED***".human(),
+        "AI line".ai(),
+        "End".human()
+    ]);
+}
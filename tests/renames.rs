@@ -0,0 +1,132 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+/// AI lines in a file renamed with `git mv` before the commit stay attributed to the AI
+#[test]
+fn test_git_mv_keeps_ai_attributions() {
+    let repo = TestRepo::new();
+
+    let mut readme = repo.filename("README.md");
+    readme.set_contents(lines!["Readme".human()]);
+    repo.stage_all_and_commit("Base").unwrap();
+
+    let mut file = repo.filename("draft.txt");
+    file.set_contents(lines![
+        "Human line".human(),
+        "AI line 1".ai(),
+        "AI line 2".ai(),
+        "End".human()
+    ]);
+
+    repo.git(&["mv", "draft.txt", "final.txt"]).unwrap();
+    repo.stage_all_and_commit("Rename").unwrap();
+
+    let mut renamed = repo.filename("final.txt");
+    renamed.assert_lines_and_blame(lines![
+        "Human line".human(),
+        "AI line 1".ai(),
+        "AI line 2".ai(),
+        "End".human()
+    ]);
+}
+
+/// A rename done outside git, followed by an edit, is picked up at the next checkpoint
+#[test]
+fn test_editor_rename_keeps_ai_attributions() {
+    let repo = TestRepo::new();
+
+    let mut readme = repo.filename("README.md");
+    readme.set_contents(lines!["Readme".human()]);
+    repo.stage_all_and_commit("Base").unwrap();
+
+    let mut file = repo.filename("src/draft.txt");
+    file.set_contents(lines![
+        "Human line".human(),
+        "AI line 1".ai(),
+        "AI line 2".ai(),
+        "End".human()
+    ]);
+
+    std::fs::create_dir_all(repo.path().join("lib")).unwrap();
+    std::fs::rename(
+        repo.path().join("src/draft.txt"),
+        repo.path().join("lib/final.txt"),
+    )
+    .unwrap();
+    std::fs::write(
+        repo.path().join("lib/final.txt"),
+        "Human line\nAI line 1\nAI line 2\nEnd\nHuman addition",
+    )
+    .unwrap();
+    repo.git_ai(&["checkpoint"]).unwrap();
+
+    let checkpoints = repo.current_working_logs().read_all_checkpoints().unwrap();
+    assert!(
+        checkpoints
+            .iter()
+            .flat_map(|checkpoint| &checkpoint.entries)
+            .all(|entry| entry.file != "src/draft.txt"),
+        "checkpoints still use the old path"
+    );
+
+    repo.stage_all_and_commit("Move draft").unwrap();
+
+    let mut renamed = repo.filename("lib/final.txt");
+    renamed.assert_lines_and_blame(lines![
+        "Human line".human(),
+        "AI line 1".ai(),
+        "AI line 2".ai(),
+        "End".human(),
+        "Human addition".human()
+    ]);
+}
+
+fn loose_object_count(repo: &TestRepo) -> String {
+    repo.git_og(&["count-objects"])
+        .unwrap()
+        .split_whitespace()
+        .next()
+        .unwrap()
+        .to_string()
+}
+
+/// A file deleted in one checkpoint and recreated under a new name in a later one is still
+/// followed, and detection doesn't write objects into the repository
+#[test]
+fn test_rename_across_checkpoints_keeps_ai_attributions() {
+    let repo = TestRepo::new();
+
+    let mut readme = repo.filename("README.md");
+    readme.set_contents(lines!["Readme".human()]);
+    repo.stage_all_and_commit("Base").unwrap();
+
+    let mut file = repo.filename("draft.txt");
+    file.set_contents(lines![
+        "Human line".human(),
+        "AI line 1".ai(),
+        "AI line 2".ai(),
+        "End".human()
+    ]);
+    let content = std::fs::read_to_string(repo.path().join("draft.txt")).unwrap();
+    let objects_before = loose_object_count(&repo);
+
+    std::fs::remove_file(repo.path().join("draft.txt")).unwrap();
+    repo.git_ai(&["checkpoint"]).unwrap();
+    std::fs::write(repo.path().join("final.txt"), &content).unwrap();
+    repo.git_ai(&["checkpoint"]).unwrap();
+    // Nothing changed, so this checkpoint skips rename detection
+    repo.git_ai(&["checkpoint"]).unwrap();
+
+    assert_eq!(loose_object_count(&repo), objects_before);
+
+    repo.stage_all_and_commit("Rename").unwrap();
+    let mut renamed = repo.filename("final.txt");
+    renamed.assert_lines_and_blame(lines![
+        "Human line".human(),
+        "AI line 1".ai(),
+        "AI line 2".ai(),
+        "End".human()
+    ]);
+}
@@ -0,0 +1,65 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+/// The report covers every section and stays self-contained
+#[test]
+fn test_report_writes_self_contained_html() {
+    let repo = TestRepo::new();
+
+    let mut readme = repo.filename("README.md");
+    readme.set_contents(lines!["# Project".human()]);
+    let base = repo.stage_all_and_commit("Initial").unwrap();
+
+    let mut main = repo.filename("src/main.rs");
+    main.set_contents(lines![
+        "fn main() {".ai(),
+        "    println!(\"hello\");".ai(),
+        "}".ai()
+    ]);
+    repo.stage_all_and_commit("AI main").unwrap();
+
+    let mut docs = repo.filename("docs/guide.md");
+    docs.set_contents(lines!["Guide".human(), "More".human()]);
+    let head = repo.stage_all_and_commit("Docs").unwrap();
+
+    let out = repo.path().join("report.html");
+    let range = format!("{}..{}", base.commit_sha, head.commit_sha);
+    let output = repo
+        .git_ai(&["report", &range, "--out", out.to_str().unwrap()])
+        .unwrap();
+    assert!(output.contains("Wrote report to"), "{}", output);
+
+    let html = std::fs::read_to_string(&out).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("AI share over time"));
+    assert!(html.contains("<svg"));
+    // Treemap cells for both directories touched in the range
+    assert!(html.contains("src (100%)"), "{}", html);
+    assert!(html.contains("docs (0%)"), "{}", html);
+    // Tool/model breakdown and prompts table
+    assert!(
+        html.contains("<td>mock_ai</td><td>unknown</td>"),
+        "{}",
+        html
+    );
+    assert!(html.contains("Top prompts by accepted lines"));
+    assert!(!html.contains("No prompts recorded in range"), "{}", html);
+
+    assert!(!html.contains("http://"));
+    assert!(!html.contains("https://"));
+    assert!(!html.contains("<script"));
+    assert!(!html.contains("<link"));
+}
+
+/// A missing range is an error
+#[test]
+fn test_report_requires_range() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("a.txt");
+    file.set_contents(lines!["a".human()]);
+    repo.stage_all_and_commit("Initial").unwrap();
+
+    assert!(repo.git_ai(&["report"]).is_err());
+}
@@ -190,6 +190,34 @@ impl TestRepo {
         repo
     }
 
+    /// Add a linked worktree of this repo on a new branch and open it as a TestRepo.
+    /// The worktree shares this repo's git-ai config and test database.
+    pub fn add_worktree(&self, branch: &str) -> Self {
+        let name = self.path.file_name().unwrap().to_string_lossy().to_string();
+        let path = self.path.with_file_name(format!("{}-{}", name, branch));
+        self.git(&["worktree", "add", "-b", branch, path.to_str().unwrap()])
+            .expect("worktree add should succeed");
+
+        Self {
+            path,
+            feature_flags: self.feature_flags.clone(),
+            config_patch: self.config_patch.clone(),
+            test_db_path: self.test_db_path.clone(),
+        }
+    }
+
+    /// Move this linked worktree with `git worktree move` and follow it to its new path
+    pub fn move_worktree(&mut self, new_path: &std::path::Path) {
+        self.git(&[
+            "worktree",
+            "move",
+            self.path.to_str().unwrap(),
+            new_path.to_str().unwrap(),
+        ])
+        .expect("worktree move should succeed");
+        self.path = new_path.to_path_buf();
+    }
+
     pub fn set_feature_flags(&mut self, feature_flags: FeatureFlags) {
         self.feature_flags = feature_flags;
     }
@@ -438,6 +466,55 @@ impl TestRepo {
         }
     }
 
+    /// Run a git command through the git-ai wrapper with data provided on stdin
+    pub fn git_with_stdin(&self, args: &[&str], stdin_data: &[u8]) -> Result<String, String> {
+        use std::io::Write;
+        use std::process::Stdio;
+
+        let binary_path = get_binary_path();
+
+        let mut full_args = vec!["-C", self.path.to_str().unwrap()];
+        full_args.extend(args);
+
+        let mut command = Command::new(binary_path);
+        command
+            .args(&full_args)
+            .env("GIT_AI", "git")
+            .env("GIT_AI_TEST_DB_PATH", self.test_db_path.to_str().unwrap())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        if let Some(patch) = &self.config_patch
+            && let Ok(patch_json) = serde_json::to_string(patch)
+        {
+            command.env("GIT_AI_TEST_CONFIG_PATCH", patch_json);
+        }
+
+        let mut child = command
+            .spawn()
+            .unwrap_or_else(|_| panic!("Failed to spawn git command: {:?}", args));
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(stdin_data)
+                .expect("Failed to write to stdin");
+        }
+
+        let output = child
+            .wait_with_output()
+            .unwrap_or_else(|_| panic!("Failed to wait for git command: {:?}", args));
+
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if output.status.success() {
+            Ok(format!("{}{}", stdout, stderr))
+        } else {
+            Err(stderr)
+        }
+    }
+
     /// Run a git-ai command with data provided on stdin
     pub fn git_ai_with_stdin(&self, args: &[&str], stdin_data: &[u8]) -> Result<String, String> {
         use std::io::Write;
@@ -589,7 +666,10 @@ impl TestRepo {
 
 impl Drop for TestRepo {
     fn drop(&mut self) {
-        fs::remove_dir_all(self.path.clone()).expect("failed to remove test repo");
+        // Worktrees may already have been removed or moved by the test
+        if self.path.exists() {
+            fs::remove_dir_all(self.path.clone()).expect("failed to remove test repo");
+        }
         // Also clean up the test database directory (may not exist if no DB operations were done)
         let _ = fs::remove_dir_all(self.test_db_path.clone());
     }
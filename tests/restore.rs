@@ -0,0 +1,109 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+/// Lines an agent wrote and `git restore` threw away aren't the AI's when a human types
+/// them again
+#[test]
+fn test_restore_drops_discarded_ai_lines() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines!["Base".human(), "End".human()]);
+    repo.stage_all_and_commit("Base").unwrap();
+
+    file.set_contents(lines!["Base".human(), "Shared line".ai(), "End".human()]);
+    repo.git(&["restore", "--staged", "--worktree", "file.txt"])
+        .unwrap();
+    assert_eq!(repo.read_file("file.txt").unwrap(), "Base\nEnd");
+
+    file.set_contents(lines!["Base".human(), "Shared line".human(), "End".human()]);
+    repo.stage_all_and_commit("Human change").unwrap();
+
+    file.assert_lines_and_blame(lines!["Base".human(), "Shared line".human(), "End".human()]);
+}
+
+/// Files an agent created and `git clean` removed leave nothing behind in the working log
+#[test]
+fn test_clean_drops_removed_ai_files() {
+    let repo = TestRepo::new();
+
+    let mut readme = repo.filename("README.md");
+    readme.set_contents(lines!["Readme".human()]);
+    repo.stage_all_and_commit("Base").unwrap();
+
+    let untracked = repo.path().join("generated.txt");
+    std::fs::write(&untracked, "Generated line\n").unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai", "generated.txt"])
+        .unwrap();
+
+    repo.git(&["clean", "-f"]).unwrap();
+    assert!(!untracked.exists());
+
+    let initial = repo.current_working_logs().read_initial_attributions();
+    assert!(!initial.files.contains_key("generated.txt"));
+    let checkpoints = repo.current_working_logs().read_all_checkpoints().unwrap();
+    assert!(
+        checkpoints
+            .iter()
+            .flat_map(|checkpoint| &checkpoint.entries)
+            .all(|entry| entry.file != "generated.txt"),
+        "checkpoints still mention the cleaned file"
+    );
+
+    let mut generated = repo.filename("generated.txt");
+    generated.set_contents(lines!["Generated line".human()]);
+    repo.stage_all_and_commit("Human file").unwrap();
+
+    generated.assert_lines_and_blame(lines!["Generated line".human()]);
+}
+
+/// `git checkout -p` only drops the hunks that were discarded; the AI keeps the rest
+#[test]
+fn test_checkout_patch_keeps_remaining_hunks() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines![
+        "Line 1", "Line 2", "Line 3", "Line 4", "Line 5", "Line 6", "Line 7", "Line 8", "Line 9",
+        "Line 10", "Line 11", "End"
+    ]);
+    repo.stage_all_and_commit("Base").unwrap();
+
+    file.set_contents(lines![
+        "Line 1".human(),
+        "AI line 2".ai(),
+        "Line 3".human(),
+        "Line 4".human(),
+        "Line 5".human(),
+        "Line 6".human(),
+        "Line 7".human(),
+        "Line 8".human(),
+        "Line 9".human(),
+        "Line 10".human(),
+        "AI line 11".ai(),
+        "End".human()
+    ]);
+
+    // Discard the first hunk, keep the second
+    repo.git_with_stdin(&["checkout", "-p", "HEAD", "--", "file.txt"], b"y\nn\n")
+        .unwrap();
+
+    repo.stage_all_and_commit("Keep one AI hunk").unwrap();
+
+    file.assert_lines_and_blame(lines![
+        "Line 1".human(),
+        "Line 2".human(),
+        "Line 3".human(),
+        "Line 4".human(),
+        "Line 5".human(),
+        "Line 6".human(),
+        "Line 7".human(),
+        "Line 8".human(),
+        "Line 9".human(),
+        "Line 10".human(),
+        "AI line 11".ai(),
+        "End".human()
+    ]);
+}
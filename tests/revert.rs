@@ -0,0 +1,345 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn read_rewrite_log(repo: &TestRepo) -> String {
+    std::fs::read_to_string(repo.path().join(".git").join("ai").join("rewrite_log"))
+        .unwrap_or_default()
+}
+
+/// Reverting a commit that deleted AI lines gives those lines back to the AI
+#[test]
+fn test_revert_restores_deleted_ai_lines() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines![
+        "Base".human(),
+        "AI line 1".ai(),
+        "AI line 2".ai(),
+        "End".human()
+    ]);
+    repo.stage_all_and_commit("Add AI lines").unwrap();
+
+    file.set_contents(lines!["Base".human(), "End".human()]);
+    let removal = repo.stage_all_and_commit("Remove AI lines").unwrap();
+
+    repo.git(&["revert", "--no-edit", "HEAD"]).unwrap();
+
+    file.assert_lines_and_blame(lines![
+        "Base".human(),
+        "AI line 1".ai(),
+        "AI line 2".ai(),
+        "End".human()
+    ]);
+
+    // The revert commit owns the restored lines, so its stats count them as AI
+    let stats = repo.stats().unwrap();
+    assert_eq!(stats.ai_additions, 2);
+
+    let rewrite_log = read_rewrite_log(&repo);
+    assert!(
+        rewrite_log.contains(&format!("\"reverted_commit\":\"{}\"", removal.commit_sha)),
+        "revert should be recorded in the rewrite log: {}",
+        rewrite_log
+    );
+}
+
+/// `git revert --no-commit` stages the restored lines with their attributions for the next commit
+#[test]
+fn test_revert_no_commit_carries_attributions_to_next_commit() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines![
+        "Base".human(),
+        "AI line 1".ai(),
+        "AI line 2".ai(),
+        "End".human()
+    ]);
+    repo.stage_all_and_commit("Add AI lines").unwrap();
+
+    file.set_contents(lines!["Base".human(), "End".human()]);
+    repo.stage_all_and_commit("Remove AI lines").unwrap();
+
+    repo.git(&["revert", "--no-commit", "HEAD"]).unwrap();
+    repo.stage_all_and_commit("Bring the AI lines back")
+        .unwrap();
+
+    file.assert_lines_and_blame(lines![
+        "Base".human(),
+        "AI line 1".ai(),
+        "AI line 2".ai(),
+        "End".human()
+    ]);
+
+    let rewrite_log = read_rewrite_log(&repo);
+    assert!(rewrite_log.contains("\"revert_mixed\""));
+}
+
+/// Reverting a range undoes each commit with its own authorship
+#[test]
+fn test_revert_range_restores_lines_from_each_commit() {
+    let repo = TestRepo::new();
+
+    let mut first = repo.filename("first.txt");
+    let mut second = repo.filename("second.txt");
+    first.set_contents(lines!["First".human(), "AI first".ai(), "End".human()]);
+    second.set_contents(lines!["Second".human(), "AI second".ai(), "End".human()]);
+    let base = repo.stage_all_and_commit("Add AI lines").unwrap();
+
+    first.set_contents(lines!["First".human(), "End".human()]);
+    repo.stage_all_and_commit("Remove first AI line").unwrap();
+    second.set_contents(lines!["Second".human(), "End".human()]);
+    repo.stage_all_and_commit("Remove second AI line").unwrap();
+
+    let range = format!("{}..HEAD", base.commit_sha);
+    repo.git(&["revert", "--no-edit", &range]).unwrap();
+
+    first.assert_lines_and_blame(lines!["First".human(), "AI first".ai(), "End".human()]);
+    second.assert_lines_and_blame(lines!["Second".human(), "AI second".ai(), "End".human()]);
+
+    let rewrite_log = read_rewrite_log(&repo);
+    assert_eq!(rewrite_log.matches("\"revert_mixed\"").count(), 2);
+}
+
+/// `git revert --no-commit A B` restores lines from both commits into a single commit
+#[test]
+fn test_revert_no_commit_multiple_commits() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines![
+        "Base".human(),
+        "AI line 1".ai(),
+        "Middle".human(),
+        "AI line 2".ai(),
+        "End".human()
+    ]);
+    repo.stage_all_and_commit("Add AI lines").unwrap();
+
+    file.delete_at(1);
+    let first = repo.stage_all_and_commit("Remove first AI line").unwrap();
+    file.delete_at(2);
+    let second = repo.stage_all_and_commit("Remove second AI line").unwrap();
+
+    repo.git(&[
+        "revert",
+        "--no-commit",
+        &second.commit_sha,
+        &first.commit_sha,
+    ])
+    .unwrap();
+    repo.stage_all_and_commit("Bring both AI lines back")
+        .unwrap();
+
+    file.assert_lines_and_blame(lines![
+        "Base".human(),
+        "AI line 1".ai(),
+        "Middle".human(),
+        "AI line 2".ai(),
+        "End".human()
+    ]);
+    assert_eq!(repo.stats().unwrap().ai_additions, 2);
+}
+
+/// Lines the reverted commit added are removed and the rest keep their authors
+#[test]
+fn test_revert_of_ai_commit_keeps_remaining_attribution() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines!["Base".human(), "AI kept".ai(), "End".human()]);
+    repo.stage_all_and_commit("Add AI line").unwrap();
+
+    file.insert_at(2, lines!["AI added later".ai()]);
+    repo.stage_all_and_commit("Add another AI line").unwrap();
+
+    repo.git(&["revert", "--no-edit", "HEAD"]).unwrap();
+
+    file.assert_lines_and_blame(lines!["Base".human(), "AI kept".ai(), "End".human()]);
+    assert_eq!(repo.stats().unwrap().ai_additions, 0);
+}
+
+/// Sets up `git revert <removal> <change>` so the first revert applies and the second conflicts
+///
+/// Returns the commit that removed the AI lines from file.txt and the commit whose revert
+/// conflicts in other.txt.
+fn start_conflicting_revert(repo: &TestRepo) -> (String, String) {
+    let mut file = repo.filename("file.txt");
+    let mut other = repo.filename("other.txt");
+    file.set_contents(lines![
+        "Base".human(),
+        "AI line 1".ai(),
+        "AI line 2".ai(),
+        "End".human()
+    ]);
+    other.set_contents(lines!["one".human()]);
+    repo.stage_all_and_commit("Add AI lines").unwrap();
+
+    file.set_contents(lines!["Base".human(), "End".human()]);
+    let removal = repo.stage_all_and_commit("Remove AI lines").unwrap();
+    other.set_contents(lines!["two".human()]);
+    let change = repo.stage_all_and_commit("Change other").unwrap();
+    other.set_contents(lines!["three".human()]);
+    repo.stage_all_and_commit("Change other again").unwrap();
+
+    let result = repo.git(&[
+        "revert",
+        "--no-edit",
+        &removal.commit_sha,
+        &change.commit_sha,
+    ]);
+    assert!(result.is_err(), "second revert should stop on a conflict");
+
+    (removal.commit_sha, change.commit_sha)
+}
+
+fn assert_ai_lines_restored(repo: &TestRepo) {
+    repo.filename("file.txt").assert_lines_and_blame(lines![
+        "Base".human(),
+        "AI line 1".ai(),
+        "AI line 2".ai(),
+        "End".human()
+    ]);
+}
+
+/// Revert commits made before a conflict get their attributions once `--continue` finishes the run
+#[test]
+fn test_revert_continue_after_conflict_keeps_earlier_commits() {
+    let repo = TestRepo::new();
+    let (removal, change) = start_conflicting_revert(&repo);
+
+    std::fs::write(repo.path().join("other.txt"), "one").unwrap();
+    repo.git(&["add", "other.txt"]).unwrap();
+    repo.git_with_env(&["revert", "--continue"], &[("GIT_EDITOR", "true")], None)
+        .unwrap();
+
+    assert_ai_lines_restored(&repo);
+
+    let rewrite_log = read_rewrite_log(&repo);
+    assert!(rewrite_log.contains(&format!("\"reverted_commit\":\"{}\"", removal)));
+    assert!(rewrite_log.contains(&format!("\"reverted_commit\":\"{}\"", change)));
+}
+
+/// `--skip` drops the conflicting commit and still processes the rest of the run
+#[test]
+fn test_revert_skip_after_conflict_keeps_earlier_commits() {
+    let repo = TestRepo::new();
+    let (removal, change) = start_conflicting_revert(&repo);
+
+    repo.git(&["revert", "--skip"]).unwrap();
+
+    assert_ai_lines_restored(&repo);
+
+    let rewrite_log = read_rewrite_log(&repo);
+    assert!(rewrite_log.contains(&format!("\"reverted_commit\":\"{}\"", removal)));
+    assert!(!rewrite_log.contains(&format!("\"reverted_commit\":\"{}\"", change)));
+}
+
+/// `--quit` keeps the revert commits made so far, so they get their attributions
+#[test]
+fn test_revert_quit_after_conflict_keeps_earlier_commits() {
+    let repo = TestRepo::new();
+    let (removal, _change) = start_conflicting_revert(&repo);
+
+    repo.git(&["revert", "--quit"]).unwrap();
+
+    assert_ai_lines_restored(&repo);
+
+    let rewrite_log = read_rewrite_log(&repo);
+    assert!(rewrite_log.contains(&format!("\"reverted_commit\":\"{}\"", removal)));
+}
+
+/// `--abort` ends the run without carrying anything, and the next revert starts fresh
+#[test]
+fn test_revert_abort_after_conflict_discards_the_run() {
+    let repo = TestRepo::new();
+    let (removal, _change) = start_conflicting_revert(&repo);
+
+    repo.git(&["revert", "--abort"]).unwrap();
+
+    let rewrite_log = read_rewrite_log(&repo);
+    assert!(rewrite_log.contains("\"revert_abort\""));
+    assert!(!rewrite_log.contains("\"revert_mixed\""));
+    repo.filename("file.txt")
+        .assert_lines_and_blame(lines!["Base".human(), "End".human()]);
+
+    // A later revert uses its own targets, not the aborted run's
+    repo.git(&["revert", "--no-edit", &removal]).unwrap();
+    assert_ai_lines_restored(&repo);
+    assert_eq!(
+        read_rewrite_log(&repo).matches("\"revert_mixed\"").count(),
+        1
+    );
+}
+
+/// A `--no-commit` revert that stops on a conflict carries its attributions once resolved
+#[test]
+fn test_revert_no_commit_after_conflict_carries_attributions() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("file.txt");
+    let mut other = repo.filename("other.txt");
+    file.set_contents(lines![
+        "Base".human(),
+        "AI line 1".ai(),
+        "AI line 2".ai(),
+        "End".human()
+    ]);
+    other.set_contents(lines!["one".human()]);
+    repo.stage_all_and_commit("Add AI lines").unwrap();
+
+    file.set_contents(lines!["Base".human(), "End".human()]);
+    other.set_contents(lines!["two".human()]);
+    let removal = repo.stage_all_and_commit("Remove AI lines").unwrap();
+    other.set_contents(lines!["three".human()]);
+    repo.stage_all_and_commit("Change other again").unwrap();
+
+    let result = repo.git(&["revert", "--no-commit", &removal.commit_sha]);
+    assert!(result.is_err(), "revert should stop on a conflict");
+    assert!(!read_rewrite_log(&repo).contains("\"revert_abort\""));
+
+    std::fs::write(repo.path().join("other.txt"), "three").unwrap();
+    repo.stage_all_and_commit("Bring the AI lines back")
+        .unwrap();
+
+    assert_ai_lines_restored(&repo);
+    let rewrite_log = read_rewrite_log(&repo);
+    assert!(rewrite_log.contains(&format!("\"reverted_commit\":\"{}\"", removal.commit_sha)));
+    assert!(!rewrite_log.contains("\"revert_abort\""));
+}
+
+/// `-m 2` restores lines from the merge's second parent
+#[test]
+fn test_revert_merge_uses_mainline_parent() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines![
+        "Base".human(),
+        "AI line 1".ai(),
+        "AI line 2".ai(),
+        "End".human()
+    ]);
+    repo.stage_all_and_commit("Add AI lines").unwrap();
+    let main_branch = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+    repo.filename("feature.txt")
+        .set_contents(lines!["Feature".human()]);
+    repo.stage_all_and_commit("Add feature").unwrap();
+
+    repo.git(&["checkout", &main_branch]).unwrap();
+    file.set_contents(lines!["Base".human(), "End".human()]);
+    repo.stage_all_and_commit("Remove AI lines").unwrap();
+    repo.git(&["merge", "--no-ff", "-m", "Merge feature", "feature"])
+        .unwrap();
+
+    // Only the second parent (feature) still has the AI lines
+    repo.git(&["revert", "--no-edit", "-m", "2", "HEAD"])
+        .unwrap();
+
+    assert_ai_lines_restored(&repo);
+}
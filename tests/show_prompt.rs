@@ -1,6 +1,7 @@
 #[macro_use]
 mod repos;
 
+use git_ai::authorship::transcript::{AiTranscript, Message};
 use repos::test_file::ExpectedLineExt;
 use repos::test_repo::TestRepo;
 
@@ -167,3 +168,71 @@ fn show_prompt_with_offset_skips_occurrences() {
         err
     );
 }
+
+/// Write `file_name` and record it as an agent_v1 AI edit whose transcript holds `message`
+fn checkpoint_with_message(repo: &TestRepo, file_name: &str, message: &str, conversation_id: &str) {
+    std::fs::write(repo.path().join(file_name), "AI line\n").unwrap();
+
+    let mut transcript = AiTranscript::new();
+    transcript.add_message(Message::user(message.to_string(), None));
+    let hook_input = serde_json::json!({
+        "type": "ai_agent",
+        "repo_working_dir": repo.path().to_str().unwrap(),
+        "edited_filepaths": [repo.path().join(file_name).to_str().unwrap()],
+        "transcript": transcript,
+        "agent_name": "test-agent",
+        "model": "test-model",
+        "conversation_id": conversation_id,
+    });
+    repo.git_ai(&[
+        "checkpoint",
+        "agent-v1",
+        "--hook-input",
+        &hook_input.to_string(),
+    ])
+    .expect("checkpoint should succeed");
+}
+
+#[test]
+fn show_prompt_resolves_messages_offloaded_to_cas() {
+    let repo = TestRepo::new();
+    repo.git_og(&["config", "gitai.noteMessagesBudget", "1k"])
+        .unwrap();
+
+    let long_message = "Refactor the parser. ".repeat(200);
+    checkpoint_with_message(&repo, "large.txt", &long_message, "large-session");
+    checkpoint_with_message(&repo, "small.txt", "Fix the typo", "small-session");
+    let commit = repo.stage_all_and_commit("AI commit").unwrap();
+
+    // The oversized transcript is replaced by a CAS reference; the small one stays inline
+    let prompts = &commit.authorship_log.metadata.prompts;
+    let (large_id, large) = prompts
+        .iter()
+        .find(|(_, p)| p.agent_id.id == "large-session")
+        .expect("expected the large prompt");
+    assert!(large.messages.is_empty());
+    assert!(
+        large
+            .messages_url
+            .as_deref()
+            .is_some_and(|url| url.contains("/cas/")),
+        "expected a CAS reference, got {:?}",
+        large.messages_url
+    );
+    let small = prompts
+        .values()
+        .find(|p| p.agent_id.id == "small-session")
+        .expect("expected the small prompt");
+    assert_eq!(small.messages.len(), 1);
+    assert!(small.messages_url.is_none());
+
+    // show-prompt loads the offloaded transcript from the local CAS queue
+    let output = repo
+        .git_ai(&["show-prompt", large_id])
+        .expect("show-prompt should succeed");
+    let json: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    assert_eq!(
+        json["prompt"]["messages"][0]["text"].as_str(),
+        Some(long_message.as_str())
+    );
+}
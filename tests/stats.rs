@@ -220,6 +220,7 @@ fn test_markdown_stats_deletion_only() {
         git_diff_deleted_lines: 5,
         git_diff_added_lines: 0,
         tool_model_breakdown: BTreeMap::new(),
+        merge_resolution: false,
     };
 
     let markdown = write_stats_to_markdown(&stats);
@@ -243,6 +244,7 @@ fn test_markdown_stats_all_human() {
         git_diff_deleted_lines: 0,
         git_diff_added_lines: 10,
         tool_model_breakdown: BTreeMap::new(),
+        merge_resolution: false,
     };
 
     let markdown = write_stats_to_markdown(&stats);
@@ -266,6 +268,7 @@ fn test_markdown_stats_all_ai() {
         git_diff_deleted_lines: 0,
         git_diff_added_lines: 15,
         tool_model_breakdown: BTreeMap::new(),
+        merge_resolution: false,
     };
 
     let markdown = write_stats_to_markdown(&stats);
@@ -289,6 +292,7 @@ fn test_markdown_stats_mixed() {
         git_diff_deleted_lines: 5,
         git_diff_added_lines: 30,
         tool_model_breakdown: BTreeMap::new(),
+        merge_resolution: false,
     };
 
     let markdown = write_stats_to_markdown(&stats);
@@ -312,6 +316,7 @@ fn test_markdown_stats_no_mixed() {
         git_diff_deleted_lines: 0,
         git_diff_added_lines: 20,
         tool_model_breakdown: BTreeMap::new(),
+        merge_resolution: false,
     };
 
     let markdown = write_stats_to_markdown(&stats);
@@ -336,6 +341,7 @@ fn test_markdown_stats_minimal_human() {
         git_diff_deleted_lines: 0,
         git_diff_added_lines: 100,
         tool_model_breakdown: BTreeMap::new(),
+        merge_resolution: false,
     };
 
     let markdown = write_stats_to_markdown(&stats);
@@ -372,9 +378,96 @@ fn test_markdown_stats_formatting() {
         git_diff_deleted_lines: 2,
         git_diff_added_lines: 13,
         tool_model_breakdown,
+        merge_resolution: false,
     };
 
     let markdown = write_stats_to_markdown(&stats);
     println!("{}", markdown);
     assert_debug_snapshot!(markdown);
 }
+
+/// `--group-by directory` splits each commit's lines across top-level directories
+#[test]
+fn test_stats_cli_range_group_by_directory() {
+    let repo = TestRepo::new();
+
+    let mut readme = repo.filename("README.md");
+    readme.set_contents(lines!["# Project".human()]);
+    let first = repo.stage_all_and_commit("Initial").unwrap();
+
+    let mut src = repo.filename("src/README.md");
+    src.set_contents(lines!["AI line 1".ai(), "AI line 2".ai()]);
+    let mut docs = repo.filename("docs/guide.md");
+    docs.set_contents(lines!["Guide".human()]);
+    readme.set_contents(lines!["# Project".human(), "More".human()]);
+    let second = repo.stage_all_and_commit("Mixed commit").unwrap();
+
+    let range = format!("{}..{}", first.commit_sha, second.commit_sha);
+    let raw = repo
+        .git_ai(&["stats", &range, "--group-by", "directory", "--json"])
+        .unwrap();
+    let grouped: git_ai::authorship::grouped_stats::GroupedRangeStats =
+        serde_json::from_str(&extract_json_object(&raw)).unwrap();
+
+    assert_eq!(grouped.group_by, "directory");
+    let keys: Vec<&str> = grouped.groups.iter().map(|g| g.key.as_str()).collect();
+    assert_eq!(keys, vec![".", "docs", "src"]);
+    let stats = |key: &str| {
+        &grouped
+            .groups
+            .iter()
+            .find(|group| group.key == key)
+            .unwrap()
+            .stats
+    };
+    assert!(stats(".").git_diff_added_lines > 0);
+    assert_eq!(stats(".").ai_additions, 0);
+    assert_eq!(stats("docs").git_diff_added_lines, 1);
+    assert_eq!(stats("docs").ai_additions, 0);
+    assert_eq!(stats("src").ai_additions, 2);
+    assert_eq!(stats("src").git_diff_added_lines, 2);
+    assert!(stats(".").tool_model_breakdown.is_empty());
+    assert!(
+        stats("src")
+            .tool_model_breakdown
+            .contains_key("mock_ai::unknown")
+    );
+}
+
+/// `--group-by tool` puts AI lines under their tool and human-only commits under "none",
+/// as a table or CSV
+#[test]
+fn test_stats_cli_range_group_by_tool() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines!["Base".human()]);
+    let first = repo.stage_all_and_commit("Initial").unwrap();
+
+    file.set_contents(lines!["Base".human(), "AI line".ai()]);
+    repo.stage_all_and_commit("AI commit").unwrap();
+    let mut other = repo.filename("other.txt");
+    other.set_contents(lines!["Human line".human()]);
+    let last = repo.stage_all_and_commit("Human commit").unwrap();
+
+    let range = format!("{}..{}", first.commit_sha, last.commit_sha);
+    let csv = repo
+        .git_ai(&["stats", &range, "--group-by", "tool", "--csv"])
+        .unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert!(lines[0].starts_with("group,commits,"), "{}", csv);
+    assert!(lines[1].starts_with("mock_ai,1,0,0,0,1,0,1,"), "{}", csv);
+    assert_eq!(lines.len(), 3, "{}", csv);
+    assert!(lines[2].starts_with("none,1,1,0,1,0,0,0,"), "{}", csv);
+
+    let table = repo
+        .git_ai(&["stats", &range, "--group-by", "tool"])
+        .unwrap();
+    assert!(table.starts_with("tool "), "{}", table);
+    assert!(table.contains("mock_ai"), "{}", table);
+
+    assert!(
+        repo.git_ai(&["stats", "--group-by", "tool"]).is_err(),
+        "--group-by needs a range"
+    );
+}
@@ -0,0 +1,165 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use std::path::PathBuf;
+
+use git_ai::authorship::stats::CommitStats;
+
+const SUBMODULE: &str = "vendor/lib";
+
+/// A superproject with a submodule checked out at `vendor/lib`
+fn repo_with_submodule() -> (TestRepo, TestRepo, PathBuf) {
+    let library = TestRepo::new();
+    let mut readme = library.filename("README.md");
+    readme.set_contents(lines!["Library".human()]);
+    library.stage_all_and_commit("Initial library").unwrap();
+
+    let repo = TestRepo::new();
+    let mut readme = repo.filename("README.md");
+    readme.set_contents(lines!["Superproject".human()]);
+    repo.stage_all_and_commit("Initial superproject").unwrap();
+
+    repo.git(&[
+        "-c",
+        "protocol.file.allow=always",
+        "submodule",
+        "add",
+        library.path().to_str().unwrap(),
+        SUBMODULE,
+    ])
+    .unwrap();
+    repo.git(&["commit", "-m", "Add library submodule"])
+        .unwrap();
+
+    let submodule_dir = repo.path().join(SUBMODULE);
+    repo.git_from_working_dir(&submodule_dir, &["config", "user.name", "Test User"])
+        .unwrap();
+    repo.git_from_working_dir(
+        &submodule_dir,
+        &["config", "user.email", "test@example.com"],
+    )
+    .unwrap();
+    (repo, library, submodule_dir)
+}
+
+fn parse_json<T: serde::de::DeserializeOwned>(output: &str) -> T {
+    let json = output
+        .lines()
+        .find(|line| line.starts_with('{'))
+        .expect("command should print JSON");
+    serde_json::from_str(json).unwrap()
+}
+
+/// Checkpoints taken from the superproject record submodule edits in the submodule's own
+/// working log, so committing inside the submodule writes its note
+#[test]
+fn test_checkpoint_routes_submodule_edits_to_submodule() {
+    let (repo, _library, submodule_dir) = repo_with_submodule();
+    let file_path = submodule_dir.join("lib.txt");
+
+    std::fs::write(&file_path, "Human line\n").unwrap();
+    repo.git_ai(&["checkpoint"]).unwrap();
+
+    std::fs::write(&file_path, "Human line\nAI line 1\nAI line 2\n").unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai", "vendor/lib/lib.txt"])
+        .unwrap();
+    std::fs::write(&file_path, "Human line\nAI line 1\nAI line 2\nEnd\n").unwrap();
+    repo.git_ai(&["checkpoint"]).unwrap();
+
+    repo.git_from_working_dir(&submodule_dir, &["add", "-A"])
+        .unwrap();
+    repo.git_from_working_dir(&submodule_dir, &["commit", "-m", "AI library change"])
+        .unwrap();
+
+    let note = repo
+        .git_from_working_dir(&submodule_dir, &["notes", "--ref=ai", "show", "HEAD"])
+        .expect("the submodule commit should have an authorship note");
+    assert!(note.contains("lib.txt"), "note: {}", note);
+    assert!(note.contains("mock_ai"), "note: {}", note);
+
+    // Nothing was recorded against the superproject's gitlink
+    let superproject_checkpoints = repo.current_working_logs().read_all_checkpoints().unwrap();
+    assert!(
+        superproject_checkpoints
+            .iter()
+            .all(|checkpoint| checkpoint.entries.is_empty()),
+        "the superproject shouldn't record the submodule's edits"
+    );
+}
+
+/// `stats --recurse-submodules` counts the submodule commits a gitlink update pulls in, and
+/// `blame --recurse-submodules` reads the submodule's notes
+#[test]
+fn test_stats_and_blame_recurse_into_submodules() {
+    let (repo, _library, submodule_dir) = repo_with_submodule();
+    let file_path = submodule_dir.join("lib.txt");
+
+    std::fs::write(&file_path, "Human line\n").unwrap();
+    repo.git_ai(&["checkpoint"]).unwrap();
+    std::fs::write(&file_path, "Human line\nAI line 1\nAI line 2\n").unwrap();
+    // Naming only the submodule checkpoints everything changed inside it
+    repo.git_ai(&["checkpoint", "mock_ai", SUBMODULE]).unwrap();
+    std::fs::write(&file_path, "Human line\nAI line 1\nAI line 2\nEnd\n").unwrap();
+    repo.git_ai(&["checkpoint"]).unwrap();
+
+    repo.git_from_working_dir(&submodule_dir, &["add", "-A"])
+        .unwrap();
+    repo.git_from_working_dir(&submodule_dir, &["commit", "-m", "AI library change"])
+        .unwrap();
+    repo.git(&["add", SUBMODULE]).unwrap();
+    repo.git(&["commit", "-m", "Bump library"]).unwrap();
+
+    let stats: CommitStats = parse_json(&repo.git_ai(&["stats", "--json"]).unwrap());
+    assert_eq!(stats.ai_additions, 0);
+
+    let stats: CommitStats = parse_json(
+        &repo
+            .git_ai(&["stats", "--json", "--recurse-submodules"])
+            .unwrap(),
+    );
+    assert_eq!(stats.ai_additions, 2);
+    assert_eq!(stats.git_diff_added_lines, 4);
+    assert!(stats.tool_model_breakdown.contains_key("mock_ai::unknown"));
+
+    let blame = repo
+        .git_ai(&["blame", "--recurse-submodules", "vendor/lib/lib.txt"])
+        .unwrap();
+    let ai_lines: Vec<&str> = blame
+        .lines()
+        .filter(|line| line.contains("mock_ai"))
+        .collect();
+    assert_eq!(ai_lines.len(), 2, "blame: {}", blame);
+    assert!(ai_lines[0].contains("AI line 1"));
+}
+
+/// `status --recurse-submodules` includes uncommitted work inside submodules
+#[test]
+fn test_status_recurses_into_submodules() {
+    let (repo, _library, submodule_dir) = repo_with_submodule();
+    let file_path = submodule_dir.join("lib.txt");
+
+    std::fs::write(&file_path, "Human line\n").unwrap();
+    repo.git_ai(&["checkpoint"]).unwrap();
+    std::fs::write(&file_path, "Human line\nAI line 1\nAI line 2\n").unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai", "vendor/lib/lib.txt"])
+        .unwrap();
+    std::fs::write(&file_path, "Human line\nAI line 1\nAI line 2\nEnd\n").unwrap();
+    repo.git_ai(&["checkpoint"]).unwrap();
+
+    let status: serde_json::Value = parse_json(
+        &repo
+            .git_ai(&["status", "--json", "--recurse-submodules"])
+            .unwrap(),
+    );
+    assert_eq!(status["stats"]["ai_accepted"], 2);
+    let checkpoints = status["checkpoints"].as_array().unwrap();
+    assert!(
+        checkpoints
+            .iter()
+            .any(|checkpoint| checkpoint["submodule"] == SUBMODULE
+                && checkpoint["is_human"] == false),
+        "status: {}",
+        status
+    );
+}
@@ -0,0 +1,117 @@
+#[macro_use]
+mod repos;
+
+use git_ai::commands::verify::{VerifyState, verify_commits};
+use git_ai::git::find_repository_in_path;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Generate an ed25519 key and an allowed signers file trusting it for `principal`
+fn setup_signing_key(dir: &Path, principal: &str) -> (PathBuf, PathBuf) {
+    let key_path = dir.join("id_ed25519");
+    let status = Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-C", principal, "-f"])
+        .arg(&key_path)
+        .status()
+        .expect("ssh-keygen should be installed");
+    assert!(status.success());
+
+    let public_key = std::fs::read_to_string(key_path.with_extension("pub")).unwrap();
+    let allowed_signers = dir.join("allowed_signers");
+    std::fs::write(&allowed_signers, format!("{} {}", principal, public_key)).unwrap();
+
+    (key_path, allowed_signers)
+}
+
+#[test]
+fn test_signed_notes_verify() {
+    let repo = TestRepo::new();
+    let keys = tempfile::tempdir().unwrap();
+    let (key_path, allowed_signers) = setup_signing_key(keys.path(), "dev@example.com");
+    repo.git_og(&["config", "gitai.signingKey", key_path.to_str().unwrap()])
+        .unwrap();
+
+    let mut file = repo.filename("test.txt");
+    file.set_contents(lines!["Base".human(), "AI line 1".ai()]);
+    let commit = repo.stage_all_and_commit("First commit").unwrap();
+    assert!(commit.authorship_log.metadata.signature.is_some());
+
+    let output = repo
+        .git_ai(&[
+            "verify",
+            "HEAD",
+            "--allowed-signers",
+            allowed_signers.to_str().unwrap(),
+        ])
+        .expect("verify should succeed for a freshly signed note");
+    assert!(
+        output.contains("good signature from dev@example.com"),
+        "unexpected output: {}",
+        output
+    );
+}
+
+#[test]
+fn test_verify_rejects_edited_and_unsigned_notes() {
+    let repo = TestRepo::new();
+    let keys = tempfile::tempdir().unwrap();
+    let (key_path, allowed_signers) = setup_signing_key(keys.path(), "dev@example.com");
+    repo.git_og(&[
+        "config",
+        "gitai.allowedSignersFile",
+        allowed_signers.to_str().unwrap(),
+    ])
+    .unwrap();
+
+    let mut file = repo.filename("test.txt");
+    file.set_contents(lines!["Base".human(), "AI line 1".ai()]);
+    repo.stage_all_and_commit("Unsigned commit").unwrap();
+
+    repo.git_ai(&["verify", "HEAD"])
+        .expect_err("unsigned notes should fail verification");
+
+    repo.git_og(&["config", "gitai.signingKey", key_path.to_str().unwrap()])
+        .unwrap();
+    file.insert_at(2, lines!["AI line 2".ai()]);
+    let commit = repo.stage_all_and_commit("Signed commit").unwrap();
+    repo.git_ai(&["verify", "HEAD"])
+        .expect("signed note should verify using gitai.allowedSignersFile");
+
+    // Hand-edit the attestation while keeping the signature
+    let hash = commit
+        .authorship_log
+        .metadata
+        .prompts
+        .keys()
+        .next()
+        .unwrap();
+    let note = repo
+        .git_og(&["notes", "--ref=ai", "show", &commit.commit_sha])
+        .unwrap();
+    let edited = note.replacen(&format!("  {} ", hash), &format!("  {} 1,", hash), 1);
+    assert_ne!(note, edited);
+    repo.git_og(&[
+        "notes",
+        "--ref=ai",
+        "add",
+        "-f",
+        "-m",
+        &edited,
+        &commit.commit_sha,
+    ])
+    .unwrap();
+
+    repo.git_ai(&["verify", "HEAD"])
+        .expect_err("edited note should fail verification");
+
+    let gitai_repo = find_repository_in_path(repo.path().to_str().unwrap()).unwrap();
+    let report = verify_commits(
+        &gitai_repo,
+        std::slice::from_ref(&commit.commit_sha),
+        &allowed_signers,
+    )
+    .unwrap();
+    assert_eq!(report.commits[0].state, VerifyState::Bad);
+}
@@ -0,0 +1,140 @@
+#[macro_use]
+mod repos;
+use git_ai::git::find_repository_in_path;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn git_dir(repo: &TestRepo) -> std::path::PathBuf {
+    find_repository_in_path(repo.path().to_str().unwrap())
+        .unwrap()
+        .path()
+        .to_path_buf()
+}
+
+/// Two worktrees on the same base commit keep separate working logs
+#[test]
+fn test_worktrees_on_same_base_commit_do_not_share_working_logs() {
+    let repo = TestRepo::new();
+    let mut readme = repo.filename("README.md");
+    readme.set_contents(lines!["# Project".human()]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    let agent_a = repo.add_worktree("agent-a");
+    let agent_b = repo.add_worktree("agent-b");
+
+    let mut file_a = agent_a.filename("a.txt");
+    file_a.set_contents(lines!["A human".human(), "A ai".ai(), "A end".human()]);
+    let mut file_b = agent_b.filename("b.txt");
+    file_b.set_contents(lines!["B human".human(), "B ai".ai(), "B end".human()]);
+
+    // Both worktrees sit on the same base commit but record checkpoints separately
+    let logs_a = agent_a.current_working_logs();
+    let logs_b = agent_b.current_working_logs();
+    assert_ne!(logs_a.dir, logs_b.dir);
+    assert!(
+        logs_a
+            .read_all_checkpoints()
+            .unwrap()
+            .iter()
+            .all(|checkpoint| checkpoint.entries.iter().all(|e| e.file == "a.txt"))
+    );
+
+    // Committing in one worktree leaves the other's pending attributions alone
+    agent_a.stage_all_and_commit("Agent A work").unwrap();
+    assert!(!logs_b.read_all_checkpoints().unwrap().is_empty());
+    agent_b.stage_all_and_commit("Agent B work").unwrap();
+
+    file_a.assert_lines_and_blame(lines!["A human".human(), "A ai".ai(), "A end".human()]);
+    file_b.assert_lines_and_blame(lines!["B human".human(), "B ai".ai(), "B end".human()]);
+
+    // The main worktree's working log never saw either agent's edits
+    assert!(
+        repo.current_working_logs()
+            .read_all_checkpoints()
+            .unwrap()
+            .is_empty()
+    );
+}
+
+/// Each worktree has its own rewrite log
+#[test]
+fn test_worktree_rewrite_logs_are_separate() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines!["Base".human()]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    let worktree = repo.add_worktree("feature");
+    let mut feature_file = worktree.filename("feature.txt");
+    feature_file.set_contents(lines!["Feature".human(), "AI feature".ai(), "End".human()]);
+    let commit = worktree.stage_all_and_commit("Feature work").unwrap();
+
+    let main_storage = git_dir(&repo).join("ai").join("rewrite_log");
+    let worktree_storage = git_dir(&worktree).join("ai").join("rewrite_log");
+    assert_ne!(main_storage, worktree_storage);
+
+    let main_log = std::fs::read_to_string(main_storage).unwrap();
+    let worktree_log = std::fs::read_to_string(worktree_storage).unwrap();
+    assert!(worktree_log.contains(&commit.commit_sha));
+    assert!(!main_log.contains(&commit.commit_sha));
+}
+
+/// Repository config set in the main checkout applies inside linked worktrees
+#[test]
+fn test_worktree_reads_shared_repository_config() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines!["Base".human()]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    repo.git_og(&["config", "gitai.noteEncoding", "zstd"])
+        .unwrap();
+
+    let worktree = repo.add_worktree("feature");
+    let mut feature_file = worktree.filename("feature.txt");
+    feature_file.set_contents(lines!["Feature".human(), "AI feature".ai(), "End".human()]);
+    let commit = worktree.stage_all_and_commit("Feature work").unwrap();
+
+    let note = repo
+        .git_og(&["notes", "--ref=ai", "show", &commit.commit_sha])
+        .unwrap();
+    assert!(
+        note.contains("\n---\nencoding: zstd+base64\n"),
+        "worktree should honor the shared config: {}",
+        note
+    );
+}
+
+/// Pending attributions survive `git worktree move` and go away with `git worktree remove`
+#[test]
+fn test_worktree_move_keeps_state_and_remove_cleans_it_up() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("file.txt");
+    file.set_contents(lines!["Base".human()]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    let mut worktree = repo.add_worktree("feature");
+    let admin_dir = git_dir(&worktree);
+    worktree.filename("feature.txt").set_contents(lines![
+        "Feature".human(),
+        "AI feature".ai(),
+        "End".human()
+    ]);
+
+    let moved_path = worktree.path().with_file_name(format!(
+        "{}-moved",
+        worktree.path().file_name().unwrap().to_string_lossy()
+    ));
+    worktree.move_worktree(&moved_path);
+    assert_eq!(git_dir(&worktree), admin_dir);
+
+    worktree.stage_all_and_commit("Feature work").unwrap();
+    worktree
+        .filename("feature.txt")
+        .assert_lines_and_blame(lines!["Feature".human(), "AI feature".ai(), "End".human()]);
+
+    assert!(admin_dir.join("ai").exists());
+    repo.git(&["worktree", "remove", worktree.path().to_str().unwrap()])
+        .unwrap();
+    assert!(!admin_dir.exists());
+    assert!(git_dir(&repo).join("ai").exists());
+}